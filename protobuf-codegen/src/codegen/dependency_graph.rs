@@ -0,0 +1,199 @@
+use std::fmt::Write;
+
+use protobuf::descriptor::FileDescriptorProto;
+
+/// Format of the import graph written by
+/// [`Codegen::emit_dependency_graph`](crate::Codegen::emit_dependency_graph).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DependencyGraphFormat {
+    /// Graphviz `digraph`, public imports are bold edges and weak imports are dashed edges.
+    Dot,
+    /// `{"nodes": [..], "edges": [{"from": .., "to": .., "kind": ..}]}`,
+    /// where kind is `import`, `public` or `weak`.
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EdgeKind {
+    Import,
+    Public,
+    Weak,
+}
+
+impl EdgeKind {
+    fn name(self) -> &'static str {
+        match self {
+            EdgeKind::Import => "import",
+            EdgeKind::Public => "public",
+            EdgeKind::Weak => "weak",
+        }
+    }
+}
+
+fn edges(file: &FileDescriptorProto) -> impl Iterator<Item = (&str, EdgeKind)> {
+    file.dependency.iter().enumerate().map(move |(i, dep)| {
+        let i = i as i32;
+        let kind = if file.public_dependency.contains(&i) {
+            EdgeKind::Public
+        } else if file.weak_dependency.contains(&i) {
+            EdgeKind::Weak
+        } else {
+            EdgeKind::Import
+        };
+        (dep.as_str(), kind)
+    })
+}
+
+/// Quoted Graphviz ID.
+fn dot_quote(s: &str) -> String {
+    let mut r = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => r.push_str("\\\""),
+            '\\' => r.push_str("\\\\"),
+            '\n' => r.push_str("\\n"),
+            c => r.push(c),
+        }
+    }
+    r.push('"');
+    r
+}
+
+/// JSON string literal.
+fn json_quote(s: &str) -> String {
+    let mut r = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => r.push_str("\\\""),
+            '\\' => r.push_str("\\\\"),
+            '\n' => r.push_str("\\n"),
+            '\r' => r.push_str("\\r"),
+            '\t' => r.push_str("\\t"),
+            c if (c as u32) < 0x20 => write!(r, "\\u{:04x}", c as u32).unwrap(),
+            c => r.push(c),
+        }
+    }
+    r.push('"');
+    r
+}
+
+/// Render import graph of given files.
+pub(crate) fn dependency_graph(
+    files: &[FileDescriptorProto],
+    format: DependencyGraphFormat,
+) -> String {
+    let mut r = String::new();
+    match format {
+        DependencyGraphFormat::Dot => {
+            r.push_str("digraph protos {\n");
+            for file in files {
+                writeln!(r, "    {};", dot_quote(file.name())).unwrap();
+            }
+            for file in files {
+                for (dep, kind) in edges(file) {
+                    let attrs = match kind {
+                        EdgeKind::Public => " [style=bold, label=\"public\"]",
+                        EdgeKind::Weak => " [style=dashed, label=\"weak\"]",
+                        EdgeKind::Import => "",
+                    };
+                    writeln!(
+                        r,
+                        "    {} -> {}{};",
+                        dot_quote(file.name()),
+                        dot_quote(dep),
+                        attrs
+                    )
+                    .unwrap();
+                }
+            }
+            r.push_str("}\n");
+        }
+        DependencyGraphFormat::Json => {
+            let nodes: Vec<String> = files.iter().map(|f| json_quote(f.name())).collect();
+            let edges: Vec<String> = files
+                .iter()
+                .flat_map(|file| {
+                    edges(file).map(move |(dep, kind)| {
+                        format!(
+                            "{{\"from\": {}, \"to\": {}, \"kind\": \"{}\"}}",
+                            json_quote(file.name()),
+                            json_quote(dep),
+                            kind.name()
+                        )
+                    })
+                })
+                .collect();
+            writeln!(
+                r,
+                "{{\"nodes\": [{}], \"edges\": [{}]}}",
+                nodes.join(", "),
+                edges.join(", ")
+            )
+            .unwrap();
+        }
+    }
+    r
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::dot_quote;
+    use super::json_quote;
+    use crate::Codegen;
+    use crate::DependencyGraphFormat;
+
+    #[test]
+    fn quote() {
+        assert_eq!("\"a\\\"b\\\\c\\nd\te\"", dot_quote("a\"b\\c\nd\te"));
+        assert_eq!(r#""a\"b\\c\nd\te\u0001""#, json_quote("a\"b\\c\nd\te\x01"));
+    }
+
+    #[test]
+    fn dependency_graph() {
+        let mut codegen = Codegen::new();
+        codegen
+            .pure()
+            .input_content(
+                "a.proto",
+                "syntax = 'proto2'; import public 'b.proto'; import weak 'c.proto'; import 'd \\\"e\\\".proto';",
+            )
+            .include_content("b.proto", "syntax = 'proto2';")
+            .include_content("c.proto", "syntax = 'proto2';")
+            .include_content("d \"e\".proto", "syntax = 'proto2';");
+
+        assert_eq!(
+            "digraph protos {\n    \
+                \"b.proto\";\n    \
+                \"c.proto\";\n    \
+                \"d \\\"e\\\".proto\";\n    \
+                \"a.proto\";\n    \
+                \"a.proto\" -> \"b.proto\" [style=bold, label=\"public\"];\n    \
+                \"a.proto\" -> \"c.proto\" [style=dashed, label=\"weak\"];\n    \
+                \"a.proto\" -> \"d \\\"e\\\".proto\";\n\
+            }\n",
+            codegen
+                .dependency_graph(DependencyGraphFormat::Dot)
+                .unwrap()
+        );
+        assert_eq!(
+            "{\"nodes\": [\"b.proto\", \"c.proto\", \"d \\\"e\\\".proto\", \"a.proto\"], \
+            \"edges\": [{\"from\": \"a.proto\", \"to\": \"b.proto\", \"kind\": \"public\"}, \
+            {\"from\": \"a.proto\", \"to\": \"c.proto\", \"kind\": \"weak\"}, \
+            {\"from\": \"a.proto\", \"to\": \"d \\\"e\\\".proto\", \"kind\": \"import\"}]}\n",
+            codegen
+                .dependency_graph(DependencyGraphFormat::Json)
+                .unwrap()
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        let graph = dir.path().join("graph.dot");
+        let report = codegen
+            .emit_dependency_graph(&graph, DependencyGraphFormat::Dot)
+            .run_with_report()
+            .unwrap();
+        assert_eq!(vec![graph.clone()], report.output_files);
+        assert!(fs::read_to_string(&graph).unwrap().starts_with("digraph"));
+    }
+}
@@ -0,0 +1,132 @@
+//! Expansion of `~` and environment variables for
+//! [`Codegen::include_env_expanded`](crate::Codegen::include_env_expanded).
+
+use std::env;
+use std::ffi::OsString;
+use std::path::Path;
+use std::path::PathBuf;
+
+#[derive(Debug, thiserror::Error)]
+pub(crate) enum EnvExpandError {
+    #[error("environment variable `{0}` referenced in path `{1}` is not set")]
+    VarNotSet(String, String),
+    #[error("unterminated `${{` in path `{0}`")]
+    Unterminated(String),
+}
+
+fn is_var_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Expand leading `~` to home directory, and `$VAR` and `${VAR}` to variable values.
+pub(crate) fn expand(path: &Path) -> Result<PathBuf, EnvExpandError> {
+    expand_with(path, |name| env::var_os(name))
+}
+
+fn home_var() -> &'static str {
+    if cfg!(windows) {
+        "USERPROFILE"
+    } else {
+        "HOME"
+    }
+}
+
+fn expand_with(
+    path: &Path,
+    var: impl Fn(&str) -> Option<OsString>,
+) -> Result<PathBuf, EnvExpandError> {
+    // Variable references are ASCII, so they can only appear in UTF-8 paths.
+    let path = match path.to_str() {
+        Some(path) => path,
+        None => return Ok(path.to_owned()),
+    };
+    let lookup = |name: &str| {
+        var(name).ok_or_else(|| EnvExpandError::VarNotSet(name.to_owned(), path.to_owned()))
+    };
+
+    let mut r = OsString::new();
+    let mut rest = path;
+    if let Some(after) = rest.strip_prefix('~') {
+        if after.is_empty() || after.starts_with(['/', '\\']) {
+            r.push(lookup(home_var())?);
+            rest = after;
+        }
+    }
+    while let Some(pos) = rest.find('$') {
+        r.push(&rest[..pos]);
+        rest = &rest[pos + 1..];
+        let name = match rest.strip_prefix('{') {
+            Some(braced) => {
+                let end = braced
+                    .find('}')
+                    .ok_or_else(|| EnvExpandError::Unterminated(path.to_owned()))?;
+                rest = &braced[end + 1..];
+                &braced[..end]
+            }
+            None => {
+                let end = rest.find(|c| !is_var_char(c)).unwrap_or(rest.len());
+                let name = &rest[..end];
+                rest = &rest[end..];
+                name
+            }
+        };
+        match name {
+            // Lone `$` is kept as is.
+            "" => r.push("$"),
+            name => r.push(lookup(name)?),
+        }
+    }
+    r.push(rest);
+    Ok(PathBuf::from(r))
+}
+
+#[cfg(test)]
+mod test {
+    use std::ffi::OsString;
+    use std::path::Path;
+    use std::path::PathBuf;
+
+    use super::expand_with;
+    use crate::Codegen;
+
+    fn var(name: &str) -> Option<OsString> {
+        match name {
+            "HOME" | "USERPROFILE" => Some("/home/u".into()),
+            "PROTOS" => Some("/opt/protos".into()),
+            _ => None,
+        }
+    }
+
+    fn expand(path: &str) -> PathBuf {
+        expand_with(Path::new(path), var).unwrap()
+    }
+
+    fn expand_err(path: &str) -> String {
+        expand_with(Path::new(path), var).unwrap_err().to_string()
+    }
+
+    #[test]
+    fn expand_path() {
+        assert_eq!(Path::new("/home/u/protos"), expand("~/protos"));
+        assert_eq!(Path::new("/home/u"), expand("~"));
+        assert_eq!(Path::new("~user/x"), expand("~user/x"));
+        assert_eq!(Path::new("/opt/protos/v1"), expand("${PROTOS}/v1"));
+        assert_eq!(Path::new("/opt/protos/v1"), expand("$PROTOS/v1"));
+        assert_eq!(Path::new("a/$/b"), expand("a/$/b"));
+        assert_eq!(
+            "environment variable `MISSING` referenced in path `${MISSING}/x` is not set",
+            expand_err("${MISSING}/x")
+        );
+        assert_eq!(
+            "unterminated `${` in path `${PROTOS`",
+            expand_err("${PROTOS")
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "environment variable `PROTOBUF_CODEGEN_TEST_MISSING` \
+        referenced in path `$PROTOBUF_CODEGEN_TEST_MISSING/protos` is not set")]
+    fn include_env_expanded_not_set() {
+        Codegen::new().include_env_expanded("$PROTOBUF_CODEGEN_TEST_MISSING/protos");
+    }
+}
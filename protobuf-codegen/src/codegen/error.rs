@@ -0,0 +1,120 @@
+use protobuf_parse::pure::is_io_error;
+use protobuf_parse::pure::ParserErrorsWithLocation;
+use protobuf_parse::pure::WithFileError;
+
+/// Error returned by [`Codegen::run_typed`](crate::Codegen::run_typed).
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum CodegenError {
+    /// No output is configured.
+    #[error("none of out_dir, descriptor_set_out or emit_dependency_graph is specified")]
+    OutDirNotSpecified,
+    /// Parser reported warnings and [`deny_warnings`](crate::Codegen::deny_warnings) is on.
+    #[error("{} warning(s) denied by `deny_warnings`:\n{}", .0.len(), .0.join("\n"))]
+    WarningsDenied(Vec<String>),
+    /// File requested from [`run_single_to_string`](crate::Codegen::run_single_to_string)
+    /// was not parsed.
+    #[error("file `{0}` is neither an input nor an import of an input")]
+    FileNotParsed(String),
+    /// `.proto` file cannot be found or read.
+    #[error("{error:#}")]
+    Io {
+        /// Underlying error.
+        error: anyhow::Error,
+    },
+    /// `.proto` file is not syntactically valid.
+    #[error("{error:#}")]
+    Parse {
+        /// Path of the file.
+        file: String,
+        /// 1-based line of the first error.
+        line: u32,
+        /// 1-based column of the first error.
+        col: u32,
+        /// Underlying error.
+        error: anyhow::Error,
+    },
+    /// `.proto` file is parsed but is not valid, e.g. references undefined type.
+    #[error("{error:#}")]
+    Typecheck {
+        /// Path of the file.
+        file: String,
+        /// 1-based line of the offending declaration if known.
+        line: Option<u32>,
+        /// 1-based column of the offending declaration if known.
+        col: Option<u32>,
+        /// Underlying error.
+        error: anyhow::Error,
+    },
+    /// `protoc` cannot be run or reported an error, e.g. in a `.proto` file.
+    #[error("{error:#}")]
+    Protoc {
+        /// Underlying error.
+        error: anyhow::Error,
+    },
+    /// Output file cannot be written.
+    #[error("{error:#}")]
+    Write {
+        /// Underlying error.
+        error: anyhow::Error,
+    },
+    /// Other error, e.g. failure to generate code.
+    #[error("{0:#}")]
+    Other(anyhow::Error),
+}
+
+impl CodegenError {
+    pub(crate) fn io(error: impl Into<anyhow::Error>) -> CodegenError {
+        CodegenError::Io {
+            error: error.into(),
+        }
+    }
+
+    pub(crate) fn write(error: impl Into<anyhow::Error>) -> CodegenError {
+        CodegenError::Write {
+            error: error.into(),
+        }
+    }
+
+    pub(crate) fn other(error: impl Into<anyhow::Error>) -> CodegenError {
+        CodegenError::Other(error.into())
+    }
+
+    /// Error returned by pure parser.
+    pub(crate) fn pure_parser(error: anyhow::Error) -> CodegenError {
+        if let Some(with_file) = error
+            .chain()
+            .find_map(|e| e.downcast_ref::<WithFileError>())
+        {
+            let file = with_file.file().to_owned();
+            let parse = error
+                .chain()
+                .find_map(|e| e.downcast_ref::<ParserErrorsWithLocation>())
+                .and_then(|e| e.0.first())
+                .map(|e| (e.line, e.col));
+            let loc = with_file.line_col();
+            let error = error.context("parse and typecheck");
+            return match parse {
+                Some((line, col)) => CodegenError::Parse {
+                    file,
+                    line,
+                    col,
+                    error,
+                },
+                None => CodegenError::Typecheck {
+                    file,
+                    line: loc.map(|(line, _)| line),
+                    col: loc.map(|(_, col)| col),
+                    error,
+                },
+            };
+        }
+        let io = is_io_error(&error);
+        let error = error.context("parse and typecheck");
+        if io {
+            CodegenError::Io { error }
+        } else {
+            CodegenError::Other(error)
+        }
+    }
+}
@@ -0,0 +1,184 @@
+//! Minimal glob expansion for [`Codegen::inputs_glob`](crate::Codegen::inputs_glob).
+//!
+//! Supported syntax is `*` and `?` within a path component,
+//! and `**` as a whole component matching any number of directories.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::path::PathBuf;
+
+#[derive(Debug, thiserror::Error)]
+enum GlobError {
+    #[error("could not read directory `{0}` while expanding glob `{1}`: {2}")]
+    CouldNotReadDir(String, String, io::Error),
+    #[error("glob `{0}` matches no files")]
+    MatchesNothing(String),
+}
+
+/// Does path component `name` match pattern component `pattern`.
+fn matches(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|i| matches(rest, &name[i..])),
+        Some(('?', rest)) => !name.is_empty() && matches(rest, &name[1..]),
+        Some((c, rest)) => name.first() == Some(c) && matches(rest, &name[1..]),
+    }
+}
+
+fn is_pattern(component: &str) -> bool {
+    component.contains(['*', '?'])
+}
+
+fn walk(dir: &Path, components: &[&str], glob: &str, r: &mut Vec<PathBuf>) -> anyhow::Result<()> {
+    let (component, rest) = match components.split_first() {
+        Some(split) => split,
+        None => return Ok(()),
+    };
+    let read_dir = |dir: &Path| {
+        fs::read_dir(dir)
+            .map_err(|e| GlobError::CouldNotReadDir(dir.display().to_string(), glob.to_owned(), e))
+    };
+    if *component == "**" {
+        walk(dir, rest, glob, r)?;
+        for entry in read_dir(dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                walk(&entry.path(), components, glob, r)?;
+            }
+        }
+        return Ok(());
+    }
+    let pattern: Vec<char> = component.chars().collect();
+    for entry in read_dir(dir)? {
+        let entry = entry?;
+        let name: Vec<char> = entry.file_name().to_string_lossy().chars().collect();
+        if !matches(&pattern, &name) {
+            continue;
+        }
+        let is_dir = entry.file_type()?.is_dir();
+        if rest.is_empty() && !is_dir {
+            r.push(entry.path());
+        } else if !rest.is_empty() && is_dir {
+            walk(&entry.path(), rest, glob, r)?;
+        }
+    }
+    Ok(())
+}
+
+/// Paths of files matching `glob`, sorted.
+pub(crate) fn expand(glob: &str, allow_empty: bool) -> anyhow::Result<Vec<PathBuf>> {
+    let components: Vec<&str> = glob.split(['/', '\\']).collect();
+    let literal = components
+        .iter()
+        .position(|c| is_pattern(c))
+        .unwrap_or(components.len());
+    let mut r = Vec::new();
+    if literal == components.len() {
+        if Path::new(glob).is_file() {
+            r.push(PathBuf::from(glob));
+        }
+    } else {
+        let base: PathBuf = match literal {
+            0 => PathBuf::from("."),
+            _ => components[..literal].join("/").into(),
+        };
+        walk(&base, &components[literal..], glob, &mut r)?;
+    }
+    if r.is_empty() && !allow_empty {
+        return Err(GlobError::MatchesNothing(glob.to_owned()).into());
+    }
+    r.sort();
+    r.dedup();
+    Ok(r)
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+    use crate::Codegen;
+
+    #[test]
+    fn test_matches() {
+        let m = |p: &str, n: &str| {
+            matches(
+                &p.chars().collect::<Vec<_>>(),
+                &n.chars().collect::<Vec<_>>(),
+            )
+        };
+        assert!(m("*.proto", "a.proto"));
+        assert!(m("*.proto", ".proto"));
+        assert!(m("a?c", "abc"));
+        assert!(!m("*.proto", "a.rs"));
+        assert!(!m("a?c", "ac"));
+    }
+
+    #[test]
+    fn test_expand() {
+        let dir = tempfile::tempdir().unwrap();
+        let d = dir.path();
+        fs::create_dir_all(d.join("x/y")).unwrap();
+        for name in ["a.proto", "b.txt", "x/c.proto", "x/y/d.proto"] {
+            fs::write(d.join(name), "").unwrap();
+        }
+        let expand = |glob: &str| {
+            expand(&format!("{}/{}", d.display(), glob), false)
+                .map(|r| {
+                    r.iter()
+                        .map(|p| {
+                            p.strip_prefix(d)
+                                .unwrap()
+                                .to_str()
+                                .unwrap()
+                                .replace('\\', "/")
+                        })
+                        .collect::<Vec<_>>()
+                })
+                .map_err(|e| e.to_string())
+        };
+        assert_eq!(Ok(vec!["a.proto".to_owned()]), expand("*.proto"));
+        assert_eq!(
+            Ok(vec![
+                "a.proto".to_owned(),
+                "x/c.proto".to_owned(),
+                "x/y/d.proto".to_owned()
+            ]),
+            expand("**/*.proto")
+        );
+        assert_eq!(Ok(vec!["x/y/d.proto".to_owned()]), expand("*/?/*.proto"));
+        assert!(expand("*.rs").unwrap_err().contains("matches no files"));
+        assert_eq!(Ok(vec!["b.txt".to_owned()]), expand("b.txt"));
+    }
+
+    #[test]
+    fn inputs_glob() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("sub")).unwrap();
+        fs::write(dir.path().join("a.proto"), "syntax = 'proto3';").unwrap();
+        fs::write(dir.path().join("sub").join("b.proto"), "syntax = 'proto3';").unwrap();
+
+        let generated = Codegen::new()
+            .pure()
+            .include(dir.path())
+            .inputs_glob(&format!("{}/**/*.proto", dir.path().display()))
+            .run_to_map()
+            .unwrap();
+        let mut names: Vec<_> = generated.keys().map(|p| p.to_str().unwrap()).collect();
+        names.sort();
+        assert_eq!(vec!["a.rs", "b.rs", "mod.rs"], names);
+
+        let err = Codegen::new()
+            .pure()
+            .include(dir.path())
+            .inputs_glob(&format!("{}/*.txt", dir.path().display()))
+            .run_to_map()
+            .unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("matches no files"),
+            "{:#}",
+            err
+        );
+    }
+}
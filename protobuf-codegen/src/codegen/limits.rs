@@ -0,0 +1,55 @@
+/// Limits on the work done by the parser, for tools parsing untrusted `.proto` files.
+///
+/// Default is no limits. Limits are not supported by `protoc` parser.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CodegenLimits {
+    pub(crate) max_files: Option<usize>,
+    pub(crate) max_import_depth: Option<usize>,
+}
+
+impl CodegenLimits {
+    /// No limits.
+    pub fn new() -> CodegenLimits {
+        CodegenLimits::default()
+    }
+
+    /// Maximum number of files parsed, including imports.
+    pub fn max_files(mut self, max_files: usize) -> Self {
+        self.max_files = Some(max_files);
+        self
+    }
+
+    /// Maximum nesting of imports. Input files have depth `1`.
+    pub fn max_import_depth(mut self, max_import_depth: usize) -> Self {
+        self.max_import_depth = Some(max_import_depth);
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use crate::Codegen;
+    use crate::CodegenLimits;
+
+    #[test]
+    fn limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        fs::write(&a_proto, "syntax = 'proto3'; import 'b.proto';").unwrap();
+        fs::write(dir.path().join("b.proto"), "syntax = 'proto3';").unwrap();
+
+        let run = |limits: CodegenLimits| {
+            Codegen::new()
+                .pure()
+                .include(dir.path())
+                .input(&a_proto)
+                .limits(limits)
+                .run_to_map()
+        };
+        run(CodegenLimits::new().max_files(2).max_import_depth(2)).unwrap();
+        assert!(run(CodegenLimits::new().max_files(1)).is_err());
+        assert!(run(CodegenLimits::new().max_import_depth(1)).is_err());
+    }
+}
@@ -1,17 +1,57 @@
+mod dependency_graph;
+mod env_expand;
+mod error;
+mod glob;
+mod limits;
+mod observer;
+mod output_layout;
+mod report;
+mod required;
+mod rust_keywords;
+
+use std::collections::HashMap;
 use std::env;
 use std::ffi::OsString;
+use std::fmt;
 use std::fs;
+use std::io;
+use std::io::Write;
 use std::path::Component;
 use std::path::Path;
 use std::path::PathBuf;
 use std::process;
+use std::sync::Arc;
 
 use anyhow::Context;
+use protobuf::descriptor::FileDescriptorProto;
+use protobuf::descriptor::FileDescriptorSet;
+use protobuf::reflect::Syntax;
+use protobuf::Message;
+use protobuf_parse::pure::FileLoader;
+use protobuf_parse::pure::WellKnownTypes;
+use protobuf_parse::pure::WellKnownTypesHolder;
+use protobuf_parse::DuplicateIncludePolicy;
+use protobuf_parse::ParsedAndTypechecked;
 use protobuf_parse::Parser;
+use protobuf_parse::ProtoPath;
+use protobuf_parse::ProtoPathBuf;
+use protobuf_parse::Unsupported;
 
+pub use crate::codegen::dependency_graph::DependencyGraphFormat;
+pub use crate::codegen::error::CodegenError;
+pub use crate::codegen::limits::CodegenLimits;
+pub use crate::codegen::observer::CodegenObserver;
+use crate::codegen::observer::CodegenObserverHolder;
+pub use crate::codegen::output_layout::OutputLayout;
+pub use crate::codegen::report::CodegenReport;
+use crate::compiler_plugin;
 use crate::customize::CustomizeCallback;
 use crate::customize::CustomizeCallbackHolder;
-use crate::gen_and_write::gen_and_write;
+use crate::gen::all::gen_all;
+use crate::gen_and_write::check_out_dir;
+use crate::gen_and_write::write_file_atomically;
+use crate::gen_and_write::write_files;
+use crate::gen_and_write::write_with_writer;
 use crate::Customize;
 
 #[derive(Debug)]
@@ -26,12 +66,6 @@ impl Default for WhichParser {
     }
 }
 
-#[derive(Debug, thiserror::Error)]
-enum CodegenError {
-    #[error("out_dir is not specified")]
-    OutDirNotSpecified,
-}
-
 /// Entry point for `.proto` to `.rs` code generation.
 ///
 /// This is similar to `protoc --rust_out...`.
@@ -43,12 +77,24 @@ pub struct Codegen {
     create_out_dir: bool,
     /// --lang_out= param
     out_dir: Option<PathBuf>,
+    /// Fail instead of overwriting files in `out_dir` which were not generated.
+    refuse_overwrite_non_generated: bool,
+    /// Do not write files in `out_dir` which already have the generated content.
+    skip_unchanged: bool,
     /// -I args
     includes: Vec<PathBuf>,
     /// List of .proto files to compile
     inputs: Vec<PathBuf>,
+    /// Globs expanded to .proto files to compile
+    input_globs: Vec<String>,
+    /// Do not fail when an input glob matches no files.
+    allow_empty_globs: bool,
     /// Customize code generation
     customize: Customize,
+    /// Customize code generation of particular files, applied over `customize`
+    customize_files: HashMap<ProtoPathBuf, Customize>,
+    /// Cargo features guarding modules of proto packages, by package prefix.
+    package_features: Vec<(String, String)>,
     /// Customize code generation
     customize_callback: CustomizeCallbackHolder,
     /// Protoc command path
@@ -57,6 +103,86 @@ pub struct Codegen {
     protoc_extra_args: Vec<OsString>,
     /// Capture stderr when running `protoc`.
     capture_stderr: bool,
+    /// `--descriptor_set_out=` param
+    descriptor_set_out: Option<PathBuf>,
+    /// Write import graph to this file.
+    dependency_graph: Option<(PathBuf, DependencyGraphFormat)>,
+    /// `--include_imports`
+    include_imports: bool,
+    /// `--include_source_info`
+    include_source_info: bool,
+    /// Populate `source_code_info` with comments for generated code.
+    capture_comments: bool,
+    /// Print `cargo:rerun-if-changed` for each file read.
+    emit_rerun_if_changed: Option<bool>,
+    /// Do not use protos bundled with the parser.
+    disable_embedded_protos: bool,
+    /// Use protos bundled with the parser even if found in include path.
+    prefer_embedded_well_known: bool,
+    /// Warn when protos found in include path differ from bundled ones.
+    warn_well_known_drift: bool,
+    /// Additional in-memory `.proto` files.
+    extra_embedded_protos: Vec<(ProtoPathBuf, String)>,
+    /// In-memory `.proto` files taking precedence over include path.
+    content_files: Vec<(ProtoPathBuf, String)>,
+    /// In-memory `.proto` files to compile.
+    content_inputs: Vec<ProtoPathBuf>,
+    /// Load `.proto` files before looking them up in include path.
+    file_loader: Option<FileLoaderHolder>,
+    /// What to do when a file is found in more than one include directory.
+    duplicate_include_policy: DuplicateIncludePolicy,
+    /// Look up imports in the include directory of the importing file first.
+    prefer_importer_include: bool,
+    /// Treat parent directories of inputs outside of include path as include directories.
+    auto_include_input_dirs: bool,
+    /// Number of threads used to parse `.proto` files.
+    parallelism: Option<usize>,
+    /// Directory to cache parsed files between runs.
+    cache_dir: Option<PathBuf>,
+    /// Fail on options the parser cannot fully represent.
+    strict: bool,
+    /// Syntax assumed for files without `syntax` or `edition` statement.
+    default_syntax: Option<Syntax>,
+    /// Fail when the parser reported any warnings.
+    deny_warnings: bool,
+    /// Warn about messages containing `required` fields.
+    warn_required: bool,
+    /// Warn about declarations named like Rust keywords.
+    warn_rust_keywords: bool,
+    /// Comment at the top of generated files instead of the default one.
+    header_comment: Option<String>,
+    /// Fail when imports match file names only ignoring case.
+    require_exact_case: Option<bool>,
+    /// Limits on parsed files.
+    limits: CodegenLimits,
+    /// Compiled dependencies used instead of `.proto` files.
+    descriptors: Vec<FileDescriptorProto>,
+    /// Notified about progress.
+    observer: Option<CodegenObserverHolder>,
+    /// Applied to text of each file generated from `.proto` file.
+    post_process: Option<PostProcessHolder>,
+    /// Used instead of bundled well-known type protos.
+    well_known_types: Option<WellKnownTypesHolder>,
+}
+
+#[derive(Clone)]
+struct FileLoaderHolder(Arc<dyn FileLoader>);
+
+impl fmt::Debug for FileLoaderHolder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FileLoaderHolder").finish_non_exhaustive()
+    }
+}
+
+type PostProcessFn = dyn Fn(&ProtoPath, String) -> anyhow::Result<String> + Send + Sync;
+
+#[derive(Clone)]
+struct PostProcessHolder(Arc<PostProcessFn>);
+
+impl fmt::Debug for PostProcessHolder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PostProcessHolder").finish_non_exhaustive()
+    }
 }
 
 impl Codegen {
@@ -90,6 +216,26 @@ impl Codegen {
         self
     }
 
+    /// Fail instead of overwriting an existing file in output directory
+    /// which does not look like a file generated by this crate,
+    /// e.g. when output directory is accidentally pointed at sources.
+    ///
+    /// Off by default.
+    pub fn refuse_overwrite_non_generated(&mut self, refuse: bool) -> &mut Self {
+        self.refuse_overwrite_non_generated = refuse;
+        self
+    }
+
+    /// Do not rewrite files in output directory which already have the generated content,
+    /// so their modification times are preserved and dependent crates are not rebuilt.
+    ///
+    /// Off by default. Has no effect when output directory is recreated,
+    /// like with [`cargo_out_dir`](Self::cargo_out_dir).
+    pub fn skip_unchanged(&mut self, skip_unchanged: bool) -> &mut Self {
+        self.skip_unchanged = skip_unchanged;
+        self
+    }
+
     /// Set output directory relative to Cargo output dir.
     ///
     /// With this option, output directory is erased and recreated during invocation.
@@ -129,6 +275,21 @@ impl Codegen {
         self
     }
 
+    /// Add an include directory, expanding leading `~` to home directory
+    /// and `$VAR` or `${VAR}` to environment variable values,
+    /// like `${OUT_DIR}/protos` or `~/protos`.
+    ///
+    /// Paths are expanded immediately.
+    /// [`include`](Self::include) does not expand anything.
+    ///
+    /// # Panics
+    ///
+    /// If a referenced variable is not set, or `${` is not terminated.
+    pub fn include_env_expanded(&mut self, include: impl AsRef<Path>) -> &mut Self {
+        let include = env_expand::expand(include.as_ref()).unwrap_or_else(|e| panic!("{}", e));
+        self.include(include)
+    }
+
     /// Add include directories.
     pub fn includes(&mut self, includes: impl IntoIterator<Item = impl AsRef<Path>>) -> &mut Self {
         for include in includes {
@@ -151,6 +312,23 @@ impl Codegen {
         self
     }
 
+    /// Append `.proto` files matching a glob like `protos/**/*.proto` to compile.
+    ///
+    /// Relative globs are expanded against the current directory when codegen is run.
+    /// `*` and `?` match within a path component, `**` matches any number of directories.
+    /// Codegen fails if a glob matches no files, unless
+    /// [`allow_empty_globs`](Self::allow_empty_globs) is set.
+    pub fn inputs_glob(&mut self, glob: &str) -> &mut Self {
+        self.input_globs.push(glob.to_owned());
+        self
+    }
+
+    /// Do not fail when a glob passed to [`inputs_glob`](Self::inputs_glob) matches no files.
+    pub fn allow_empty_globs(&mut self, allow_empty_globs: bool) -> &mut Self {
+        self.allow_empty_globs = allow_empty_globs;
+        self
+    }
+
     /// Specify `protoc` command path to be used when invoking code generation.
     ///
     /// # Examples
@@ -194,18 +372,412 @@ impl Codegen {
         self
     }
 
+    /// Also write parsed files as binary `FileDescriptorSet` to specified path.
+    ///
+    /// This is similar to `protoc --descriptor_set_out=...`.
+    /// When this option is set, [`out_dir`](Self::out_dir) is optional,
+    /// and `.rs` files are not generated if output directory is not specified.
+    pub fn descriptor_set_out(&mut self, path: impl AsRef<Path>) -> &mut Self {
+        self.descriptor_set_out = Some(path.as_ref().to_owned());
+        self
+    }
+
+    /// Also write import graph of parsed files to specified path,
+    /// with nodes for input files and their dependencies, and edges for imports.
+    ///
+    /// Like with [`descriptor_set_out`](Self::descriptor_set_out),
+    /// [`out_dir`](Self::out_dir) is optional when this option is set.
+    pub fn emit_dependency_graph(
+        &mut self,
+        path: impl AsRef<Path>,
+        format: DependencyGraphFormat,
+    ) -> &mut Self {
+        self.dependency_graph = Some((path.as_ref().to_owned(), format));
+        self
+    }
+
+    /// Include all dependencies of inputs in the [`descriptor_set_out`](Self::descriptor_set_out).
+    ///
+    /// This is similar to `protoc --include_imports`.
+    pub fn include_imports(&mut self, include_imports: bool) -> &mut Self {
+        self.include_imports = include_imports;
+        self
+    }
+
+    /// Keep `source_code_info` in the [`descriptor_set_out`](Self::descriptor_set_out).
+    ///
+    /// This is similar to `protoc --include_source_info`.
+    pub fn include_source_info(&mut self, include_source_info: bool) -> &mut Self {
+        self.include_source_info = include_source_info;
+        self
+    }
+
+    /// Capture leading and trailing comments of declarations into `source_code_info`,
+    /// so they are emitted as doc comments in generated code.
+    pub fn capture_comments(&mut self, capture_comments: bool) -> &mut Self {
+        self.capture_comments = capture_comments;
+        self
+    }
+
+    /// Print `cargo:rerun-if-changed=...` to stdout for every `.proto` file read,
+    /// including transitive imports of inputs.
+    ///
+    /// This is enabled by default in [`run_from_script`](Self::run_from_script)
+    /// and disabled by default in [`run`](Self::run).
+    pub fn emit_rerun_if_changed(&mut self, emit_rerun_if_changed: bool) -> &mut Self {
+        self.emit_rerun_if_changed = Some(emit_rerun_if_changed);
+        self
+    }
+
+    /// Use well-known type protos and `rustproto.proto` bundled with the parser
+    /// when an import is not found in include path. Enabled by default.
+    ///
+    /// This option is not supported by `protoc` parser.
+    pub fn embedded_protos(&mut self, enabled: bool) -> &mut Self {
+        self.disable_embedded_protos = !enabled;
+        self
+    }
+
+    /// Use well-known type protos and `rustproto.proto` bundled with the parser
+    /// even if a file with the same path is found in include path,
+    /// so generated code does not depend on the version of protos installed.
+    ///
+    /// This option is not supported by `protoc` parser.
+    pub fn prefer_embedded_well_known(&mut self, prefer_embedded_well_known: bool) -> &mut Self {
+        self.prefer_embedded_well_known = prefer_embedded_well_known;
+        self
+    }
+
+    /// Add a [warning](CodegenReport::warnings) when a well-known type proto
+    /// like `google/protobuf/timestamp.proto` found in include path
+    /// differs from the version bundled with the parser, ignoring whitespace,
+    /// to catch descriptors drifting from builds which use bundled protos.
+    ///
+    /// This option is not supported by `protoc` parser.
+    pub fn warn_well_known_drift(&mut self, warn_well_known_drift: bool) -> &mut Self {
+        self.warn_well_known_drift = warn_well_known_drift;
+        self
+    }
+
+    /// Use given well-known type protos instead of the ones bundled with the parser,
+    /// e.g. to match descriptors produced by a particular protobuf release.
+    ///
+    /// This option is not supported by `protoc` parser.
+    pub fn well_known_types(&mut self, well_known_types: impl WellKnownTypes) -> &mut Self {
+        self.well_known_types = Some(WellKnownTypesHolder(Arc::new(well_known_types)));
+        self
+    }
+
+    /// Protobuf release well-known type protos bundled with the parser come from.
+    pub fn well_known_types_version() -> &'static str {
+        Parser::well_known_types_version()
+    }
+
+    /// Register an in-memory `.proto` file to be used
+    /// when an import is not found in include path.
+    ///
+    /// Such files take precedence over bundled protos.
+    ///
+    /// # Panics
+    ///
+    /// If `proto_path` is not a valid relative `.proto` path.
+    pub fn add_embedded_proto(&mut self, proto_path: &str, content: &str) -> &mut Self {
+        let proto_path = ProtoPathBuf::new(proto_path.to_owned())
+            .unwrap_or_else(|e| panic!("invalid proto path `{}`: {}", proto_path, e));
+        self.extra_embedded_protos
+            .push((proto_path, content.to_owned()));
+        self
+    }
+
+    /// Compile an in-memory `.proto` file, e.g. one embedded with `include_str!`,
+    /// so code can be generated with [`run_to_map`](Self::run_to_map)
+    /// without touching the filesystem.
+    ///
+    /// The file is also available for import like one added with
+    /// [`include_content`](Self::include_content).
+    ///
+    /// # Panics
+    ///
+    /// If `proto_path` is not a valid relative `.proto` path.
+    pub fn input_content(&mut self, proto_path: &str, content: &str) -> &mut Self {
+        self.include_content(proto_path, content);
+        let (proto_path, _) = self.content_files.last().unwrap();
+        self.content_inputs.push(proto_path.clone());
+        self
+    }
+
+    /// Register an in-memory `.proto` file available for import.
+    ///
+    /// Unlike [`add_embedded_proto`](Self::add_embedded_proto),
+    /// such files take precedence over files in include path.
+    ///
+    /// # Panics
+    ///
+    /// If `proto_path` is not a valid relative `.proto` path.
+    pub fn include_content(&mut self, proto_path: &str, content: &str) -> &mut Self {
+        let proto_path = ProtoPathBuf::new(proto_path.to_owned())
+            .unwrap_or_else(|e| panic!("invalid proto path `{}`: {}", proto_path, e));
+        self.content_files.push((proto_path, content.to_owned()));
+        self
+    }
+
+    /// Load `.proto` files (including inputs) using given loader
+    /// before looking them up in include path, e.g. from an in-memory map.
+    ///
+    /// This option is not supported by `protoc` parser.
+    pub fn file_loader(&mut self, file_loader: impl FileLoader) -> &mut Self {
+        self.file_loader = Some(FileLoaderHolder(Arc::new(file_loader)));
+        self
+    }
+
+    /// What to do when a `.proto` file is found in more than one include directory.
+    ///
+    /// By default the file from the include directory added first is used, like `protoc` does.
+    /// [`DuplicateIncludePolicy::Error`] makes builds independent of include order.
+    pub fn on_duplicate_include(&mut self, policy: DuplicateIncludePolicy) -> &mut Self {
+        self.duplicate_include_policy = policy;
+        self
+    }
+
+    /// Look up imports in the include directory the importing file was found in first,
+    /// so siblings of an input win over files of the same name in other include directories.
+    ///
+    /// This option is not supported by `protoc` parser.
+    pub fn prefer_importer_include(&mut self, prefer_importer_include: bool) -> &mut Self {
+        self.prefer_importer_include = prefer_importer_include;
+        self
+    }
+
+    /// When an input file is not under any include directory,
+    /// use its parent directory as an implicit include directory.
+    ///
+    /// Disabled by default, in which case such inputs are an error.
+    pub fn auto_include_input_dirs(&mut self, auto_include_input_dirs: bool) -> &mut Self {
+        self.auto_include_input_dirs = auto_include_input_dirs;
+        self
+    }
+
+    /// Parse `.proto` files on up to `parallelism` threads.
+    ///
+    /// Generated code does not depend on this setting.
+    /// This option is not supported by `protoc` parser.
+    pub fn parallelism(&mut self, parallelism: usize) -> &mut Self {
+        self.parallelism = Some(parallelism);
+        self
+    }
+
+    /// Cache parsed `.proto` files in given directory, so build scripts
+    /// do not parse unchanged files on every invocation.
+    ///
+    /// A file is reparsed when it or any of its transitive imports change.
+    /// This option is not supported by `protoc` parser.
+    pub fn cache_dir(&mut self, cache_dir: impl AsRef<Path>) -> &mut Self {
+        self.cache_dir = Some(cache_dir.as_ref().to_owned());
+        self
+    }
+
+    /// Fail on options which the pure parser cannot fully represent
+    /// instead of dropping them from generated descriptors,
+    /// and on files without `syntax` or `edition` statement.
+    ///
+    /// When not strict, dropped options are listed in [`CodegenReport::warnings`].
+    /// This option is not supported by `protoc` parser.
+    pub fn strict(&mut self, strict: bool) -> &mut Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Syntax assumed for files without `syntax` or `edition` statement,
+    /// proto2 by default.
+    ///
+    /// Files with explicit declaration are not affected. In [`strict`](Self::strict) mode
+    /// files without declaration are errors.
+    /// This option is not supported by `protoc` parser.
+    pub fn default_syntax(&mut self, default_syntax: Syntax) -> &mut Self {
+        self.default_syntax = Some(default_syntax);
+        self
+    }
+
+    /// Fail if parsing produced any [warnings](CodegenReport::warnings),
+    /// with an error listing all of them.
+    ///
+    /// Unlike [`strict`](Self::strict), files are still parsed leniently,
+    /// so all problems are reported at once.
+    ///
+    /// Declarations listed in [`CodegenReport::deprecated`] are not warnings
+    /// and are not denied. With `protoc` parser only warnings enabled by
+    /// [`warn_required`](Self::warn_required) and
+    /// [`warn_rust_keywords`](Self::warn_rust_keywords) are denied,
+    /// warnings printed by `protoc` itself are not.
+    pub fn deny_warnings(&mut self, deny_warnings: bool) -> &mut Self {
+        self.deny_warnings = deny_warnings;
+        self
+    }
+
+    /// Replace comment at the top of generated files,
+    /// like `This file is generated by rust-protobuf 3.x.x. Do not edit`.
+    ///
+    /// Each line is written as a `//` comment, e.g. to include license or SPDX header.
+    /// `{version}` is replaced with rust-protobuf version
+    /// and `{parser}` with parser description. Empty string disables the comment.
+    ///
+    /// `@generated` marker and lint attributes are written regardless.
+    pub fn header_comment(&mut self, header_comment: impl Into<String>) -> &mut Self {
+        self.header_comment = Some(header_comment.into());
+        self
+    }
+
+    /// Add a [warning](CodegenReport::warnings) for each message in input files
+    /// which contains proto2 `required` fields, directly or through nested message fields,
+    /// with the chain of fields leading to the `required` field.
+    ///
+    /// Messages with missing `required` fields fail to parse,
+    /// which is a common source of errors when messages evolve.
+    pub fn warn_required(&mut self, warn_required: bool) -> &mut Self {
+        self.warn_required = warn_required;
+        self
+    }
+
+    /// Add a [warning](CodegenReport::warnings) for each message, enum, enum value,
+    /// field or oneof in input files named like a Rust keyword, e.g. `type` or `self`,
+    /// with its location.
+    ///
+    /// Such names are valid in protobuf and are escaped with `_` suffix
+    /// in generated code, so field `type` becomes `type_`.
+    /// Combined with [`deny_warnings`](Self::deny_warnings) they are errors.
+    pub fn warn_rust_keywords(&mut self, warn_rust_keywords: bool) -> &mut Self {
+        self.warn_rust_keywords = warn_rust_keywords;
+        self
+    }
+
+    /// Fail when an imported file is found only because filesystem is case-insensitive,
+    /// like `import "Foo.proto"` resolving to `foo.proto` on macOS or Windows,
+    /// so such imports are caught before they break builds on Linux.
+    ///
+    /// Enabled by default. This option is not supported by `protoc` parser.
+    pub fn require_exact_case(&mut self, require_exact_case: bool) -> &mut Self {
+        self.require_exact_case = Some(require_exact_case);
+        self
+    }
+
+    /// Fail when parsing `.proto` files exceeds given limits.
+    ///
+    /// Useful when parsing untrusted input. There are no limits by default.
+    /// Limits are not supported by `protoc` parser.
+    pub fn limits(&mut self, limits: CodegenLimits) -> &mut Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Compile inputs against already compiled file, which is used
+    /// instead of looking it up in include path when imported.
+    /// Its dependencies which are not added too are looked up in include path.
+    ///
+    /// Code is not generated for added descriptors.
+    pub fn add_descriptor(&mut self, descriptor: FileDescriptorProto) -> &mut Self {
+        self.descriptors.push(descriptor);
+        self
+    }
+
+    /// Compile inputs against already compiled files,
+    /// like output of `protoc --descriptor_set_out`.
+    ///
+    /// Code is not generated for added descriptors.
+    pub fn add_descriptor_set(&mut self, descriptor_set: FileDescriptorSet) -> &mut Self {
+        self.descriptors.extend(descriptor_set.file);
+        self
+    }
+
     /// Set options to customize code generation
     pub fn customize(&mut self, customize: Customize) -> &mut Self {
         self.customize.update_with(&customize);
         self
     }
 
+    /// Where generated files are placed in the output directory,
+    /// [`OutputLayout::IncludeRelative`] by default.
+    pub fn output_layout(&mut self, output_layout: OutputLayout) -> &mut Self {
+        self.customize.output_layout = Some(output_layout);
+        self
+    }
+
+    /// Omit package prefix like `com.example.internal` from module paths
+    /// of generated files with [`OutputLayout::Package`].
+    ///
+    /// Only Rust module layout is affected, descriptors keep full package names.
+    pub fn strip_package_prefix(&mut self, prefix: impl Into<String>) -> &mut Self {
+        self.customize.strip_package_prefix = Some(prefix.into());
+        self
+    }
+
+    /// Generate a file declaring all the generated modules, so the output
+    /// can be included with a single `include!` or `mod` declaration.
+    ///
+    /// This is a shortcut for [`Customize::gen_mod_rs`], which is on by default.
+    /// File name and re-exports are configured with [`Customize::mod_rs_name`]
+    /// and [`Customize::mod_rs_reexport`]. Generated files are placed
+    /// directly in output directory regardless of `.proto` file directories,
+    /// so modules are declared at the same level.
+    pub fn generate_mod_rs(&mut self, generate_mod_rs: bool) -> &mut Self {
+        self.customize.gen_mod_rs = Some(generate_mod_rs);
+        self
+    }
+
+    /// Set options to customize code generation of one file,
+    /// applied over options set with [`customize`](Self::customize).
+    ///
+    /// Options specified in `.proto` file itself, and options returned by
+    /// [`customize_callback`](Self::customize_callback), take precedence.
+    ///
+    /// # Panics
+    ///
+    /// If `proto_path` is not a valid relative `.proto` path.
+    pub fn customize_file(&mut self, proto_path: &str, customize: Customize) -> &mut Self {
+        let proto_path = ProtoPathBuf::new(proto_path.to_owned())
+            .unwrap_or_else(|e| panic!("invalid proto path `{}`: {}", proto_path, e));
+        self.customize_files
+            .entry(proto_path)
+            .or_default()
+            .update_with(&customize);
+        self
+    }
+
+    /// Declare modules generated for proto package `package` and its subpackages
+    /// in `mod.rs` only when cargo feature `feature` is enabled.
+    ///
+    /// When several prefixes match a package, the longest one is used.
+    /// [`Customize::mod_feature`] set for a file takes precedence.
+    pub fn package_feature(&mut self, package: &str, feature: &str) -> &mut Self {
+        self.package_features
+            .push((package.to_owned(), feature.to_owned()));
+        self
+    }
+
+    /// Notify given observer when files are parsed, typechecked and written.
+    pub fn observer(&mut self, observer: impl CodegenObserver) -> &mut Self {
+        self.observer = Some(CodegenObserverHolder(Arc::new(observer)));
+        self
+    }
+
     /// Callback for dynamic per-element customization.
     pub fn customize_callback(&mut self, callback: impl CustomizeCallback) -> &mut Self {
         self.customize_callback = CustomizeCallbackHolder::new(callback);
         self
     }
 
+    /// Transform text of each file generated from `.proto` file before it is written
+    /// or returned, e.g. to run `rustfmt` or to add derives.
+    ///
+    /// The function is called with proto path of the source file, like `foo/bar.proto`.
+    /// `mod.rs` is not post-processed.
+    pub fn post_process(
+        &mut self,
+        post_process: impl Fn(&ProtoPath, String) -> anyhow::Result<String> + Send + Sync + 'static,
+    ) -> &mut Self {
+        self.post_process = Some(PostProcessHolder(Arc::new(post_process)));
+        self
+    }
+
     /// Invoke the code generation.
     ///
     /// This is roughly equivalent to `protoc --rust_out=...` but
@@ -214,18 +786,329 @@ impl Codegen {
     /// This function uses pure Rust parser or `protoc` parser depending on
     /// how this object was configured.
     pub fn run(&self) -> anyhow::Result<()> {
-        let out_dir = match &self.out_dir {
-            Some(out_dir) => out_dir,
-            None => return Err(CodegenError::OutDirNotSpecified.into()),
-        };
+        Ok(self.run_typed()?)
+    }
+
+    /// Same as [`run`](Self::run), but return an error which can be matched
+    /// on to tell missing files from invalid files or output failures.
+    pub fn run_typed(&self) -> Result<(), CodegenError> {
+        let report = self.run_with_report_typed()?;
+        if self.emit_rerun_if_changed.unwrap_or(false) {
+            report.emit_rerun_if_changed();
+        }
+        Ok(())
+    }
+
+    /// Same as [`run`](Self::run), but also return the list of files read and written.
+    pub fn run_with_report(&self) -> anyhow::Result<CodegenReport> {
+        Ok(self.run_with_report_typed()?)
+    }
+
+    fn run_with_report_typed(&self) -> Result<CodegenReport, CodegenError> {
+        if self.out_dir.is_none()
+            && self.descriptor_set_out.is_none()
+            && self.dependency_graph.is_none()
+        {
+            return Err(CodegenError::OutDirNotSpecified);
+        }
+
+        if let Some(out_dir) = &self.out_dir {
+            if self.create_out_dir {
+                if out_dir.exists() {
+                    fs::remove_dir_all(out_dir).map_err(CodegenError::write)?;
+                }
+                fs::create_dir(out_dir).map_err(CodegenError::write)?;
+            }
+
+            check_out_dir(out_dir).map_err(CodegenError::write)?;
+        }
+
+        let parsed_and_typechecked = self.parse_and_typecheck()?;
+
+        let mut output_files = Vec::new();
+
+        if let Some(descriptor_set_out) = &self.descriptor_set_out {
+            let fds = self.file_descriptor_set(&parsed_and_typechecked);
+            let bytes = fds.write_to_bytes().map_err(CodegenError::other)?;
+            write_file_atomically(descriptor_set_out, &bytes).map_err(CodegenError::write)?;
+            output_files.push(descriptor_set_out.clone());
+        }
+
+        if let Some((path, format)) = &self.dependency_graph {
+            let graph = dependency_graph::dependency_graph(
+                &parsed_and_typechecked.file_descriptors,
+                *format,
+            );
+            write_file_atomically(path, graph.as_bytes()).map_err(CodegenError::write)?;
+            output_files.push(path.clone());
+        }
+
+        if let Some(out_dir) = &self.out_dir {
+            let generated = self
+                .gen(&parsed_and_typechecked)
+                .map_err(CodegenError::other)?;
+
+            let mut files: Vec<_> = generated
+                .iter()
+                .map(|(name, content)| (name.as_path(), content.as_slice()))
+                .collect();
+            files.sort();
+            write_files(
+                out_dir,
+                files,
+                self.refuse_overwrite_non_generated,
+                self.skip_unchanged,
+                self.observer.as_ref().map(|o| &*o.0),
+            )
+            .map_err(CodegenError::write)?;
+
+            let mut generated_files: Vec<_> =
+                generated.keys().map(|name| out_dir.join(name)).collect();
+            generated_files.sort();
+            output_files.extend(generated_files);
+        }
 
-        if self.create_out_dir {
-            if out_dir.exists() {
-                fs::remove_dir_all(&out_dir)?;
+        let deprecated = report::deprecated_symbols(
+            parsed_and_typechecked.file_descriptors.iter().filter(|f| {
+                parsed_and_typechecked
+                    .relative_paths
+                    .iter()
+                    .any(|p| p.to_str() == f.name())
+            }),
+        );
+
+        Ok(CodegenReport {
+            output_files,
+            read_files: parsed_and_typechecked.read_files,
+            embedded_files: parsed_and_typechecked.embedded_files,
+            warnings: parsed_and_typechecked.warnings,
+            deprecated,
+            well_known_types_version: match (&self.which_parser, &self.well_known_types) {
+                (Some(WhichParser::Pure), Some(well_known_types)) => {
+                    Some(well_known_types.0.version().to_owned())
+                }
+                (Some(WhichParser::Pure), None) => {
+                    Some(Codegen::well_known_types_version().to_owned())
+                }
+                // `protoc` uses protos bundled with it.
+                _ => None,
+            },
+        })
+    }
+
+    /// Invoke the code generation, but return generated files instead of writing them.
+    ///
+    /// Keys of the returned map are file paths relative to the output directory,
+    /// values are file contents. [`out_dir`](Self::out_dir) is ignored by this function.
+    pub fn run_to_map(&self) -> anyhow::Result<HashMap<PathBuf, Vec<u8>>> {
+        let parsed_and_typechecked = self.parse_and_typecheck()?;
+        self.gen(&parsed_and_typechecked)
+    }
+
+    /// Invoke the code generation, writing each generated file to a writer
+    /// returned by `writer` for the file path relative to the output directory,
+    /// like `foo/bar.rs` or `mod.rs`.
+    ///
+    /// Files are written in order of their paths.
+    /// [`out_dir`](Self::out_dir) and [`descriptor_set_out`](Self::descriptor_set_out)
+    /// are ignored by this function.
+    pub fn run_with_writer(
+        &self,
+        writer: impl FnMut(&ProtoPath) -> io::Result<Box<dyn Write>>,
+    ) -> anyhow::Result<()> {
+        let generated = self.run_to_map()?;
+        let mut files = generated
+            .iter()
+            .map(|(name, content)| Ok((ProtoPathBuf::from_path(name)?, content.as_slice())))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        files.sort_by(|a, b| a.0.to_str().cmp(b.0.to_str()));
+        write_with_writer(
+            files
+                .iter()
+                .map(|(name, content)| (name.as_path(), *content)),
+            writer,
+            self.observer.as_ref().map(|o| &*o.0),
+        )
+    }
+
+    /// Parse and typecheck inputs, and return all parsed files as `FileDescriptorSet`.
+    ///
+    /// The result contains input files and all their dependencies,
+    /// dependencies are placed before files which import them
+    /// (like `protoc --include_imports --descriptor_set_out=...` does).
+    pub fn parse_to_file_descriptor_set(&self) -> anyhow::Result<FileDescriptorSet> {
+        let parsed_and_typechecked = self.parse_and_typecheck()?;
+        let mut fds = FileDescriptorSet::new();
+        fds.file = parsed_and_typechecked.file_descriptors;
+        Ok(fds)
+    }
+
+    /// Parse inputs and return import graph of input files and all their dependencies
+    /// without generating code.
+    pub fn dependency_graph(&self, format: DependencyGraphFormat) -> anyhow::Result<String> {
+        let parsed_and_typechecked = self.parse_and_typecheck()?;
+        Ok(dependency_graph::dependency_graph(
+            &parsed_and_typechecked.file_descriptors,
+            format,
+        ))
+    }
+
+    /// Parse and typecheck inputs with pure parser without generating code,
+    /// and list constructs it cannot fully represent.
+    ///
+    /// Useful to check compatibility of schemas when migrating from `protoc`.
+    /// Configured parser and [`strict`](Self::strict) mode are ignored.
+    ///
+    /// Only option values which pure parser drops are reported:
+    /// options of group type, and extension fields and `Any` type URLs
+    /// in message option values.
+    /// Files using editions other than 2023, or setting `features` options,
+    /// are not reported, parsing them fails instead.
+    /// Descriptors added with [`add_descriptor`](Self::add_descriptor) are not parsed,
+    /// so their options are not checked.
+    pub fn check_only(&self) -> anyhow::Result<Vec<Unsupported>> {
+        let mut parser = self.parser()?;
+        parser.pure();
+        parser.strict(false);
+        let parsed_and_typechecked = parser
+            .parse_and_typecheck()
+            .context("parse and typecheck")?;
+        Ok(parsed_and_typechecked.unsupported)
+    }
+
+    /// Parse all inputs and their imports,
+    /// but generate and return code only for one file given by proto path, like `foo/bar.proto`.
+    ///
+    /// The file may be an input or an import of an input. `mod.rs` is not generated.
+    pub fn run_single_to_string(&self, proto_path: &str) -> anyhow::Result<String> {
+        let parsed_and_typechecked = self.parse_and_typecheck()?;
+        let proto_path = ProtoPathBuf::new(proto_path.to_owned())?;
+        if !parsed_and_typechecked
+            .file_descriptors
+            .iter()
+            .any(|f| f.name() == proto_path.to_str())
+        {
+            return Err(CodegenError::FileNotParsed(proto_path.to_string()).into());
+        }
+        let results =
+            self.gen_results(&parsed_and_typechecked, std::slice::from_ref(&proto_path))?;
+        let result = results
+            .into_iter()
+            .find(|r| r.source.as_ref() == Some(&proto_path))
+            .expect("file is generated");
+        Ok(String::from_utf8(result.content)?)
+    }
+
+    fn gen(
+        &self,
+        parsed_and_typechecked: &ParsedAndTypechecked,
+    ) -> anyhow::Result<HashMap<PathBuf, Vec<u8>>> {
+        let results = self.gen_results(
+            parsed_and_typechecked,
+            &parsed_and_typechecked.relative_paths,
+        )?;
+
+        Ok(results
+            .into_iter()
+            .map(|r| (PathBuf::from(r.name), r.content))
+            .collect())
+    }
+
+    fn gen_results(
+        &self,
+        parsed_and_typechecked: &ParsedAndTypechecked,
+        files_to_generate: &[ProtoPathBuf],
+    ) -> anyhow::Result<Vec<compiler_plugin::GenResult>> {
+        let mut results = gen_all(
+            &parsed_and_typechecked.file_descriptors,
+            &parsed_and_typechecked.parser,
+            files_to_generate,
+            &self.customize,
+            &self.customize_files_with_features(parsed_and_typechecked),
+            &*self.customize_callback,
+            self.header_comment.as_deref(),
+        )?;
+        if let Some(post_process) = &self.post_process {
+            for result in &mut results {
+                let proto_path = match &result.source {
+                    Some(proto_path) => proto_path,
+                    None => continue,
+                };
+                let content = String::from_utf8(std::mem::take(&mut result.content))?;
+                let content = (post_process.0)(proto_path, content)
+                    .with_context(|| format!("post-processing `{}`", proto_path))?;
+                result.content = content.into_bytes();
+            }
+        }
+        Ok(results)
+    }
+
+    /// Per-file options with [`mod_feature`](Customize::mod_feature)
+    /// set from [`package_feature`](Self::package_feature).
+    fn customize_files_with_features(
+        &self,
+        parsed_and_typechecked: &ParsedAndTypechecked,
+    ) -> HashMap<ProtoPathBuf, Customize> {
+        let mut customize_files = self.customize_files.clone();
+        if self.package_features.is_empty() {
+            return customize_files;
+        }
+        for path in &parsed_and_typechecked.relative_paths {
+            let package = match parsed_and_typechecked
+                .file_descriptors
+                .iter()
+                .find(|fd| fd.name() == path.to_str())
+            {
+                Some(fd) => fd.package(),
+                None => continue,
+            };
+            let feature = self
+                .package_features
+                .iter()
+                .filter(|(prefix, _)| {
+                    package == prefix
+                        || package
+                            .strip_prefix(prefix.as_str())
+                            .is_some_and(|rem| rem.starts_with('.'))
+                })
+                .max_by_key(|(prefix, _)| prefix.len());
+            if let Some((_, feature)) = feature {
+                let customize = customize_files.entry(path.clone()).or_default();
+                if customize.mod_feature.is_none() {
+                    customize.mod_feature = Some(feature.clone());
+                }
             }
-            fs::create_dir(&out_dir)?;
         }
+        customize_files
+    }
 
+    /// `FileDescriptorSet` to be written to [`descriptor_set_out`](Self::descriptor_set_out).
+    fn file_descriptor_set(
+        &self,
+        parsed_and_typechecked: &ParsedAndTypechecked,
+    ) -> FileDescriptorSet {
+        let mut fds = FileDescriptorSet::new();
+        fds.file = parsed_and_typechecked
+            .file_descriptors
+            .iter()
+            .filter(|fd| {
+                self.include_imports
+                    || parsed_and_typechecked
+                        .relative_paths
+                        .iter()
+                        .any(|p| p.to_str() == fd.name())
+            })
+            .cloned()
+            .collect();
+        if !self.include_source_info {
+            for fd in &mut fds.file {
+                fd.source_code_info.clear();
+            }
+        }
+        fds
+    }
+
+    fn parser(&self) -> anyhow::Result<Parser> {
         let mut parser = Parser::new();
         parser.protoc();
         if let Some(protoc) = &self.protoc {
@@ -242,32 +1125,919 @@ impl Codegen {
         }
 
         parser.inputs(&self.inputs);
+        for input_glob in &self.input_globs {
+            parser.inputs(glob::expand(input_glob, self.allow_empty_globs)?);
+        }
         parser.includes(&self.includes);
         parser.protoc_extra_args(&self.protoc_extra_args);
+        parser.include_source_info(self.source_info_needed());
 
         if self.capture_stderr {
             parser.capture_stderr();
         }
 
-        let parsed_and_typechecked = parser
-            .parse_and_typecheck()
-            .context("parse and typecheck")?;
+        parser.embedded_protos(!self.disable_embedded_protos);
+        parser.prefer_embedded_well_known(self.prefer_embedded_well_known);
+        parser.warn_well_known_drift(self.warn_well_known_drift);
+        for (proto_path, content) in &self.extra_embedded_protos {
+            parser.embedded_proto(proto_path.clone(), content.as_str());
+        }
+        for (proto_path, content) in &self.content_files {
+            if self.content_inputs.contains(proto_path) {
+                parser.input_content(proto_path.clone(), content.as_str());
+            } else {
+                parser.include_content(proto_path.clone(), content.as_str());
+            }
+        }
+        if let Some(file_loader) = &self.file_loader {
+            parser.file_loader(file_loader.0.clone());
+        }
+        if let Some(well_known_types) = &self.well_known_types {
+            parser.well_known_types(well_known_types.0.clone());
+        }
+        parser.on_duplicate_include(self.duplicate_include_policy);
+        parser.prefer_importer_include(self.prefer_importer_include);
+        parser.auto_include_input_dirs(self.auto_include_input_dirs);
+        if let Some(parallelism) = self.parallelism {
+            parser.parallelism(parallelism);
+        }
+        if let Some(cache_dir) = &self.cache_dir {
+            parser.cache_dir(cache_dir);
+        }
+        parser.strict(self.strict);
+        if let Some(default_syntax) = self.default_syntax {
+            parser.default_syntax(default_syntax);
+        }
+        if let Some(require_exact_case) = self.require_exact_case {
+            parser.require_exact_case(require_exact_case);
+        }
+        if let Some(max_files) = self.limits.max_files {
+            parser.max_files(max_files);
+        }
+        if let Some(max_import_depth) = self.limits.max_import_depth {
+            parser.max_import_depth(max_import_depth);
+        }
+        for descriptor in &self.descriptors {
+            parser.add_descriptor(descriptor.clone());
+        }
+        if let (Some(observer), Some(WhichParser::Pure)) = (&self.observer, &self.which_parser) {
+            parser.observer(observer.clone());
+        }
+        Ok(parser)
+    }
 
-        gen_and_write(
-            &parsed_and_typechecked.file_descriptors,
-            &parsed_and_typechecked.parser,
-            &parsed_and_typechecked.relative_paths,
-            &out_dir,
-            &self.customize,
-            &*self.customize_callback,
-        )
+    fn source_info_needed(&self) -> bool {
+        self.capture_comments || (self.descriptor_set_out.is_some() && self.include_source_info)
+    }
+
+    fn parse_and_typecheck(&self) -> Result<ParsedAndTypechecked, CodegenError> {
+        // Only fails when input globs cannot be expanded.
+        let mut parser = self.parser().map_err(CodegenError::io)?;
+        // Locations for warnings.
+        if self.warn_rust_keywords {
+            parser.include_source_info(true);
+        }
+        let mut parsed_and_typechecked =
+            parser
+                .parse_and_typecheck()
+                .map_err(|error| match self.which_parser {
+                    Some(WhichParser::Pure) => CodegenError::pure_parser(error),
+                    _ => CodegenError::Protoc {
+                        error: error.context("parse and typecheck"),
+                    },
+                })?;
+        let is_input = |f: &FileDescriptorProto| {
+            parsed_and_typechecked
+                .relative_paths
+                .iter()
+                .any(|p| p.to_str() == f.name())
+        };
+        if self.warn_required {
+            let inputs = parsed_and_typechecked
+                .file_descriptors
+                .iter()
+                .filter(|f| is_input(f));
+            let warnings =
+                required::required_warnings(inputs, &parsed_and_typechecked.file_descriptors);
+            parsed_and_typechecked.warnings.extend(warnings);
+        }
+        if self.warn_rust_keywords {
+            let inputs = parsed_and_typechecked
+                .file_descriptors
+                .iter()
+                .filter(|f| is_input(f));
+            let warnings = rust_keywords::rust_keyword_warnings(inputs);
+            parsed_and_typechecked.warnings.extend(warnings);
+            if !self.source_info_needed() {
+                for fd in &mut parsed_and_typechecked.file_descriptors {
+                    fd.source_code_info.clear();
+                }
+            }
+        }
+        if self.deny_warnings && !parsed_and_typechecked.warnings.is_empty() {
+            return Err(CodegenError::WarningsDenied(
+                parsed_and_typechecked.warnings,
+            ));
+        }
+        Ok(parsed_and_typechecked)
     }
 
     /// Similar to `run`, but prints the message to stderr and exits the process on error.
     pub fn run_from_script(&self) {
-        if let Err(e) = self.run() {
-            eprintln!("codegen failed: {:?}", e);
-            process::exit(1);
+        match self.run_with_report() {
+            Ok(report) => {
+                if self.emit_rerun_if_changed.unwrap_or(true) {
+                    report.emit_rerun_if_changed();
+                }
+            }
+            Err(e) => {
+                eprintln!("codegen failed: {:?}", e);
+                process::exit(1);
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::env;
+    use std::fs;
+    use std::io;
+    use std::path::Path;
+    use std::path::PathBuf;
+
+    use protobuf::descriptor::file_options::OptimizeMode;
+    use protobuf::descriptor::FileDescriptorSet;
+    use protobuf::reflect::Syntax;
+    use protobuf::Message;
+    use protobuf_parse::ProtoPath;
+
+    use crate::Codegen;
+    use crate::CodegenError;
+    use crate::Customize;
+    use crate::WellKnownTypes;
+
+    #[test]
+    fn run_to_map() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        fs::write(&a_proto, "syntax = 'proto3'; message A { int32 x = 1; }").unwrap();
+
+        let generated = Codegen::new()
+            .pure()
+            .include(dir.path())
+            .input(&a_proto)
+            .run_to_map()
+            .unwrap();
+
+        let mut names: Vec<_> = generated.keys().cloned().collect();
+        names.sort();
+        assert_eq!(vec![PathBuf::from("a.rs"), PathBuf::from("mod.rs")], names);
+        let a = String::from_utf8(generated[Path::new("a.rs")].clone()).unwrap();
+        assert!(a.contains("pub struct A {"), "{}", a);
+        // Nothing is written.
+        assert_eq!(1, fs::read_dir(dir.path()).unwrap().count());
+    }
+
+    #[test]
+    fn descriptor_set_out() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        let b_proto = dir.path().join("b.proto");
+        let fds_path = dir.path().join("fds.bin");
+        fs::write(&a_proto, "syntax = 'proto3'; message A {}").unwrap();
+        fs::write(
+            &b_proto,
+            "syntax = 'proto3'; import 'a.proto'; message B { A a = 1; }",
+        )
+        .unwrap();
+
+        let run = |include_imports| {
+            Codegen::new()
+                .pure()
+                .include(dir.path())
+                .input(&b_proto)
+                .descriptor_set_out(&fds_path)
+                .include_imports(include_imports)
+                .run()
+                .unwrap();
+            let fds = FileDescriptorSet::parse_from_bytes(&fs::read(&fds_path).unwrap()).unwrap();
+            fds.file
+                .iter()
+                .map(|f| f.name().to_owned())
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(vec!["b.proto"], run(false));
+        assert_eq!(vec!["a.proto", "b.proto"], run(true));
+        let fds = FileDescriptorSet::parse_from_bytes(&fs::read(&fds_path).unwrap()).unwrap();
+        assert_eq!(".A", fds.file[1].message_type[0].field[0].type_name());
+    }
+
+    #[test]
+    fn customize_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        let b_proto = dir.path().join("b.proto");
+        fs::write(&a_proto, "syntax = 'proto3'; message A {}").unwrap();
+        fs::write(&b_proto, "syntax = 'proto3'; message B {}").unwrap();
+
+        let generated = Codegen::new()
+            .pure()
+            .include(dir.path())
+            .inputs([&a_proto, &b_proto])
+            .customize(Customize::default().gen_mod_rs(false))
+            .customize_file("b.proto", Customize::default().lite_runtime(true))
+            .run_to_map()
+            .unwrap();
+
+        let has_descriptor = |name: &str| {
+            String::from_utf8_lossy(&generated[Path::new(name)]).contains("fn file_descriptor()")
+        };
+        assert!(has_descriptor("a.rs"));
+        assert!(!has_descriptor("b.rs"));
+        assert!(!generated.contains_key(Path::new("mod.rs")));
+    }
+
+    #[test]
+    fn rustproto_options() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        fs::write(
+            &a_proto,
+            r#"syntax = "proto3";
+            import "rustproto.proto";
+            option (rustproto.lite_runtime_all) = true;
+            message A {
+                option (rustproto.generate_accessors) = true;
+                int32 x = 1;
+                int32 y = 2 [(rustproto.generate_accessors_field) = false];
+            }
+            message B { int32 z = 1; }
+            "#,
+        )
+        .unwrap();
+
+        let generated = Codegen::new()
+            .pure()
+            .include(dir.path())
+            .input(&a_proto)
+            .run_to_map()
+            .unwrap();
+        let a_rs = String::from_utf8_lossy(&generated[Path::new("a.rs")]).into_owned();
+
+        assert!(!a_rs.contains("fn file_descriptor()"));
+        assert!(a_rs.contains("pub fn set_x("));
+        assert!(!a_rs.contains("pub fn set_y("));
+        assert!(!a_rs.contains("pub fn set_z("));
+    }
+
+    #[test]
+    fn run_with_writer() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        fs::write(&a_proto, "syntax = 'proto3'; message A {}").unwrap();
+
+        let mut written = Vec::new();
+        Codegen::new()
+            .pure()
+            .include(dir.path())
+            .input(&a_proto)
+            .run_with_writer(|name| {
+                written.push(name.to_str().to_owned());
+                Ok(Box::new(io::sink()))
+            })
+            .unwrap();
+        assert_eq!(vec!["a.rs", "mod.rs"], written);
+    }
+
+    #[test]
+    fn generate_mod_rs() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        let b_proto = dir.path().join("sub").join("b.proto");
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(
+            &a_proto,
+            "syntax = 'proto3'; message A {} enum E { X = 0; }",
+        )
+        .unwrap();
+        fs::write(
+            &b_proto,
+            "syntax = 'proto3'; message B { map<string, B> m = 1; }",
+        )
+        .unwrap();
+
+        let generated = Codegen::new()
+            .pure()
+            .include(dir.path())
+            .inputs([&b_proto, &a_proto])
+            .generate_mod_rs(true)
+            .customize(
+                Customize::default()
+                    .mod_rs_name("protos.rs")
+                    .mod_rs_reexport(true),
+            )
+            .run_to_map()
+            .unwrap();
+
+        assert!(!generated.contains_key(Path::new("mod.rs")));
+        assert_eq!(
+            "// @generated\n\
+            \n\
+            pub mod a;\n\
+            pub mod b;\n\
+            \n\
+            pub use self::a::A;\n\
+            pub use self::a::E;\n\
+            pub use self::b::B;\n",
+            String::from_utf8_lossy(&generated[Path::new("protos.rs")])
+        );
+    }
+
+    #[test]
+    fn generate_mod_rs_reexport_collision() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        let b_proto = dir.path().join("b.proto");
+        fs::write(&a_proto, "syntax = 'proto3'; package p; message M {}").unwrap();
+        fs::write(&b_proto, "syntax = 'proto3'; package q; message M {}").unwrap();
+
+        let err = Codegen::new()
+            .pure()
+            .include(dir.path())
+            .inputs([&a_proto, &b_proto])
+            .customize(Customize::default().mod_rs_reexport(true))
+            .run_to_map()
+            .unwrap_err();
+        assert!(
+            format!("{:#}", err).contains(
+                "cannot re-export `M` from both `a` and `b` in mod.rs, \
+                disable `mod_rs_reexport` or rename one of them"
+            ),
+            "{:#}",
+            err
+        );
+    }
+
+    #[test]
+    fn package_feature() {
+        let dir = tempfile::tempdir().unwrap();
+        let inputs: Vec<PathBuf> = [
+            ("a", "foo"),
+            ("b", "foo.bar"),
+            ("c", "foobar"),
+            ("d", "foo.bar.baz"),
+        ]
+        .iter()
+        .map(|(name, package)| {
+            let path = dir.path().join(format!("{}.proto", name));
+            fs::write(
+                &path,
+                format!(
+                    "syntax = 'proto3'; package {}; message {} {{}}",
+                    package,
+                    name.to_uppercase()
+                ),
+            )
+            .unwrap();
+            path
+        })
+        .collect();
+
+        let generated = Codegen::new()
+            .pure()
+            .include(dir.path())
+            .inputs(&inputs)
+            .package_feature("foo", "foo")
+            .package_feature("foo.bar", "bar")
+            .customize_file("d.proto", Customize::default().mod_feature("baz"))
+            .customize(Customize::default().mod_rs_reexport(true))
+            .run_to_map()
+            .unwrap();
+
+        assert_eq!(
+            "// @generated\n\
+            \n\
+            #[cfg(feature = \"foo\")]\n\
+            pub mod a;\n\
+            #[cfg(feature = \"bar\")]\n\
+            pub mod b;\n\
+            pub mod c;\n\
+            #[cfg(feature = \"baz\")]\n\
+            pub mod d;\n\
+            \n\
+            #[cfg(feature = \"foo\")]\n\
+            pub use self::a::A;\n\
+            #[cfg(feature = \"bar\")]\n\
+            pub use self::b::B;\n\
+            pub use self::c::C;\n\
+            #[cfg(feature = \"baz\")]\n\
+            pub use self::d::D;\n",
+            String::from_utf8_lossy(&generated[Path::new("mod.rs")])
+        );
+    }
+
+    #[test]
+    fn attributes() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        fs::write(&a_proto, "syntax = 'proto3'; message A {}").unwrap();
+
+        let generated = Codegen::new()
+            .pure()
+            .include(dir.path())
+            .input(&a_proto)
+            .customize(
+                Customize::default()
+                    .mod_rs_reexport(true)
+                    .inner_attribute("deny(missing_docs)")
+                    .outer_attribute("cfg(unix)"),
+            )
+            .run_to_map()
+            .unwrap();
+
+        let a_rs = String::from_utf8_lossy(&generated[Path::new("a.rs")]);
+        assert!(
+            a_rs.contains("//! Generated file from `a.proto`\n#![deny(missing_docs)]\n"),
+            "{}",
+            a_rs
+        );
+        assert_eq!(
+            "// @generated\n\
+            \n\
+            #[cfg(unix)]\n\
+            pub mod a;\n\
+            \n\
+            #[cfg(unix)]\n\
+            pub use self::a::A;\n",
+            String::from_utf8_lossy(&generated[Path::new("mod.rs")])
+        );
+    }
+
+    #[test]
+    fn input_content() {
+        let generated = Codegen::new()
+            .pure()
+            .input_content(
+                "fruit/apple.proto",
+                "syntax = 'proto3'; import 'core.proto'; message Apple { Core core = 1; }",
+            )
+            .include_content("core.proto", "syntax = 'proto3'; message Core {}")
+            .run_to_map()
+            .unwrap();
+
+        let mut names: Vec<_> = generated.keys().cloned().collect();
+        names.sort();
+        assert_eq!(
+            vec![PathBuf::from("apple.rs"), PathBuf::from("mod.rs")],
+            names
+        );
+        let apple = String::from_utf8_lossy(&generated[Path::new("apple.rs")]);
+        assert!(
+            apple.contains("MessageField<super::core::Core>"),
+            "{}",
+            apple
+        );
+    }
+
+    #[test]
+    fn well_known_types_version() {
+        struct Pinned;
+
+        impl WellKnownTypes for Pinned {
+            fn version(&self) -> &str {
+                "1.2.3"
+            }
+
+            fn proto(&self, _proto_path: &ProtoPath) -> Option<String> {
+                None
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let mut codegen = Codegen::new();
+        codegen
+            .pure()
+            .out_dir(dir.path())
+            .input_content("a.proto", "syntax = 'proto3';");
+        let report = codegen.run_with_report().unwrap();
+        assert_eq!(
+            Some(Codegen::well_known_types_version()),
+            report.well_known_types_version.as_deref()
+        );
+        let report = codegen.well_known_types(Pinned).run_with_report().unwrap();
+        assert_eq!(Some("1.2.3"), report.well_known_types_version.as_deref());
+    }
+
+    #[test]
+    fn add_descriptor() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        fs::write(&a_proto, "syntax = 'proto3'; message A {}").unwrap();
+        let a = protobuf_parse::Parser::new()
+            .pure()
+            .include(dir.path())
+            .input(&a_proto)
+            .file_descriptor_set()
+            .unwrap();
+        fs::remove_file(&a_proto).unwrap();
+
+        let b_proto = dir.path().join("b.proto");
+        fs::write(
+            &b_proto,
+            "syntax = 'proto3'; import 'a.proto'; message B { A a = 1; }",
+        )
+        .unwrap();
+        let generated = Codegen::new()
+            .pure()
+            .include(dir.path())
+            .input(&b_proto)
+            .add_descriptor_set(a)
+            .customize(Customize::default().gen_mod_rs(false))
+            .run_to_map()
+            .unwrap();
+        assert_eq!(
+            vec![Path::new("b.rs")],
+            generated.keys().collect::<Vec<_>>()
+        );
+        let b_rs = String::from_utf8_lossy(&generated[Path::new("b.rs")]).into_owned();
+        assert!(b_rs.contains("super::a::A"), "{}", b_rs);
+    }
+
+    #[test]
+    fn deny_warnings() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        fs::write(
+            &a_proto,
+            "syntax = 'proto2'; import weak 'x.proto'; import weak 'y.proto';
+            message A { optional int32 x = 1 [deprecated = true]; }",
+        )
+        .unwrap();
+
+        let run = |deny_warnings| {
+            Codegen::new()
+                .pure()
+                .include(dir.path())
+                .input(&a_proto)
+                .deny_warnings(deny_warnings)
+                .run_to_map()
+        };
+        run(false).unwrap();
+        let err = format!("{}", run(true).unwrap_err());
+        assert!(
+            err.starts_with("2 warning(s) denied by `deny_warnings`:\n"),
+            "{}",
+            err
+        );
+        assert!(err.contains("weak import `x.proto` not found"), "{}", err);
+        assert!(err.contains("weak import `y.proto` not found"), "{}", err);
+    }
+
+    #[test]
+    fn run_single_to_string() {
+        let mut codegen = Codegen::new();
+        codegen
+            .pure()
+            .input_content(
+                "a.proto",
+                "syntax = 'proto3'; import 'b.proto'; message A { B b = 1; }",
+            )
+            .include_content("b.proto", "syntax = 'proto3'; message B {}");
+
+        let a_rs = codegen.run_single_to_string("a.proto").unwrap();
+        assert!(a_rs.contains("pub struct A {"), "{}", a_rs);
+        assert!(!a_rs.contains("pub struct B {"), "{}", a_rs);
+        let b_rs = codegen.run_single_to_string("b.proto").unwrap();
+        assert!(b_rs.contains("pub struct B {"), "{}", b_rs);
+        assert_eq!(
+            "file `c.proto` is neither an input nor an import of an input",
+            format!("{}", codegen.run_single_to_string("c.proto").unwrap_err())
+        );
+    }
+
+    #[test]
+    fn header_comment() {
+        let gen = |header: Option<&str>| {
+            let mut codegen = Codegen::new();
+            codegen
+                .pure()
+                .input_content("a.proto", "syntax = 'proto3'; message A {}");
+            if let Some(header) = header {
+                codegen.header_comment(header);
+            }
+            let generated = codegen.run_to_map().unwrap();
+            String::from_utf8_lossy(&generated[Path::new("a.rs")]).into_owned()
+        };
+
+        let a_rs = gen(None);
+        assert!(
+            a_rs.starts_with("// This file is generated by rust-protobuf "),
+            "{}",
+            a_rs
+        );
+
+        let a_rs = gen(Some(
+            "SPDX-License-Identifier: MIT\n\nGenerated by rust-protobuf {version}",
+        ));
+        assert!(
+            a_rs.starts_with(&format!(
+                "// SPDX-License-Identifier: MIT\n//\n// Generated by rust-protobuf {}\n// @generated\n",
+                env!("CARGO_PKG_VERSION")
+            )),
+            "{}",
+            a_rs
+        );
+
+        let a_rs = gen(Some(""));
+        assert!(a_rs.starts_with("// @generated\n"), "{}", a_rs);
+    }
+
+    #[test]
+    fn optimize_for_lite_runtime() {
+        let content = "syntax = 'proto3'; option optimize_for = LITE_RUNTIME; message A {}";
+        let parsed = Codegen::new()
+            .pure()
+            .input_content("a.proto", content)
+            .parse_and_typecheck()
+            .unwrap();
+        assert_eq!(
+            OptimizeMode::LITE_RUNTIME,
+            parsed.file_descriptors[0].options.optimize_for()
+        );
+
+        let run = |customize: Customize| {
+            let generated = Codegen::new()
+                .pure()
+                .input_content("a.proto", content)
+                .customize(customize)
+                .run_to_map()
+                .unwrap();
+            String::from_utf8_lossy(&generated[Path::new("a.rs")]).into_owned()
+        };
+        let a = run(Customize::default());
+        assert!(!a.contains("fn file_descriptor()"), "{}", a);
+        assert!(!a.contains("MessageFull"), "{}", a);
+        // Explicit customization wins.
+        let a = run(Customize::default().lite_runtime(false));
+        assert!(a.contains("fn file_descriptor()"), "{}", a);
+    }
+
+    #[test]
+    fn refuse_overwrite_non_generated() {
+        let dir = tempfile::tempdir().unwrap();
+        let run = |header: &str| {
+            Codegen::new()
+                .pure()
+                .out_dir(dir.path())
+                .input_content("a.proto", "syntax = 'proto3'; message A {}")
+                .header_comment(header)
+                .refuse_overwrite_non_generated(true)
+                .run()
+        };
+
+        run("").unwrap();
+        // Previously generated files are overwritten, also with a different header.
+        run("custom header").unwrap();
+
+        fs::write(dir.path().join("a.rs"), "fn main() {}\n").unwrap();
+        let err = format!("{}", run("").unwrap_err());
+        assert!(
+            err.starts_with("refusing to overwrite `")
+                && err.ends_with("a.rs`, which does not look like a generated file"),
+            "{}",
+            err
+        );
+        assert_eq!(
+            "fn main() {}\n",
+            fs::read_to_string(dir.path().join("a.rs")).unwrap()
+        );
+    }
+
+    #[test]
+    fn run_typed() {
+        let dir = tempfile::tempdir().unwrap();
+        let run = |content: &str| {
+            Codegen::new()
+                .pure()
+                .out_dir(dir.path())
+                .input_content("a.proto", content)
+                .run_typed()
+                .unwrap_err()
+        };
+
+        match run("syntax = 'proto3'; import 'missing.proto';") {
+            CodegenError::Io { .. } => {}
+            e => panic!("{:?}", e),
+        }
+        match run("syntax = 'proto3';\nmessage {}") {
+            CodegenError::Parse { file, line, .. } => {
+                assert_eq!("a.proto", file);
+                assert_eq!(2, line);
+            }
+            e => panic!("{:?}", e),
+        }
+        match run("syntax = 'proto3';\nmessage A {\n  B b = 1;\n}") {
+            CodegenError::Typecheck { file, line, .. } => {
+                assert_eq!("a.proto", file);
+                assert_eq!(Some(3), line);
+            }
+            e => panic!("{:?}", e),
+        }
+        match Codegen::new().pure().run_typed().unwrap_err() {
+            CodegenError::OutDirNotSpecified => {}
+            e => panic!("{:?}", e),
+        }
+        let err = Codegen::new()
+            .protoc()
+            .protoc_path(&dir.path().join("missing-protoc"))
+            .out_dir(dir.path())
+            .input_content("a.proto", "syntax = 'proto3';")
+            .run_typed()
+            .unwrap_err();
+        match err {
+            CodegenError::Protoc { .. } => {}
+            e => panic!("{:?}", e),
+        }
+
+        fs::write(dir.path().join("a.rs"), "").unwrap();
+        let err = Codegen::new()
+            .pure()
+            .out_dir(dir.path().join("a.rs"))
+            .input_content("a.proto", "syntax = 'proto3';")
+            .run_typed()
+            .unwrap_err();
+        match err {
+            CodegenError::Write { .. } => {}
+            e => panic!("{:?}", e),
+        }
+    }
+
+    #[test]
+    fn post_process() {
+        let mut codegen = Codegen::new();
+        codegen
+            .pure()
+            .input_content("a.proto", "syntax = 'proto3'; message A {}")
+            .post_process(|proto_path, content| {
+                Ok(format!("// post-processed {}\n{}", proto_path, content))
+            });
+
+        let generated = codegen.run_to_map().unwrap();
+        let a = String::from_utf8(generated[Path::new("a.rs")].clone()).unwrap();
+        assert!(a.starts_with("// post-processed a.proto\n"), "{}", a);
+        let mod_rs = String::from_utf8(generated[Path::new("mod.rs")].clone()).unwrap();
+        assert!(!mod_rs.contains("post-processed"), "{}", mod_rs);
+
+        let dir = tempfile::tempdir().unwrap();
+        codegen.out_dir(dir.path()).run().unwrap();
+        assert_eq!(a, fs::read_to_string(dir.path().join("a.rs")).unwrap());
+        assert_eq!(a, codegen.run_single_to_string("a.proto").unwrap());
+
+        codegen.post_process(|_, _| Err(anyhow::anyhow!("bad")));
+        let err = format!("{:#}", codegen.run_to_map().unwrap_err());
+        assert_eq!("post-processing `a.proto`: bad", err);
+    }
+
+    #[test]
+    fn skip_unchanged() {
+        let dir = tempfile::tempdir().unwrap();
+        let run = |content: &str| {
+            Codegen::new()
+                .pure()
+                .out_dir(dir.path())
+                .input_content("a.proto", "syntax = 'proto3'; message A {}")
+                .input_content("b.proto", content)
+                .skip_unchanged(true)
+                .run()
+                .unwrap()
+        };
+        let mtime = |name: &str| {
+            fs::metadata(dir.path().join(name))
+                .unwrap()
+                .modified()
+                .unwrap()
+        };
+
+        run("syntax = 'proto3'; message B {}");
+        let old = std::time::SystemTime::UNIX_EPOCH;
+        for name in ["a.rs", "b.rs"] {
+            fs::File::options()
+                .write(true)
+                .open(dir.path().join(name))
+                .unwrap()
+                .set_modified(old)
+                .unwrap();
+        }
+
+        run("syntax = 'proto3'; message B { int32 x = 1; }");
+        assert_eq!(old, mtime("a.rs"));
+        assert_ne!(old, mtime("b.rs"));
+        assert!(fs::read_to_string(dir.path().join("b.rs"))
+            .unwrap()
+            .contains("pub x: i32"));
+    }
+
+    #[test]
+    fn capture_comments() {
+        let generated = Codegen::new()
+            .pure()
+            .input_content(
+                "a.proto",
+                r#"syntax = "proto3";
+// Message comment.
+//
+// Second paragraph.
+message A {
+    /*
+     * Field comment
+     * on two lines.
+     */
+    int32 x = 1;
+}
+/** Enum comment. */
+enum E {
+    // Zero value.
+    ZERO = 0;
+    ONE = 1;
+}
+"#,
+            )
+            .capture_comments(true)
+            .run_to_map()
+            .unwrap();
+        let a = String::from_utf8(generated[Path::new("a.rs")].clone()).unwrap();
+        for expected in [
+            "/// Message comment.\n///\n/// Second paragraph.\n//",
+            "    /// Field comment\n    /// on two lines.\n    //",
+            "/// Enum comment.\n#[derive(",
+            "    /// Zero value.\n    // @@protoc_insertion_point(enum_value:E.ZERO)\n    ZERO = 0,\n    // @@protoc_insertion_point(enum_value:E.ONE)\n",
+        ] {
+            assert!(a.contains(expected), "{}", a);
+        }
+    }
+
+    #[test]
+    fn check_only() {
+        let unsupported = Codegen::new()
+            .strict(true)
+            .input_content(
+                "a.proto",
+                r#"syntax = "proto2";
+import "google/protobuf/descriptor.proto";
+message Opt { optional int32 a = 1; extensions 100 to 200; }
+extend Opt { optional int32 b = 100; }
+extend google.protobuf.MessageOptions { optional Opt opt = 50000; }
+message M { option (opt) = { [b]: 2 }; }
+"#,
+            )
+            .check_only()
+            .unwrap();
+        assert_eq!(1, unsupported.len(), "{:?}", unsupported);
+        assert_eq!("a.proto", unsupported[0].file.to_str());
+        assert_eq!((6, 20), (unsupported[0].line, unsupported[0].col));
+        assert_eq!(
+            "extension field `[b]` in value of option `(opt)`",
+            unsupported[0].construct
+        );
+    }
+
+    #[test]
+    fn default_syntax() {
+        let run = |content: &str, default_syntax: Option<Syntax>| {
+            let mut codegen = Codegen::new();
+            codegen.pure().input_content("a.proto", content);
+            if let Some(default_syntax) = default_syntax {
+                codegen.default_syntax(default_syntax);
+            }
+            let generated = codegen.run_to_map().unwrap();
+            String::from_utf8(generated[Path::new("a.rs")].clone()).unwrap()
+        };
+
+        let a = run("message M { int32 x = 1; }", Some(Syntax::Proto3));
+        assert!(a.contains("pub x: i32,"), "{}", a);
+
+        let a = run(
+            "syntax = 'proto2'; message M { optional int32 x = 1; }",
+            Some(Syntax::Proto3),
+        );
+        assert!(a.contains("pub x: ::std::option::Option<i32>,"), "{}", a);
+
+        let a = run("message M { optional int32 x = 1; }", None);
+        assert!(a.contains("pub x: ::std::option::Option<i32>,"), "{}", a);
+
+        let err = Codegen::new()
+            .pure()
+            .input_content("a.proto", "message M { int32 x = 1; }")
+            .strict(true)
+            .default_syntax(Syntax::Proto3)
+            .run_to_map()
+            .unwrap_err();
+        assert!(
+            format!("{:#}", err).contains("error in `a.proto:1:1`: no syntax specified"),
+            "{:#}",
+            err
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use std::fmt;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+
+use protobuf_parse::pure::ParseObserver;
+use protobuf_parse::ProtoPath;
+
+/// Receive notifications about codegen progress, e.g. to display a progress bar
+/// or to find slow files.
+///
+/// All methods do nothing by default.
+/// Parse notifications are only sent when pure rust parser is used,
+/// and not for files taken from [cache](crate::Codegen::cache_dir).
+pub trait CodegenObserver: Send + Sync + 'static {
+    /// A `.proto` file is parsed and is about to be typechecked.
+    fn file_parsed(&self, _proto_path: &ProtoPath, _elapsed: Duration) {}
+    /// A `.proto` file is typechecked and converted to descriptor.
+    fn file_converted(&self, _proto_path: &ProtoPath, _elapsed: Duration) {}
+    /// A generated file is written. Path is relative to the output directory.
+    fn file_written(&self, _path: &Path, _elapsed: Duration) {}
+}
+
+#[derive(Clone)]
+pub(crate) struct CodegenObserverHolder(pub(crate) Arc<dyn CodegenObserver>);
+
+impl fmt::Debug for CodegenObserverHolder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CodegenObserverHolder")
+            .finish_non_exhaustive()
+    }
+}
+
+impl ParseObserver for CodegenObserverHolder {
+    fn file_parsed(&self, proto_path: &ProtoPath, elapsed: Duration) {
+        self.0.file_parsed(proto_path, elapsed)
+    }
+
+    fn file_converted(&self, proto_path: &ProtoPath, elapsed: Duration) {
+        self.0.file_converted(proto_path, elapsed)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::path::Path;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+    use std::time::Duration;
+
+    use protobuf_parse::ProtoPath;
+
+    use crate::Codegen;
+    use crate::CodegenObserver;
+
+    #[test]
+    fn observer() {
+        struct Observer(Arc<Mutex<Vec<String>>>);
+
+        impl CodegenObserver for Observer {
+            fn file_parsed(&self, proto_path: &ProtoPath, _elapsed: Duration) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("parsed {}", proto_path));
+            }
+
+            fn file_converted(&self, proto_path: &ProtoPath, _elapsed: Duration) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("converted {}", proto_path));
+            }
+
+            fn file_written(&self, path: &Path, _elapsed: Duration) {
+                self.0
+                    .lock()
+                    .unwrap()
+                    .push(format!("written {}", path.display()));
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let out_dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        fs::write(&a_proto, "syntax = 'proto3'; import 'b.proto';").unwrap();
+        fs::write(dir.path().join("b.proto"), "syntax = 'proto3';").unwrap();
+
+        let events = Arc::new(Mutex::new(Vec::new()));
+        Codegen::new()
+            .pure()
+            .include(dir.path())
+            .input(&a_proto)
+            .out_dir(out_dir.path())
+            .observer(Observer(events.clone()))
+            .run()
+            .unwrap();
+        assert_eq!(
+            vec![
+                "parsed a.proto",
+                "parsed b.proto",
+                "converted b.proto",
+                "converted a.proto",
+                "written a.rs",
+                "written mod.rs",
+            ],
+            *events.lock().unwrap()
+        );
+    }
+}
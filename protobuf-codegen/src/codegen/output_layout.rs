@@ -0,0 +1,221 @@
+/// Where generated `.rs` files are placed in the output directory.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum OutputLayout {
+    /// Flat output directory, file `foo/bar.proto` is generated as `bar.rs`.
+    #[default]
+    IncludeRelative,
+    /// Directory per proto package component,
+    /// file `foo/bar.proto` with `package my.pkg` is generated as `my/pkg/bar.rs`,
+    /// and `mod.rs` declares nested modules `my::pkg::bar`.
+    ///
+    /// It is an error if a module of a file has the same path as a module of a package,
+    /// like `a.proto` without package and `b.proto` with `package a`.
+    Package,
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+    use std::path::Path;
+    use std::path::PathBuf;
+
+    use crate::Codegen;
+    use crate::OutputLayout;
+
+    #[test]
+    fn output_layout_nested_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let out_dir = dir.path().join("out");
+        fs::create_dir(&out_dir).unwrap();
+
+        let run = || {
+            Codegen::new()
+                .pure()
+                .out_dir(&out_dir)
+                .input_content(
+                    "a.proto",
+                    "syntax = 'proto3'; package com.example.deep.nested.pkg; message A {}",
+                )
+                .input_content(
+                    "b.proto",
+                    "syntax = 'proto3'; package com.example; message B {}",
+                )
+                .output_layout(OutputLayout::Package)
+                .run()
+        };
+
+        run().unwrap();
+        assert!(out_dir.join("com/example/deep/nested/pkg/a.rs").is_file());
+        assert!(out_dir.join("com/example/b.rs").is_file());
+        // Existing directories are reused.
+        run().unwrap();
+
+        fs::remove_dir_all(out_dir.join("com/example/deep")).unwrap();
+        fs::write(out_dir.join("com/example/deep"), "").unwrap();
+        let err = format!("{}", run().unwrap_err());
+        assert!(
+            err.contains("deep` exists and is not a directory"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn output_layout_mod_collides_with_package() {
+        let err = Codegen::new()
+            .pure()
+            .input_content("a.proto", "syntax = 'proto3'; message A {}")
+            .input_content("b.proto", "syntax = 'proto3'; package a; message B {}")
+            .output_layout(OutputLayout::Package)
+            .run_to_map()
+            .unwrap_err();
+        assert!(
+            format!("{:#}", err).contains(
+                "module `a` generated for `a.proto` has the same path \
+                as module of package of `b.proto`, rename the file or the package"
+            ),
+            "{:#}",
+            err
+        );
+    }
+
+    #[test]
+    fn output_layout() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("x")).unwrap();
+        let a_proto = dir.path().join("x/a.proto");
+        let b_proto = dir.path().join("b.proto");
+        fs::write(&a_proto, "syntax = 'proto3'; package my.pkg; message A {}").unwrap();
+        fs::write(
+            &b_proto,
+            "syntax = 'proto3'; package my.other; import 'x/a.proto'; message B { my.pkg.A a = 1; }",
+        )
+        .unwrap();
+
+        let run = |output_layout| {
+            Codegen::new()
+                .pure()
+                .include(dir.path())
+                .inputs([&a_proto, &b_proto])
+                .output_layout(output_layout)
+                .run_to_map()
+                .unwrap()
+        };
+
+        let mut generated = run(OutputLayout::IncludeRelative);
+        let mut names: Vec<_> = generated.keys().cloned().collect();
+        names.sort();
+        assert_eq!(
+            vec![
+                PathBuf::from("a.rs"),
+                PathBuf::from("b.rs"),
+                PathBuf::from("mod.rs")
+            ],
+            names
+        );
+
+        generated = run(OutputLayout::Package);
+        let mut names: Vec<_> = generated.keys().cloned().collect();
+        names.sort();
+        assert_eq!(
+            vec![
+                PathBuf::from("mod.rs"),
+                PathBuf::from("my/other/b.rs"),
+                PathBuf::from("my/pkg/a.rs"),
+            ],
+            names
+        );
+        let b = String::from_utf8(generated[Path::new("my/other/b.rs")].clone()).unwrap();
+        assert!(b.contains("MessageField<super::super::pkg::a::A>"), "{}", b);
+        assert!(
+            b.contains("super::super::pkg::a::file_descriptor()"),
+            "{}",
+            b
+        );
+        let mod_rs = String::from_utf8(generated[Path::new("mod.rs")].clone()).unwrap();
+        assert!(
+            mod_rs.contains(
+                "pub mod my {\n    pub mod other {\n        pub mod b;\n    }\n    pub mod pkg {\n        pub mod a;\n    }\n}\n"
+            ),
+            "{}",
+            mod_rs
+        );
+    }
+
+    #[test]
+    fn strip_package_prefix() {
+        let generated = Codegen::new()
+            .pure()
+            .input_content(
+                "b.proto",
+                "syntax = 'proto3'; package com.example.internal.other; import 'a.proto'; message B { com.example.internal.pkg.A a = 1; }",
+            )
+            .include_content(
+                "a.proto",
+                "syntax = 'proto3'; package com.example.internal.pkg; message A {}",
+            )
+            .input_content("c.proto", "syntax = 'proto3'; package com.example.internalx; message C {}")
+            .output_layout(OutputLayout::Package)
+            .strip_package_prefix("com.example.internal")
+            .run_to_map()
+            .unwrap();
+        let mut names: Vec<_> = generated.keys().cloned().collect();
+        names.sort();
+        assert_eq!(
+            vec![
+                PathBuf::from("com/example/internalx/c.rs"),
+                PathBuf::from("mod.rs"),
+                PathBuf::from("other/b.rs"),
+            ],
+            names
+        );
+        let b = String::from_utf8(generated[Path::new("other/b.rs")].clone()).unwrap();
+        assert!(b.contains("MessageField<super::super::pkg::a::A>"), "{}", b);
+        let mod_rs = String::from_utf8(generated[Path::new("mod.rs")].clone()).unwrap();
+        assert!(
+            mod_rs.contains("pub mod other {\n    pub mod b;\n}\n"),
+            "{}",
+            mod_rs
+        );
+    }
+
+    #[test]
+    fn same_file_name_in_different_dirs() {
+        let generated = Codegen::new()
+            .pure()
+            .input_content(
+                "a/config.proto",
+                "syntax = 'proto3'; package a; message A {}",
+            )
+            .input_content(
+                "b/config.proto",
+                "syntax = 'proto3'; package b; message B {}",
+            )
+            .input_content(
+                "c.proto",
+                "syntax = 'proto3'; import 'a/config.proto'; message C { a.A a = 1; }",
+            )
+            .run_to_map()
+            .unwrap();
+        let mut names: Vec<_> = generated.keys().cloned().collect();
+        names.sort();
+        assert_eq!(
+            vec![
+                PathBuf::from("a_config.rs"),
+                PathBuf::from("b_config.rs"),
+                PathBuf::from("c.rs"),
+                PathBuf::from("mod.rs"),
+            ],
+            names
+        );
+        let c = String::from_utf8(generated[Path::new("c.rs")].clone()).unwrap();
+        assert!(c.contains("MessageField<super::a_config::A>"), "{}", c);
+        assert!(c.contains("super::a_config::file_descriptor()"), "{}", c);
+        let mod_rs = String::from_utf8(generated[Path::new("mod.rs")].clone()).unwrap();
+        assert!(
+            mod_rs.contains("pub mod a_config;\npub mod b_config;\npub mod c;\n"),
+            "{}",
+            mod_rs
+        );
+    }
+}
@@ -0,0 +1,148 @@
+use std::path::PathBuf;
+
+use protobuf::descriptor::DescriptorProto;
+use protobuf::descriptor::EnumDescriptorProto;
+use protobuf::descriptor::FileDescriptorProto;
+use protobuf_parse::ProtoPathBuf;
+
+/// Summary of what [`Codegen::run_with_report`](crate::Codegen::run_with_report) did.
+#[derive(Debug, Default, Clone)]
+pub struct CodegenReport {
+    /// Files written, including `.rs` files
+    /// and [`descriptor_set_out`](crate::Codegen::descriptor_set_out) file if any.
+    pub output_files: Vec<PathBuf>,
+    /// `.proto` files read from the filesystem, including transitive imports of inputs.
+    ///
+    /// These are the paths to emit `cargo:rerun-if-changed` for.
+    pub read_files: Vec<PathBuf>,
+    /// Imported files not found in include path
+    /// which were taken from protos bundled with the parser
+    /// (like `google/protobuf/descriptor.proto`).
+    pub embedded_files: Vec<ProtoPathBuf>,
+    /// Options dropped by the parser because it cannot represent them,
+    /// weak imports which were not found and imports which were not used,
+    /// and diagnostics enabled by [`warn_required`](crate::Codegen::warn_required)
+    /// and [`warn_well_known_drift`](crate::Codegen::warn_well_known_drift).
+    ///
+    /// Options are errors when [`strict`](crate::Codegen::strict) mode is enabled,
+    /// and any warning is an error with [`deny_warnings`](crate::Codegen::deny_warnings).
+    pub warnings: Vec<String>,
+    /// Fully qualified names of messages, fields, enums, enum values, services and methods
+    /// marked `deprecated` in input files, e.g. `my.pkg.Message.field`.
+    pub deprecated: Vec<String>,
+    /// Protobuf release of well-known type protos available to pure parser,
+    /// bundled or provided with [`well_known_types`](crate::Codegen::well_known_types).
+    ///
+    /// `None` with `protoc` parser, which uses well-known type protos bundled with `protoc`.
+    pub well_known_types_version: Option<String>,
+}
+
+impl CodegenReport {
+    /// Print `cargo:rerun-if-changed=...` for each of [`read_files`](Self::read_files).
+    pub fn emit_rerun_if_changed(&self) {
+        for path in &self.read_files {
+            println!("cargo:rerun-if-changed={}", path.display());
+        }
+    }
+}
+
+fn qualified(scope: &str, name: &str) -> String {
+    match scope {
+        "" => name.to_owned(),
+        _ => format!("{}.{}", scope, name),
+    }
+}
+
+fn deprecated_in_enum(scope: &str, e: &EnumDescriptorProto, r: &mut Vec<String>) {
+    let name = qualified(scope, e.name());
+    if e.options.deprecated() {
+        r.push(name.clone());
+    }
+    for v in &e.value {
+        if v.options.deprecated() {
+            r.push(qualified(&name, v.name()));
+        }
+    }
+}
+
+fn deprecated_in_message(scope: &str, m: &DescriptorProto, r: &mut Vec<String>) {
+    let name = qualified(scope, m.name());
+    if m.options.deprecated() {
+        r.push(name.clone());
+    }
+    for f in m.field.iter().chain(&m.extension) {
+        if f.options.deprecated() {
+            r.push(qualified(&name, f.name()));
+        }
+    }
+    for n in &m.nested_type {
+        deprecated_in_message(&name, n, r);
+    }
+    for e in &m.enum_type {
+        deprecated_in_enum(&name, e, r);
+    }
+}
+
+/// Declarations marked `deprecated` in given files, in declaration order.
+pub(crate) fn deprecated_symbols<'a>(
+    files: impl IntoIterator<Item = &'a FileDescriptorProto>,
+) -> Vec<String> {
+    let mut r = Vec::new();
+    for file in files {
+        let package = file.package();
+        for m in &file.message_type {
+            deprecated_in_message(package, m, &mut r);
+        }
+        for e in &file.enum_type {
+            deprecated_in_enum(package, e, &mut r);
+        }
+        for f in &file.extension {
+            if f.options.deprecated() {
+                r.push(qualified(package, f.name()));
+            }
+        }
+        for s in &file.service {
+            let name = qualified(package, s.name());
+            if s.options.deprecated() {
+                r.push(name.clone());
+            }
+            for m in &s.method {
+                if m.options.deprecated() {
+                    r.push(qualified(&name, m.name()));
+                }
+            }
+        }
+    }
+    r
+}
+
+#[cfg(test)]
+mod test {
+    use protobuf_parse::ProtoPath;
+
+    use super::*;
+
+    #[test]
+    fn deprecated() {
+        let file = protobuf_parse::pure::parse_single(
+            ProtoPath::new("a.proto").unwrap(),
+            r#"syntax = "proto3";
+            package p;
+            message M {
+                option deprecated = true;
+                int32 a = 1 [deprecated = true];
+                int32 b = 2;
+                message N { string c = 1 [deprecated = true]; }
+            }
+            enum E { A = 0; B = 1 [deprecated = true]; }
+            service S { rpc R(M) returns (M) { option deprecated = true; } }
+            "#,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            vec!["p.M", "p.M.a", "p.M.N.c", "p.E.B", "p.S.R"],
+            deprecated_symbols(&[file])
+        );
+    }
+}
@@ -0,0 +1,155 @@
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::collections::VecDeque;
+
+use protobuf::descriptor::field_descriptor_proto::Label;
+use protobuf::descriptor::DescriptorProto;
+use protobuf::descriptor::FileDescriptorProto;
+
+/// Messages by fully qualified name with leading dot, like `.p.M`.
+fn index<'a>(
+    scope: &str,
+    messages: &'a [DescriptorProto],
+    r: &mut HashMap<String, &'a DescriptorProto>,
+) {
+    for m in messages {
+        let name = format!("{}.{}", scope, m.name());
+        index(&name, &m.nested_type, r);
+        r.insert(name, m);
+    }
+}
+
+/// Shortest chain of fields from message `name` to a `required` field.
+fn required_chain(name: &str, messages: &HashMap<String, &DescriptorProto>) -> Option<Vec<String>> {
+    let mut visited = HashSet::from([name.to_owned()]);
+    let mut queue = VecDeque::from([(name.to_owned(), Vec::new())]);
+    while let Some((name, chain)) = queue.pop_front() {
+        let message = match messages.get(&name) {
+            Some(message) => message,
+            None => continue,
+        };
+        for f in &message.field {
+            let mut chain = chain.clone();
+            // Map entry is reported as the map field only.
+            if !message.options.map_entry() {
+                chain.push(format!("{}.{}", &name[1..], f.name()));
+            }
+            if f.label() == Label::LABEL_REQUIRED {
+                return Some(chain);
+            }
+            if visited.insert(f.type_name().to_owned()) {
+                queue.push_back((f.type_name().to_owned(), chain));
+            }
+        }
+    }
+    None
+}
+
+fn message_names(scope: &str, messages: &[DescriptorProto], r: &mut Vec<String>) {
+    for m in messages {
+        if m.options.map_entry() {
+            continue;
+        }
+        let name = format!("{}.{}", scope, m.name());
+        r.push(name.clone());
+        message_names(&name, &m.nested_type, r);
+    }
+}
+
+/// Warn about messages declared in `inputs` which contain `required` fields,
+/// directly or through fields of message types.
+pub(crate) fn required_warnings<'a>(
+    inputs: impl IntoIterator<Item = &'a FileDescriptorProto>,
+    all: &[FileDescriptorProto],
+) -> Vec<String> {
+    let mut messages = HashMap::new();
+    for file in all {
+        index(&package_scope(file), &file.message_type, &mut messages);
+    }
+
+    let mut r = Vec::new();
+    for file in inputs {
+        let mut names = Vec::new();
+        message_names(&package_scope(file), &file.message_type, &mut names);
+        for name in names {
+            if let Some(chain) = required_chain(&name, &messages) {
+                r.push(format!(
+                    "{}: message `{}` contains required field: {}",
+                    file.name(),
+                    &name[1..],
+                    chain.join(" -> ")
+                ));
+            }
+        }
+    }
+    r
+}
+
+fn package_scope(file: &FileDescriptorProto) -> String {
+    match file.package() {
+        "" => String::new(),
+        package => format!(".{}", package),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use protobuf_parse::ProtoPath;
+
+    use super::required_warnings;
+    use crate::Codegen;
+
+    #[test]
+    fn required() {
+        let file = protobuf_parse::pure::parse_single(
+            ProtoPath::new("a.proto").unwrap(),
+            r#"syntax = "proto2";
+            package p;
+            message A { optional B b = 1; optional C c = 2; }
+            message B { optional A a = 1; optional D d = 2; }
+            message C { required int32 x = 1; }
+            message D { optional int32 y = 1; message E { required string z = 1; } }
+            message F { map<string, C> m = 1; }
+            "#,
+            &[],
+        )
+        .unwrap();
+        assert_eq!(
+            vec![
+                "a.proto: message `p.A` contains required field: p.A.c -> p.C.x",
+                "a.proto: message `p.B` contains required field: p.B.a -> p.A.c -> p.C.x",
+                "a.proto: message `p.C` contains required field: p.C.x",
+                "a.proto: message `p.D.E` contains required field: p.D.E.z",
+                "a.proto: message `p.F` contains required field: p.F.m -> p.C.x",
+            ],
+            required_warnings([&file], std::slice::from_ref(&file))
+        );
+    }
+
+    #[test]
+    fn warn_required() {
+        let run = |warn_required| {
+            let dir = tempfile::tempdir().unwrap();
+            Codegen::new()
+                .pure()
+                .out_dir(dir.path())
+                .input_content(
+                    "a.proto",
+                    "syntax = 'proto2'; message A { optional B b = 1; } \
+                    message B { required int32 x = 1; }",
+                )
+                .warn_required(warn_required)
+                .run_with_report()
+                .unwrap()
+                .warnings
+        };
+        assert!(run(false).is_empty());
+        assert_eq!(
+            vec![
+                "a.proto: message `A` contains required field: A.b -> B.x",
+                "a.proto: message `B` contains required field: B.x",
+            ],
+            run(true)
+        );
+    }
+}
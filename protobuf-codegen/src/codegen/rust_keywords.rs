@@ -0,0 +1,179 @@
+use std::collections::HashMap;
+
+use protobuf::descriptor::DescriptorProto;
+use protobuf::descriptor::EnumDescriptorProto;
+use protobuf::descriptor::FileDescriptorProto;
+use protobuf::reflect::MessageDescriptor;
+use protobuf::rt::Lazy;
+use protobuf::MessageFull;
+
+use crate::gen::rust::keywords::is_rust_keyword;
+
+static FILE_MESSAGE_TYPE: Lazy<i32> = Lazy::new();
+static FILE_ENUM_TYPE: Lazy<i32> = Lazy::new();
+static MESSAGE_FIELD: Lazy<i32> = Lazy::new();
+static MESSAGE_NESTED_TYPE: Lazy<i32> = Lazy::new();
+static MESSAGE_ENUM_TYPE: Lazy<i32> = Lazy::new();
+static MESSAGE_ONEOF_DECL: Lazy<i32> = Lazy::new();
+static ENUM_VALUE: Lazy<i32> = Lazy::new();
+
+/// Number of field `name` of `descriptor.proto` message `M`, used in `source_code_info` paths.
+fn field_number<M: MessageFull>(number: &Lazy<i32>, name: &str) -> i32 {
+    *number.get(|| {
+        MessageDescriptor::for_type::<M>()
+            .field_by_name(name)
+            .unwrap_or_else(|| panic!("`{}` must exist", name))
+            .proto()
+            .number()
+    })
+}
+
+struct Check<'a> {
+    file: &'a str,
+    /// 1-based line and column of declarations by `source_code_info` path.
+    locations: HashMap<&'a [i32], (i32, i32)>,
+    warnings: Vec<String>,
+}
+
+impl<'a> Check<'a> {
+    fn name(&mut self, path: &[i32], kind: &str, scope: &str, name: &str) {
+        if !is_rust_keyword(name) {
+            return;
+        }
+        let loc = match self.locations.get(path) {
+            Some((line, col)) => format!(":{}:{}", line, col),
+            None => String::new(),
+        };
+        self.warnings.push(format!(
+            "{}{}: {} `{}{}` is a Rust keyword, generated as `{}_`",
+            self.file, loc, kind, scope, name, name
+        ));
+    }
+
+    fn enums(&mut self, path: &[i32], field: i32, scope: &str, enums: &[EnumDescriptorProto]) {
+        for (i, e) in enums.iter().enumerate() {
+            let path = [path, &[field, i as i32]].concat();
+            self.name(&path, "enum", scope, e.name());
+            let scope = format!("{}{}.", scope, e.name());
+            for (i, v) in e.value.iter().enumerate() {
+                let value = field_number::<EnumDescriptorProto>(&ENUM_VALUE, "value");
+                let path = [&path[..], &[value, i as i32]].concat();
+                self.name(&path, "enum value", &scope, v.name());
+            }
+        }
+    }
+
+    fn messages(&mut self, path: &[i32], field: i32, scope: &str, messages: &[DescriptorProto]) {
+        for (i, m) in messages.iter().enumerate() {
+            let path = [path, &[field, i as i32]].concat();
+            self.name(&path, "message", scope, m.name());
+            let scope = format!("{}{}.", scope, m.name());
+            for (i, f) in m.field.iter().enumerate() {
+                let field = field_number::<DescriptorProto>(&MESSAGE_FIELD, "field");
+                let path = [&path[..], &[field, i as i32]].concat();
+                self.name(&path, "field", &scope, f.name());
+            }
+            for (i, o) in m.oneof_decl.iter().enumerate() {
+                let oneof_decl = field_number::<DescriptorProto>(&MESSAGE_ONEOF_DECL, "oneof_decl");
+                let path = [&path[..], &[oneof_decl, i as i32]].concat();
+                self.name(&path, "oneof", &scope, o.name());
+            }
+            self.messages(
+                &path,
+                field_number::<DescriptorProto>(&MESSAGE_NESTED_TYPE, "nested_type"),
+                &scope,
+                &m.nested_type,
+            );
+            self.enums(
+                &path,
+                field_number::<DescriptorProto>(&MESSAGE_ENUM_TYPE, "enum_type"),
+                &scope,
+                &m.enum_type,
+            );
+        }
+    }
+}
+
+/// Warn about declarations in `inputs` named like Rust keywords,
+/// which are escaped with `_` suffix in generated code.
+///
+/// Locations are reported when descriptors have `source_code_info`.
+pub(crate) fn rust_keyword_warnings<'a>(
+    inputs: impl IntoIterator<Item = &'a FileDescriptorProto>,
+) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for file in inputs {
+        let mut check = Check {
+            file: file.name(),
+            locations: file
+                .source_code_info
+                .location
+                .iter()
+                .filter(|l| l.span.len() >= 2)
+                .map(|l| (&l.path[..], (l.span[0] + 1, l.span[1] + 1)))
+                .collect(),
+            warnings: Vec::new(),
+        };
+        let scope = match file.package() {
+            "" => String::new(),
+            package => format!("{}.", package),
+        };
+        check.messages(
+            &[],
+            field_number::<FileDescriptorProto>(&FILE_MESSAGE_TYPE, "message_type"),
+            &scope,
+            &file.message_type,
+        );
+        check.enums(
+            &[],
+            field_number::<FileDescriptorProto>(&FILE_ENUM_TYPE, "enum_type"),
+            &scope,
+            &file.enum_type,
+        );
+        warnings.extend(check.warnings);
+    }
+    warnings
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use crate::Codegen;
+
+    #[test]
+    fn warn_rust_keywords() {
+        let run = |warn_rust_keywords| {
+            let dir = tempfile::tempdir().unwrap();
+            let mut codegen = Codegen::new();
+            codegen
+                .pure()
+                .out_dir(dir.path())
+                .input_content(
+                    "a.proto",
+                    "syntax = 'proto3';\n\
+                    package p;\n\
+                    message Self { int32 type = 1; oneof match { string try = 2; } }\n\
+                    enum E { ZERO = 0; self = 1; }\n",
+                )
+                .warn_rust_keywords(warn_rust_keywords);
+            let report = codegen.run_with_report().unwrap();
+            let a = fs::read_to_string(dir.path().join("a.rs")).unwrap();
+            assert!(a.contains("pub type_: i32,"), "{}", a);
+            assert!(a.contains("fn try_(&self)"), "{}", a);
+            assert!(!a.contains("source_code_info"));
+            report.warnings
+        };
+        assert!(run(false).is_empty());
+        assert_eq!(
+            vec![
+                "a.proto:3:1: message `p.Self` is a Rust keyword, generated as `Self_`",
+                "a.proto:3:16: field `p.Self.type` is a Rust keyword, generated as `type_`",
+                "a.proto:3:46: field `p.Self.try` is a Rust keyword, generated as `try_`",
+                "a.proto:3:32: oneof `p.Self.match` is a Rust keyword, generated as `match_`",
+                "a.proto:4:20: enum value `p.E.self` is a Rust keyword, generated as `self_`",
+            ],
+            run(true)
+        );
+    }
+}
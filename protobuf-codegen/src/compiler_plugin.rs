@@ -16,6 +16,8 @@ pub struct GenRequest<'a> {
 pub struct GenResult {
     pub name: String,
     pub content: Vec<u8>,
+    /// `.proto` file this file is generated for, `None` for `mod.rs`.
+    pub source: Option<ProtoPathBuf>,
 }
 
 pub fn plugin_main<F>(gen: F) -> anyhow::Result<()>
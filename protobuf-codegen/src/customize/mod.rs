@@ -11,6 +11,8 @@ use protobuf::reflect::FileDescriptor;
 use protobuf::reflect::MessageDescriptor;
 use protobuf::reflect::OneofDescriptor;
 
+use crate::OutputLayout;
+
 /// Dynamic callback to customize code generation.
 pub trait CustomizeCallback: 'static {
     fn file(&self, file: &FileDescriptor) -> Customize {
@@ -106,6 +108,23 @@ pub struct Customize {
     ///
     /// This option will likely be on by default in rust-protobuf version 3.
     pub(crate) gen_mod_rs: Option<bool>,
+    /// Name of the file generated when `gen_mod_rs` is on, `mod.rs` by default.
+    pub(crate) mod_rs_name: Option<String>,
+    /// Re-export top-level messages and enums of each module from `mod.rs`.
+    pub(crate) mod_rs_reexport: Option<bool>,
+    /// Declare module of the generated file in `mod.rs` only when this cargo feature is enabled.
+    pub(crate) mod_feature: Option<String>,
+    /// Inner attributes written at the top of the generated file.
+    pub(crate) inner_attributes: Option<Vec<String>>,
+    /// Outer attributes written on the generated module declaration in `mod.rs`.
+    pub(crate) outer_attributes: Option<Vec<String>>,
+    /// Set from [`Codegen::output_layout`](crate::Codegen::output_layout).
+    pub(crate) output_layout: Option<OutputLayout>,
+    /// Set from [`Codegen::strip_package_prefix`](crate::Codegen::strip_package_prefix).
+    pub(crate) strip_package_prefix: Option<String>,
+    /// Generated files named by full proto path to avoid module name collisions,
+    /// set internally.
+    pub(crate) full_path_mod_names: Option<Vec<String>>,
     /// Used internally to generate protos bundled in protobuf crate
     /// like `descriptor.proto`
     pub(crate) inside_protobuf: Option<bool>,
@@ -152,6 +171,9 @@ impl Customize {
     /// So the generated code (and more importantly, generated binary size) is smaller,
     /// but reflection, text format, JSON serialization won't work.
     ///
+    /// When not set, lite runtime is used for files with
+    /// `option optimize_for = LITE_RUNTIME`.
+    ///
     /// Note when using `protoc` plugin `protoc-gen-rust`, the option name is just `lite`.
     pub fn lite_runtime(mut self, lite_runtime: bool) -> Self {
         self.lite_runtime = Some(lite_runtime);
@@ -165,6 +187,49 @@ impl Customize {
         self
     }
 
+    /// Name of the file with all the generated modules, `mod.rs` by default.
+    pub fn mod_rs_name(mut self, mod_rs_name: &str) -> Self {
+        self.mod_rs_name = Some(mod_rs_name.to_owned());
+        self
+    }
+
+    /// In `mod.rs`, also re-export top-level messages and enums of all the generated modules,
+    /// so they can be referenced without module name.
+    ///
+    /// Code generation fails if two modules have top-level items with the same name.
+    pub fn mod_rs_reexport(mut self, mod_rs_reexport: bool) -> Self {
+        self.mod_rs_reexport = Some(mod_rs_reexport);
+        self
+    }
+
+    /// Guard declaration of the generated module in `mod.rs`
+    /// with `#[cfg(feature = "...")]`.
+    pub fn mod_feature(mut self, feature: &str) -> Self {
+        self.mod_feature = Some(feature.to_owned());
+        self
+    }
+
+    /// Add inner attribute like `allow(clippy::all)` written as `#![allow(clippy::all)]`
+    /// at the top of the generated file.
+    pub fn inner_attribute(mut self, attribute: &str) -> Self {
+        self.inner_attributes
+            .get_or_insert_with(Vec::new)
+            .push(attribute.to_owned());
+        self
+    }
+
+    /// Add outer attribute like `cfg(unix)` written as `#[cfg(unix)]`
+    /// on declaration of the generated module in `mod.rs`
+    /// and on re-exports of its items.
+    ///
+    /// Outer attributes are not written anywhere when `mod.rs` is not generated.
+    pub fn outer_attribute(mut self, attribute: &str) -> Self {
+        self.outer_attributes
+            .get_or_insert_with(Vec::new)
+            .push(attribute.to_owned());
+        self
+    }
+
     /// Generate code bundled in protobuf crate. Regular users don't need this option.
     pub fn inside_protobuf(mut self, inside_protobuf: bool) -> Self {
         self.inside_protobuf = Some(inside_protobuf);
@@ -197,6 +262,30 @@ impl Customize {
         if let Some(v) = that.inside_protobuf {
             self.inside_protobuf = Some(v);
         }
+        if let Some(v) = &that.mod_rs_name {
+            self.mod_rs_name = Some(v.clone());
+        }
+        if let Some(v) = that.mod_rs_reexport {
+            self.mod_rs_reexport = Some(v);
+        }
+        if let Some(v) = &that.mod_feature {
+            self.mod_feature = Some(v.clone());
+        }
+        if let Some(v) = &that.inner_attributes {
+            self.inner_attributes = Some(v.clone());
+        }
+        if let Some(v) = &that.outer_attributes {
+            self.outer_attributes = Some(v.clone());
+        }
+        if let Some(v) = that.output_layout {
+            self.output_layout = Some(v);
+        }
+        if let Some(v) = &that.strip_package_prefix {
+            self.strip_package_prefix = Some(v.clone());
+        }
+        if let Some(v) = &that.full_path_mod_names {
+            self.full_path_mod_names = Some(v.clone());
+        }
     }
 
     /// Update unset fields of self with fields from other customize
@@ -236,6 +325,12 @@ impl Customize {
                 r.lite_runtime = Some(parse_bool(v)?);
             } else if n == "gen_mod_rs" {
                 r.gen_mod_rs = Some(parse_bool(v)?);
+            } else if n == "mod_rs_name" {
+                r.mod_rs_name = Some(v.to_owned());
+            } else if n == "mod_rs_reexport" {
+                r.mod_rs_reexport = Some(parse_bool(v)?);
+            } else if n == "mod_feature" {
+                r.mod_feature = Some(v.to_owned());
             } else if n == "inside_protobuf" {
                 r.inside_protobuf = Some(parse_bool(v)?);
             } else if n == "lite" {
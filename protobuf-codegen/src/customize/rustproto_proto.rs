@@ -15,6 +15,14 @@ pub(crate) fn customize_from_rustproto_for_message(source: &MessageOptions) -> C
     let lite_runtime = None;
     let gen_mod_rs = None;
     let inside_protobuf = None;
+    let mod_rs_name = None;
+    let mod_rs_reexport = None;
+    let mod_feature = None;
+    let inner_attributes = None;
+    let outer_attributes = None;
+    let output_layout = None;
+    let strip_package_prefix = None;
+    let full_path_mod_names = None;
     Customize {
         before,
         generate_accessors,
@@ -24,6 +32,14 @@ pub(crate) fn customize_from_rustproto_for_message(source: &MessageOptions) -> C
         lite_runtime,
         gen_mod_rs,
         inside_protobuf,
+        mod_rs_name,
+        mod_rs_reexport,
+        mod_feature,
+        inner_attributes,
+        outer_attributes,
+        output_layout,
+        strip_package_prefix,
+        full_path_mod_names,
     }
 }
 
@@ -40,6 +56,14 @@ pub(crate) fn customize_from_rustproto_for_field(source: &FieldOptions) -> Custo
     let lite_runtime = None;
     let gen_mod_rs = None;
     let inside_protobuf = None;
+    let mod_rs_name = None;
+    let mod_rs_reexport = None;
+    let mod_feature = None;
+    let inner_attributes = None;
+    let outer_attributes = None;
+    let output_layout = None;
+    let strip_package_prefix = None;
+    let full_path_mod_names = None;
     Customize {
         before,
         generate_accessors,
@@ -49,6 +73,14 @@ pub(crate) fn customize_from_rustproto_for_field(source: &FieldOptions) -> Custo
         lite_runtime,
         gen_mod_rs,
         inside_protobuf,
+        mod_rs_name,
+        mod_rs_reexport,
+        mod_feature,
+        inner_attributes,
+        outer_attributes,
+        output_layout,
+        strip_package_prefix,
+        full_path_mod_names,
     }
 }
 
@@ -61,6 +93,14 @@ pub(crate) fn customize_from_rustproto_for_file(source: &FileOptions) -> Customi
     let lite_runtime = rustproto::exts::lite_runtime_all.get(source);
     let gen_mod_rs = None;
     let inside_protobuf = None;
+    let mod_rs_name = None;
+    let mod_rs_reexport = None;
+    let mod_feature = None;
+    let inner_attributes = None;
+    let outer_attributes = None;
+    let output_layout = None;
+    let strip_package_prefix = None;
+    let full_path_mod_names = None;
     Customize {
         before,
         generate_accessors,
@@ -69,6 +109,14 @@ pub(crate) fn customize_from_rustproto_for_file(source: &FileOptions) -> Customi
         tokio_bytes_for_string,
         lite_runtime,
         inside_protobuf,
+        mod_rs_name,
+        mod_rs_reexport,
+        mod_feature,
+        inner_attributes,
+        outer_attributes,
+        output_layout,
+        strip_package_prefix,
+        full_path_mod_names,
         gen_mod_rs,
     }
 }
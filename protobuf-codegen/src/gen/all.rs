@@ -1,4 +1,5 @@
 use std::collections::HashMap;
+use std::collections::HashSet;
 
 use protobuf::descriptor::FileDescriptorProto;
 use protobuf::reflect::FileDescriptor;
@@ -9,19 +10,61 @@ use crate::compiler_plugin;
 use crate::customize::ctx::CustomizeElemCtx;
 use crate::customize::CustomizeCallback;
 use crate::gen::file::gen_file;
+use crate::gen::mod_rs::check_mod_collisions;
 use crate::gen::mod_rs::gen_mod_rs;
+use crate::gen::mod_rs::ModRsEntry;
+use crate::gen::paths::colliding_mod_names;
 use crate::gen::scope::RootScope;
 use crate::gen::well_known_types::gen_well_known_types_mod;
 use crate::Customize;
 
+/// Drop weak imports which were not found (the parser keeps them in `dependency`),
+/// generated code has no module to refer to.
+fn without_missing_weak_deps(file_descriptors: &[FileDescriptorProto]) -> Vec<FileDescriptorProto> {
+    let names: HashSet<&str> = file_descriptors.iter().map(|f| f.name()).collect();
+    file_descriptors
+        .iter()
+        .map(|file| {
+            let missing = |i: usize| {
+                file.weak_dependency.contains(&(i as i32))
+                    && !names.contains(file.dependency[i].as_str())
+            };
+            if !(0..file.dependency.len()).any(missing) {
+                return file.clone();
+            }
+            // New index of each kept dependency.
+            let mut indices = Vec::new();
+            let mut file = file.clone();
+            let dependency = std::mem::take(&mut file.dependency);
+            for (i, dep) in dependency.into_iter().enumerate() {
+                if missing(i) {
+                    indices.push(None);
+                } else {
+                    indices.push(Some(file.dependency.len() as i32));
+                    file.dependency.push(dep);
+                }
+            }
+            let remap = |list: &[i32]| -> Vec<i32> {
+                list.iter().filter_map(|&i| indices[i as usize]).collect()
+            };
+            file.public_dependency = remap(&file.public_dependency);
+            file.weak_dependency = remap(&file.weak_dependency);
+            file
+        })
+        .collect()
+}
+
 pub(crate) fn gen_all(
     file_descriptors: &[FileDescriptorProto],
     parser: &str,
     files_to_generate: &[ProtoPathBuf],
     customize: &Customize,
+    customize_files: &HashMap<ProtoPathBuf, Customize>,
     customize_callback: &dyn CustomizeCallback,
+    header_comment: Option<&str>,
 ) -> anyhow::Result<Vec<compiler_plugin::GenResult>> {
-    let file_descriptors = FileDescriptor::new_dynamic_fds(file_descriptors.to_vec(), &[])?;
+    let file_descriptors =
+        FileDescriptor::new_dynamic_fds(without_missing_weak_deps(file_descriptors), &[])?;
 
     let root_scope = RootScope {
         file_descriptors: &file_descriptors,
@@ -35,6 +78,13 @@ pub(crate) fn gen_all(
 
     let mut mods = Vec::new();
 
+    let generated: Vec<&FileDescriptor> = files_to_generate
+        .iter()
+        .filter_map(|f| files_map.get(f.as_path()).copied())
+        .collect();
+    let mut customize = customize.clone();
+    customize.full_path_mod_names = Some(colliding_mod_names(&generated, &customize));
+
     let customize = CustomizeElemCtx {
         for_elem: customize.clone(),
         for_children: customize.clone(),
@@ -47,17 +97,58 @@ pub(crate) fn gen_all(
             file_name,
             files_map.keys()
         ));
-        let gen_file_result = gen_file(file, &files_map, &root_scope, &customize, parser)?;
+        let customize = match customize_files.get(file_name) {
+            Some(for_file) => {
+                let mut for_file_over_global = customize.for_children.clone();
+                for_file_over_global.update_with(for_file);
+                CustomizeElemCtx {
+                    for_elem: for_file_over_global.clone(),
+                    for_children: for_file_over_global,
+                    callback: customize_callback,
+                }
+            }
+            None => customize.clone(),
+        };
+        let feature = customize.for_elem.mod_feature.clone();
+        let attributes = customize
+            .for_elem
+            .outer_attributes
+            .clone()
+            .unwrap_or_default();
+        let gen_file_result = gen_file(
+            file,
+            &files_map,
+            &root_scope,
+            &customize,
+            parser,
+            header_comment,
+        )?;
         results.push(gen_file_result.compiler_plugin_result);
-        mods.push(gen_file_result.mod_name);
+        mods.push(ModRsEntry {
+            parent_mods: gen_file_result.parent_mods,
+            name: gen_file_result.mod_name,
+            feature,
+            attributes,
+            top_level_items: gen_file_result.top_level_items,
+            proto_path: file_name.to_string(),
+        });
     }
+    check_mod_collisions(&mods)?;
 
     if customize.for_elem.inside_protobuf.unwrap_or(false) {
         results.push(gen_well_known_types_mod());
     }
 
     if customize.for_elem.gen_mod_rs.unwrap_or(true) {
-        results.push(gen_mod_rs(&mods));
+        results.push(gen_mod_rs(
+            &mods,
+            customize
+                .for_elem
+                .mod_rs_name
+                .as_deref()
+                .unwrap_or("mod.rs"),
+            customize.for_elem.mod_rs_reexport.unwrap_or(false),
+        )?);
     }
 
     Ok(results)
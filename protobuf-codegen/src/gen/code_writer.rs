@@ -60,16 +60,38 @@ impl<'a> CodeWriter<'a> {
         }
     }
 
-    pub(crate) fn write_generated_by(&mut self, pkg: &str, version: &str, parser: &str) {
-        self.write_line(format!(
-            "// This file is generated by {pkg} {version}. Do not edit",
-            pkg = pkg,
-            version = version
-        ));
-        self.write_line(format!(
-            "// .proto file is parsed by {parser}",
-            parser = parser
-        ));
+    /// Write header comment, default one when `header` is not specified.
+    pub(crate) fn write_generated_by(
+        &mut self,
+        header: Option<&str>,
+        pkg: &str,
+        version: &str,
+        parser: &str,
+    ) {
+        match header {
+            None => {
+                self.write_line(format!(
+                    "// This file is generated by {pkg} {version}. Do not edit",
+                    pkg = pkg,
+                    version = version
+                ));
+                self.write_line(format!(
+                    "// .proto file is parsed by {parser}",
+                    parser = parser
+                ));
+            }
+            Some(header) => {
+                let header = header
+                    .replace("{version}", version)
+                    .replace("{parser}", parser);
+                for line in header.lines() {
+                    match line {
+                        "" => self.write_line("//"),
+                        line => self.write_line(format!("// {}", line)),
+                    }
+                }
+            }
+        }
         self.write_generated_common();
     }
 
@@ -332,11 +354,19 @@ impl<'a> CodeWriter<'a> {
             .and_then(|ls| ls.iter().find(|l| l.path == path))
             .map(|l| l.leading_comments());
 
-        let lines = doc
+        // Comments keep the space following `//` or `*` marker,
+        // and `/** */` comments start with `*`.
+        let mut lines = doc
             .iter()
-            .map(|doc| doc.lines())
-            .flatten()
+            .flat_map(|doc| doc.strip_prefix('*').unwrap_or(doc).lines())
+            .map(|line| line.strip_prefix(' ').unwrap_or(line).trim_end())
             .collect::<Vec<_>>();
+        while lines.last() == Some(&"") {
+            lines.pop();
+        }
+        while lines.first() == Some(&"") {
+            lines.remove(0);
+        }
 
         // Skip comments with code blocks to avoid rustdoc trying to compile them.
         if !lines.iter().any(|line| line.starts_with("    ")) {
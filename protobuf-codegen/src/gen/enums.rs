@@ -173,8 +173,19 @@ impl<'a> EnumGen<'a> {
             &self.customize.for_elem,
             &self.enum_with_scope.en,
         );
+        static VALUE_NUMBER: protobuf::rt::Lazy<i32> = protobuf::rt::Lazy::new();
+        let value_number = *VALUE_NUMBER.get(|| {
+            protobuf::reflect::MessageDescriptor::for_type::<EnumDescriptorProto>()
+                .field_by_name("value")
+                .expect("`value` must exist")
+                .proto()
+                .number()
+        });
         w.expr_block(&format!("pub enum {}", type_name), |w| {
-            for value in self.values_all() {
+            for (id, value) in self.values_all().into_iter().enumerate() {
+                let mut path = self.path.to_vec();
+                path.extend([value_number, id as i32]);
+                w.all_documentation(self.info, &path);
                 write_protoc_insertion_point_for_enum_value(
                     w,
                     &self.customize.for_children,
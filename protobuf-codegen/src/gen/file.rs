@@ -4,6 +4,7 @@ use protobuf::descriptor::file_options;
 use protobuf::descriptor::FileDescriptorProto;
 use protobuf::reflect::FileDescriptor;
 use protobuf_parse::ProtoPath;
+use protobuf_parse::ProtoPathBuf;
 
 use crate::compiler_plugin;
 use crate::customize::ctx::CustomizeElemCtx;
@@ -14,14 +15,20 @@ use crate::gen::extensions::write_extensions;
 use crate::gen::file_descriptor::write_file_descriptor_data;
 use crate::gen::inside::protobuf_crate_path;
 use crate::gen::message::MessageGen;
-use crate::gen::paths::proto_path_to_rust_mod;
+use crate::gen::paths::file_mod_name;
+use crate::gen::paths::proto_file_output_path;
+use crate::gen::paths::proto_file_parent_mods;
 use crate::gen::scope::FileScope;
 use crate::gen::scope::RootScope;
-use crate::proto_name_to_rs;
+use crate::gen::scope::WithScope;
 
 pub(crate) struct GenFileResult {
     pub(crate) compiler_plugin_result: compiler_plugin::GenResult,
+    /// Modules enclosing the generated module.
+    pub(crate) parent_mods: Vec<String>,
     pub(crate) mod_name: String,
+    /// Rust names of top-level messages and enums.
+    pub(crate) top_level_items: Vec<String>,
 }
 
 pub(crate) fn gen_file(
@@ -30,6 +37,7 @@ pub(crate) fn gen_file(
     root_scope: &RootScope,
     parent_customize: &CustomizeElemCtx,
     parser: &str,
+    header_comment: Option<&str>,
 ) -> anyhow::Result<GenFileResult> {
     let lite_runtime_from_builtin_option = file_descriptor
         .proto()
@@ -53,14 +61,30 @@ pub(crate) fn gen_file(
 
     let lite_runtime = customize.for_elem.lite_runtime.unwrap_or(false);
 
+    let top_level_items = scope
+        .messages()
+        .iter()
+        .filter(|m| !m.is_map())
+        .map(|m| m.rust_name().to_string())
+        .chain(scope.enums().iter().map(|e| e.rust_name().to_string()))
+        .collect();
+
     let v = CodeWriter::with(|w| {
-        w.write_generated_by("rust-protobuf", env!("CARGO_PKG_VERSION"), parser);
+        w.write_generated_by(
+            header_comment,
+            "rust-protobuf",
+            env!("CARGO_PKG_VERSION"),
+            parser,
+        );
 
         w.write_line("");
         w.write_line(&format!(
             "//! Generated file from `{}`",
             file_descriptor.proto().name()
         ));
+        for attribute in customize.for_elem.inner_attributes.iter().flatten() {
+            w.write_line(format!("#![{}]", attribute));
+        }
 
         if customize.for_elem.lite_runtime.unwrap_or(false) {
             w.comment("Generated for lite runtime");
@@ -141,9 +165,14 @@ pub(crate) fn gen_file(
 
     Ok(GenFileResult {
         compiler_plugin_result: compiler_plugin::GenResult {
-            name: proto_name_to_rs(file_descriptor.proto().name()),
+            name: proto_file_output_path(file_descriptor, &customize.for_elem),
             content: v.into_bytes(),
+            source: Some(ProtoPathBuf::new(
+                file_descriptor.proto().name().to_owned(),
+            )?),
         },
-        mod_name: proto_path_to_rust_mod(file_descriptor.proto().name()).into_string(),
+        parent_mods: proto_file_parent_mods(file_descriptor, &customize.for_elem),
+        mod_name: file_mod_name(file_descriptor.proto().name(), &customize.for_elem).into_string(),
+        top_level_items,
     })
 }
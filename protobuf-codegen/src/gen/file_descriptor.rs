@@ -36,7 +36,7 @@ fn write_generate_file_descriptor(
     customize: &Customize,
     w: &mut CodeWriter,
 ) {
-    let deps = &file_descriptor.proto().dependency;
+    let deps = file_descriptor.deps();
     w.write_line(&format!(
         "let mut deps = {vec_with_capacity};",
         vec_with_capacity = expr_vec_with_capacity_const(deps.len())
@@ -44,7 +44,7 @@ fn write_generate_file_descriptor(
     for f in deps {
         w.write_line(&format!(
             "deps.push({}().clone());",
-            proto_path_to_fn_file_descriptor(f, customize)
+            proto_path_to_fn_file_descriptor(file_descriptor, f, customize)
         ));
     }
 
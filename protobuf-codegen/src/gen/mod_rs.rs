@@ -1,18 +1,141 @@
+use std::collections::HashMap;
+
 use crate::compiler_plugin;
 use crate::gen::code_writer::CodeWriter;
 
-pub(crate) fn gen_mod_rs(mods: &[String]) -> compiler_plugin::GenResult {
+/// Generated module declared in `mod.rs`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) struct ModRsEntry {
+    /// Inline modules enclosing the module, empty unless output layout is by package.
+    pub(crate) parent_mods: Vec<String>,
+    pub(crate) name: String,
+    /// Cargo feature the module declaration is conditional on.
+    pub(crate) feature: Option<String>,
+    /// Outer attributes of the module declaration.
+    pub(crate) attributes: Vec<String>,
+    pub(crate) top_level_items: Vec<String>,
+    /// `.proto` file the module is generated from.
+    pub(crate) proto_path: String,
+}
+
+impl ModRsEntry {
+    fn write_cfg(&self, w: &mut CodeWriter) {
+        if let Some(feature) = &self.feature {
+            w.write_line(format!("#[cfg(feature = \"{}\")]", feature));
+        }
+        for attribute in &self.attributes {
+            w.write_line(format!("#[{}]", attribute));
+        }
+    }
+}
+
+/// Declare sorted modules, nesting them in inline modules of their parents
+/// starting at `depth`.
+fn write_mods(w: &mut CodeWriter, mods: &[&ModRsEntry], depth: usize) {
+    let mut i = 0;
+    while i < mods.len() {
+        match mods[i].parent_mods.get(depth) {
+            None => {
+                mods[i].write_cfg(w);
+                w.write_line(format!("pub mod {};", mods[i].name));
+                i += 1;
+            }
+            Some(parent) => {
+                let end = i + mods[i..]
+                    .iter()
+                    .take_while(|m| m.parent_mods.get(depth) == Some(parent))
+                    .count();
+                w.pub_mod(parent, |w| write_mods(w, &mods[i..end], depth + 1));
+                i = end;
+            }
+        }
+    }
+}
+
+impl ModRsEntry {
+    /// Path of the module relative to `mod.rs`.
+    fn path(&self) -> String {
+        let mut path: Vec<&str> = self.parent_mods.iter().map(|p| p.as_str()).collect();
+        path.push(&self.name);
+        path.join("::")
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+enum ModRsError {
+    #[error(
+        "cannot re-export `{0}` from both `{1}` and `{2}` in mod.rs, \
+        disable `mod_rs_reexport` or rename one of them"
+    )]
+    ReexportCollision(String, String, String),
+    #[error(
+        "module `{0}` generated for `{1}` has the same path as module of package of `{2}`, \
+        rename the file or the package"
+    )]
+    ModCollidesWithPackage(String, String, String),
+}
+
+/// Check no generated module has the same path as a module enclosing other generated modules,
+/// like module of `a.proto` and module of package `a` with output layout by package.
+pub(crate) fn check_mod_collisions(mods: &[ModRsEntry]) -> anyhow::Result<()> {
+    let mut parents: HashMap<&[String], &ModRsEntry> = HashMap::new();
+    for m in mods {
+        for len in 1..=m.parent_mods.len() {
+            parents.entry(&m.parent_mods[..len]).or_insert(m);
+        }
+    }
+    for m in mods {
+        let path: Vec<String> = m.parent_mods.iter().chain([&m.name]).cloned().collect();
+        if let Some(other) = parents.get(&path[..]) {
+            return Err(ModRsError::ModCollidesWithPackage(
+                m.path(),
+                m.proto_path.clone(),
+                other.proto_path.clone(),
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
+
+/// Generate `mod.rs` declaring given modules.
+pub(crate) fn gen_mod_rs(
+    mods: &[ModRsEntry],
+    name: &str,
+    reexport: bool,
+) -> anyhow::Result<compiler_plugin::GenResult> {
+    let mut mods: Vec<&ModRsEntry> = mods.iter().collect();
+    mods.sort();
+    if reexport {
+        let mut reexported: HashMap<&str, &ModRsEntry> = HashMap::new();
+        for m in &mods {
+            for item in &m.top_level_items {
+                if let Some(prev) = reexported.insert(item, m) {
+                    return Err(
+                        ModRsError::ReexportCollision(item.clone(), prev.path(), m.path()).into(),
+                    );
+                }
+            }
+        }
+    }
+
     let v = CodeWriter::with_no_error(|w| {
         w.comment(&format!("{}generated", "@"));
         w.write_line("");
-        let mut mods: Vec<&String> = mods.into_iter().collect();
-        mods.sort();
-        for m in mods {
-            w.write_line(&format!("pub mod {};", m));
+        write_mods(w, &mods, 0);
+        if reexport {
+            w.write_line("");
+            for m in &mods {
+                for item in &m.top_level_items {
+                    m.write_cfg(w);
+                    w.write_line(format!("pub use self::{}::{};", m.path(), item));
+                }
+            }
         }
     });
-    compiler_plugin::GenResult {
-        name: "mod.rs".to_owned(),
+    Ok(compiler_plugin::GenResult {
+        name: name.to_owned(),
         content: v.into_bytes(),
-    }
+        source: None,
+    })
 }
@@ -1,9 +1,15 @@
+use std::collections::HashMap;
+
+use protobuf::reflect::FileDescriptor;
+
 use crate::gen::inside::protobuf_crate_path;
+use crate::gen::rust::component::RustPathComponent;
 use crate::gen::rust::ident::RustIdent;
 use crate::gen::rust::path::RustPath;
 use crate::gen::strx;
 use crate::gen::well_known_types::WELL_KNOWN_TYPES_PROTO_FILE_FULL_NAMES;
 use crate::Customize;
+use crate::OutputLayout;
 
 // Copy-pasted from libsyntax.
 fn ident_start(c: char) -> bool {
@@ -17,9 +23,11 @@ fn ident_continue(c: char) -> bool {
 
 pub(crate) fn proto_path_to_rust_mod(path: &str) -> RustIdent {
     let without_dir = strx::remove_to(path, std::path::is_separator);
-    let without_suffix = strx::remove_suffix(without_dir, ".proto");
+    mod_ident(strx::remove_suffix(without_dir, ".proto"))
+}
 
-    let name = without_suffix
+fn mod_ident(path: &str) -> RustIdent {
+    let name = path
         .chars()
         .enumerate()
         .map(|(i, c)| {
@@ -39,17 +47,122 @@ pub(crate) fn proto_path_to_rust_mod(path: &str) -> RustIdent {
     RustIdent::new(&name)
 }
 
+/// Module name of generated file.
+///
+/// Made of full proto path, like `a_config` for `a/config.proto`,
+/// when file name collides with another file generated in the same module.
+pub(crate) fn file_mod_name(proto_path: &str, customize: &Customize) -> RustIdent {
+    let full_path = customize
+        .full_path_mod_names
+        .as_ref()
+        .is_some_and(|names| names.iter().any(|n| n == proto_path));
+    if full_path {
+        mod_ident(strx::remove_suffix(proto_path, ".proto"))
+    } else {
+        proto_path_to_rust_mod(proto_path)
+    }
+}
+
+/// Files which would be generated as modules of the same name in the same module.
+pub(crate) fn colliding_mod_names(files: &[&FileDescriptor], customize: &Customize) -> Vec<String> {
+    let mut by_mod: HashMap<(Vec<String>, RustIdent), Vec<&str>> = HashMap::new();
+    for file in files {
+        let key = (
+            proto_file_parent_mods(file, customize),
+            proto_path_to_rust_mod(file.name()),
+        );
+        by_mod.entry(key).or_default().push(file.name());
+    }
+    let mut r: Vec<String> = by_mod
+        .into_values()
+        .filter(|names| names.len() > 1)
+        .flatten()
+        .map(|n| n.to_owned())
+        .collect();
+    r.sort();
+    r
+}
+
 /// Used in protobuf-codegen-identical-test
 pub fn proto_name_to_rs(proto_file_path: &str) -> String {
     format!("{}.rs", proto_path_to_rust_mod(proto_file_path))
 }
 
+/// Package with [`Customize::strip_package_prefix`] removed
+/// if package is equal to the prefix or is nested in it.
+fn strip_package_prefix<'a>(package: &'a str, customize: &Customize) -> &'a str {
+    let prefix = match customize.strip_package_prefix.as_deref() {
+        Some(prefix) => prefix.trim_matches('.'),
+        None => return package,
+    };
+    match package.strip_prefix(prefix) {
+        Some("") => "",
+        Some(rest) if !prefix.is_empty() => rest.strip_prefix('.').unwrap_or(package),
+        _ => package,
+    }
+}
+
+/// Modules enclosing module of generated file, relative to output directory.
+fn file_parent_mods(package: &str, customize: &Customize) -> Vec<RustIdent> {
+    match customize.output_layout.unwrap_or_default() {
+        OutputLayout::IncludeRelative => Vec::new(),
+        OutputLayout::Package => strip_package_prefix(package, customize)
+            .split('.')
+            .filter(|p| !p.is_empty())
+            .map(RustIdent::new)
+            .collect(),
+    }
+}
+
+/// Path of generated file relative to output directory.
+pub(crate) fn proto_file_output_path(file: &FileDescriptor, customize: &Customize) -> String {
+    let mut path = String::new();
+    for m in file_parent_mods(file.package(), customize) {
+        path.push_str(&format!("{}/", m));
+    }
+    path.push_str(&format!("{}.rs", file_mod_name(file.name(), customize)));
+    path
+}
+
+/// Names of modules enclosing module of generated file, to be declared in `mod.rs`.
+pub(crate) fn proto_file_parent_mods(file: &FileDescriptor, customize: &Customize) -> Vec<String> {
+    file_parent_mods(file.package(), customize)
+        .iter()
+        .map(|m| m.to_string())
+        .collect()
+}
+
+/// Path from module of generated file of package `from_package`
+/// to module of generated file `to`.
+pub(crate) fn path_to_file_mod(
+    from_package: &str,
+    to: &FileDescriptor,
+    customize: &Customize,
+) -> RustPath {
+    let from_mods = file_parent_mods(from_package, customize);
+    let to_mods = file_parent_mods(to.package(), customize);
+    let common = from_mods
+        .iter()
+        .zip(&to_mods)
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut path = RustPath::super_path();
+    for _ in common..from_mods.len() {
+        path = path.append_component(RustPathComponent::SUPER);
+    }
+    for m in &to_mods[common..] {
+        path = path.append_ident(m.clone());
+    }
+    path.append_ident(file_mod_name(to.name(), customize))
+}
+
 pub(crate) fn proto_path_to_fn_file_descriptor(
-    proto_path: &str,
+    from: &FileDescriptor,
+    to: &FileDescriptor,
     customize: &Customize,
 ) -> RustPath {
     let protobuf_crate = protobuf_crate_path(customize);
-    match proto_path {
+    match to.name() {
         "rustproto.proto" => protobuf_crate.append("rustproto::file_descriptor".into()),
         "google/protobuf/descriptor.proto" => {
             protobuf_crate.append("descriptor::file_descriptor".into())
@@ -58,9 +171,7 @@ pub(crate) fn proto_path_to_fn_file_descriptor(
             .append_ident("well_known_types".into())
             .append_ident(proto_path_to_rust_mod(s))
             .append_ident("file_descriptor".into()),
-        s => RustPath::super_path()
-            .append_ident(proto_path_to_rust_mod(s))
-            .append_ident("file_descriptor".into()),
+        _ => path_to_file_mod(from.package(), to, customize).append_ident("file_descriptor".into()),
     }
 }
 
@@ -23,10 +23,6 @@ impl RustIdentWithPath {
         RustIdentWithPath { path, ident }
     }
 
-    pub fn prepend_ident(&mut self, ident: RustIdent) {
-        self.path.prepend_ident(ident)
-    }
-
     pub fn to_path(&self) -> RustPath {
         self.path.clone().append_ident(self.ident.clone())
     }
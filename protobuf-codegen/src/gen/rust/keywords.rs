@@ -56,6 +56,7 @@ static RUST_KEYWORDS: &'static [&'static str] = &[
     "final",
     "override",
     "macro",
+    "try",
 ];
 
 // https://internals.rust-lang.org/t/raw-identifiers-dont-work-for-all-identifiers/9094/3
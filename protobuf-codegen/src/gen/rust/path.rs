@@ -34,11 +34,6 @@ impl RustPath {
         self.path.remove_first()
     }
 
-    pub fn prepend_ident(&mut self, ident: RustIdent) {
-        assert!(!self.absolute);
-        self.path.prepend_ident(ident);
-    }
-
     pub fn append(self, path: RustPath) -> RustPath {
         if path.absolute {
             path
@@ -48,10 +48,6 @@ impl RustRelativePath {
         }
     }
 
-    pub fn prepend_ident(&mut self, ident: RustIdent) {
-        self.path.insert(0, RustPathComponent::Ident(ident));
-    }
-
     pub fn append(mut self, path: RustRelativePath) -> RustRelativePath {
         for c in path.path {
             self.path.push(c);
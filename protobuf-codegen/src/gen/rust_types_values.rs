@@ -11,8 +11,8 @@ use crate::gen::field::type_ext::TypeExt;
 use crate::gen::file_and_mod::FileAndMod;
 use crate::gen::inside::protobuf_crate_path;
 use crate::gen::message::RustTypeMessage;
+use crate::gen::paths::path_to_file_mod;
 use crate::gen::paths::proto_path_to_rust_mod;
-use crate::gen::rust::component::RustPathComponent;
 use crate::gen::rust::ident::RustIdent;
 use crate::gen::rust::ident_with_path::RustIdentWithPath;
 use crate::gen::rust::path::RustPath;
@@ -517,8 +517,12 @@ pub(crate) fn message_or_enum_to_rust_relative(
             .relative_mod
             .to_reverse()
             .into_path()
-            .append_component(RustPathComponent::SUPER)
-            .append_with_ident(message_or_enum.rust_name_with_file())
+            .append(path_to_file_mod(
+                &current.package,
+                &message_or_enum.file_descriptor(),
+                &current.customize,
+            ))
+            .append_with_ident(message_or_enum.rust_name_to_file())
     }
 }
 
@@ -17,7 +17,6 @@ use crate::gen::field::rust_field_name_for_protobuf_field_name;
 use crate::gen::file_and_mod::FileAndMod;
 use crate::gen::map::map_entry;
 use crate::gen::message::message_name_to_nested_mod_name;
-use crate::gen::paths::proto_path_to_rust_mod;
 use crate::gen::rust::ident::RustIdent;
 use crate::gen::rust::ident_with_path::RustIdentWithPath;
 use crate::gen::rust::rel_path::RustRelativePath;
@@ -279,6 +278,7 @@ impl<'a> Scope<'a> {
     pub fn file_and_mod(&self, customize: Customize) -> FileAndMod {
         FileAndMod {
             file: self.file_scope.file_descriptor.proto().name().to_owned(),
+            package: self.file_scope.file_descriptor.proto().package().to_owned(),
             relative_mod: self.rust_path_to_file(),
             customize,
         }
@@ -325,15 +325,6 @@ pub(crate) trait WithScope<'a> {
             .into_path()
             .with_ident(self.rust_name())
     }
-
-    // fully-qualified name of this type
-    fn rust_name_with_file(&self) -> RustIdentWithPath {
-        let mut r = self.rust_name_to_file();
-        r.prepend_ident(proto_path_to_rust_mod(
-            self.scope().file_descriptor().name(),
-        ));
-        r
-    }
 }
 
 #[derive(Clone, Debug)]
@@ -102,6 +102,7 @@ pub(crate) fn gen_well_known_types_mod() -> compiler_plugin::GenResult {
     compiler_plugin::GenResult {
         name: "well_known_types_mod.rs".to_string(),
         content: v.into_bytes(),
+        source: None,
     }
 }
 
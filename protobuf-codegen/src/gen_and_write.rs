@@ -1,12 +1,17 @@
 #![doc(hidden)]
 
+use std::collections::HashMap;
 use std::fs;
 use std::io;
+use std::io::Write;
 use std::path::Path;
+use std::time::Instant;
 
 use protobuf::descriptor::FileDescriptorProto;
+use protobuf_parse::ProtoPath;
 use protobuf_parse::ProtoPathBuf;
 
+use crate::codegen::CodegenObserver;
 use crate::customize::CustomizeCallback;
 use crate::gen::all::gen_all;
 use crate::Customize;
@@ -19,6 +24,12 @@ enum Error {
     OutputDoesNotExistOrNotAccssible(String, #[source] io::Error),
     #[error("failed to create file `{0}`: {1}")]
     FailedToWriteFile(String, #[source] io::Error),
+    #[error("failed to rename temporary file to `{0}`: {1}")]
+    FailedToPersistFile(String, #[source] io::Error),
+    #[error("cannot write `{1}`: `{0}` exists and is not a directory")]
+    NotADirectory(String, String),
+    #[error("refusing to overwrite `{0}`, which does not look like a generated file")]
+    OverwriteNonGenerated(String),
 }
 
 #[doc(hidden)]
@@ -28,8 +39,34 @@ pub fn gen_and_write(
     files_to_generate: &[ProtoPathBuf],
     out_dir: &Path,
     customize: &Customize,
+    customize_files: &HashMap<ProtoPathBuf, Customize>,
     customize_callback: &dyn CustomizeCallback,
 ) -> anyhow::Result<()> {
+    check_out_dir(out_dir)?;
+
+    let results = gen_all(
+        file_descriptors,
+        parser,
+        files_to_generate,
+        customize,
+        customize_files,
+        customize_callback,
+        None,
+    )?;
+
+    write_files(
+        out_dir,
+        results
+            .iter()
+            .map(|r| (Path::new(&r.name), r.content.as_slice())),
+        false,
+        false,
+        None,
+    )
+}
+
+/// Check that output directory exists and is a directory.
+pub(crate) fn check_out_dir(out_dir: &Path) -> anyhow::Result<()> {
     match out_dir.metadata() {
         Ok(m) => {
             if !m.is_dir() {
@@ -42,21 +79,135 @@ pub fn gen_and_write(
             );
         }
     }
+    Ok(())
+}
 
-    let results = gen_all(
-        file_descriptors,
-        parser,
-        files_to_generate,
-        customize,
-        customize_callback,
-    )?;
+/// Whether file content starts with comments written to generated files.
+fn is_generated(content: &[u8]) -> bool {
+    String::from_utf8_lossy(content)
+        .lines()
+        .take_while(|l| l.is_empty() || l.starts_with("//"))
+        .any(|l| {
+            l.contains("@generated")
+                || l.contains("This file is generated by")
+                || l.starts_with("//! Generated file from")
+        })
+}
+
+/// Whether file exists and has given content.
+fn is_unchanged(path: &Path, content: &[u8]) -> bool {
+    match fs::metadata(path) {
+        // Do not read files which cannot be equal.
+        Ok(m) if m.is_file() && m.len() == content.len() as u64 => {}
+        _ => return false,
+    }
+    fs::read(path).is_ok_and(|existing| existing == content)
+}
 
-    for r in &results {
-        let mut file_path = out_dir.to_owned();
-        file_path.push(&r.name);
-        fs::write(&file_path, r.content.as_slice())
+/// Write generated files relative to output directory.
+///
+/// With `refuse_overwrite_non_generated` nothing is written
+/// if any of existing files does not look generated.
+/// With `skip_unchanged` files which already have the same content are not touched.
+pub(crate) fn write_files<'a>(
+    out_dir: &Path,
+    files: impl IntoIterator<Item = (&'a Path, &'a [u8])>,
+    refuse_overwrite_non_generated: bool,
+    skip_unchanged: bool,
+    observer: Option<&dyn CodegenObserver>,
+) -> anyhow::Result<()> {
+    let files: Vec<_> = files.into_iter().collect();
+    if refuse_overwrite_non_generated {
+        for (name, _) in &files {
+            let file_path = out_dir.join(name);
+            match fs::read(&file_path) {
+                Ok(existing) if !is_generated(&existing) => {
+                    return Err(
+                        Error::OverwriteNonGenerated(file_path.display().to_string()).into(),
+                    );
+                }
+                _ => {}
+            }
+        }
+    }
+    for (name, content) in files {
+        let start = observer.map(|_| Instant::now());
+        let file_path = out_dir.join(name);
+        if skip_unchanged && is_unchanged(&file_path, content) {
+            continue;
+        }
+        create_parent_dirs(out_dir, name)?;
+        fs::write(&file_path, content)
             .map_err(|e| Error::FailedToWriteFile(file_path.display().to_string(), e))?;
+        if let (Some(observer), Some(start)) = (observer, start) {
+            observer.file_written(name, start.elapsed());
+        }
     }
+    Ok(())
+}
+
+/// Create directories of file `name` relative to output directory,
+/// failing clearly if one of them is an existing file.
+fn create_parent_dirs(out_dir: &Path, name: &Path) -> anyhow::Result<()> {
+    let mut dir = out_dir.to_owned();
+    for component in name.parent().into_iter().flat_map(|p| p.components()) {
+        dir.push(component);
+        match fs::metadata(&dir) {
+            Ok(m) if m.is_dir() => continue,
+            Ok(_) => {
+                return Err(Error::NotADirectory(
+                    dir.display().to_string(),
+                    out_dir.join(name).display().to_string(),
+                )
+                .into())
+            }
+            Err(_) => {}
+        }
+        match fs::create_dir(&dir) {
+            Ok(()) => {}
+            // Created concurrently.
+            Err(_) if dir.is_dir() => {}
+            Err(e) => {
+                return Err(
+                    Error::FailedToWriteFile(out_dir.join(name).display().to_string(), e).into(),
+                )
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Write files to writers provided by `writer` for each file name.
+pub(crate) fn write_with_writer<'a>(
+    files: impl IntoIterator<Item = (&'a ProtoPath, &'a [u8])>,
+    mut writer: impl FnMut(&ProtoPath) -> io::Result<Box<dyn Write>>,
+    observer: Option<&dyn CodegenObserver>,
+) -> anyhow::Result<()> {
+    for (name, content) in files {
+        let start = observer.map(|_| Instant::now());
+        let error = |e| Error::FailedToWriteFile(name.to_string(), e);
+        let mut w = writer(name).map_err(error)?;
+        w.write_all(content).map_err(error)?;
+        w.flush().map_err(error)?;
+        if let (Some(observer), Some(start)) = (observer, start) {
+            observer.file_written(name.to_path(), start.elapsed());
+        }
+    }
+    Ok(())
+}
 
+/// Write a file by writing a temporary file in the same directory first
+/// and then renaming it, so readers never observe partially written file.
+pub(crate) fn write_file_atomically(path: &Path, content: &[u8]) -> anyhow::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let mut temp = tempfile::NamedTempFile::new_in(dir)
+        .map_err(|e| Error::FailedToWriteFile(path.display().to_string(), e))?;
+    temp.write_all(content)
+        .map_err(|e| Error::FailedToWriteFile(path.display().to_string(), e))?;
+    temp.persist(path)
+        .map_err(|e| Error::FailedToPersistFile(path.display().to_string(), e.error))?;
     Ok(())
 }
@@ -139,7 +139,18 @@ pub mod gen_and_write;
 pub mod protoc_gen_rust;
 
 pub use codegen::Codegen;
+pub use codegen::CodegenError;
+pub use codegen::CodegenLimits;
+pub use codegen::CodegenObserver;
+pub use codegen::CodegenReport;
+pub use codegen::DependencyGraphFormat;
+pub use codegen::OutputLayout;
 pub use customize::Customize;
 pub use customize::CustomizeCallback;
 #[doc(hidden)]
 pub use gen::paths::proto_name_to_rs;
+pub use protobuf_parse::pure::ArchiveFileLoader;
+pub use protobuf_parse::pure::FileLoader;
+pub use protobuf_parse::pure::WellKnownTypes;
+pub use protobuf_parse::DuplicateIncludePolicy;
+pub use protobuf_parse::Unsupported;
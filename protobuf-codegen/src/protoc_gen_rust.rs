@@ -1,5 +1,7 @@
 #![doc(hidden)]
 
+use std::collections::HashMap;
+
 use crate::compiler_plugin;
 use crate::customize::CustomizeCallbackDefault;
 use crate::gen::all::gen_all;
@@ -14,7 +16,9 @@ pub fn protoc_gen_rust_main() {
             "protoc --rust-out=...",
             r.files_to_generate,
             &customize,
+            &HashMap::new(),
             &CustomizeCallbackDefault,
+            None,
         )
     })
     .expect("plugin failed");
@@ -0,0 +1,23 @@
+use std::path::PathBuf;
+
+/// What to do when a `.proto` file is found in more than one include directory.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum DuplicateIncludePolicy {
+    /// Use the file from the include directory added first, like `protoc` does.
+    #[default]
+    FirstWins,
+    /// Use the file from the include directory added last.
+    LastWins,
+    /// Fail parsing. Useful for reproducible builds.
+    Error,
+}
+
+impl DuplicateIncludePolicy {
+    /// Include directories in the order they should be searched.
+    pub(crate) fn search_order(&self, includes: &[PathBuf]) -> Vec<PathBuf> {
+        match self {
+            DuplicateIncludePolicy::LastWins => includes.iter().rev().cloned().collect(),
+            DuplicateIncludePolicy::FirstWins | DuplicateIncludePolicy::Error => includes.to_vec(),
+        }
+    }
+}
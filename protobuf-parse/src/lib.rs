@@ -13,6 +13,7 @@
 extern crate core;
 
 mod case_convert;
+mod duplicate_include_policy;
 mod parse_and_typecheck;
 mod parser;
 mod path;
@@ -25,12 +26,14 @@ mod protobuf_rel_path;
 pub(crate) mod protoc;
 pub mod pure;
 mod rel_path;
+mod schema_diff;
 mod test_against_protobuf_protos;
 mod which_parser;
 
 // Public API
 // Non-public API used by codegen crate.
 pub use case_convert::*;
+pub use duplicate_include_policy::DuplicateIncludePolicy;
 pub use parse_and_typecheck::*;
 pub use parser::Parser;
 pub use proto_path::*;
@@ -38,6 +41,8 @@ use protobuf::reflect::FileDescriptor;
 pub use protobuf_abs_path::*;
 pub use protobuf_ident::*;
 pub use protobuf_rel_path::*;
+pub use schema_diff::schema_diff;
+pub use schema_diff::SchemaChange;
 
 use crate::pure::model;
 
@@ -1,4 +1,14 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+
+use protobuf::descriptor::DescriptorProto;
+use protobuf::descriptor::FileDescriptorProto;
+use protobuf::UnknownValueRef;
+
 use crate::ProtoPathBuf;
+use crate::ProtobufAbsPath;
+use crate::ProtobufIdentRef;
 
 /// Result of parsing `.proto` files.
 #[doc(hidden)]
@@ -6,17 +16,175 @@ pub struct ParsedAndTypechecked {
     /// One entry for each input `.proto` file.
     pub relative_paths: Vec<ProtoPathBuf>,
     /// All parsed `.proto` files including dependencies of input files.
-    pub file_descriptors: Vec<protobuf::descriptor::FileDescriptorProto>,
+    pub file_descriptors: Vec<FileDescriptorProto>,
     /// Description of the parser (e.g. to include in generated files).
     pub parser: String,
+    /// Filesystem paths of all `.proto` files read, including dependencies of input files.
+    pub read_files: Vec<PathBuf>,
+    /// Dependencies which were not found in include path
+    /// and were taken from protos bundled with the parser.
+    pub embedded_files: Vec<ProtoPathBuf>,
+    /// Options which were dropped because parser cannot represent them,
+    /// weak imports which were not found and imports which were not used,
+    /// prefixed with file name and location.
+    pub warnings: Vec<String>,
+    /// Constructs which pure parser cannot fully represent and dropped from the output:
+    /// options of group type, and extension fields and `Any` type URLs
+    /// in message option values.
+    ///
+    /// Always empty for `protoc` parser.
+    pub unsupported: Vec<Unsupported>,
+}
+
+/// Construct in a `.proto` file which pure parser cannot fully represent,
+/// like an option value referencing an extension field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unsupported {
+    /// File containing the construct.
+    pub file: ProtoPathBuf,
+    /// 1-based line.
+    pub line: u32,
+    /// 1-based column.
+    pub col: u32,
+    /// What is not supported, like ``option `x` of group type``.
+    pub construct: String,
+}
+
+impl fmt::Display for Unsupported {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: {}",
+            self.file, self.line, self.col, self.construct
+        )
+    }
+}
+
+/// Syntax or edition of a parsed `.proto` file.
+///
+/// Unlike [`protobuf::reflect::Syntax`], which is used to configure parsing
+/// (for example in [`Parser::default_syntax`](crate::Parser::default_syntax)),
+/// this also describes files using editions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FileSyntax {
+    /// `syntax = "proto2"`, also when file has no `syntax` statement.
+    Proto2,
+    /// `syntax = "proto3"`
+    Proto3,
+    /// `edition = "..."`, like `2023`.
+    Edition(String),
+}
+
+impl FileSyntax {
+    /// Syntax of a parsed file, for example of a file from
+    /// [`Parser::file_descriptor_set`](crate::Parser::file_descriptor_set).
+    pub fn from_file_proto(file: &FileDescriptorProto) -> FileSyntax {
+        match file.syntax() {
+            "proto3" => FileSyntax::Proto3,
+            "editions" => {
+                // Bundled `descriptor.proto` predates editions, so the field is unknown.
+                let edition = match file.special_fields.unknown_fields().get(14) {
+                    Some(UnknownValueRef::Varint(1000)) => "2023".to_owned(),
+                    Some(UnknownValueRef::Varint(1001)) => "2024".to_owned(),
+                    Some(UnknownValueRef::Varint(n)) => n.to_string(),
+                    _ => String::new(),
+                };
+                FileSyntax::Edition(edition)
+            }
+            _ => FileSyntax::Proto2,
+        }
+    }
+}
+
+impl ParsedAndTypechecked {
+    /// Syntax of each parsed file, including dependencies of input files.
+    pub fn syntaxes(&self) -> HashMap<ProtoPathBuf, FileSyntax> {
+        self.file_descriptors
+            .iter()
+            .filter_map(|file| {
+                let path = ProtoPathBuf::new(file.name().to_owned()).ok()?;
+                Some((path, FileSyntax::from_file_proto(file)))
+            })
+            .collect()
+    }
+
+    /// [`type_names`] of all input files.
+    pub fn all_type_names(&self) -> Vec<String> {
+        let mut names = Vec::new();
+        for relative_path in &self.relative_paths {
+            let file = match self
+                .file_descriptors
+                .iter()
+                .find(|f| f.name() == relative_path.to_str())
+            {
+                Some(file) => file,
+                None => continue,
+            };
+            names.extend(type_names(file));
+        }
+        names
+    }
+}
+
+/// Fully qualified names of messages and enums declared in a file,
+/// including nested ones, like `.my.pkg.Message.Nested`.
+///
+/// Each message is followed by its nested types.
+/// Entry messages synthesized for map fields are not included.
+pub fn type_names(file: &FileDescriptorProto) -> Vec<String> {
+    file_type_names(file, false)
+}
+
+/// Fully qualified names of messages and enums declared in a file,
+/// optionally including map entry messages.
+pub(crate) fn file_type_names(file: &FileDescriptorProto, map_entries: bool) -> Vec<String> {
+    let mut names = Vec::new();
+    let package = ProtobufAbsPath::package_from_file_proto(file);
+    for message in &file.message_type {
+        message_type_names(&package, message, map_entries, &mut names);
+    }
+    for enumeration in &file.enum_type {
+        names.push(child_name(&package, enumeration.name()).to_string());
+    }
+    names
+}
+
+fn child_name(scope: &ProtobufAbsPath, name: &str) -> ProtobufAbsPath {
+    let mut path = scope.clone();
+    path.push_simple(ProtobufIdentRef::new(name));
+    path
+}
+
+fn message_type_names(
+    scope: &ProtobufAbsPath,
+    message: &DescriptorProto,
+    map_entries: bool,
+    names: &mut Vec<String>,
+) {
+    if !map_entries && message.options.map_entry() {
+        return;
+    }
+    let path = child_name(scope, message.name());
+    names.push(path.to_string());
+    for nested in &message.nested_type {
+        message_type_names(&path, nested, map_entries, names);
+    }
+    for enumeration in &message.enum_type {
+        names.push(child_name(&path, enumeration.name()).to_string());
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use std::collections::HashMap;
     use std::collections::HashSet;
     use std::fs;
 
+    use protobuf::descriptor::FileDescriptorProto;
+
+    use crate::FileSyntax;
     use crate::Parser;
+    use crate::ProtoPathBuf;
 
     #[test]
     fn parse_and_typecheck() {
@@ -77,4 +245,83 @@ mod test {
                 .name()
         );
     }
+
+    #[test]
+    fn all_type_names() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        let b_proto = dir.path().join("b.proto");
+        let c_proto = dir.path().join("c.proto");
+        fs::write(&a_proto, "syntax = 'proto3'; message Dep {}").unwrap();
+        fs::write(
+            &b_proto,
+            "syntax = 'proto3'; package p.q; import 'a.proto';
+            message M {
+                message N { enum E { A = 0; } }
+                enum F { B = 0; }
+                map<string, Dep> m = 1;
+            }
+            enum G { C = 0; }",
+        )
+        .unwrap();
+        fs::write(&c_proto, "syntax = 'proto3'; message Top {}").unwrap();
+
+        let parsed = Parser::new()
+            .pure()
+            .include(dir.path())
+            .inputs([&b_proto, &c_proto])
+            .parse_and_typecheck()
+            .unwrap();
+        assert_eq!(
+            vec![
+                ".p.q.M",
+                ".p.q.M.N",
+                ".p.q.M.N.E",
+                ".p.q.M.F",
+                ".p.q.G",
+                ".Top",
+            ],
+            parsed.all_type_names()
+        );
+    }
+
+    #[test]
+    fn syntaxes() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("a.proto"), "syntax = 'proto2';").unwrap();
+        fs::write(dir.path().join("b.proto"), "message B {}").unwrap();
+        fs::write(dir.path().join("c.proto"), "edition = '2023';").unwrap();
+        let d_proto = dir.path().join("d.proto");
+        fs::write(
+            &d_proto,
+            "syntax = 'proto3'; import 'a.proto'; import 'b.proto'; import 'c.proto';",
+        )
+        .unwrap();
+
+        let parsed = Parser::new()
+            .pure()
+            .include(dir.path())
+            .input(&d_proto)
+            .parse_and_typecheck()
+            .unwrap();
+        let path = |p: &str| ProtoPathBuf::new(p.to_owned()).unwrap();
+        assert_eq!(
+            HashMap::from([
+                (path("a.proto"), FileSyntax::Proto2),
+                (path("b.proto"), FileSyntax::Proto2),
+                (path("c.proto"), FileSyntax::Edition("2023".to_owned())),
+                (path("d.proto"), FileSyntax::Proto3),
+            ]),
+            parsed.syntaxes()
+        );
+
+        let mut e = FileDescriptorProto::new();
+        e.set_name("e.proto".to_owned());
+        e.set_syntax("editions".to_owned());
+        e.special_fields.mut_unknown_fields().add_varint(14, 1000);
+        assert_eq!(
+            FileSyntax::Edition("2023".to_owned()),
+            FileSyntax::from_file_proto(&e)
+        );
+    }
 }
@@ -3,14 +3,25 @@ use std::ffi::OsStr;
 use std::ffi::OsString;
 use std::path::Path;
 use std::path::PathBuf;
+use std::sync::Arc;
 
 use anyhow::Context;
+use protobuf::descriptor::FileDescriptorProto;
 use protobuf::descriptor::FileDescriptorSet;
+use protobuf::reflect::Syntax;
 
 use crate::protoc;
 use crate::pure;
+use crate::pure::parse_and_typecheck::FileLoaderHolder;
+use crate::pure::parse_and_typecheck::ParseObserverHolder;
+use crate::pure::parse_and_typecheck::WellKnownTypesHolder;
+use crate::pure::FileLoader;
+use crate::pure::ParseObserver;
+use crate::pure::WellKnownTypes;
 use crate::which_parser::WhichParser;
+use crate::DuplicateIncludePolicy;
 use crate::ParsedAndTypechecked;
+use crate::ProtoPathBuf;
 
 /// Configure and invoke `.proto` parser.
 #[derive(Default, Debug)]
@@ -21,6 +32,27 @@ pub struct Parser {
     pub(crate) protoc: Option<PathBuf>,
     pub(crate) protoc_extra_args: Vec<OsString>,
     pub(crate) capture_stderr: bool,
+    pub(crate) include_source_info: bool,
+    pub(crate) disable_embedded_protos: bool,
+    pub(crate) prefer_embedded_well_known: bool,
+    pub(crate) warn_well_known_drift: bool,
+    pub(crate) extra_embedded_protos: Vec<(ProtoPathBuf, String)>,
+    pub(crate) well_known_types: Option<WellKnownTypesHolder>,
+    pub(crate) content_files: Vec<(ProtoPathBuf, String)>,
+    pub(crate) content_inputs: Vec<ProtoPathBuf>,
+    pub(crate) file_loader: Option<FileLoaderHolder>,
+    pub(crate) observer: Option<ParseObserverHolder>,
+    pub(crate) duplicate_include_policy: DuplicateIncludePolicy,
+    pub(crate) prefer_importer_include: bool,
+    pub(crate) auto_include_input_dirs: bool,
+    pub(crate) parallelism: usize,
+    pub(crate) cache_dir: Option<PathBuf>,
+    pub(crate) strict: bool,
+    pub(crate) default_syntax: Option<Syntax>,
+    pub(crate) require_exact_case: Option<bool>,
+    pub(crate) max_files: Option<usize>,
+    pub(crate) max_import_depth: Option<usize>,
+    pub(crate) descriptors: Vec<FileDescriptorProto>,
 }
 
 impl Parser {
@@ -97,6 +129,236 @@ impl Parser {
         self
     }
 
+    /// Populate `source_code_info` in parsed file descriptors.
+    ///
+    /// Pure parser only records locations and comments of declarations
+    /// (messages, fields, enums, enum values, services and methods).
+    pub fn include_source_info(&mut self, include_source_info: bool) -> &mut Self {
+        self.include_source_info = include_source_info;
+        self
+    }
+
+    /// Use protos bundled with this crate (like `google/protobuf/descriptor.proto`)
+    /// when an import is not found in include path. Enabled by default.
+    ///
+    /// `protoc` always uses its own bundled protos,
+    /// so disabling them fails with `protoc` parser.
+    pub fn embedded_protos(&mut self, enabled: bool) -> &mut Self {
+        self.disable_embedded_protos = !enabled;
+        self
+    }
+
+    /// Use bundled well-known type protos and `rustproto.proto`
+    /// even if a file with the same path is found in include path,
+    /// so the result does not depend on the version of protos installed.
+    ///
+    /// This option is not supported by `protoc` parser.
+    pub fn prefer_embedded_well_known(&mut self, prefer_embedded_well_known: bool) -> &mut Self {
+        self.prefer_embedded_well_known = prefer_embedded_well_known;
+        self
+    }
+
+    /// Warn when a well-known type proto (or `rustproto.proto`) found in include path
+    /// differs from the version bundled with the parser, ignoring whitespace.
+    ///
+    /// Such files produce different descriptors than builds which use bundled protos.
+    /// This option is not supported by `protoc` parser.
+    pub fn warn_well_known_drift(&mut self, warn_well_known_drift: bool) -> &mut Self {
+        self.warn_well_known_drift = warn_well_known_drift;
+        self
+    }
+
+    /// Use given well-known type protos instead of the ones bundled with the parser,
+    /// e.g. to match descriptors produced by a particular protobuf release.
+    ///
+    /// This option is not supported by `protoc` parser.
+    pub fn well_known_types(&mut self, well_known_types: impl WellKnownTypes) -> &mut Self {
+        self.well_known_types = Some(WellKnownTypesHolder(Arc::new(well_known_types)));
+        self
+    }
+
+    /// Protobuf release well-known type protos bundled with the parser come from.
+    pub fn well_known_types_version() -> &'static str {
+        crate::proto::WELL_KNOWN_TYPES_VERSION
+    }
+
+    /// Register an in-memory `.proto` file used when an import is not found in include path.
+    ///
+    /// Files registered with this function take precedence over bundled protos,
+    /// but files found in include path take precedence over them.
+    pub fn embedded_proto(
+        &mut self,
+        proto_path: ProtoPathBuf,
+        content: impl Into<String>,
+    ) -> &mut Self {
+        self.extra_embedded_protos
+            .push((proto_path, content.into()));
+        self
+    }
+
+    /// Append an in-memory `.proto` file to compile, e.g. one embedded with `include_str!`.
+    ///
+    /// The file is also available for import like one added with
+    /// [`include_content`](Self::include_content).
+    pub fn input_content(
+        &mut self,
+        proto_path: ProtoPathBuf,
+        content: impl Into<String>,
+    ) -> &mut Self {
+        self.include_content(proto_path.clone(), content);
+        self.content_inputs.push(proto_path);
+        self
+    }
+
+    /// Register an in-memory `.proto` file available for import.
+    ///
+    /// Unlike [`embedded_proto`](Self::embedded_proto),
+    /// such files take precedence over files in include path.
+    pub fn include_content(
+        &mut self,
+        proto_path: ProtoPathBuf,
+        content: impl Into<String>,
+    ) -> &mut Self {
+        self.content_files.push((proto_path, content.into()));
+        self
+    }
+
+    /// Load `.proto` files using given loader before looking them up in include path.
+    ///
+    /// This option is not supported by `protoc` parser.
+    pub fn file_loader(&mut self, file_loader: impl FileLoader) -> &mut Self {
+        self.file_loader = Some(FileLoaderHolder(Arc::new(file_loader)));
+        self
+    }
+
+    /// Notify given observer when files are parsed and typechecked.
+    ///
+    /// This option is not supported by `protoc` parser.
+    pub fn observer(&mut self, observer: impl ParseObserver) -> &mut Self {
+        self.observer = Some(ParseObserverHolder(Arc::new(observer)));
+        self
+    }
+
+    /// What to do when a file is found in more than one include directory.
+    ///
+    /// By default the file from the include directory added first is used.
+    pub fn on_duplicate_include(&mut self, policy: DuplicateIncludePolicy) -> &mut Self {
+        self.duplicate_include_policy = policy;
+        self
+    }
+
+    /// Look up imports in the include directory the importing file was found in first,
+    /// then in all include directories as usual.
+    ///
+    /// Useful when an input directory is also an include directory and siblings
+    /// of the input must win over files of the same name elsewhere.
+    /// Works with any [`DuplicateIncludePolicy`], with
+    /// [`Error`](DuplicateIncludePolicy::Error) duplicates are still rejected.
+    ///
+    /// This option is not supported by `protoc` parser.
+    pub fn prefer_importer_include(&mut self, prefer_importer_include: bool) -> &mut Self {
+        self.prefer_importer_include = prefer_importer_include;
+        self
+    }
+
+    /// Use parent directory of an input file as an implicit include directory
+    /// if the input is not under any include directory, like `protoc` does.
+    pub fn auto_include_input_dirs(&mut self, auto_include_input_dirs: bool) -> &mut Self {
+        self.auto_include_input_dirs = auto_include_input_dirs;
+        self
+    }
+
+    /// Parse files on up to `parallelism` threads before typechecking them in dependency order.
+    ///
+    /// Result does not depend on this setting. Default is `1`, which is parse sequentially.
+    ///
+    /// This option is not supported by `protoc` parser.
+    pub fn parallelism(&mut self, parallelism: usize) -> &mut Self {
+        self.parallelism = parallelism;
+        self
+    }
+
+    /// Reuse typechecked descriptors of files which did not change since previous run,
+    /// including their transitive imports. Cache entries are stored in given directory.
+    ///
+    /// Warnings and unsupported constructs of cached files are reported again.
+    ///
+    /// This option is not supported by `protoc` parser.
+    pub fn cache_dir(&mut self, cache_dir: impl AsRef<Path>) -> &mut Self {
+        self.cache_dir = Some(cache_dir.as_ref().to_owned());
+        self
+    }
+
+    /// Fail on options which pure parser cannot fully represent instead of dropping them,
+    /// and on files without `syntax` or `edition` statement.
+    ///
+    /// When not strict, dropped options are reported in
+    /// [`ParsedAndTypechecked::warnings`].
+    ///
+    /// This option is not supported by `protoc` parser.
+    pub fn strict(&mut self, strict: bool) -> &mut Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Syntax assumed for files without `syntax` or `edition` statement.
+    ///
+    /// Default is proto2, like `protoc`. In [`strict`](Self::strict) mode
+    /// such files are errors.
+    ///
+    /// This option is not supported by `protoc` parser.
+    pub fn default_syntax(&mut self, default_syntax: Syntax) -> &mut Self {
+        self.default_syntax = Some(default_syntax);
+        self
+    }
+
+    /// Fail when an imported file is found only because filesystem is case-insensitive,
+    /// like `import "Foo.proto"` resolving to `foo.proto` on macOS or Windows.
+    ///
+    /// Enabled by default. This option is not supported by `protoc` parser.
+    pub fn require_exact_case(&mut self, require_exact_case: bool) -> &mut Self {
+        self.require_exact_case = Some(require_exact_case);
+        self
+    }
+
+    /// Fail if more than `max_files` files are parsed, including imports.
+    ///
+    /// Useful to bound the work done on untrusted input. There is no limit by default.
+    ///
+    /// This option is not supported by `protoc` parser.
+    pub fn max_files(&mut self, max_files: usize) -> &mut Self {
+        self.max_files = Some(max_files);
+        self
+    }
+
+    /// Fail if imports are nested deeper than `max_import_depth`.
+    ///
+    /// Input files have depth `1`, files they import have depth `2` and so on.
+    /// There is no limit by default.
+    ///
+    /// This option is not supported by `protoc` parser.
+    pub fn max_import_depth(&mut self, max_import_depth: usize) -> &mut Self {
+        self.max_import_depth = Some(max_import_depth);
+        self
+    }
+
+    /// Use already compiled file instead of looking it up in include path
+    /// when it is imported.
+    ///
+    /// Dependencies of the file which are not added too are looked up
+    /// in include path like imports of parsed files.
+    pub fn add_descriptor(&mut self, descriptor: FileDescriptorProto) -> &mut Self {
+        self.descriptors.push(descriptor);
+        self
+    }
+
+    /// Use already compiled files, like output of `protoc --descriptor_set_out`,
+    /// instead of looking them up in include path.
+    pub fn add_descriptor_set(&mut self, descriptor_set: FileDescriptorSet) -> &mut Self {
+        self.descriptors.extend(descriptor_set.file);
+        self
+    }
+
     /// Parse `.proto` files and typecheck them using pure Rust parser of `protoc` command.
     pub fn parse_and_typecheck(&self) -> anyhow::Result<ParsedAndTypechecked> {
         match &self.which_parser {
@@ -1,4 +1,6 @@
+use std::borrow::Cow;
 use std::path::is_separator;
+use std::path::Path;
 
 use crate::proto_path::ProtoPath;
 
@@ -9,9 +11,28 @@ pub(crate) fn fs_path_to_proto_path(path: &ProtoPath) -> String {
         .collect()
 }
 
+/// Replace backslash separators with forward slashes on Windows,
+/// so `protos\sub\a.proto` is split into the same components as `protos/sub/a.proto`.
+#[cfg(windows)]
+pub(crate) fn normalize_separators(path: &Path) -> Cow<'_, Path> {
+    match path.to_str() {
+        Some(s) if s.contains('\\') => Cow::Owned(std::path::PathBuf::from(s.replace('\\', "/"))),
+        _ => Cow::Borrowed(path),
+    }
+}
+
+/// Backslash is not a separator outside of Windows, so path is returned unchanged.
+#[cfg(not(windows))]
+pub(crate) fn normalize_separators(path: &Path) -> Cow<'_, Path> {
+    Cow::Borrowed(path)
+}
+
 #[cfg(test)]
 mod test {
+    use std::path::Path;
+
     use crate::path::fs_path_to_proto_path;
+    use crate::path::normalize_separators;
     use crate::ProtoPath;
 
     #[test]
@@ -25,4 +46,22 @@ mod test {
             fs_path_to_proto_path(ProtoPath::new("bar/foo.proto").unwrap())
         );
     }
+
+    #[test]
+    fn test_normalize_separators() {
+        #[cfg(windows)]
+        assert_eq!(
+            Path::new("protos/sub/a.proto"),
+            normalize_separators(Path::new("protos\\sub\\a.proto"))
+        );
+        #[cfg(not(windows))]
+        assert_eq!(
+            Path::new("protos\\sub\\a.proto"),
+            normalize_separators(Path::new("protos\\sub\\a.proto"))
+        );
+        assert_eq!(
+            Path::new("sub/a.proto"),
+            normalize_separators(Path::new("sub/a.proto"))
+        );
+    }
 }
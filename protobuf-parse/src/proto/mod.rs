@@ -4,6 +4,9 @@
 //! Files are copied here because when publishing to crates,
 //! referencing files from outside is not allowed.
 
+/// Protobuf release well-known type protos below are copied from.
+pub(crate) const WELL_KNOWN_TYPES_VERSION: &str = "3.19.4";
+
 pub(crate) const RUSTPROTO_PROTO: &str = include_str!("rustproto.proto");
 pub(crate) const ANY_PROTO: &str = include_str!("google/protobuf/any.proto");
 pub(crate) const API_PROTO: &str = include_str!("google/protobuf/api.proto");
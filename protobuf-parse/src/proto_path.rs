@@ -8,6 +8,8 @@ use std::path::Component;
 use std::path::Path;
 use std::path::PathBuf;
 
+use crate::path::normalize_separators;
+
 #[derive(Debug, thiserror::Error)]
 enum Error {
     #[error("path is empty")]
@@ -134,7 +136,12 @@ impl ProtoPathBuf {
         Ok(ProtoPathBuf { path })
     }
 
+    /// Convert relative filesystem path to proto path.
+    ///
+    /// Both `/` and `\` are accepted as separators on Windows.
+    /// Elsewhere `\` is not a separator, and a path with `\` is rejected.
     pub fn from_path(path: &Path) -> anyhow::Result<ProtoPathBuf> {
+        let path = &*normalize_separators(path);
         let mut path_str = String::new();
         for component in path.components() {
             match component {
@@ -149,10 +156,47 @@ impl ProtoPathBuf {
                     if !path_str.is_empty() {
                         path_str.push('/');
                     }
-                    path_str.push_str(c.to_str().ok_or_else(|| Error::NotUtf8(path.to_owned()))?);
+                    let c = c.to_str().ok_or_else(|| Error::NotUtf8(path.to_owned()))?;
+                    if c.contains('\\') {
+                        return Err(Error::Backslashes(path.display().to_string()).into());
+                    }
+                    path_str.push_str(c);
                 }
             }
         }
         Ok(ProtoPathBuf { path: path_str })
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::path::Path;
+
+    use crate::ProtoPathBuf;
+
+    #[test]
+    fn from_path() {
+        assert_eq!(
+            "sub/a.proto",
+            ProtoPathBuf::from_path(Path::new("./sub/a.proto"))
+                .unwrap()
+                .to_str()
+        );
+        assert!(ProtoPathBuf::from_path(Path::new("../a.proto")).is_err());
+        assert!(ProtoPathBuf::from_path(Path::new("/a.proto")).is_err());
+    }
+
+    #[test]
+    fn from_path_backslash_separators() {
+        #[cfg(windows)]
+        assert_eq!(
+            "sub/a.proto",
+            ProtoPathBuf::from_path(Path::new("sub\\a.proto"))
+                .unwrap()
+                .to_str()
+        );
+        #[cfg(not(windows))]
+        assert!(ProtoPathBuf::from_path(Path::new("sub\\a.proto")).is_err());
+        assert!(ProtoPathBuf::from_path(Path::new("..\\a.proto")).is_err());
+    }
+}
@@ -5,7 +5,7 @@ use crate::protobuf_rel_path::ProtobufRelPath;
 
 /// Protobuf identifier can be absolute or relative.
 #[derive(Debug, Eq, PartialEq, Clone, Hash)]
-pub(crate) enum ProtobufPath {
+pub enum ProtobufPath {
     Abs(ProtobufAbsPath),
     Rel(ProtobufRelPath),
 }
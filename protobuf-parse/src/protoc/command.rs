@@ -55,6 +55,8 @@ pub(crate) struct DescriptorSetOutArgs {
     inputs: Vec<PathBuf>,
     /// `--include_imports`
     include_imports: bool,
+    /// `--include_source_info`
+    include_source_info: bool,
     /// Extra command line flags (like `--experimental_allow_proto3_optional`)
     extra_args: Vec<OsString>,
     /// Capture stderr instead of inheriting it.
@@ -102,6 +104,12 @@ impl DescriptorSetOutArgs {
         self
     }
 
+    /// Set `--include_source_info`
+    pub fn include_source_info(&mut self, include_source_info: bool) -> &mut Self {
+        self.include_source_info = include_source_info;
+        self
+    }
+
     /// Add command line flags like `--experimental_allow_proto3_optional`.
     pub fn extra_arg(&mut self, arg: impl Into<OsString>) -> &mut Self {
         self.extra_args.push(arg.into());
@@ -147,10 +155,18 @@ impl DescriptorSetOutArgs {
             true => Some("--include_imports".into()),
         };
 
+        // --include_source_info
+        let include_source_info_flag = if self.include_source_info {
+            Some("--include_source_info".into())
+        } else {
+            None
+        };
+
         let mut cmd_args = Vec::new();
         cmd_args.extend(include_flags);
         cmd_args.push(descriptor_set_out_flag);
         cmd_args.extend(include_imports_flag);
+        cmd_args.extend(include_source_info_flag);
         cmd_args.extend(self.inputs.iter().map(|path| path.as_os_str().to_owned()));
         cmd_args.extend(self.extra_args.iter().cloned());
         self.protoc.run_with_args(cmd_args, self.capture_stderr)
@@ -282,6 +298,7 @@ impl Protoc {
             includes: Vec::new(),
             inputs: Vec::new(),
             include_imports: false,
+            include_source_info: false,
             extra_args: Vec::new(),
             capture_stderr: false,
         }
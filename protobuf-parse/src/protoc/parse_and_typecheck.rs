@@ -1,25 +1,68 @@
+use std::ffi::OsString;
 use std::fs;
+use std::path::Path;
 
 use protobuf::descriptor::FileDescriptorSet;
 use protobuf::Message;
 
 use crate::protoc::command::Protoc;
+use crate::pure::parse_and_typecheck::effective_includes;
+use crate::pure::parse_and_typecheck::find_in_includes;
 use crate::pure::parse_and_typecheck::path_to_proto_path;
+use crate::pure::parse_and_typecheck::sort_file_descriptors;
 use crate::ParsedAndTypechecked;
 use crate::Parser;
 use crate::ProtoPathBuf;
 
+#[derive(Debug, thiserror::Error)]
+enum Error {
+    #[error("option `{0}` is not supported by `protoc` parser")]
+    UnsupportedOption(&'static str),
+}
+
+/// Fail on options of pure parser which `protoc` cannot honor.
+fn check_options(parser: &Parser) -> anyhow::Result<()> {
+    let unsupported = [
+        ("embedded_protos", parser.disable_embedded_protos),
+        (
+            "prefer_embedded_well_known",
+            parser.prefer_embedded_well_known,
+        ),
+        ("warn_well_known_drift", parser.warn_well_known_drift),
+        ("well_known_types", parser.well_known_types.is_some()),
+        ("file_loader", parser.file_loader.is_some()),
+        ("observer", parser.observer.is_some()),
+        ("parallelism", parser.parallelism > 1),
+        ("cache_dir", parser.cache_dir.is_some()),
+        ("strict", parser.strict),
+        ("default_syntax", parser.default_syntax.is_some()),
+        ("require_exact_case", parser.require_exact_case.is_some()),
+        ("max_files", parser.max_files.is_some()),
+        ("max_import_depth", parser.max_import_depth.is_some()),
+        ("prefer_importer_include", parser.prefer_importer_include),
+    ];
+    match unsupported.iter().find(|(_, set)| *set) {
+        Some((option, _)) => Err(Error::UnsupportedOption(option).into()),
+        None => Ok(()),
+    }
+}
+
 /// Parse `.proto` files using `protoc` command.
 pub(crate) fn parse_and_typecheck(parser: &Parser) -> anyhow::Result<ParsedAndTypechecked> {
+    check_options(parser)?;
+
     let temp_dir = tempfile::Builder::new()
         .prefix("protobuf-parse")
         .tempdir()?;
     let temp_file = temp_dir.path().join("descriptor.pbbin");
 
-    let relative_paths: Vec<ProtoPathBuf> = parser
+    let policy = parser.duplicate_include_policy;
+    let user_includes = effective_includes(parser);
+
+    let mut relative_paths: Vec<ProtoPathBuf> = parser
         .inputs
         .iter()
-        .map(|p| path_to_proto_path(p, &parser.includes))
+        .map(|p| path_to_proto_path(p, &user_includes))
         .collect::<anyhow::Result<_>>()?;
 
     let protoc = match &parser.protoc {
@@ -27,15 +70,50 @@ pub(crate) fn parse_and_typecheck(parser: &Parser) -> anyhow::Result<ParsedAndTy
         None => Protoc::from_env_path(),
     };
 
-    protoc
-        .descriptor_set_out_args()
-        .inputs(&parser.inputs)
-        .includes(&parser.includes)
+    // `protoc` has no API to supply in-memory files,
+    // so write them into temporary include directory searched last.
+    let embedded_dir = temp_dir.path().join("embedded");
+    let content_dir = temp_dir.path().join("content");
+    let mut includes = user_includes.clone();
+    let mut inputs = parser.inputs.clone();
+    if !parser.content_files.is_empty() {
+        for (proto_path, content) in &parser.content_files {
+            write_file(&content_dir.join(proto_path.to_path()), content)?;
+        }
+        // In-memory files take precedence over include path.
+        includes.insert(0, content_dir.clone());
+        for proto_path in &parser.content_inputs {
+            if !relative_paths.contains(proto_path) {
+                inputs.push(content_dir.join(proto_path.to_path()));
+                relative_paths.push(proto_path.clone());
+            }
+        }
+    }
+    if !parser.extra_embedded_protos.is_empty() {
+        for (proto_path, content) in &parser.extra_embedded_protos {
+            write_file(&embedded_dir.join(proto_path.to_path()), content)?;
+        }
+        includes.push(embedded_dir);
+    }
+
+    let mut args = protoc.descriptor_set_out_args();
+    args.inputs(&inputs)
+        .includes(&includes)
         .out(&temp_file)
         .include_imports(true)
+        .include_source_info(parser.include_source_info)
         .extra_args(&parser.protoc_extra_args)
-        .capture_stderr(parser.capture_stderr)
-        .write_descriptor_set()?;
+        .capture_stderr(parser.capture_stderr);
+    if !parser.descriptors.is_empty() {
+        let descriptor_set_in = temp_dir.path().join("descriptor_set_in.pbbin");
+        let mut fds = FileDescriptorSet::new();
+        fds.file = parser.descriptors.clone();
+        fs::write(&descriptor_set_in, fds.write_to_bytes()?)?;
+        let mut arg = OsString::from("--descriptor_set_in=");
+        arg.push(&descriptor_set_in);
+        args.extra_arg(arg);
+    }
+    args.write_descriptor_set()?;
 
     let version = protoc.version()?;
 
@@ -44,9 +122,61 @@ pub(crate) fn parse_and_typecheck(parser: &Parser) -> anyhow::Result<ParsedAndTy
 
     let fds: protobuf::descriptor::FileDescriptorSet = FileDescriptorSet::parse_from_bytes(&fds)?;
 
+    // `protoc` does not report which files it read,
+    // so resolve file names against include path the same way `protoc` does.
+    // This also reports files found in more than one include directory.
+    let mut read_files = Vec::new();
+    let mut embedded_files = Vec::new();
+    for file in &fds.file {
+        if parser.descriptors.iter().any(|d| d.name() == file.name())
+            || parser
+                .content_files
+                .iter()
+                .any(|(p, _)| p.to_str() == file.name())
+        {
+            continue;
+        }
+        let proto_path = ProtoPathBuf::new(file.name().to_owned())?;
+        match find_in_includes(&proto_path, &user_includes, policy)? {
+            Some(fs_path) => read_files.push(fs_path),
+            None => embedded_files.push(proto_path),
+        }
+    }
+
     Ok(ParsedAndTypechecked {
         relative_paths,
-        file_descriptors: fds.file,
+        file_descriptors: sort_file_descriptors(fds.file),
         parser: format!("protoc {}", version),
+        read_files,
+        embedded_files,
+        warnings: Vec::new(),
+        unsupported: Vec::new(),
     })
 }
+
+fn write_file(path: &Path, content: &str) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::Parser;
+
+    #[test]
+    fn unsupported_options() {
+        let err = Parser::new()
+            .protoc()
+            .strict(true)
+            .parse_and_typecheck()
+            .err()
+            .unwrap();
+        assert_eq!(
+            "option `strict` is not supported by `protoc` parser",
+            err.root_cause().to_string()
+        );
+    }
+}
@@ -0,0 +1,669 @@
+//! [`FileLoader`] reading `.proto` files from a tar or zip archive.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs::File;
+use std::io;
+use std::io::Cursor;
+use std::io::Read;
+use std::io::Seek;
+use std::io::SeekFrom;
+use std::path::Path;
+use std::path::PathBuf;
+use std::str;
+use std::sync::Mutex;
+
+use crate::proto_path::ProtoPath;
+use crate::pure::inflate::inflate;
+use crate::pure::inflate::InflateError;
+use crate::pure::FileLoader;
+
+const BLOCK_SIZE: u64 = 512;
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZIP_LOCAL_HEADER: &[u8] = b"PK\x03\x04";
+const ZIP_END_OF_CENTRAL_DIRECTORY: &[u8] = b"PK\x05\x06";
+const ZIP_CENTRAL_DIRECTORY_HEADER: &[u8] = b"PK\x01\x02";
+
+const ZIP_STORED: u16 = 0;
+const ZIP_DEFLATED: u16 = 8;
+
+/// Limit of decompressed size of gzip archive or zip entry,
+/// so a small malicious archive cannot exhaust memory.
+const MAX_DECOMPRESSED_SIZE: usize = 256 << 20;
+
+/// CRC-32 lookup table, polynomial used by gzip and zip.
+const CRC32_TABLE: [u32; 256] = {
+    let mut table = [0; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut c = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            c = if c & 1 != 0 {
+                0xedb88320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+            k += 1;
+        }
+        table[i] = c;
+        i += 1;
+    }
+    table
+};
+
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0, |crc, &b| {
+        CRC32_TABLE[((crc ^ b as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+trait ReadSeek: Read + Seek + Send {}
+
+impl<R: Read + Seek + Send> ReadSeek for R {}
+
+/// Location of archive entry content.
+#[derive(Clone, Copy)]
+struct Entry {
+    offset: u64,
+    /// Size of entry data in archive.
+    size: u64,
+    /// Zip compression method, `ZIP_STORED` for tar entries.
+    method: u16,
+    /// Size and CRC-32 of uncompressed data of zip entry.
+    checked: Option<(u64, u32)>,
+}
+
+fn invalid_data(path: &Path, message: impl fmt::Display) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!("{}: {}", path.display(), message),
+    )
+}
+
+/// Load `.proto` files from an archive, like one shipped as CI artifact,
+/// without extracting it.
+///
+/// Supported are tar archives, gzip compressed tar archives (`.tar.gz`)
+/// and zip archives with stored or deflated entries. Format is detected by content.
+///
+/// Archive entry `a/b.proto` (or `./a/b.proto`) is loaded for proto path `a/b.proto`.
+/// Files not found in the archive are looked up in include path.
+/// Entries are read once and kept in memory. `.tar.gz` archive is decompressed
+/// into memory when opened.
+///
+/// Sizes and CRC-32 checksums of decompressed data are verified,
+/// and decompressed gzip archive or zip entry is limited to 256 MiB.
+pub struct ArchiveFileLoader {
+    path: PathBuf,
+    file: Mutex<Box<dyn ReadSeek>>,
+    /// Regular file entries by name.
+    entries: HashMap<String, Entry>,
+    loaded: Mutex<HashMap<String, String>>,
+}
+
+impl fmt::Debug for ArchiveFileLoader {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ArchiveFileLoader")
+            .field("path", &self.path)
+            .field("entries", &self.entries.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl ArchiveFileLoader {
+    /// Open archive and read its index. Entry contents are read when requested.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<ArchiveFileLoader> {
+        let path = path.as_ref().to_owned();
+        let mut file = File::open(&path)?;
+        let mut magic = Vec::with_capacity(4);
+        (&mut file).take(4).read_to_end(&mut magic)?;
+        file.seek(SeekFrom::Start(0))?;
+
+        let (file, entries): (Box<dyn ReadSeek>, _) = if magic.starts_with(GZIP_MAGIC) {
+            let mut compressed = Vec::new();
+            file.read_to_end(&mut compressed)?;
+            let mut tar = Cursor::new(gunzip(&path, &compressed)?);
+            let entries = read_tar_index(&path, &mut tar)?;
+            (Box::new(tar), entries)
+        } else if magic == ZIP_LOCAL_HEADER || magic == ZIP_END_OF_CENTRAL_DIRECTORY {
+            let entries = read_zip_index(&path, &mut file)?;
+            (Box::new(file), entries)
+        } else {
+            let entries = read_tar_index(&path, &mut file)?;
+            (Box::new(file), entries)
+        };
+        Ok(ArchiveFileLoader {
+            path,
+            file: Mutex::new(file),
+            entries,
+            loaded: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Names of `.proto` files in the archive.
+    pub fn proto_paths(&self) -> Vec<&str> {
+        let mut r: Vec<&str> = self
+            .entries
+            .keys()
+            .map(|n| n.as_str())
+            .filter(|n| n.ends_with(".proto"))
+            .collect();
+        r.sort();
+        r
+    }
+}
+
+impl FileLoader for ArchiveFileLoader {
+    fn load(&self, proto_path: &ProtoPath) -> io::Result<Option<String>> {
+        let name = proto_path.to_str();
+        if let Some(content) = self.loaded.lock().unwrap().get(name) {
+            return Ok(Some(content.clone()));
+        }
+        let entry = match self.entries.get(name) {
+            Some(entry) => *entry,
+            None => return Ok(None),
+        };
+
+        let mut content = Vec::new();
+        let mut file = self.file.lock().unwrap();
+        file.seek(SeekFrom::Start(entry.offset))?;
+        (&mut *file).take(entry.size).read_to_end(&mut content)?;
+        drop(file);
+        if content.len() as u64 != entry.size {
+            return Err(invalid_data(
+                &self.path,
+                format!("entry `{}` is truncated", name),
+            ));
+        }
+        let content = match entry.method {
+            ZIP_STORED => content,
+            ZIP_DEFLATED => {
+                // Declared size is checked against the limit when the index is read.
+                let limit = entry
+                    .checked
+                    .map_or(MAX_DECOMPRESSED_SIZE, |(size, _)| size as usize);
+                match inflate(&content, limit) {
+                    Ok((content, _)) => content,
+                    Err(InflateError::Invalid) => {
+                        return Err(invalid_data(
+                            &self.path,
+                            format!("entry `{}` is not valid deflate data", name),
+                        ))
+                    }
+                    Err(InflateError::TooLarge) => {
+                        return Err(invalid_data(
+                            &self.path,
+                            format!("entry `{}` is larger than declared", name),
+                        ))
+                    }
+                }
+            }
+            method => {
+                return Err(invalid_data(
+                    &self.path,
+                    format!(
+                        "entry `{}` uses unsupported compression method {}",
+                        name, method
+                    ),
+                ))
+            }
+        };
+        if let Some((size, crc)) = entry.checked {
+            if content.len() as u64 != size || crc32(&content) != crc {
+                return Err(invalid_data(
+                    &self.path,
+                    format!("entry `{}` has invalid size or CRC-32", name),
+                ));
+            }
+        }
+        let content = String::from_utf8(content).map_err(|_| {
+            invalid_data(&self.path, format!("entry `{}` is not valid UTF-8", name))
+        })?;
+
+        self.loaded
+            .lock()
+            .unwrap()
+            .insert(name.to_owned(), content.clone());
+        Ok(Some(content))
+    }
+}
+
+/// NUL-terminated string field of tar header.
+fn header_str(field: &[u8]) -> &[u8] {
+    let end = field.iter().position(|&b| b == 0).unwrap_or(field.len());
+    &field[..end]
+}
+
+/// Octal number field of tar header.
+fn header_octal(path: &Path, field: &[u8]) -> io::Result<u64> {
+    let s = str::from_utf8(header_str(field))
+        .map_err(|_| invalid_data(path, "invalid number in tar header"))?
+        .trim();
+    match s {
+        "" => Ok(0),
+        s => u64::from_str_radix(s, 8)
+            .map_err(|_| invalid_data(path, format!("invalid number `{}` in tar header", s))),
+    }
+}
+
+/// `path` record of pax extended header, records are like `20 path=a/b.proto\n`.
+fn pax_path(data: &[u8]) -> Option<String> {
+    let mut rest = data;
+    while !rest.is_empty() {
+        let space = rest.iter().position(|&b| b == b' ')?;
+        let len: usize = str::from_utf8(&rest[..space]).ok()?.parse().ok()?;
+        let record = rest.get(space + 1..len)?;
+        if let Some(value) = record.strip_prefix(b"path=") {
+            let value = value.strip_suffix(b"\n").unwrap_or(value);
+            return String::from_utf8(value.to_vec()).ok();
+        }
+        rest = &rest[len..];
+    }
+    None
+}
+
+fn entry_name(name: &str) -> &str {
+    name.trim_start_matches("./")
+}
+
+/// Decompress gzip file, RFC 1952.
+fn gunzip(path: &Path, data: &[u8]) -> io::Result<Vec<u8>> {
+    const FHCRC: u8 = 0x02;
+    const FEXTRA: u8 = 0x04;
+    const FNAME: u8 = 0x08;
+    const FCOMMENT: u8 = 0x10;
+
+    let invalid = || invalid_data(path, "invalid gzip data");
+    if data.get(2) != Some(&8) {
+        return Err(invalid_data(path, "unsupported gzip compression method"));
+    }
+    let flags = *data.get(3).ok_or_else(invalid)?;
+    let mut pos = 10;
+    if flags & FEXTRA != 0 {
+        let len = data.get(pos..pos + 2).ok_or_else(invalid)?;
+        pos += 2 + u16::from_le_bytes([len[0], len[1]]) as usize;
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            let rest = data.get(pos..).ok_or_else(invalid)?;
+            pos += rest.iter().position(|&b| b == 0).ok_or_else(invalid)? + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        pos += 2;
+    }
+
+    let (content, len) = match inflate(data.get(pos..).ok_or_else(invalid)?, MAX_DECOMPRESSED_SIZE)
+    {
+        Ok(r) => r,
+        Err(InflateError::Invalid) => return Err(invalid()),
+        Err(InflateError::TooLarge) => {
+            return Err(invalid_data(
+                path,
+                format!(
+                    "gzip data is larger than {} bytes when decompressed",
+                    MAX_DECOMPRESSED_SIZE
+                ),
+            ))
+        }
+    };
+    // Trailer is CRC-32 and size modulo 2^32 of decompressed data.
+    let trailer = data.get(pos + len..pos + len + 8).ok_or_else(invalid)?;
+    if le_u32(trailer, 0) != crc32(&content) || le_u32(trailer, 4) != content.len() as u32 {
+        return Err(invalid_data(path, "gzip data has invalid size or CRC-32"));
+    }
+    Ok(content)
+}
+
+fn le_u16(data: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([data[offset], data[offset + 1]])
+}
+
+fn le_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_zip_index(path: &Path, file: &mut File) -> io::Result<HashMap<String, Entry>> {
+    const END_SIZE: u64 = 22;
+    const CENTRAL_HEADER_SIZE: usize = 46;
+    const LOCAL_HEADER_SIZE: u64 = 30;
+
+    // End of central directory record is followed by comment up to 64K.
+    let len = file.seek(SeekFrom::End(0))?;
+    let tail_start = len.saturating_sub(END_SIZE + u16::MAX as u64);
+    let mut tail = Vec::new();
+    file.seek(SeekFrom::Start(tail_start))?;
+    file.read_to_end(&mut tail)?;
+    let end = (0..tail.len().saturating_sub(END_SIZE as usize - 1))
+        .rev()
+        .find(|&i| tail[i..].starts_with(ZIP_END_OF_CENTRAL_DIRECTORY))
+        .map(|i| &tail[i..])
+        .ok_or_else(|| invalid_data(path, "not a zip archive"))?;
+    let count = le_u16(end, 10);
+    let directory_size = le_u32(end, 12);
+    let directory_offset = le_u32(end, 16);
+    if count == u16::MAX || directory_offset == u32::MAX {
+        return Err(invalid_data(path, "zip64 archives are not supported"));
+    }
+
+    let mut directory = Vec::new();
+    file.seek(SeekFrom::Start(directory_offset as u64))?;
+    (&mut *file)
+        .take(directory_size as u64)
+        .read_to_end(&mut directory)?;
+
+    let mut entries = HashMap::new();
+    let mut rest = &directory[..];
+    for _ in 0..count {
+        if rest.len() < CENTRAL_HEADER_SIZE || !rest.starts_with(ZIP_CENTRAL_DIRECTORY_HEADER) {
+            return Err(invalid_data(path, "invalid zip central directory"));
+        }
+        let flags = le_u16(rest, 8);
+        let method = le_u16(rest, 10);
+        let crc = le_u32(rest, 16);
+        let compressed_size = le_u32(rest, 20);
+        let size = le_u32(rest, 24);
+        let name_len = le_u16(rest, 28) as usize;
+        let extra_len = le_u16(rest, 30) as usize;
+        let comment_len = le_u16(rest, 32) as usize;
+        let local_header_offset = le_u32(rest, 42);
+        let header_len = CENTRAL_HEADER_SIZE + name_len + extra_len + comment_len;
+        let name = rest
+            .get(CENTRAL_HEADER_SIZE..CENTRAL_HEADER_SIZE + name_len)
+            .ok_or_else(|| invalid_data(path, "invalid zip central directory"))?;
+        let name = String::from_utf8_lossy(name).into_owned();
+        rest = rest.get(header_len..).unwrap_or_default();
+
+        if name.ends_with('/') {
+            continue;
+        }
+        if flags & 1 != 0 {
+            return Err(invalid_data(path, format!("entry `{}` is encrypted", name)));
+        }
+        if size as usize > MAX_DECOMPRESSED_SIZE {
+            return Err(invalid_data(
+                path,
+                format!(
+                    "entry `{}` is larger than {} bytes",
+                    name, MAX_DECOMPRESSED_SIZE
+                ),
+            ));
+        }
+
+        // Name and extra field lengths in local header may differ from central directory.
+        let mut local = Vec::with_capacity(LOCAL_HEADER_SIZE as usize);
+        file.seek(SeekFrom::Start(local_header_offset as u64))?;
+        (&mut *file)
+            .take(LOCAL_HEADER_SIZE)
+            .read_to_end(&mut local)?;
+        if local.len() as u64 != LOCAL_HEADER_SIZE || !local.starts_with(ZIP_LOCAL_HEADER) {
+            return Err(invalid_data(
+                path,
+                format!("invalid zip local header of entry `{}`", name),
+            ));
+        }
+        let offset = local_header_offset as u64
+            + LOCAL_HEADER_SIZE
+            + le_u16(&local, 26) as u64
+            + le_u16(&local, 28) as u64;
+        entries.insert(
+            entry_name(&name).to_owned(),
+            Entry {
+                offset,
+                size: compressed_size as u64,
+                method,
+                checked: Some((size as u64, crc)),
+            },
+        );
+    }
+    Ok(entries)
+}
+
+fn read_tar_index(path: &Path, file: &mut impl ReadSeek) -> io::Result<HashMap<String, Entry>> {
+    let mut entries = HashMap::new();
+    let mut offset = 0;
+    // Name for the next entry from GNU long name or pax header.
+    let mut long_name: Option<String> = None;
+    loop {
+        let mut header = Vec::with_capacity(BLOCK_SIZE as usize);
+        file.seek(SeekFrom::Start(offset))?;
+        (&mut *file).take(BLOCK_SIZE).read_to_end(&mut header)?;
+        match header.len() as u64 {
+            BLOCK_SIZE => {}
+            // Archive without end-of-archive blocks.
+            _ if offset != 0 && header.is_empty() => break,
+            _ => return Err(invalid_data(path, "not a tar archive")),
+        }
+        if header.iter().all(|&b| b == 0) {
+            break;
+        }
+        // Checksum is computed with checksum field filled with spaces.
+        let checksum: u64 = header
+            .iter()
+            .enumerate()
+            .map(|(i, &b)| match i {
+                148..=155 => b' ' as u64,
+                _ => b as u64,
+            })
+            .sum();
+        if header_octal(path, &header[148..156])? != checksum {
+            return Err(invalid_data(path, "not a tar archive"));
+        }
+
+        let size = header_octal(path, &header[124..136])?;
+        let data_offset = offset + BLOCK_SIZE;
+        offset = data_offset + size.div_ceil(BLOCK_SIZE) * BLOCK_SIZE;
+
+        let mut read_data = || -> io::Result<Vec<u8>> {
+            let mut data = Vec::new();
+            (&mut *file).take(size).read_to_end(&mut data)?;
+            Ok(data)
+        };
+        match header[156] {
+            // Regular file.
+            b'0' | 0 => {}
+            b'L' => {
+                let data = read_data()?;
+                long_name = Some(String::from_utf8_lossy(header_str(&data)).into_owned());
+                continue;
+            }
+            b'x' => {
+                long_name = pax_path(&read_data()?);
+                continue;
+            }
+            // Directories, links, global pax headers.
+            _ => {
+                long_name = None;
+                continue;
+            }
+        }
+
+        let name = match long_name.take() {
+            Some(name) => name,
+            None => {
+                let name = String::from_utf8_lossy(header_str(&header[..100]));
+                let prefix = String::from_utf8_lossy(header_str(&header[345..500]));
+                if &header[257..262] == b"ustar" && !prefix.is_empty() {
+                    format!("{}/{}", prefix, name)
+                } else {
+                    name.into_owned()
+                }
+            }
+        };
+        entries.insert(
+            entry_name(&name).to_owned(),
+            Entry {
+                offset: data_offset,
+                size,
+                method: ZIP_STORED,
+                checked: None,
+            },
+        );
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+    use crate::Parser;
+
+    fn header(name: &str, size: usize, typeflag: u8) -> Vec<u8> {
+        let mut header = vec![0; BLOCK_SIZE as usize];
+        header[..name.len()].copy_from_slice(name.as_bytes());
+        header[100..107].copy_from_slice(b"0000644");
+        header[124..135].copy_from_slice(format!("{:011o}", size).as_bytes());
+        header[156] = typeflag;
+        header[257..263].copy_from_slice(b"ustar\0");
+        header[148..156].copy_from_slice(b"        ");
+        let checksum: u32 = header.iter().map(|&b| b as u32).sum();
+        header[148..155].copy_from_slice(format!("{:06o}\0", checksum).as_bytes());
+        header
+    }
+
+    fn tar(entries: &[(&str, &str)]) -> Vec<u8> {
+        let mut r = Vec::new();
+        for (name, content) in entries {
+            let typeflag = if name.ends_with('/') { b'5' } else { b'0' };
+            r.extend(header(name, content.len(), typeflag));
+            r.extend(content.as_bytes());
+            r.resize(
+                r.len().div_ceil(BLOCK_SIZE as usize) * BLOCK_SIZE as usize,
+                0,
+            );
+        }
+        r.resize(r.len() + 2 * BLOCK_SIZE as usize, 0);
+        r
+    }
+
+    #[test]
+    fn archive_file_loader() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("protos.tar");
+        fs::write(
+            &archive,
+            tar(&[
+                ("./dep/", ""),
+                (
+                    "./dep/a.proto",
+                    "syntax = 'proto3'; package dep; message A {}",
+                ),
+                ("README", "not a proto"),
+            ]),
+        )
+        .unwrap();
+
+        let loader = ArchiveFileLoader::open(&archive).unwrap();
+        assert_eq!(vec!["dep/a.proto"], loader.proto_paths());
+
+        let parsed = Parser::new()
+            .pure()
+            .input_content(
+                crate::ProtoPathBuf::new("b.proto".to_owned()).unwrap(),
+                "syntax = 'proto3'; import 'dep/a.proto'; message B { dep.A a = 1; }",
+            )
+            .file_loader(loader)
+            .parse_and_typecheck()
+            .unwrap();
+        assert_eq!(
+            vec!["dep/a.proto", "b.proto"],
+            parsed
+                .file_descriptors
+                .iter()
+                .map(|f| f.name())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn archive_file_loader_errors() {
+        let dir = tempfile::tempdir().unwrap();
+        let archive = dir.path().join("protos.tar.gz");
+        fs::write(&archive, [0x1f, 0x8b, 8, 0]).unwrap();
+        assert!(ArchiveFileLoader::open(&archive)
+            .unwrap_err()
+            .to_string()
+            .ends_with("invalid gzip data"));
+
+        let mut invalid = tar(&[("a.proto", "")]);
+        invalid[0] = b'b';
+        fs::write(&archive, invalid).unwrap();
+        assert!(ArchiveFileLoader::open(&archive)
+            .unwrap_err()
+            .to_string()
+            .ends_with("not a tar archive"));
+
+        let archives = Path::new(env!("CARGO_MANIFEST_DIR")).join("test-data/archives");
+        let mut gz = fs::read(archives.join("protos.tar.gz")).unwrap();
+        let crc = gz.len() - 8;
+        gz[crc] ^= 1;
+        fs::write(&archive, gz).unwrap();
+        assert!(ArchiveFileLoader::open(&archive)
+            .unwrap_err()
+            .to_string()
+            .ends_with("gzip data has invalid size or CRC-32"));
+
+        // Corrupt content of stored `README` entry.
+        let archive = dir.path().join("protos.zip");
+        let mut zip = fs::read(archives.join("protos.zip")).unwrap();
+        let readme = zip
+            .windows(6)
+            .position(|w| w == b"Protos")
+            .expect("stored README");
+        zip[readme] = b'p';
+        fs::write(&archive, zip).unwrap();
+        let loader = ArchiveFileLoader::open(&archive).unwrap();
+        assert!(loader
+            .load(ProtoPath::new("README").unwrap())
+            .unwrap_err()
+            .to_string()
+            .ends_with("entry `README` has invalid size or CRC-32"));
+        loader.load(ProtoPath::new("dep/a.proto").unwrap()).unwrap();
+    }
+
+    #[test]
+    fn archive_formats() {
+        // Archives are created with `tar --format=gnu`, `tar --format=posix | gzip`
+        // and `zip`, with `README` stored and `.proto` files deflated in zip.
+        let nested =
+            "dep/a_very_long_directory_name_to_not_fit_into_one_hundred_bytes_of_tar_header\
+                      /nested_directory/nested.proto";
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("test-data/archives");
+        for archive in ["protos.tar", "protos.tar.gz", "protos.zip"] {
+            let loader = ArchiveFileLoader::open(dir.join(archive)).unwrap();
+            assert_eq!(
+                vec!["dep/a.proto", nested],
+                loader.proto_paths(),
+                "{}",
+                archive
+            );
+
+            let load = |name| loader.load(ProtoPath::new(name).unwrap()).unwrap().unwrap();
+            assert_eq!(
+                "syntax = 'proto3';\n\npackage dep;\n\nmessage A {}\n",
+                load("dep/a.proto"),
+                "{}",
+                archive
+            );
+            let content = load(nested);
+            assert_eq!(3525, content.len(), "{}", archive);
+            assert!(
+                content.ends_with(
+                    "message Nested40 {\n  string name_40 = 1;\n  int64 value_40 = 2;\n}\n\n"
+                ),
+                "{}",
+                archive
+            );
+            assert_eq!(
+                "Protos for ArchiveFileLoader tests.\n",
+                load("README"),
+                "{}",
+                archive
+            );
+        }
+    }
+}
@@ -0,0 +1,337 @@
+//! Cache of typechecked file descriptors between runs.
+//!
+//! Entry is stored in a file named after hash of `.proto` file content,
+//! and contains a key which also covers hashes of all the dependencies,
+//! so an entry is used only if neither the file nor its transitive imports changed.
+//!
+//! Entry also stores the file content, which is compared on load,
+//! so a collision of content hashes is a cache miss rather than a wrong result.
+//! Warnings and unsupported constructs reported when the file was typechecked
+//! are stored too, so they are reported again for cached files.
+
+use std::fmt;
+use std::fs;
+use std::path::PathBuf;
+
+use log::warn;
+use protobuf::descriptor::FileDescriptorProto;
+use protobuf::Message;
+use protobuf_support::lexer::loc::Loc;
+
+use crate::proto_path::ProtoPath;
+use crate::pure::model::Syntax;
+
+/// Entries written by different crate versions are never used.
+const VERSION_TAG: &str = concat!("protobuf-parse ", env!("CARGO_PKG_VERSION"));
+
+/// 128-bit FNV-1a hash, stable across platforms and Rust versions.
+///
+/// Hashes in std are not guaranteed to be stable, and cache is not worth a dependency
+/// on a cryptographic hash: entries are verified against file content on load.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub(crate) struct Hash(u128);
+
+impl Hash {
+    const OFFSET: u128 = 0x6c62272e07bb014262b821756295c58d;
+    const PRIME: u128 = 0x0000000001000000000000000000013b;
+
+    fn new() -> Hash {
+        Hash(Self::OFFSET)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= b as u128;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+        // Separate fields so `("ab", "c")` and `("a", "bc")` hash differently.
+        for b in (bytes.len() as u64).to_le_bytes() {
+            self.0 ^= b as u128;
+            self.0 = self.0.wrapping_mul(Self::PRIME);
+        }
+    }
+
+    /// Hash of file content and parse settings affecting the result.
+    pub(crate) fn content(
+        protobuf_path: &ProtoPath,
+        content: &[u8],
+        include_source_info: bool,
+        strict: bool,
+        default_syntax: Syntax,
+    ) -> Hash {
+        let mut hash = Hash::new();
+        hash.write(VERSION_TAG.as_bytes());
+        hash.write(protobuf_path.to_str().as_bytes());
+        hash.write(&[include_source_info as u8]);
+        hash.write(&[strict as u8]);
+        hash.write(&[(default_syntax == Syntax::Proto3) as u8]);
+        hash.write(content);
+        hash
+    }
+
+    /// Hash of a descriptor added with [`Parser::add_descriptor`](crate::Parser::add_descriptor).
+    pub(crate) fn descriptor(descriptor: &FileDescriptorProto) -> Hash {
+        let mut hash = Hash::new();
+        hash.write(VERSION_TAG.as_bytes());
+        hash.write(b"descriptor");
+        // Order of unknown fields may vary between runs, which only makes a cache miss.
+        hash.write(
+            &descriptor
+                .write_to_bytes()
+                .expect("descriptor is serializable"),
+        );
+        hash
+    }
+
+    /// Key of a weak import which was not found.
+    pub(crate) fn missing(protobuf_path: &ProtoPath) -> Hash {
+        let mut hash = Hash::new();
+        hash.write(VERSION_TAG.as_bytes());
+        hash.write(b"missing");
+        hash.write(protobuf_path.to_str().as_bytes());
+        hash
+    }
+
+    /// Key of a file is a hash of its content and keys of its dependencies.
+    pub(crate) fn key(content: Hash, deps: &[Hash]) -> Hash {
+        let mut hash = Hash::new();
+        hash.write(&content.0.to_le_bytes());
+        for dep in deps {
+            hash.write(&dep.0.to_le_bytes());
+        }
+        hash
+    }
+}
+
+impl fmt::Display for Hash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:032x}", self.0)
+    }
+}
+
+/// Cached result of typechecking a file.
+pub(crate) struct Entry {
+    /// Key the entry was stored with.
+    pub(crate) key: Hash,
+    pub(crate) descriptor: FileDescriptorProto,
+    /// Warnings without file name prefix.
+    pub(crate) warnings: Vec<String>,
+    pub(crate) unsupported: Vec<(Loc, String)>,
+}
+
+/// Append length-prefixed record to cache entry.
+fn write_record(bytes: &mut Vec<u8>, tag: &str, data: &[u8]) {
+    bytes.extend(format!("{} {}\n", tag, data.len()).into_bytes());
+    bytes.extend(data);
+}
+
+/// Read record written by [`write_record`].
+fn read_record<'a>(bytes: &mut &'a [u8]) -> Option<(&'a str, &'a [u8])> {
+    let header_end = bytes.iter().position(|&b| b == b'\n')?;
+    let header = std::str::from_utf8(&bytes[..header_end]).ok()?;
+    let (tag, len) = header.split_once(' ')?;
+    let len: usize = len.parse().ok()?;
+    let data = bytes.get(header_end + 1..header_end + 1 + len)?;
+    *bytes = &bytes[header_end + 1 + len..];
+    Some((tag, data))
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct Cache {
+    dir: PathBuf,
+}
+
+impl Cache {
+    pub(crate) fn new(dir: PathBuf) -> Cache {
+        Cache { dir }
+    }
+
+    fn entry_path(&self, content_hash: Hash) -> PathBuf {
+        self.dir.join(format!("{}.pb", content_hash))
+    }
+
+    /// Load entry for file with given content.
+    ///
+    /// Missing or malformed entries are treated as cache miss.
+    pub(crate) fn load(&self, content_hash: Hash, content: &[u8]) -> Option<Entry> {
+        let bytes = fs::read(self.entry_path(content_hash)).ok()?;
+        let mut rest = &bytes[..];
+        // Entries written by other versions may have a different layout.
+        if read_record(&mut rest)? != ("version", VERSION_TAG.as_bytes()) {
+            return None;
+        }
+        let mut entry = Entry {
+            key: Hash(0),
+            descriptor: FileDescriptorProto::new(),
+            warnings: Vec::new(),
+            unsupported: Vec::new(),
+        };
+        let mut content_matches = false;
+        while !rest.is_empty() {
+            let (tag, data) = read_record(&mut rest)?;
+            match tag {
+                "key" => {
+                    let key = std::str::from_utf8(data).ok()?;
+                    entry.key = Hash(u128::from_str_radix(key, 16).ok()?);
+                }
+                "content" => content_matches = data == content,
+                "descriptor" => {
+                    entry.descriptor = FileDescriptorProto::parse_from_bytes(data).ok()?
+                }
+                "warning" => entry
+                    .warnings
+                    .push(std::str::from_utf8(data).ok()?.to_owned()),
+                "unsupported" => {
+                    let data = std::str::from_utf8(data).ok()?;
+                    let mut parts = data.splitn(3, ':');
+                    let line = parts.next()?.parse().ok()?;
+                    let col = parts.next()?.parse().ok()?;
+                    let construct = parts.next()?.to_owned();
+                    entry.unsupported.push((Loc { line, col }, construct));
+                }
+                _ => return None,
+            }
+        }
+        if !content_matches {
+            return None;
+        }
+        Some(entry)
+    }
+
+    /// Store entry for file with given content. Failure to write cache is not an error.
+    pub(crate) fn store(&self, content_hash: Hash, content: &[u8], entry: &Entry) {
+        if let Err(e) = self.try_store(content_hash, content, entry) {
+            warn!("failed to write cache to {}: {}", self.dir.display(), e);
+        }
+    }
+
+    fn try_store(&self, content_hash: Hash, content: &[u8], entry: &Entry) -> anyhow::Result<()> {
+        fs::create_dir_all(&self.dir)?;
+        let mut bytes = Vec::new();
+        write_record(&mut bytes, "version", VERSION_TAG.as_bytes());
+        write_record(&mut bytes, "key", entry.key.to_string().as_bytes());
+        write_record(&mut bytes, "content", content);
+        for warning in &entry.warnings {
+            write_record(&mut bytes, "warning", warning.as_bytes());
+        }
+        for (loc, construct) in &entry.unsupported {
+            let data = format!("{}:{}:{}", loc.line, loc.col, construct);
+            write_record(&mut bytes, "unsupported", data.as_bytes());
+        }
+        write_record(
+            &mut bytes,
+            "descriptor",
+            &entry.descriptor.write_to_bytes()?,
+        );
+        // Write to temporary file first so concurrent builds never read partial entry.
+        let temp = tempfile::NamedTempFile::new_in(&self.dir)?;
+        fs::write(temp.path(), bytes)?;
+        temp.persist(self.entry_path(content_hash))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+    use crate::Parser;
+
+    #[test]
+    fn entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache = Cache::new(dir.path().to_owned());
+        let mut descriptor = FileDescriptorProto::new();
+        descriptor.set_name("a.proto".to_owned());
+        let hash = Hash(1);
+        cache.store(
+            hash,
+            b"content",
+            &Entry {
+                key: Hash(2),
+                descriptor: descriptor.clone(),
+                warnings: vec!["1:1: warning\nwith two lines".to_owned()],
+                unsupported: vec![(Loc { line: 2, col: 3 }, "option `x: y`".to_owned())],
+            },
+        );
+
+        let entry = cache.load(hash, b"content").unwrap();
+        assert_eq!(Hash(2), entry.key);
+        assert_eq!(descriptor, entry.descriptor);
+        assert_eq!(vec!["1:1: warning\nwith two lines"], entry.warnings);
+        assert_eq!(
+            vec![(Loc { line: 2, col: 3 }, "option `x: y`".to_owned())],
+            entry.unsupported
+        );
+
+        // Different content with the same hash.
+        assert!(cache.load(hash, b"other").is_none());
+    }
+
+    #[test]
+    fn cache_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_dir = dir.path().join("cache");
+        let a_proto = dir.path().join("a.proto");
+        let b_proto = dir.path().join("b.proto");
+        fs::write(
+            &a_proto,
+            "syntax = 'proto3'; package p.q; import 'b.proto'; message A { B b = 1; }",
+        )
+        .unwrap();
+        fs::write(&b_proto, "syntax = 'proto3'; package p; message B {}").unwrap();
+
+        let parse = || {
+            Parser::new()
+                .pure()
+                .include(dir.path())
+                .input(&a_proto)
+                .cache_dir(&cache_dir)
+                .parse_and_typecheck()
+                .unwrap()
+                .file_descriptors
+        };
+        let a_field_type = |fds: &[FileDescriptorProto]| {
+            let a = fds.iter().find(|f| f.name() == "a.proto").unwrap();
+            a.message_type[0].field[0].type_name().to_owned()
+        };
+
+        let first = parse();
+        assert_eq!(".p.B", a_field_type(&first));
+        assert_eq!(2, fs::read_dir(&cache_dir).unwrap().count());
+
+        let cached = parse();
+        assert_eq!(first, cached);
+        assert_eq!(2, fs::read_dir(&cache_dir).unwrap().count());
+
+        // Changed import changes how `a.proto` is resolved although `a.proto` is unchanged.
+        fs::write(&b_proto, "syntax = 'proto3'; package p.q; message B {}").unwrap();
+        assert_eq!(".p.q.B", a_field_type(&parse()));
+    }
+
+    #[test]
+    fn cache_dir_warnings() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_dir = dir.path().join("cache");
+        let a_proto = dir.path().join("a.proto");
+        fs::write(&a_proto, "import 'b.proto'; message A {}").unwrap();
+        fs::write(dir.path().join("b.proto"), "syntax = 'proto3';").unwrap();
+
+        let parse = |strict| {
+            Parser::new()
+                .pure()
+                .include(dir.path())
+                .input(&a_proto)
+                .cache_dir(&cache_dir)
+                .strict(strict)
+                .parse_and_typecheck()
+        };
+        let first = parse(false).unwrap().warnings;
+        assert_eq!(1, first.len());
+        assert_eq!(first, parse(false).unwrap().warnings);
+        // Strict mode is part of the key, and file without syntax is an error in strict mode.
+        assert!(parse(true).is_err());
+        assert_eq!(3, fs::read_dir(&cache_dir).unwrap().count());
+    }
+}
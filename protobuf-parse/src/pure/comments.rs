@@ -0,0 +1,335 @@
+//! Comments in `.proto` file, used to populate `SourceCodeInfo`.
+//!
+//! Comments are attached to tokens following `protoc` tokenizer rules.
+
+use protobuf_support::lexer::lexer_impl::Lexer;
+use protobuf_support::lexer::loc::Loc;
+use protobuf_support::lexer::loc::FIRST_LINE;
+use protobuf_support::lexer::parser_language::ParserLanguage;
+use protobuf_support::lexer::token::Token;
+
+struct Comment {
+    /// Location of comment start.
+    loc: Loc,
+    /// Last line of comment.
+    end_line: u32,
+    /// `//` comment, as opposed to `/* */`.
+    line_comment: bool,
+    /// Comment text without comment markers.
+    text: String,
+}
+
+impl Comment {
+    fn new(loc: Loc, end_line: u32, comment: &str) -> Comment {
+        if let Some(text) = comment.strip_prefix("//") {
+            let mut text = text.strip_suffix('\r').unwrap_or(text).to_owned();
+            text.push('\n');
+            Comment {
+                loc,
+                end_line: loc.line,
+                line_comment: true,
+                text,
+            }
+        } else {
+            let text = comment
+                .strip_prefix("/*")
+                .and_then(|c| c.strip_suffix("*/"))
+                .unwrap_or(comment);
+            Comment {
+                loc,
+                end_line,
+                line_comment: false,
+                text: strip_block_comment(text),
+            }
+        }
+    }
+}
+
+/// Strip leading `*` of each line of `/* */` comment like `protoc` does.
+fn strip_block_comment(text: &str) -> String {
+    let mut r = String::new();
+    for (i, line) in text.split('\n').enumerate() {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        if i == 0 {
+            r.push_str(line);
+            continue;
+        }
+        r.push('\n');
+        let trimmed = line.trim_start();
+        r.push_str(trimmed.strip_prefix('*').unwrap_or(trimmed));
+    }
+    r
+}
+
+/// Comments of a token.
+struct TokenComments {
+    start: Loc,
+    end: Loc,
+    symbol: Option<char>,
+    leading: Option<String>,
+    detached: Vec<String>,
+    trailing: Option<String>,
+}
+
+/// Comments between two tokens, like `CommentCollector` of `protoc`.
+#[derive(Default)]
+struct Collector {
+    /// Pending comment and whether it is `//` comment.
+    buffer: Option<(String, bool)>,
+    can_attach_to_prev: bool,
+    trailing: Option<String>,
+    detached: Vec<String>,
+}
+
+impl Collector {
+    fn flush(&mut self) {
+        if let Some((text, _)) = self.buffer.take() {
+            if self.can_attach_to_prev {
+                self.trailing = Some(text);
+                self.can_attach_to_prev = false;
+            } else {
+                self.detached.push(text);
+            }
+        }
+    }
+
+    fn detach_from_prev(&mut self) {
+        self.can_attach_to_prev = false;
+    }
+
+    fn add(&mut self, comment: &Comment) {
+        match &mut self.buffer {
+            // Consecutive `//` comments are joined.
+            Some((text, true)) if comment.line_comment => text.push_str(&comment.text),
+            _ => {
+                self.flush();
+                self.buffer = Some((comment.text.clone(), comment.line_comment));
+            }
+        }
+    }
+}
+
+/// Split comments between previous token ending at `prev_line` and next token starting
+/// at `next_line` into trailing comment of previous token, detached comments
+/// and leading comment of next token.
+///
+/// `next_line` is `None` at the end of file.
+fn collect(
+    prev_line: Option<u32>,
+    comments: &[Comment],
+    next_line: Option<u32>,
+    end_of_scope: bool,
+) -> (Option<String>, Vec<String>, Option<String>) {
+    let mut collector = Collector {
+        can_attach_to_prev: prev_line.is_some(),
+        ..Collector::default()
+    };
+    let mut comments = comments.iter().peekable();
+    // Line after the last consumed comment.
+    let mut line = prev_line.map_or(FIRST_LINE, |l| l + 1);
+
+    if let Some(prev_line) = prev_line {
+        // A comment starting on the same line is attached to the previous token.
+        match comments.next_if(|c| c.loc.line == prev_line) {
+            Some(first) => {
+                let next_item_line = comments.peek().map(|c| c.loc.line).or(next_line);
+                if !first.line_comment && next_item_line == Some(first.end_line) {
+                    // Next token is on the same line, so it is not clear
+                    // which token the comment belongs to.
+                    return (None, Vec::new(), None);
+                }
+                collector.add(first);
+                collector.flush();
+                line = first.end_line + 1;
+            }
+            None if next_line == Some(prev_line) => return (None, Vec::new(), None),
+            None => {}
+        }
+    }
+
+    for comment in comments {
+        if comment.loc.line > line {
+            // Blank line separates comments.
+            collector.flush();
+            collector.detach_from_prev();
+        }
+        collector.add(comment);
+        line = comment.end_line + 1;
+    }
+
+    if next_line.is_some_and(|next_line| next_line > line) {
+        collector.flush();
+        collector.detach_from_prev();
+    }
+    if end_of_scope {
+        // Comment can't be attached to the end of scope.
+        collector.flush();
+    }
+    let leading = collector.buffer.take().map(|(text, _)| text);
+    (collector.trailing, collector.detached, leading)
+}
+
+/// Comments of a declaration.
+pub(crate) struct Declaration<'a> {
+    /// Location after the last token of declaration.
+    pub end: Loc,
+    pub leading: Option<&'a str>,
+    pub trailing: Option<&'a str>,
+    pub detached: &'a [String],
+}
+
+/// All comments of a file, attached to tokens.
+#[derive(Default)]
+pub(crate) struct Comments {
+    tokens: Vec<TokenComments>,
+}
+
+impl Comments {
+    /// Extract comments from `.proto` file content.
+    ///
+    /// Content is expected to be successfully parsed,
+    /// comments after lexer error are ignored.
+    pub fn parse(input: &str) -> Comments {
+        let mut lexer = Lexer::new(input, ParserLanguage::Proto);
+        let mut tokens: Vec<TokenComments> = Vec::new();
+        let mut comments = Vec::new();
+        loop {
+            lexer.skip_whitespaces();
+            let start = lexer.loc;
+            match lexer.next_comment_opt() {
+                Ok(Some(comment)) => {
+                    comments.push(Comment::new(start, lexer.loc.line, comment));
+                    continue;
+                }
+                Ok(None) => {}
+                Err(_) => break,
+            }
+            if lexer.eof() {
+                let prev_line = tokens.last().map(|t| t.end.line);
+                let (trailing, _, _) = collect(prev_line, &comments, None, true);
+                if let Some(prev) = tokens.last_mut() {
+                    prev.trailing = trailing;
+                }
+                break;
+            }
+
+            let symbol = match lexer.next_token_inner() {
+                Ok(Token::Symbol(c)) => Some(c),
+                Ok(_) => None,
+                Err(_) => break,
+            };
+            let end_of_scope = matches!(symbol, Some('}' | ']' | ')'));
+            let prev_line = tokens.last().map(|t| t.end.line);
+            let (trailing, detached, leading) =
+                collect(prev_line, &comments, Some(start.line), end_of_scope);
+            if let Some(prev) = tokens.last_mut() {
+                prev.trailing = trailing;
+            }
+            comments.clear();
+            tokens.push(TokenComments {
+                start,
+                end: lexer.loc,
+                symbol,
+                leading,
+                detached,
+                trailing: None,
+            });
+        }
+        Comments { tokens }
+    }
+
+    /// Declaration starting at given location.
+    ///
+    /// Declaration ends with `;` or with a `{ }` block. Trailing comment of a declaration
+    /// with a block is the comment after the opening brace, like in `protoc`.
+    pub fn declaration(&self, start: Loc) -> Option<Declaration<'_>> {
+        let first = self.tokens.binary_search_by_key(&start, |t| t.start).ok()?;
+        let (end, trailing) = self.declaration_end(first)?;
+        Some(Declaration {
+            end: end.end,
+            leading: self.tokens[first].leading.as_deref(),
+            trailing: trailing.trailing.as_deref(),
+            detached: &self.tokens[first].detached,
+        })
+    }
+
+    /// Last token of declaration and token which trailing comment belongs to.
+    fn declaration_end(&self, first: usize) -> Option<(&TokenComments, &TokenComments)> {
+        let mut depth = 0;
+        // Opening brace of declaration block.
+        let mut block = None;
+        for token in &self.tokens[first..] {
+            match token.symbol {
+                Some(c @ ('{' | '(' | '[')) => {
+                    if depth == 0 && c == '{' {
+                        block = Some(token);
+                    }
+                    depth += 1;
+                }
+                Some(c @ ('}' | ')' | ']')) => {
+                    depth -= 1;
+                    if depth < 0 {
+                        return None;
+                    }
+                    if let (0, '}', Some(block)) = (depth, c, block) {
+                        return Some((token, block));
+                    }
+                }
+                Some(';') if depth == 0 => return Some((token, token)),
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn leading_and_trailing() {
+        let input = r#"
+// first
+// second
+message M { // trailing
+  /* block */
+  string s = 1; // "quoted" // not a comment
+  string t = 2 [default = "//"];
+  // after t
+
+  // detached
+
+  int32 u = 3; /* ambiguous */ int32 v = 4;
+  int32 w = 5;
+  // end of scope
+}
+"#;
+        let comments = Comments::parse(input);
+        let decl = |line, col| comments.declaration(Loc { line, col }).unwrap();
+
+        let m = decl(4, 1);
+        assert_eq!(Loc { line: 15, col: 2 }, m.end);
+        assert_eq!(Some(" first\n second\n"), m.leading);
+        assert_eq!(Some(" trailing\n"), m.trailing);
+
+        let s = decl(6, 3);
+        assert_eq!(Loc { line: 6, col: 16 }, s.end);
+        assert_eq!(Some(" block "), s.leading);
+        assert_eq!(Some(" \"quoted\" // not a comment\n"), s.trailing);
+
+        let t = decl(7, 3);
+        assert_eq!(None, t.leading);
+        assert_eq!(Some(" after t\n"), t.trailing);
+
+        let u = decl(12, 3);
+        assert_eq!(&[" detached\n".to_owned()], u.detached);
+        assert_eq!(None, u.leading);
+        assert_eq!(None, u.trailing);
+        assert_eq!(None, decl(12, 32).leading);
+
+        // Comment before the end of scope is attached to the previous declaration.
+        let w = decl(13, 3);
+        assert_eq!(Some(" end of scope\n"), w.trailing);
+    }
+}
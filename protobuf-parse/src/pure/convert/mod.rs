@@ -1,16 +1,27 @@
 //! Convert parser model to rust-protobuf model
 
 mod option_resolver;
+mod source_code_info;
 mod type_resolver;
+mod unused_imports;
+
+use std::cell::Cell;
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::fmt;
+use std::ops::RangeInclusive;
 
 use protobuf;
 use protobuf::descriptor::descriptor_proto::ReservedRange;
+use protobuf::descriptor::enum_descriptor_proto::EnumReservedRange;
 use protobuf::descriptor::field_descriptor_proto;
 use protobuf::descriptor::field_descriptor_proto::Type;
 use protobuf::descriptor::FieldDescriptorProto;
 use protobuf::descriptor::OneofDescriptorProto;
 use protobuf::reflect::FileDescriptor;
+use protobuf::Message;
 use protobuf_support::json_name::json_name;
+use protobuf_support::lexer::loc::Loc;
 use protobuf_support::text_format::escape_bytes_to;
 
 use crate::case_convert::camel_case;
@@ -18,11 +29,14 @@ use crate::path::fs_path_to_proto_path;
 use crate::proto_path::ProtoPath;
 use crate::protobuf_abs_path::ProtobufAbsPath;
 use crate::protobuf_ident::ProtobufIdent;
+use crate::protobuf_path::ProtobufPath;
+use crate::pure::comments::Comments;
 use crate::pure::convert::option_resolver::OptionResoler;
 use crate::pure::convert::option_resolver::ProtobufOptions;
 use crate::pure::convert::type_resolver::MessageOrEnum;
 use crate::pure::convert::type_resolver::TypeResolver;
 use crate::pure::model;
+use crate::pure::model::FIELD_NUMBER_MAX;
 use crate::FileDescriptorPair;
 use crate::ProtobufAbsPathRef;
 use crate::ProtobufIdentRef;
@@ -35,8 +49,119 @@ enum ConvertError {
     ExpectingMessage(ProtobufAbsPath),
     #[error("expecting an enum for name {0}")]
     ExpectingEnum(ProtobufAbsPath),
+    #[error("field `{1}` in message `{0}` has number {2} out of range 1..={max}", max = FIELD_NUMBER_MAX)]
+    FieldNumberOutOfRange(ProtobufAbsPath, String, i32),
+    #[error("field `{1}` in message `{0}` has number {2} reserved for protobuf implementation")]
+    FieldNumberReservedForImplementation(ProtobufAbsPath, String, i32),
+    #[error("fields `{1}` and `{2}` in message `{0}` have the same number {3}")]
+    FieldNumberDuplicate(ProtobufAbsPath, String, String, i32),
+    #[error("field `{1}` in message `{0}` uses reserved number {2}")]
+    FieldNumberReserved(ProtobufAbsPath, String, i32),
+    #[error("field `{1}` in message `{0}` uses reserved name")]
+    FieldNameReserved(ProtobufAbsPath, String),
+    #[error("value `{1}` in enum `{0}` uses reserved number {2}")]
+    EnumValueNumberReserved(ProtobufAbsPath, String, i32),
+    #[error("value `{1}` in enum `{0}` uses reserved name")]
+    EnumValueNameReserved(ProtobufAbsPath, String),
+    #[error("first value `{1}` of enum `{0}` has number {2}, but it must be zero")]
+    EnumFirstValueNotZero(ProtobufAbsPath, String, i32),
+    #[error("values `{1}` and `{2}` in enum `{0}` have the same number {3}, but `allow_alias` is not set")]
+    EnumValueNumberDuplicate(ProtobufAbsPath, String, String, i32),
+    #[error("enum `{0}` sets `allow_alias`, but no values share a number")]
+    EnumAllowAliasWithoutAliases(ProtobufAbsPath),
+    #[error("map field `{1}` in message `{0}` has key type `{2}`, but key must be an integral, string or bool type")]
+    MapKeyTypeNotAllowed(ProtobufAbsPath, String, String),
+    #[error("default value `{2}` of field `{1}` in message `{0}` is not a valid `{3}`")]
+    DefaultValueIncompatible(ProtobufAbsPath, String, String, String),
+    #[error("field `{1}` in message `{0}` is a message, which cannot have default value")]
+    DefaultValueForMessage(ProtobufAbsPath, String),
+    #[error("field `{1}` in message `{0}` uses number {2} declared as an extension number")]
+    FieldNumberInExtensionRange(ProtobufAbsPath, String, i32),
+    #[error("extension ranges `{1}` and `{2}` in message `{0}` overlap")]
+    ExtensionRangesOverlap(ProtobufAbsPath, String, String),
+    #[error("extension `{1}` has number {2}, which is not declared as an extension number in message `{0}`")]
+    ExtensionNumberNotInRange(ProtobufAbsPath, String, i32),
+    #[error("fields `{1}` and `{2}` in message `{0}` have conflicting JSON names")]
+    JsonNameConflict(ProtobufAbsPath, String, String),
+    #[error("extension `{0}` sets `json_name`, which is not allowed for extensions")]
+    JsonNameOnExtension(String),
+    #[error("oneof `{1}` in message `{0}` must have at least one field")]
+    OneofEmpty(ProtobufAbsPath, String),
+    #[error("no syntax specified, which is not allowed in strict mode")]
+    SyntaxNotSpecified,
+}
+
+/// Error in a declaration at given location.
+///
+/// Displayed as the inner error, location is reported by the caller
+/// which knows the file name.
+#[derive(Debug)]
+pub(crate) struct ConvertErrorWithLoc {
+    pub(crate) loc: Loc,
+    pub(crate) error: anyhow::Error,
+}
+
+impl fmt::Display for ConvertErrorWithLoc {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.error, f)
+    }
+}
+
+impl std::error::Error for ConvertErrorWithLoc {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.error.source()
+    }
+}
+
+/// Attach location of a declaration to the error,
+/// unless it already has location of a nested declaration.
+fn with_loc<T>(loc: Loc, result: anyhow::Result<T>) -> anyhow::Result<T> {
+    result.map_err(|error| {
+        if error.is::<ConvertErrorWithLoc>() {
+            error
+        } else {
+            ConvertErrorWithLoc { loc, error }.into()
+        }
+    })
 }
 
+/// Format floating point number like `SimpleDtoa` of `protoc` does:
+/// `%g` with the smallest precision of 15 or 17 digits which round-trips.
+fn simple_dtoa(f: f64) -> String {
+    if f.is_nan() {
+        return "nan".to_owned();
+    }
+    if f.is_infinite() {
+        return if f > 0.0 { "inf" } else { "-inf" }.to_owned();
+    }
+    let precision = match format!("{:.14e}", f).parse::<f64>() {
+        Ok(p) if p == f => 15,
+        _ => 17,
+    };
+    // `%g`: scientific notation when exponent is less than -4 or not less than precision,
+    // trailing zeros removed.
+    let scientific = format!("{:.*e}", precision - 1, f);
+    let (mantissa, exp) = scientific.split_once('e').unwrap();
+    let exp: i32 = exp.parse().unwrap();
+    let trim = |s: &str| -> String {
+        if s.contains('.') {
+            s.trim_end_matches('0').trim_end_matches('.').to_owned()
+        } else {
+            s.to_owned()
+        }
+    };
+    if exp < -4 || exp >= precision as i32 {
+        let sign = if exp < 0 { '-' } else { '+' };
+        format!("{}e{}{:02}", trim(mantissa), sign, exp.abs())
+    } else {
+        let decimals = (precision as i32 - 1 - exp) as usize;
+        trim(&format!("{:.*}", decimals, f))
+    }
+}
+
+/// Field numbers reserved for protobuf implementation.
+const FIELD_NUMBER_RESERVED_FOR_IMPLEMENTATION: RangeInclusive<i32> = 19000..=19999;
+
 pub struct WithFullName<T> {
     full_name: ProtobufAbsPath,
     t: T,
@@ -126,6 +251,27 @@ impl TypeResolved {
             _ => None,
         }
     }
+
+    /// Type name for error messages.
+    fn name_for_diag(&self) -> String {
+        match self.type_name() {
+            Some(name) => name.to_string(),
+            None => format!("{:?}", self).to_lowercase(),
+        }
+    }
+
+    /// Type can be used as a key of map field.
+    fn is_map_key(&self) -> bool {
+        !matches!(
+            self,
+            TypeResolved::Double
+                | TypeResolved::Float
+                | TypeResolved::Bytes
+                | TypeResolved::Message(_)
+                | TypeResolved::Enum(_)
+                | TypeResolved::Group(_)
+        )
+    }
 }
 
 pub(crate) struct Resolver<'a> {
@@ -141,6 +287,33 @@ impl<'a> Resolver<'a> {
         ProtobufIdent::from(format!("{}Entry", camel_case(field_name)))
     }
 
+    /// Name of synthetic oneof for proto3 optional field, computed like `protoc` does:
+    /// field name prefixed with `_`, and then with `X` until it does not conflict
+    /// with fields, oneofs or previously generated synthetic oneofs.
+    fn synthetic_oneof_name(
+        message: &model::Message,
+        synthetic_oneofs: &[OneofDescriptorProto],
+        field_name: &str,
+    ) -> String {
+        let mut name = if field_name.starts_with('_') {
+            field_name.to_owned()
+        } else {
+            format!("_{}", field_name)
+        };
+        let conflicts = |name: &str| {
+            message
+                .regular_fields_including_in_oneofs()
+                .iter()
+                .any(|f| f.t.name == name)
+                || message.oneofs().iter().any(|o| o.name == name)
+                || synthetic_oneofs.iter().any(|o| o.name() == name)
+        };
+        while conflicts(&name) {
+            name = format!("X{}", name);
+        }
+        name
+    }
+
     fn map_entry_field(
         &self,
         scope: &ProtobufAbsPath,
@@ -175,6 +348,16 @@ impl<'a> Resolver<'a> {
         key: &model::FieldType,
         value: &model::FieldType,
     ) -> anyhow::Result<protobuf::descriptor::DescriptorProto> {
+        let key_type = self.field_type(scope, "key", key)?;
+        if !key_type.is_map_key() {
+            return Err(ConvertError::MapKeyTypeNotAllowed(
+                scope.to_owned(),
+                field_name.to_owned(),
+                key_type.name_for_diag(),
+            )
+            .into());
+        }
+
         let mut output = protobuf::descriptor::DescriptorProto::new();
 
         output.options.mut_or_insert_default().set_map_entry(true);
@@ -214,13 +397,16 @@ impl<'a> Resolver<'a> {
         let mut nested_scope = scope.to_owned();
         nested_scope.push_simple(ProtobufIdentRef::new(&input.name));
 
+        Self::check_field_numbers(&nested_scope, input)?;
+        self.check_json_names(&nested_scope, input)?;
+
         let mut output = protobuf::descriptor::DescriptorProto::new();
         output.set_name(input.name.clone());
 
         let mut nested_messages = Vec::new();
 
         for m in &input.messages {
-            let message = self.message(&nested_scope, &m.t)?;
+            let message = with_loc(m.loc, self.message(&nested_scope, &m.t))?;
             nested_messages.push(model::WithLoc {
                 t: message,
                 loc: m.loc,
@@ -230,7 +416,10 @@ impl<'a> Resolver<'a> {
         for f in input.regular_fields_including_in_oneofs() {
             match &f.t.typ {
                 model::FieldType::Map(t) => {
-                    let message = self.map_entry_message(&nested_scope, &f.t.name, &t.0, &t.1)?;
+                    let message = with_loc(
+                        f.loc,
+                        self.map_entry_message(&nested_scope, &f.t.name, &t.0, &t.1),
+                    )?;
                     nested_messages.push(model::WithLoc {
                         t: message,
                         loc: f.loc,
@@ -241,7 +430,8 @@ impl<'a> Resolver<'a> {
                     fields,
                     ..
                 }) => {
-                    let message = self.group_message(&nested_scope, group_name, fields)?;
+                    let message =
+                        with_loc(f.loc, self.group_message(&nested_scope, group_name, fields))?;
                     nested_messages.push(model::WithLoc {
                         t: message,
                         loc: f.loc,
@@ -261,21 +451,30 @@ impl<'a> Resolver<'a> {
         output.enum_type = input
             .enums
             .iter()
-            .map(|e| self.enumeration(scope, e))
+            .map(|e| with_loc(e.loc, self.enumeration(&nested_scope, e)))
             .collect::<Result<_, _>>()?;
 
         {
             let mut fields = Vec::new();
 
+            // Synthetic oneofs for proto3 optional fields must be placed after all real oneofs.
+            // https://github.com/protocolbuffers/protobuf/blob/main/docs/implementing_proto3_presence.md
+            let real_oneof_count = input.oneofs().len();
+            let mut synthetic_oneofs = Vec::new();
+
             for fo in &input.fields {
                 match &fo.t {
                     model::FieldOrOneOf::Field(f) => {
                         let oneof_index = if self.is_proto3_optional(f) {
-                            let oneof_index = output.oneof_decl.len() as i32;
+                            let oneof_index = real_oneof_count + synthetic_oneofs.len();
                             let mut oneof = OneofDescriptorProto::new();
-                            oneof.set_name(format!("_{}", f.name));
-                            output.oneof_decl.push(oneof);
-                            Some(oneof_index)
+                            oneof.set_name(Resolver::synthetic_oneof_name(
+                                input,
+                                &synthetic_oneofs,
+                                &f.name,
+                            ));
+                            synthetic_oneofs.push(oneof);
+                            Some(oneof_index as i32)
                         } else {
                             None
                         };
@@ -286,35 +485,33 @@ impl<'a> Resolver<'a> {
                         for f in &o.fields {
                             fields.push(self.field(&nested_scope, f, Some(oneof_index as i32))?);
                         }
-                        output.oneof_decl.push(self.oneof(scope, o)?);
+                        output
+                            .oneof_decl
+                            .push(with_loc(fo.loc, self.oneof(&nested_scope, o))?);
                     }
                 }
             }
 
             output.field = fields;
+            output.oneof_decl.extend(synthetic_oneofs);
         }
 
         for ext in &input.extension_ranges {
             let mut extension_range = protobuf::descriptor::descriptor_proto::ExtensionRange::new();
             extension_range.set_start(ext.from);
-            extension_range.set_end(ext.to + 1);
+            extension_range.set_end(Self::range_end(input, ext)?);
             output.extension_range.push(extension_range);
         }
         for ext in &input.extensions {
             let mut extension = self.field(scope, &ext.t.field, None)?;
-            extension.set_extendee(
-                self.type_resolver
-                    .resolve_message_or_enum(scope, &ext.t.extendee)?
-                    .full_name
-                    .path,
-            );
+            extension.set_extendee(with_loc(ext.loc, self.extendee(scope, &ext.t))?.path);
             output.extension.push(extension);
         }
 
         for reserved in &input.reserved_nums {
             let mut reserved_range = ReservedRange::new();
             reserved_range.set_start(reserved.from);
-            reserved_range.set_end(reserved.to + 1);
+            reserved_range.set_end(Self::range_end(input, reserved)?);
             output.reserved_range.push(reserved_range);
         }
         output.reserved_name = input.reserved_names.clone().into();
@@ -322,25 +519,160 @@ impl<'a> Resolver<'a> {
         Ok(output)
     }
 
+    /// Exclusive end of extension or reserved range of a message, as stored by `protoc`.
+    ///
+    /// Ranges declared `to max` in messages with `message_set_wire_format`
+    /// extend to the largest `i32`, like in `protoc`.
+    fn range_end(message: &model::Message, range: &model::FieldNumberRange) -> anyhow::Result<i32> {
+        let message_set = message
+            .options
+            .as_slice()
+            .by_name_bool("message_set_wire_format")?
+            .unwrap_or(false);
+        Ok(match range.to {
+            FIELD_NUMBER_MAX if message_set => i32::MAX,
+            to => to + 1,
+        })
+    }
+
+    /// Like `protoc`, reject fields with JSON names differing only in case.
+    ///
+    /// In proto2 only conflicts between names set with `json_name` option are errors.
+    fn check_json_names(
+        &self,
+        message_name: &ProtobufAbsPathRef,
+        input: &model::Message,
+    ) -> anyhow::Result<()> {
+        // Proto3 and editions default to `features.json_format = ALLOW`.
+        let json_format_allow = self.current_file.syntax != model::Syntax::Proto2;
+        // Field location, name, default JSON name and JSON name set explicitly.
+        let mut fields: Vec<(Loc, &str, String, Option<String>)> = Vec::new();
+        for f in input.regular_fields_including_in_oneofs() {
+            let custom = f.t.options.as_slice().by_name_string("json_name")?;
+            fields.push((f.loc, &f.t.name, json_name(&f.t.name), custom));
+        }
+        for (i, (loc, name, default, custom)) in fields.iter().enumerate() {
+            let effective = custom.as_ref().unwrap_or(default);
+            for (_, prev, prev_default, prev_custom) in &fields[..i] {
+                let prev_effective = prev_custom.as_ref().unwrap_or(prev_default);
+                let conflict = match (prev_custom, custom) {
+                    (Some(_), Some(_)) => prev_effective.eq_ignore_ascii_case(effective),
+                    _ => {
+                        json_format_allow
+                            && (prev_default.eq_ignore_ascii_case(default)
+                                || prev_effective.eq_ignore_ascii_case(effective))
+                    }
+                };
+                if conflict {
+                    let error = ConvertError::JsonNameConflict(
+                        message_name.to_owned(),
+                        prev.to_string(),
+                        name.to_string(),
+                    );
+                    return with_loc(*loc, Err(error.into()));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn check_field_numbers(
+        message_name: &ProtobufAbsPathRef,
+        input: &model::Message,
+    ) -> anyhow::Result<()> {
+        let fields = input.regular_fields_including_in_oneofs();
+        for (i, f) in fields.iter().enumerate() {
+            with_loc(
+                f.loc,
+                Self::check_field_number(message_name, input, &fields[..i], f),
+            )?;
+        }
+        for (i, r) in input.extension_ranges.iter().enumerate() {
+            if let Some(prev) = input.extension_ranges[..i]
+                .iter()
+                .find(|p| p.from <= r.to && r.from <= p.to)
+            {
+                return Err(ConvertError::ExtensionRangesOverlap(
+                    message_name.to_owned(),
+                    prev.to_string(),
+                    r.to_string(),
+                )
+                .into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Check number and name of a field, `prev` are fields declared before it.
+    fn check_field_number(
+        message_name: &ProtobufAbsPathRef,
+        input: &model::Message,
+        prev: &[&model::WithLoc<model::Field>],
+        f: &model::WithLoc<model::Field>,
+    ) -> anyhow::Result<()> {
+        let field_error = |e: fn(ProtobufAbsPath, String, i32) -> ConvertError| {
+            e(message_name.to_owned(), f.t.name.clone(), f.t.number)
+        };
+        if f.t.number < 1 || f.t.number > FIELD_NUMBER_MAX {
+            return Err(field_error(ConvertError::FieldNumberOutOfRange).into());
+        }
+        if FIELD_NUMBER_RESERVED_FOR_IMPLEMENTATION.contains(&f.t.number) {
+            return Err(field_error(ConvertError::FieldNumberReservedForImplementation).into());
+        }
+        if input
+            .reserved_nums
+            .iter()
+            .any(|r| (r.from..=r.to).contains(&f.t.number))
+        {
+            return Err(field_error(ConvertError::FieldNumberReserved).into());
+        }
+        if input
+            .extension_ranges
+            .iter()
+            .any(|r| (r.from..=r.to).contains(&f.t.number))
+        {
+            return Err(field_error(ConvertError::FieldNumberInExtensionRange).into());
+        }
+        if input.reserved_names.contains(&f.t.name) {
+            return Err(
+                ConvertError::FieldNameReserved(message_name.to_owned(), f.t.name.clone()).into(),
+            );
+        }
+        if let Some(prev) = prev.iter().find(|p| p.t.number == f.t.number) {
+            return Err(ConvertError::FieldNumberDuplicate(
+                message_name.to_owned(),
+                prev.t.name.clone(),
+                f.t.name.clone(),
+                f.t.number,
+            )
+            .into());
+        }
+        Ok(())
+    }
+
     fn service_method(
         &self,
         input: &model::Method,
     ) -> anyhow::Result<protobuf::descriptor::MethodDescriptorProto> {
         let scope = &self.current_file.package;
+        let message_type = |name: &ProtobufPath| -> anyhow::Result<String> {
+            let t = self.type_resolver.resolve_message_or_enum(scope, name)?;
+            match t.t {
+                MessageOrEnum::Message(..) => Ok(t.full_name.to_string()),
+                MessageOrEnum::Enum(..) => Err(ConvertError::ExpectingMessage(t.full_name).into()),
+            }
+        };
         let mut output = protobuf::descriptor::MethodDescriptorProto::new();
         output.set_name(input.name.clone());
-        output.set_input_type(
-            self.type_resolver
-                .resolve_message_or_enum(scope, &input.input_type)?
-                .full_name
-                .to_string(),
-        );
-        output.set_output_type(
-            self.type_resolver
-                .resolve_message_or_enum(scope, &input.output_type)?
-                .full_name
-                .to_string(),
-        );
+        output.set_input_type(message_type(&input.input_type)?);
+        output.set_output_type(message_type(&input.output_type)?);
+        // `protoc` sets streaming flags only when they are true.
+        if input.client_streaming {
+            output.set_client_streaming(true);
+        }
+        if input.server_streaming {
+            output.set_server_streaming(true);
+        }
         Ok(output)
     }
 
@@ -354,7 +686,7 @@ impl<'a> Resolver<'a> {
         output.method = input
             .methods
             .iter()
-            .map(|m| self.service_method(m))
+            .map(|m| with_loc(m.loc, self.service_method(m)))
             .collect::<Result<_, _>>()?;
 
         Ok(output)
@@ -370,6 +702,15 @@ impl<'a> Resolver<'a> {
         scope: &ProtobufAbsPathRef,
         input: &model::WithLoc<model::Field>,
         oneof_index: Option<i32>,
+    ) -> anyhow::Result<protobuf::descriptor::FieldDescriptorProto> {
+        with_loc(input.loc, self.field_without_loc(scope, input, oneof_index))
+    }
+
+    fn field_without_loc(
+        &self,
+        scope: &ProtobufAbsPathRef,
+        input: &model::WithLoc<model::Field>,
+        oneof_index: Option<i32>,
     ) -> anyhow::Result<protobuf::descriptor::FieldDescriptorProto> {
         let mut output = protobuf::descriptor::FieldDescriptorProto::new();
         output.set_name(input.t.name.clone());
@@ -392,27 +733,8 @@ impl<'a> Resolver<'a> {
 
         output.set_number(input.t.number);
         // TODO: move default to option parser
-        if let Some(ref default) = input.t.options.as_slice().by_name("default") {
-            let default = match output.type_() {
-                protobuf::descriptor::field_descriptor_proto::Type::TYPE_STRING => {
-                    if let &model::ProtobufConstant::String(ref s) = default {
-                        s.decode_utf8()?
-                    } else {
-                        return Err(ConvertError::DefaultValueIsNotStringLiteral.into());
-                    }
-                }
-                protobuf::descriptor::field_descriptor_proto::Type::TYPE_BYTES => {
-                    if let &model::ProtobufConstant::String(ref s) = default {
-                        let mut buf = String::new();
-                        escape_bytes_to(&s.decode_bytes()?, &mut buf);
-                        buf
-                    } else {
-                        return Err(ConvertError::DefaultValueIsNotStringLiteral.into());
-                    }
-                }
-                _ => default.format(),
-            };
-            output.set_default_value(default);
+        if let Some(default) = input.t.options.as_slice().by_name("default") {
+            output.set_default_value(self.default_value(scope, &input.t.name, &t, default)?);
         }
 
         if let Some(oneof_index) = oneof_index {
@@ -428,13 +750,87 @@ impl<'a> Resolver<'a> {
         Ok(output)
     }
 
+    /// Default value in the form stored in `FieldDescriptorProto.default_value` by `protoc`.
+    fn default_value(
+        &self,
+        scope: &ProtobufAbsPathRef,
+        field_name: &str,
+        t: &TypeResolved,
+        default: &model::ProtobufConstant,
+    ) -> anyhow::Result<String> {
+        let incompatible = || {
+            ConvertError::DefaultValueIncompatible(
+                scope.to_owned(),
+                field_name.to_owned(),
+                default.format(),
+                t.name_for_diag(),
+            )
+        };
+        let integer = |min: i64, max: u64| -> anyhow::Result<String> {
+            match *default {
+                model::ProtobufConstant::U64(v) if v <= max => Ok(v.to_string()),
+                model::ProtobufConstant::I64(v) if v >= min => Ok(v.to_string()),
+                _ => Err(incompatible().into()),
+            }
+        };
+        Ok(match t {
+            TypeResolved::Int32 | TypeResolved::Sint32 | TypeResolved::Sfixed32 => {
+                integer(i32::MIN as i64, i32::MAX as u64)?
+            }
+            TypeResolved::Int64 | TypeResolved::Sint64 | TypeResolved::Sfixed64 => {
+                integer(i64::MIN, i64::MAX as u64)?
+            }
+            TypeResolved::Uint32 | TypeResolved::Fixed32 => integer(0, u32::MAX as u64)?,
+            TypeResolved::Uint64 | TypeResolved::Fixed64 => integer(0, u64::MAX)?,
+            TypeResolved::Float | TypeResolved::Double => match *default {
+                model::ProtobufConstant::U64(v) => simple_dtoa(v as f64),
+                model::ProtobufConstant::I64(v) => simple_dtoa(v as f64),
+                model::ProtobufConstant::F64(v) => simple_dtoa(v),
+                _ => return Err(incompatible().into()),
+            },
+            TypeResolved::Bool => match *default {
+                model::ProtobufConstant::Bool(b) => b.to_string(),
+                _ => return Err(incompatible().into()),
+            },
+            TypeResolved::String => match *default {
+                model::ProtobufConstant::String(ref s) => s.decode_utf8()?,
+                _ => return Err(ConvertError::DefaultValueIsNotStringLiteral.into()),
+            },
+            TypeResolved::Bytes => match *default {
+                model::ProtobufConstant::String(ref s) => {
+                    let mut buf = String::new();
+                    escape_bytes_to(&s.decode_bytes()?, &mut buf);
+                    buf
+                }
+                _ => return Err(ConvertError::DefaultValueIsNotStringLiteral.into()),
+            },
+            TypeResolved::Enum(e) => {
+                let value = match *default {
+                    model::ProtobufConstant::Ident(ref i) => i.to_string(),
+                    _ => return Err(incompatible().into()),
+                };
+                match self.type_resolver.find_message_or_enum_by_abs_name(e)?.t {
+                    MessageOrEnum::Enum(e) if e.values.iter().any(|v| v.t.name == value) => value,
+                    _ => return Err(incompatible().into()),
+                }
+            }
+            TypeResolved::Message(..) | TypeResolved::Group(..) => {
+                return Err(ConvertError::DefaultValueForMessage(
+                    scope.to_owned(),
+                    field_name.to_owned(),
+                )
+                .into())
+            }
+        })
+    }
+
     fn find_message_by_abs_name(
         &self,
         abs_path: &ProtobufAbsPath,
     ) -> anyhow::Result<WithFullName<&'a model::Message>> {
         let with_full_name = self
             .type_resolver
-            .find_message_or_enum_by_abs_name(abs_path)?;
+            .find_message_or_enum_by_full_name(abs_path)?;
         match with_full_name.t {
             MessageOrEnum::Message(m) => Ok(WithFullName {
                 t: m,
@@ -450,7 +846,7 @@ impl<'a> Resolver<'a> {
     ) -> anyhow::Result<&'a model::Enumeration> {
         match self
             .type_resolver
-            .find_message_or_enum_by_abs_name(abs_path)?
+            .find_message_or_enum_by_full_name(abs_path)?
             .t
         {
             MessageOrEnum::Enum(e) => Ok(e),
@@ -519,6 +915,26 @@ impl<'a> Resolver<'a> {
         scope: &ProtobufAbsPathRef,
         input: &model::Enumeration,
     ) -> anyhow::Result<protobuf::descriptor::EnumDescriptorProto> {
+        let mut enum_name = scope.to_owned();
+        enum_name.push_simple(ProtobufIdentRef::new(&input.name));
+        for v in &input.values {
+            if input
+                .reserved_nums
+                .iter()
+                .any(|r| (r.from..=r.to).contains(&v.t.number))
+            {
+                let error =
+                    ConvertError::EnumValueNumberReserved(enum_name, v.t.name.clone(), v.t.number);
+                return with_loc(v.loc, Err(error.into()));
+            }
+            if input.reserved_names.contains(&v.t.name) {
+                let error = ConvertError::EnumValueNameReserved(enum_name, v.t.name.clone());
+                return with_loc(v.loc, Err(error.into()));
+            }
+        }
+
+        self.check_enum_value_numbers(&enum_name, input)?;
+
         let mut output = protobuf::descriptor::EnumDescriptorProto::new();
         output.set_name(input.name.clone());
         output.value = input
@@ -526,19 +942,115 @@ impl<'a> Resolver<'a> {
             .iter()
             .map(|v| self.enum_value(scope, &v))
             .collect::<Result<_, _>>()?;
+        for reserved in &input.reserved_nums {
+            let mut reserved_range = EnumReservedRange::new();
+            reserved_range.set_start(reserved.from);
+            // Unlike message reserved ranges, enum reserved ranges are inclusive.
+            reserved_range.set_end(reserved.to);
+            output.reserved_range.push(reserved_range);
+        }
+        output.reserved_name = input.reserved_names.clone();
         Ok(output)
     }
 
+    fn check_enum_value_numbers(
+        &self,
+        enum_name: &ProtobufAbsPath,
+        input: &model::Enumeration,
+    ) -> anyhow::Result<()> {
+        // Open enums need zero default value.
+        if self.current_file.syntax != model::Syntax::Proto2 {
+            if let Some(first) = input.values.first() {
+                if first.t.number != 0 {
+                    let error = ConvertError::EnumFirstValueNotZero(
+                        enum_name.clone(),
+                        first.t.name.clone(),
+                        first.t.number,
+                    );
+                    return with_loc(first.loc, Err(error.into()));
+                }
+            }
+        }
+
+        let allow_alias = input.options.as_slice().by_name_bool("allow_alias")? == Some(true);
+        let mut has_aliases = false;
+        for (i, v) in input.values.iter().enumerate() {
+            if let Some(prev) = input.values[..i].iter().find(|p| p.t.number == v.t.number) {
+                if !allow_alias {
+                    let error = ConvertError::EnumValueNumberDuplicate(
+                        enum_name.clone(),
+                        prev.t.name.clone(),
+                        v.t.name.clone(),
+                        v.t.number,
+                    );
+                    return with_loc(v.loc, Err(error.into()));
+                }
+                has_aliases = true;
+            }
+        }
+        // Like `protoc`, reject the option when it has no effect.
+        if allow_alias && !has_aliases {
+            return Err(ConvertError::EnumAllowAliasWithoutAliases(enum_name.clone()).into());
+        }
+        Ok(())
+    }
+
     fn oneof(
         &self,
-        _scope: &ProtobufAbsPathRef,
+        message: &ProtobufAbsPath,
         input: &model::OneOf,
     ) -> anyhow::Result<protobuf::descriptor::OneofDescriptorProto> {
+        if input.fields.is_empty() {
+            return Err(ConvertError::OneofEmpty(message.clone(), input.name.clone()).into());
+        }
         let mut output = protobuf::descriptor::OneofDescriptorProto::new();
         output.set_name(input.name.clone());
         Ok(output)
     }
 
+    /// Resolve extended message and check extension number is declared in it.
+    ///
+    /// Also check extension does not set `json_name`, like `protoc` does.
+    fn extendee(
+        &self,
+        scope: &ProtobufAbsPathRef,
+        input: &model::Extension,
+    ) -> anyhow::Result<ProtobufAbsPath> {
+        if input
+            .field
+            .t
+            .options
+            .as_slice()
+            .by_name("json_name")
+            .is_some()
+        {
+            return Err(ConvertError::JsonNameOnExtension(input.field.t.name.clone()).into());
+        }
+        let extendee = self
+            .type_resolver
+            .resolve_message_or_enum(scope, &input.extendee)?;
+        let message = match extendee.t {
+            MessageOrEnum::Message(m) => m,
+            MessageOrEnum::Enum(..) => {
+                return Err(ConvertError::ExpectingMessage(extendee.full_name).into())
+            }
+        };
+        let number = input.field.t.number;
+        let mut in_range = false;
+        for r in &message.extension_ranges {
+            in_range |= r.from <= number && number < Self::range_end(message, r)?;
+        }
+        if !in_range {
+            return Err(ConvertError::ExtensionNumberNotInRange(
+                extendee.full_name,
+                input.field.t.name.clone(),
+                number,
+            )
+            .into());
+        }
+        Ok(extendee.full_name)
+    }
+
     fn extension(
         &self,
         scope: &ProtobufAbsPath,
@@ -548,12 +1060,7 @@ impl<'a> Resolver<'a> {
         Option<protobuf::descriptor::DescriptorProto>,
     )> {
         let mut field = self.field(scope, &input.field, None)?;
-        field.set_extendee(
-            self.type_resolver
-                .resolve_message_or_enum(scope, &input.extendee)?
-                .full_name
-                .to_string(),
-        );
+        field.set_extendee(self.extendee(scope, input)?.to_string());
         let group_messages = if let model::FieldType::Group(g) = &input.field.t.typ {
             Some(self.group_message(scope, &g.name, &g.fields)?)
         } else {
@@ -567,9 +1074,15 @@ fn syntax(input: model::Syntax) -> String {
     match input {
         model::Syntax::Proto2 => "proto2".to_owned(),
         model::Syntax::Proto3 => "proto3".to_owned(),
+        model::Syntax::Editions(..) => "editions".to_owned(),
     }
 }
 
+/// `FileDescriptorProto.edition` field number.
+///
+/// Bundled `descriptor.proto` predates editions, so the field is stored as unknown.
+pub(crate) const FILE_DESCRIPTOR_PROTO_EDITION: u32 = 14;
+
 fn label(input: Option<model::Rule>) -> protobuf::descriptor::field_descriptor_proto::Label {
     match input {
         Some(model::Rule::Optional) => {
@@ -601,22 +1114,86 @@ pub(crate) fn populate_dependencies(
     }
 }
 
+/// Result of converting a parsed file.
+pub(crate) struct FileDescriptorOutput {
+    pub(crate) descriptor: protobuf::descriptor::FileDescriptorProto,
+    /// Warnings prefixed with location.
+    pub(crate) warnings: Vec<String>,
+    /// Constructs dropped from the descriptor in non-strict mode.
+    pub(crate) unsupported: Vec<(Loc, String)>,
+}
+
+/// Add file `name` and files it re-exports with `import public`, transitively.
+fn add_visible_dep<'a>(
+    name: &str,
+    deps: &'a [FileDescriptorPair],
+    visible: &mut Vec<&'a FileDescriptorPair>,
+) {
+    if visible.iter().any(|d| d.descriptor_proto.name() == name) {
+        return;
+    }
+    // Missing weak imports are not in `deps`.
+    let dep = match deps.iter().find(|d| d.descriptor_proto.name() == name) {
+        Some(dep) => dep,
+        None => return,
+    };
+    visible.push(dep);
+    for &i in &dep.descriptor_proto.public_dependency {
+        add_visible_dep(&dep.descriptor_proto.dependency[i as usize], deps, visible);
+    }
+}
+
+/// Files which types `input` can reference by name: direct imports
+/// and files they re-export with `import public`, transitively.
+///
+/// Like `protoc`, types of other transitive dependencies are not visible.
+fn visible_deps<'a>(
+    input: &model::FileDescriptor,
+    deps: &'a [FileDescriptorPair],
+) -> Vec<&'a FileDescriptorPair> {
+    let mut visible = Vec::new();
+    for import in &input.imports {
+        add_visible_dep(import.path.to_str(), deps, &mut visible);
+    }
+    visible
+}
+
+/// Convert parsed file into descriptor.
+///
+/// `deps` must contain all the files `input` imports, including transitive imports.
 pub(crate) fn file_descriptor(
     name: &ProtoPath,
     input: &model::FileDescriptor,
     deps: &[FileDescriptorPair],
-) -> anyhow::Result<protobuf::descriptor::FileDescriptorProto> {
+    comments: Option<&Comments>,
+    strict: bool,
+) -> anyhow::Result<FileDescriptorOutput> {
+    if strict && !input.syntax_declared {
+        return with_loc(Loc::start(), Err(ConvertError::SyntaxNotSpecified.into()));
+    }
+
     let resolver = Resolver {
         current_file: &input,
         type_resolver: TypeResolver {
             current_file: &input,
-            deps,
+            deps: visible_deps(input, deps),
+            all_deps: deps,
+            used: RefCell::new(HashSet::new()),
         },
     };
 
     let mut output = protobuf::descriptor::FileDescriptorProto::new();
     output.set_name(fs_path_to_proto_path(name));
-    output.set_syntax(syntax(input.syntax));
+    // Like `protoc`, leave the field unset when file has no `syntax` statement,
+    // unless syntax other than proto2 is assumed for such files.
+    if input.syntax_declared || input.syntax != model::Syntax::Proto2 {
+        output.set_syntax(syntax(input.syntax));
+    }
+    if let model::Syntax::Editions(edition) = input.syntax {
+        output
+            .mut_unknown_fields()
+            .add_varint(FILE_DESCRIPTOR_PROTO_EDITION, edition.number() as u64);
+    }
 
     if input.package != ProtobufAbsPath::root() {
         output.set_package(input.package.to_root_rel().to_string());
@@ -629,14 +1206,20 @@ pub(crate) fn file_descriptor(
 
     let mut extensions = Vec::new();
     for e in &input.extensions {
-        let (ext, group_messages) = resolver.extension(&resolver.current_file.package, &e.t)?;
+        let (ext, group_messages) = with_loc(
+            e.loc,
+            resolver.extension(&resolver.current_file.package, &e.t),
+        )?;
         extensions.push(ext);
         messages.extend(group_messages.map(model::WithLoc::with_loc(e.loc)));
     }
     output.extension = extensions;
 
     for m in &input.messages {
-        let message = resolver.message(&resolver.current_file.package, &m.t)?;
+        let message = with_loc(
+            m.loc,
+            resolver.message(&resolver.current_file.package, &m.t),
+        )?;
         messages.push(model::WithLoc {
             t: message,
             loc: m.loc,
@@ -644,7 +1227,7 @@ pub(crate) fn file_descriptor(
     }
 
     for s in &input.services {
-        let service = resolver.service(&s.t)?;
+        let service = with_loc(s.loc, resolver.service(&s.t))?;
         services.push(model::WithLoc {
             t: service,
             loc: s.loc,
@@ -661,7 +1244,12 @@ pub(crate) fn file_descriptor(
     output.enum_type = input
         .enums
         .iter()
-        .map(|e| resolver.enumeration(&resolver.current_file.package, e))
+        .map(|e| {
+            with_loc(
+                e.loc,
+                resolver.enumeration(&resolver.current_file.package, e),
+            )
+        })
         .collect::<Result<_, _>>()?;
 
     output.service = services
@@ -680,9 +1268,927 @@ pub(crate) fn file_descriptor(
     let option_resolver = OptionResoler {
         resolver: &resolver,
         descriptor_without_options,
+        strict,
+        warnings: RefCell::new(Vec::new()),
+        unsupported: RefCell::new(Vec::new()),
+        option_loc: Cell::new(Loc::start()),
     };
 
-    option_resolver.file(&mut output)?;
+    let options = option_resolver.file(&mut output);
+    with_loc(option_resolver.option_loc.get(), options)?;
+
+    if let Some(comments) = comments {
+        output.source_code_info =
+            Some(source_code_info::source_code_info(input, &output, comments)).into();
+    }
+
+    let mut warnings = option_resolver.warnings.into_inner();
+    let used = resolver.type_resolver.used.borrow();
+    for import in unused_imports::unused_imports(input, deps, &used) {
+        warnings.push(format!(
+            "{}: import `{}` is unused",
+            import.loc, import.path
+        ));
+    }
+
+    Ok(FileDescriptorOutput {
+        descriptor: output,
+        warnings,
+        unsupported: option_resolver.unsupported.into_inner(),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use protobuf::descriptor::DescriptorProto;
+    use protobuf::descriptor::FileDescriptorProto;
+    use protobuf::descriptor::ServiceDescriptorProto;
+    use protobuf_support::lexer::loc::Loc;
+
+    use crate::proto_path::ProtoPath;
+    use crate::pure::parse_single;
+
+    fn convert_error(content: &str) -> String {
+        let err = parse_single(ProtoPath::new("a.proto").unwrap(), content, &[]).unwrap_err();
+        format!("{}", err)
+    }
 
-    Ok(output)
+    #[test]
+    fn field_numbers() {
+        assert_eq!(
+            "field `a` in message `.p.M` has number 0 out of range 1..=536870911",
+            convert_error("syntax = 'proto3'; package p; message M { int32 a = 0; }")
+        );
+        assert_eq!(
+            "field `a` in message `.M` has number 536870912 out of range 1..=536870911",
+            convert_error("syntax = 'proto3'; message M { int32 a = 536870912; }")
+        );
+        assert_eq!(
+            "field `a` in message `.M` has number 19500 reserved for protobuf implementation",
+            convert_error("syntax = 'proto3'; message M { int32 a = 19500; }")
+        );
+        assert_eq!(
+            "fields `a` and `b` in message `.M` have the same number 1",
+            convert_error("syntax = 'proto3'; message M { int32 a = 1; oneof o { int32 b = 1; } }")
+        );
+        assert_eq!(
+            "field `b` in message `.M` uses reserved number 3",
+            convert_error("syntax = 'proto3'; message M { reserved 2 to 4; int32 b = 3; }")
+        );
+        assert_eq!(
+            "field `b` in message `.M` uses reserved name",
+            convert_error("syntax = 'proto3'; message M { reserved 'b'; int32 b = 1; }")
+        );
+    }
+
+    #[test]
+    fn reserved() {
+        let file = parse_single(
+            ProtoPath::new("a.proto").unwrap(),
+            r#"syntax = "proto2";
+            message M {
+                reserved 2, 9 to 11, 100 to max;
+                reserved "foo";
+            }
+            enum E {
+                reserved -1, 5 to max;
+                reserved "BAR";
+                ZERO = 0;
+            }"#,
+            &[],
+        )
+        .unwrap();
+        let m = &file.message_type[0];
+        assert_eq!(
+            vec![(2, 3), (9, 12), (100, 536870912)],
+            m.reserved_range
+                .iter()
+                .map(|r| (r.start(), r.end()))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(vec!["foo"], m.reserved_name);
+        let e = &file.enum_type[0];
+        assert_eq!(
+            vec![(-1, -1), (5, i32::MAX)],
+            e.reserved_range
+                .iter()
+                .map(|r| (r.start(), r.end()))
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(vec!["BAR"], e.reserved_name);
+
+        assert_eq!(
+            "value `B` in enum `.E` uses reserved number 10",
+            convert_error("syntax = 'proto3'; enum E { reserved 9 to 11; A = 0; B = 10; }")
+        );
+        assert_eq!(
+            "value `B` in enum `.E` uses reserved name",
+            convert_error("syntax = 'proto3'; enum E { reserved 'B'; A = 0; B = 1; }")
+        );
+        assert_eq!(
+            "value `B` in enum `.p.M.E` uses reserved number 10",
+            convert_error(
+                "syntax = 'proto3'; package p; \
+                message M { enum E { reserved 10; A = 0; B = 10; } }"
+            )
+        );
+    }
+
+    #[test]
+    fn group() {
+        let file = parse_single(
+            ProtoPath::new("a.proto").unwrap(),
+            r#"syntax = "proto2";
+            message M {
+                optional string a = 1;
+                repeated group Item = 2 {
+                    optional int32 b = 3;
+                }
+            }"#,
+            &[],
+        )
+        .unwrap();
+        // As printed by `protoc --descriptor_set_out`.
+        let expected: DescriptorProto = protobuf::text_format::parse_from_str(
+            r#"
+            name: "M"
+            field { name: "a" number: 1 label: LABEL_OPTIONAL type: TYPE_STRING json_name: "a" }
+            field {
+                name: "item" number: 2 label: LABEL_REPEATED type: TYPE_GROUP
+                type_name: ".M.Item" json_name: "item"
+            }
+            nested_type {
+                name: "Item"
+                field { name: "b" number: 3 label: LABEL_OPTIONAL type: TYPE_INT32 json_name: "b" }
+            }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(expected, file.message_type[0]);
+    }
+
+    #[test]
+    fn default_values() {
+        let file = parse_single(
+            ProtoPath::new("a.proto").unwrap(),
+            r#"syntax = "proto2";
+            enum E { FOO = 1; BAR = 2; }
+            message M {
+                optional int32 i32 = 1 [default = -41];
+                optional uint32 u32 = 2 [default = 0xFFFFFFFF];
+                optional sint64 s64 = 3 [default = -9223372036854775808];
+                optional fixed64 f64 = 4 [default = 18446744073709551615];
+                optional float f = 5 [default = 51.5];
+                optional double d1 = 6 [default = 52e3];
+                optional double d2 = 7 [default = 1e30];
+                optional double d3 = 8 [default = 1.5e-5];
+                optional double d4 = 9 [default = 0.1];
+                optional float f1 = 10 [default = 7];
+                optional double pos_inf = 11 [default = inf];
+                optional double neg_inf = 12 [default = -inf];
+                optional float not_a_number = 13 [default = nan];
+                optional bool b = 14 [default = true];
+                optional string s = 15 [default = "a\"bé"];
+                optional bytes by = 16 [default = "w\0\001\xff'\n"];
+                optional E e = 17 [default = BAR];
+            }"#,
+            &[],
+        )
+        .unwrap();
+        // As printed by `protoc --descriptor_set_out`.
+        let expected = [
+            "-41",
+            "4294967295",
+            "-9223372036854775808",
+            "18446744073709551615",
+            "51.5",
+            "52000",
+            "1e+30",
+            "1.5e-05",
+            "0.1",
+            "7",
+            "inf",
+            "-inf",
+            "nan",
+            "true",
+            "a\"b\u{e9}",
+            "w\\000\\001\\377\\'\\n",
+            "BAR",
+        ];
+        let actual: Vec<&str> = file.message_type[0]
+            .field
+            .iter()
+            .map(|f| f.default_value())
+            .collect();
+        assert_eq!(expected, *actual);
+    }
+
+    #[test]
+    fn default_value_errors() {
+        assert_eq!(
+            "default value `2147483648` of field `a` in message `.M` is not a valid `int32`",
+            convert_error(
+                "syntax = 'proto2'; message M { optional int32 a = 1 [default = 2147483648]; }"
+            )
+        );
+        assert_eq!(
+            "default value `-1` of field `a` in message `.M` is not a valid `uint64`",
+            convert_error("syntax = 'proto2'; message M { optional uint64 a = 1 [default = -1]; }")
+        );
+        assert_eq!(
+            "default value `1.5` of field `a` in message `.M` is not a valid `int32`",
+            convert_error("syntax = 'proto2'; message M { optional int32 a = 1 [default = 1.5]; }")
+        );
+        assert_eq!(
+            "default value `1` of field `a` in message `.M` is not a valid `bool`",
+            convert_error("syntax = 'proto2'; message M { optional bool a = 1 [default = 1]; }")
+        );
+        assert_eq!(
+            "default value `BAZ` of field `a` in message `.M` is not a valid `.E`",
+            convert_error(
+                "syntax = 'proto2'; enum E { FOO = 1; } message M { optional E a = 1 [default = BAZ]; }"
+            )
+        );
+        assert_eq!(
+            "field `a` in message `.M` is a message, which cannot have default value",
+            convert_error("syntax = 'proto2'; message M { optional M a = 1 [default = 1]; }")
+        );
+    }
+
+    #[test]
+    fn map_entry() {
+        let file = parse_single(
+            ProtoPath::new("a.proto").unwrap(),
+            r#"syntax = "proto3";
+            package p;
+            message M {
+                map<string, M> sub_items = 1 [deprecated = true];
+            }"#,
+            &[],
+        )
+        .unwrap();
+        // As printed by `protoc --descriptor_set_out`.
+        let expected: DescriptorProto = protobuf::text_format::parse_from_str(
+            r#"
+            name: "M"
+            field {
+                name: "sub_items" number: 1 label: LABEL_REPEATED type: TYPE_MESSAGE
+                type_name: ".p.M.SubItemsEntry" json_name: "subItems"
+                options { deprecated: true }
+            }
+            nested_type {
+                name: "SubItemsEntry"
+                field { name: "key" number: 1 label: LABEL_OPTIONAL type: TYPE_STRING json_name: "key" }
+                field {
+                    name: "value" number: 2 label: LABEL_OPTIONAL type: TYPE_MESSAGE
+                    type_name: ".p.M" json_name: "value"
+                }
+                options { map_entry: true }
+            }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(expected, file.message_type[0]);
+    }
+
+    #[test]
+    fn extensions() {
+        let file = parse_single(
+            ProtoPath::new("a.proto").unwrap(),
+            r#"syntax = "proto2";
+            package p;
+            message M {
+                extensions 100 to 199, 1000 to max;
+                extend M { optional string nested = 150; }
+            }
+            extend M { repeated int32 top = 1000; }"#,
+            &[],
+        )
+        .unwrap();
+        // As printed by `protoc --descriptor_set_out`.
+        let expected: FileDescriptorProto = protobuf::text_format::parse_from_str(
+            r#"
+            name: "a.proto"
+            package: "p"
+            message_type {
+                name: "M"
+                extension {
+                    name: "nested" number: 150 label: LABEL_OPTIONAL type: TYPE_STRING
+                    extendee: ".p.M" json_name: "nested"
+                }
+                extension_range { start: 100 end: 200 }
+                extension_range { start: 1000 end: 536870912 }
+            }
+            extension {
+                name: "top" number: 1000 label: LABEL_REPEATED type: TYPE_INT32
+                extendee: ".p.M" json_name: "top"
+            }
+            syntax: "proto2"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(expected, file);
+
+        // Extension ranges of dependencies are restored from descriptors.
+        let b = parse_single(
+            ProtoPath::new("b.proto").unwrap(),
+            "syntax = 'proto2'; import 'a.proto'; extend p.M { optional bool b = 199; }",
+            std::slice::from_ref(&file),
+        )
+        .unwrap();
+        assert_eq!(".p.M", b.extension[0].extendee());
+        let err = parse_single(
+            ProtoPath::new("b.proto").unwrap(),
+            "syntax = 'proto2'; import 'a.proto'; extend p.M { optional bool b = 200; }",
+            &[file],
+        )
+        .unwrap_err();
+        assert_eq!(
+            "extension `b` has number 200, \
+            which is not declared as an extension number in message `.p.M`",
+            format!("{}", err)
+        );
+    }
+
+    #[test]
+    fn extension_errors() {
+        assert_eq!(
+            "extension `a` has number 1, \
+            which is not declared as an extension number in message `.M`",
+            convert_error("syntax = 'proto2'; message M {} extend M { optional int32 a = 1; }")
+        );
+        assert_eq!(
+            "extension `a` has number 10, \
+            which is not declared as an extension number in message `.M`",
+            convert_error(
+                "syntax = 'proto2'; message M { extensions 1 to 9; } \
+                extend M { optional int32 a = 10; }"
+            )
+        );
+        assert_eq!(
+            "expecting a message for name .E",
+            convert_error(
+                "syntax = 'proto2'; enum E { A = 0; } extend E { optional int32 a = 1; }"
+            )
+        );
+    }
+
+    #[test]
+    fn json_names() {
+        let file = parse_single(
+            ProtoPath::new("a.proto").unwrap(),
+            "syntax = 'proto3'; message M { \
+                int32 foo_bar_2 = 1; \
+                int32 __x__ = 2; \
+                int32 foo_3bar = 3; \
+                int32 FOO_BAR = 4; \
+                string s = 5 [json_name = 'custom']; \
+            }",
+            &[],
+        )
+        .unwrap();
+        let json_names: Vec<&str> = file.message_type[0]
+            .field
+            .iter()
+            .map(|f| f.json_name())
+            .collect();
+        // As printed by `protoc --descriptor_set_out`.
+        assert_eq!(
+            vec!["fooBar2", "X", "foo3bar", "FOOBAR", "custom"],
+            json_names
+        );
+    }
+
+    #[test]
+    fn json_name_errors() {
+        assert_eq!(
+            "fields `foo_bar` and `fooBar` in message `.M` have conflicting JSON names",
+            convert_error("syntax = 'proto3'; message M { int32 foo_bar = 1; int32 fooBar = 2; }")
+        );
+        assert_eq!(
+            "fields `foo_bar` and `fooBar` in message `.M` have conflicting JSON names",
+            convert_error("edition = '2023'; message M { int32 foo_bar = 1; int32 fooBar = 2; }")
+        );
+        assert_eq!(
+            "fields `a` and `b` in message `.M` have conflicting JSON names",
+            convert_error(
+                "syntax = 'proto3'; message M { int32 a = 1; int32 b = 2 [json_name = 'A']; }"
+            )
+        );
+        assert_eq!(
+            "fields `a` and `b` in message `.M` have conflicting JSON names",
+            convert_error(
+                "syntax = 'proto2'; message M { \
+                    optional int32 a = 1 [json_name = 'x']; \
+                    optional int32 b = 2 [json_name = 'x']; \
+                }"
+            )
+        );
+        assert_eq!(
+            "extension `a` sets `json_name`, which is not allowed for extensions",
+            convert_error(
+                "syntax = 'proto2'; message M { extensions 1 to 9; } \
+                extend M { optional int32 a = 1 [json_name = 'x']; }"
+            )
+        );
+    }
+
+    #[test]
+    fn extension_ranges() {
+        let file = parse_single(
+            ProtoPath::new("a.proto").unwrap(),
+            "syntax = 'proto2'; \
+            message M { optional int32 f = 1; extensions 2 to 9, 10, 11 to max; } \
+            extend M { \
+                optional int32 first = 2; \
+                optional int32 single = 10; \
+                optional int32 after = 11; \
+                optional int32 last = 536870911; \
+            }",
+            &[],
+        )
+        .unwrap();
+        let numbers: Vec<i32> = file.extension.iter().map(|e| e.number()).collect();
+        assert_eq!(vec![2, 10, 11, 536870911], numbers);
+
+        assert_eq!(
+            "extension ranges `10 to 20` and `20 to 536870911` in message `.M` overlap",
+            convert_error("syntax = 'proto2'; message M { extensions 10 to 20, 20 to max; }")
+        );
+        assert_eq!(
+            "extension ranges `5 to 15` and `7` in message `.M` overlap",
+            convert_error("syntax = 'proto2'; message M { extensions 5 to 15; extensions 7; }")
+        );
+        assert_eq!(
+            "field `a` in message `.M` uses number 100 declared as an extension number",
+            convert_error(
+                "syntax = 'proto2'; message M { optional int32 a = 100; extensions 100 to 199; }"
+            )
+        );
+    }
+
+    #[test]
+    fn max_ranges() {
+        let file = parse_single(
+            ProtoPath::new("a.proto").unwrap(),
+            r#"syntax = "proto2";
+            message M { reserved 5, 100 to max; extensions 10 to 99; }
+            message S {
+                option message_set_wire_format = true;
+                extensions 4 to max;
+            }
+            extend S { optional M big = 1000000000; }
+            enum E { A = 0; reserved 5 to max; }"#,
+            &[],
+        )
+        .unwrap();
+        // As printed by `protoc --descriptor_set_out`.
+        let expected: FileDescriptorProto = protobuf::text_format::parse_from_str(
+            r#"
+            name: "a.proto"
+            message_type {
+                name: "M"
+                extension_range { start: 10 end: 100 }
+                reserved_range { start: 5 end: 6 }
+                reserved_range { start: 100 end: 536870912 }
+            }
+            message_type {
+                name: "S"
+                extension_range { start: 4 end: 2147483647 }
+                options { message_set_wire_format: true }
+            }
+            enum_type {
+                name: "E"
+                value { name: "A" number: 0 }
+                reserved_range { start: 5 end: 2147483647 }
+            }
+            extension {
+                name: "big" number: 1000000000 label: LABEL_OPTIONAL type: TYPE_MESSAGE
+                type_name: ".M" extendee: ".S" json_name: "big"
+            }
+            syntax: "proto2"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(expected, file);
+    }
+
+    #[test]
+    fn service() {
+        let file = parse_single(
+            ProtoPath::new("a.proto").unwrap(),
+            r#"syntax = "proto3";
+            package p;
+            message Req {}
+            message Resp {}
+            service S {
+                option deprecated = true;
+                rpc Unary(Req) returns (.p.Resp);
+                rpc ClientStream(stream Req) returns (Resp) {}
+                rpc ServerStream(Req) returns (stream Resp) {
+                    option deprecated = true;
+                    option idempotency_level = NO_SIDE_EFFECTS;
+                }
+                rpc Bidi(stream Req) returns (stream Resp);
+            }"#,
+            &[],
+        )
+        .unwrap();
+        // As printed by `protoc --descriptor_set_out`.
+        let expected: ServiceDescriptorProto = protobuf::text_format::parse_from_str(
+            r#"
+            name: "S"
+            method { name: "Unary" input_type: ".p.Req" output_type: ".p.Resp" }
+            method {
+                name: "ClientStream" input_type: ".p.Req" output_type: ".p.Resp"
+                client_streaming: true
+            }
+            method {
+                name: "ServerStream" input_type: ".p.Req" output_type: ".p.Resp"
+                options { deprecated: true idempotency_level: NO_SIDE_EFFECTS }
+                server_streaming: true
+            }
+            method {
+                name: "Bidi" input_type: ".p.Req" output_type: ".p.Resp"
+                client_streaming: true server_streaming: true
+            }
+            options { deprecated: true }
+            "#,
+        )
+        .unwrap();
+        assert_eq!(expected, file.service[0]);
+
+        assert_eq!(
+            "expecting a message for name .E",
+            convert_error(
+                "syntax = 'proto3'; enum E { A = 0; } message M {} \
+                service S { rpc R(E) returns (M); }"
+            )
+        );
+    }
+
+    #[test]
+    fn standard_options() {
+        let file = parse_single(
+            ProtoPath::new("a.proto").unwrap(),
+            r#"syntax = "proto2";
+            option java_package = "com.example.foo";
+            option java_outer_classname = "FooProtos";
+            option java_multiple_files = true;
+            option java_string_check_utf8 = true;
+            option optimize_for = CODE_SIZE;
+            option go_package = "example.com/foo;foo";
+            option cc_generic_services = true;
+            option java_generic_services = true;
+            option py_generic_services = true;
+            option php_generic_services = true;
+            option cc_enable_arenas = false;
+            option objc_class_prefix = "FOO";
+            option csharp_namespace = "Example.Foo";
+            option swift_prefix = "Foo";
+            option php_class_prefix = "Foo";
+            option php_namespace = "Example\\Foo";
+            option php_metadata_namespace = "Example\\Foo\\Meta";
+            option ruby_package = "Example::Foo";
+            message M {
+                option message_set_wire_format = false;
+                option no_standard_descriptor_accessor = true;
+                optional string s = 1 [ctype = CORD, lazy = true, jstype = JS_STRING];
+                repeated int32 r = 2 [packed = true, weak = false];
+            }"#,
+            &[],
+        )
+        .unwrap();
+        // As printed by `protoc --descriptor_set_out`.
+        let expected: FileDescriptorProto = protobuf::text_format::parse_from_str(
+            r#"
+            name: "a.proto"
+            message_type {
+                name: "M"
+                field {
+                    name: "s" number: 1 label: LABEL_OPTIONAL type: TYPE_STRING json_name: "s"
+                    options { ctype: CORD lazy: true jstype: JS_STRING }
+                }
+                field {
+                    name: "r" number: 2 label: LABEL_REPEATED type: TYPE_INT32 json_name: "r"
+                    options { packed: true weak: false }
+                }
+                options { message_set_wire_format: false no_standard_descriptor_accessor: true }
+            }
+            options {
+                java_package: "com.example.foo"
+                java_outer_classname: "FooProtos"
+                java_multiple_files: true
+                java_string_check_utf8: true
+                optimize_for: CODE_SIZE
+                go_package: "example.com/foo;foo"
+                cc_generic_services: true
+                java_generic_services: true
+                py_generic_services: true
+                php_generic_services: true
+                cc_enable_arenas: false
+                objc_class_prefix: "FOO"
+                csharp_namespace: "Example.Foo"
+                swift_prefix: "Foo"
+                php_class_prefix: "Foo"
+                php_namespace: "Example\\Foo"
+                php_metadata_namespace: "Example\\Foo\\Meta"
+                ruby_package: "Example::Foo"
+            }
+            syntax: "proto2"
+            "#,
+        )
+        .unwrap();
+        assert_eq!(expected, file);
+    }
+
+    #[test]
+    fn map_key_type() {
+        for key in ["float", "double", "bytes"] {
+            assert_eq!(
+                format!(
+                    "map field `m` in message `.M` has key type `{}`, \
+                    but key must be an integral, string or bool type",
+                    key
+                ),
+                convert_error(&format!(
+                    "syntax = 'proto3'; message M {{ map<{}, int32> m = 1; }}",
+                    key
+                ))
+            );
+        }
+        assert_eq!(
+            "map field `m` in message `.M` has key type `.E`, \
+            but key must be an integral, string or bool type",
+            convert_error(
+                "syntax = 'proto3'; enum E { A = 0; } message M { map<E, int32> m = 1; }"
+            )
+        );
+        assert_eq!(
+            "map field `m` in message `.M` has key type `.K`, \
+            but key must be an integral, string or bool type",
+            convert_error("syntax = 'proto3'; message K {} message M { map<K, int32> m = 1; }")
+        );
+        for key in [
+            "int32", "int64", "uint32", "uint64", "sint32", "sint64", "fixed32", "fixed64",
+            "sfixed32", "sfixed64", "bool", "string",
+        ] {
+            let content = format!(
+                "syntax = 'proto3'; message M {{ map<{}, bytes> m = 1; }}",
+                key
+            );
+            parse_single(ProtoPath::new("a.proto").unwrap(), &content, &[]).unwrap();
+        }
+    }
+
+    #[test]
+    fn enum_value_numbers() {
+        assert_eq!(
+            "first value `A` of enum `.E` has number 1, but it must be zero",
+            convert_error("syntax = 'proto3'; enum E { A = 1; B = 0; }")
+        );
+        assert_eq!(
+            "first value `A` of enum `.E` has number 1, but it must be zero",
+            convert_error("edition = '2023'; enum E { A = 1; B = 0; }")
+        );
+        assert_eq!(
+            "values `A` and `B` in enum `.E` have the same number 0, but `allow_alias` is not set",
+            convert_error("syntax = 'proto3'; enum E { A = 0; B = 0; }")
+        );
+        parse_single(
+            ProtoPath::new("a.proto").unwrap(),
+            "syntax = 'proto2'; enum E { A = 1; B = 0; }",
+            &[],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn enum_allow_alias() {
+        let file = parse_single(
+            ProtoPath::new("a.proto").unwrap(),
+            "syntax = 'proto3'; enum E { option allow_alias = true; A = 0; B = 0; }",
+            &[],
+        )
+        .unwrap();
+        assert_eq!(Some(true), file.enum_type[0].options.allow_alias);
+        assert_eq!(
+            "values `A` and `B` in enum `.E` have the same number 0, but `allow_alias` is not set",
+            convert_error(
+                "syntax = 'proto3'; enum E { option allow_alias = false; A = 0; B = 0; }"
+            )
+        );
+        assert_eq!(
+            "enum `.E` sets `allow_alias`, but no values share a number",
+            convert_error("syntax = 'proto3'; enum E { option allow_alias = true; A = 0; B = 1; }")
+        );
+    }
+
+    #[test]
+    fn implicit_syntax() {
+        let name = ProtoPath::new("a.proto").unwrap();
+        let implicit = parse_single(name, "message M { optional int32 a = 1; }", &[]).unwrap();
+        assert!(!implicit.has_syntax());
+        assert_eq!(
+            protobuf::descriptor::field_descriptor_proto::Label::LABEL_OPTIONAL,
+            implicit.message_type[0].field[0].label()
+        );
+        let explicit = parse_single(name, "syntax = 'proto2'; message M {}", &[]).unwrap();
+        assert_eq!("proto2", explicit.syntax());
+
+        let model = crate::pure::model::FileDescriptor::parse("message M {}").unwrap();
+        let output = |strict| super::file_descriptor(name, &model, &[], None, strict);
+        assert!(output(false).unwrap().warnings.is_empty());
+        let err = output(true).err().expect("error");
+        assert_eq!(
+            Loc::start(),
+            err.downcast_ref::<super::ConvertErrorWithLoc>()
+                .unwrap()
+                .loc
+        );
+        assert_eq!(
+            "no syntax specified, which is not allowed in strict mode",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn oneof() {
+        let descriptor = protobuf::descriptor::file_descriptor().proto().clone();
+        let file = parse_single(
+            ProtoPath::new("a.proto").unwrap(),
+            r#"syntax = "proto2";
+            import "google/protobuf/descriptor.proto";
+            extend google.protobuf.OneofOptions { optional int32 weight = 50000; }
+            message N {}
+            message M {
+                optional int32 a = 1;
+                oneof o {
+                    option (weight) = 7;
+                    N n = 2 [deprecated = true];
+                    string s = 3;
+                }
+            }"#,
+            &[descriptor],
+        )
+        .unwrap();
+        let m = &file.message_type[1];
+        assert_eq!(1, m.oneof_decl.len());
+        assert_eq!("o", m.oneof_decl[0].name());
+        assert!(m.oneof_decl[0].options.is_some());
+        assert!(!m.field[0].has_oneof_index());
+        assert_eq!(0, m.field[1].oneof_index());
+        assert_eq!(".N", m.field[1].type_name());
+        assert!(m.field[1].options.deprecated());
+        assert_eq!(0, m.field[2].oneof_index());
+    }
+
+    #[test]
+    fn oneof_errors() {
+        assert_eq!(
+            "oneof `o` in message `.M` must have at least one field",
+            convert_error("syntax = 'proto3'; message M { oneof o {} }")
+        );
+        for (member, error) in [
+            (
+                "repeated int32 a = 1;",
+                "label `repeated` is not allowed in oneof",
+            ),
+            (
+                "required int32 a = 1;",
+                "label `required` is not allowed in oneof",
+            ),
+            (
+                "map<int32, int32> a = 1;",
+                "map field is not allowed in oneof",
+            ),
+        ] {
+            let content = format!(
+                "syntax = 'proto2'; message M {{ oneof o {{ {} }} }}",
+                member
+            );
+            let err = convert_error(&content);
+            assert!(err.contains(error), "{}", err);
+        }
+    }
+
+    #[test]
+    fn relative_names() {
+        let dep = parse_single(
+            ProtoPath::new("dep.proto").unwrap(),
+            "syntax = 'proto3'; package foo.bar; message Baz {} service S {}",
+            &[],
+        )
+        .unwrap();
+        let file = parse_single(
+            ProtoPath::new("a.proto").unwrap(),
+            r#"syntax = "proto3";
+            package foo.qux;
+            import "dep.proto";
+            message Outer {
+                message Inner {}
+                message Child {
+                    Inner inner = 1;
+                    Outer.Inner outer_inner = 2;
+                    bar.Baz partial = 3;
+                    .foo.bar.Baz absolute = 4;
+                    Sibling.X sibling = 5;
+                }
+            }
+            message Sibling { message X {} }"#,
+            std::slice::from_ref(&dep),
+        )
+        .unwrap();
+        let child = &file.message_type[0].nested_type[1];
+        let type_names: Vec<&str> = child.field.iter().map(|f| f.type_name()).collect();
+        assert_eq!(
+            vec![
+                ".foo.qux.Outer.Inner",
+                ".foo.qux.Outer.Inner",
+                ".foo.bar.Baz",
+                ".foo.bar.Baz",
+                ".foo.qux.Sibling.X",
+            ],
+            type_names
+        );
+
+        // Like `protoc`, once the first component of a name is found,
+        // outer scopes are not searched for the rest.
+        let err = parse_single(
+            ProtoPath::new("a.proto").unwrap(),
+            r#"syntax = "proto3";
+            package foo.qux;
+            import "dep.proto";
+            message bar {}
+            message M { bar.Baz b = 1; }"#,
+            std::slice::from_ref(&dep),
+        )
+        .unwrap_err();
+        assert!(
+            format!("{}", err).starts_with("`bar.Baz` is resolved to `.foo.qux.bar.Baz`"),
+            "{}",
+            err
+        );
+        let err = parse_single(
+            ProtoPath::new("a.proto").unwrap(),
+            "syntax = 'proto3'; package foo.bar.bar; import 'dep.proto'; message M { bar.Baz b = 1; }",
+            std::slice::from_ref(&dep),
+        )
+        .unwrap_err();
+        assert!(
+            format!("{}", err).starts_with("`bar.Baz` is resolved to `.foo.bar.bar.Baz`"),
+            "{}",
+            err
+        );
+        let err = parse_single(
+            ProtoPath::new("a.proto").unwrap(),
+            "syntax = 'proto3'; package foo; import 'dep.proto'; message M { bar.S.X x = 1; }",
+            &[dep],
+        )
+        .unwrap_err();
+        assert!(
+            format!("{}", err).starts_with("`bar.S.X` is resolved to `.foo.bar.S.X`"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn packed() {
+        let file = parse_single(
+            ProtoPath::new("a.proto").unwrap(),
+            r#"syntax = "proto3";
+            enum E { A = 0; }
+            message M {
+                repeated int32 a = 1;
+                repeated E b = 2 [packed = false];
+                repeated double c = 3 [packed = true];
+                repeated string d = 4;
+            }"#,
+            &[],
+        )
+        .unwrap();
+        // As printed by `protoc --descriptor_set_out`:
+        // proto3 fields are packed by default, but `packed` is not set.
+        let fields = &file.message_type[0].field;
+        assert_eq!(None, fields[0].options.as_ref().and_then(|o| o.packed));
+        assert_eq!(Some(false), fields[1].options.packed);
+        assert_eq!(Some(true), fields[2].options.packed);
+        assert_eq!(None, fields[3].options.as_ref().and_then(|o| o.packed));
+
+        assert_eq!(
+            "field `a`: [packed] can only be specified for repeated primitive fields",
+            convert_error("syntax = 'proto2'; message M { optional int32 a = 1 [packed = true]; }")
+        );
+        assert_eq!(
+            "field `s`: [packed] can only be specified for repeated primitive fields",
+            convert_error(
+                "syntax = 'proto3'; message M { repeated string s = 1 [packed = false]; }"
+            )
+        );
+        assert_eq!(
+            "field `m`: [packed] can only be specified for repeated primitive fields",
+            convert_error("syntax = 'proto2'; message M { repeated M m = 1 [packed = true]; }")
+        );
+    }
 }
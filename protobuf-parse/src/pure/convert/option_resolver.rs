@@ -1,4 +1,9 @@
+use std::cell::Cell;
+use std::cell::RefCell;
+
 use anyhow::Context;
+use protobuf::descriptor::field_descriptor_proto::Label;
+use protobuf::descriptor::field_descriptor_proto::Type;
 use protobuf::descriptor::DescriptorProto;
 use protobuf::descriptor::EnumDescriptorProto;
 use protobuf::descriptor::EnumValueDescriptorProto;
@@ -13,6 +18,7 @@ use protobuf::reflect::MessageDescriptor;
 use protobuf::MessageFull;
 use protobuf::UnknownFields;
 use protobuf::UnknownValue;
+use protobuf_support::lexer::loc::Loc;
 use protobuf_support::lexer::str_lit::StrLitDecodeError;
 
 use crate::model;
@@ -26,6 +32,7 @@ use crate::model::WithLoc;
 use crate::protobuf_path::ProtobufPath;
 use crate::pure::convert::Resolver;
 use crate::pure::convert::TypeResolved;
+use crate::FileDescriptorPair;
 use crate::ProtobufAbsPath;
 use crate::ProtobufAbsPathRef;
 use crate::ProtobufIdent;
@@ -41,6 +48,8 @@ enum OptionResolverError {
     ExtensionIsNotMessage(String),
     #[error("unknown field name: {0}")]
     UnknownFieldName(String),
+    #[error("non-repeated field `{0}` is specified more than once")]
+    NonRepeatedFieldSpecifiedMoreThanOnce(String),
     #[error("wrong extension type: option {0} extendee {1} expected extendee {2}")]
     WrongExtensionType(String, String, String),
     #[error("extension not found: {0}")]
@@ -63,6 +72,10 @@ enum OptionResolverError {
     MessageNotFound(ProtobufAbsPath),
     #[error("message not found by name {0}")]
     MessageFoundMoreThanOnce(ProtobufAbsPath),
+    #[error("{0}")]
+    Unsupported(String),
+    #[error("field `{0}`: [packed] can only be specified for repeated primitive fields")]
+    PackedOnNonPackableField(String),
 }
 
 #[derive(Clone)]
@@ -193,7 +206,7 @@ pub(crate) trait ProtobufOptions {
 impl<'a> ProtobufOptions for &'a [model::ProtobufOption] {
     fn by_name(&self, name: &str) -> Option<&model::ProtobufConstant> {
         let option_name = ProtobufOptionName::simple(name);
-        for model::ProtobufOption { name, value } in *self {
+        for model::ProtobufOption { name, value, .. } in *self {
             if name == &option_name {
                 return Some(&value);
             }
@@ -202,28 +215,53 @@ impl<'a> ProtobufOptions for &'a [model::ProtobufOption] {
     }
 }
 
+fn is_packable(t: Type) -> bool {
+    !matches!(
+        t,
+        Type::TYPE_STRING | Type::TYPE_BYTES | Type::TYPE_MESSAGE | Type::TYPE_GROUP
+    )
+}
+
 pub(crate) struct OptionResoler<'a> {
     pub(crate) resolver: &'a Resolver<'a>,
     pub(crate) descriptor_without_options: FileDescriptor,
+    /// Fail on options which cannot be fully represented instead of dropping them.
+    pub(crate) strict: bool,
+    /// Options which were dropped in non-strict mode.
+    pub(crate) warnings: RefCell<Vec<String>>,
+    /// Constructs which cannot be fully represented, with their locations.
+    pub(crate) unsupported: RefCell<Vec<(Loc, String)>>,
+    /// Location of option currently being resolved.
+    pub(crate) option_loc: Cell<Loc>,
 }
 
 impl<'a> OptionResoler<'a> {
-    fn all_files(&self) -> Vec<FileDescriptor> {
+    /// Report a construct which is dropped from the output.
+    fn unsupported(&self, what: String) -> anyhow::Result<()> {
+        let message = format!("{}: {} is not supported", self.option_loc.get(), what);
+        if self.strict {
+            return Err(OptionResolverError::Unsupported(message).into());
+        }
+        self.warnings.borrow_mut().push(message);
+        self.unsupported
+            .borrow_mut()
+            .push((self.option_loc.get(), what));
+        Ok(())
+    }
+
+    /// Descriptors of current file and given dependencies.
+    fn files<'b>(
+        &self,
+        deps: impl IntoIterator<Item = &'b FileDescriptorPair>,
+    ) -> Vec<FileDescriptor> {
         let mut files = Vec::new();
         files.push(self.descriptor_without_options.clone());
-        files.extend(
-            self.resolver
-                .type_resolver
-                .deps
-                .iter()
-                .map(|p| p.descriptor.clone()),
-        );
+        files.extend(deps.into_iter().map(|p| p.descriptor.clone()));
         files
     }
 
-    fn root_scope(&self) -> LookupScopeUnion2 {
-        let (scopes, partial_scopes) = self
-            .all_files()
+    fn root_scope(files: Vec<FileDescriptor>) -> LookupScopeUnion2 {
+        let (scopes, partial_scopes) = files
             .into_iter()
             .partition::<Vec<_>, _>(|f| ProtobufAbsPath::package_from_file_descriptor(f).is_root());
         LookupScopeUnion2 {
@@ -233,16 +271,28 @@ impl<'a> OptionResoler<'a> {
         }
     }
 
+    /// Look up symbol by name in current file and files visible from it.
     fn lookup(&self, path: &ProtobufAbsPath) -> LookupScopeUnion2 {
-        self.root_scope().lookup(&path.to_root_rel())
+        let files = self.files(self.resolver.type_resolver.deps.iter().copied());
+        Self::root_scope(files).lookup(&path.to_root_rel())
     }
 
+    /// Find message by full name from an already resolved field type,
+    /// which may be defined in any transitive dependency.
     fn find_message_by_abs_name(
         &self,
         path: &ProtobufAbsPath,
     ) -> anyhow::Result<MessageDescriptor> {
-        let scope = self.lookup(path);
-        scope.as_message()
+        let files = self.files(self.resolver.type_resolver.all_deps);
+        let message = Self::root_scope(files)
+            .lookup(&path.to_root_rel())
+            .as_message()?;
+        self.resolver
+            .type_resolver
+            .used
+            .borrow_mut()
+            .insert(path.clone());
+        Ok(message)
     }
 
     fn scope_resolved_candidates_rel(
@@ -281,6 +331,8 @@ impl<'a> OptionResoler<'a> {
 
         for ext in scope.extensions() {
             if ext.name() == extension.get() {
+                path.push_simple(&extension);
+                self.resolver.type_resolver.used.borrow_mut().insert(path);
                 return Ok(Some(ext.clone()));
             }
         }
@@ -374,7 +426,7 @@ impl<'a> OptionResoler<'a> {
                     }
                     TypeResolved::Group(..) => {
                         // TODO: implement
-                        Ok(())
+                        self.unsupported(format!("option `{}` of group type", option_name))
                     }
                     _ => Err(OptionResolverError::ExtensionIsNotMessage(format!(
                         "scope: {}, option name: {}",
@@ -510,22 +562,42 @@ impl<'a> OptionResoler<'a> {
                                     )
                                 }
                             };
-                            let u = self
-                                .option_value_field_to_unknown_value(
-                                    ma,
-                                    v,
-                                    n,
-                                    &f.typ,
-                                    option_name_for_diag,
-                                )
-                                .map_err(OptionResolverError::OtherError)?;
-                            unknown_fields.add_value(f.number as u32, u);
+                            let repeated = f.rule == Some(model::Rule::Repeated)
+                                || matches!(f.typ, model::FieldType::Map(..));
+                            if !repeated && v.as_repeated().len() > 1 {
+                                return Err(
+                                    OptionResolverError::NonRepeatedFieldSpecifiedMoreThanOnce(
+                                        n.clone(),
+                                    )
+                                    .into(),
+                                );
+                            }
+                            for v in v.as_repeated() {
+                                let u = self
+                                    .option_value_field_to_unknown_value(
+                                        ma,
+                                        v,
+                                        n,
+                                        &f.typ,
+                                        option_name_for_diag,
+                                    )
+                                    .map_err(OptionResolverError::OtherError)?;
+                                unknown_fields.add_value(f.number as u32, u);
+                            }
                         }
                         ProtobufConstantMessageFieldName::Extension(..) => {
                             // TODO: implement extension fields in constants
+                            self.unsupported(format!(
+                                "extension field `{}` in value of option `{}`",
+                                n, option_name_for_diag
+                            ))?;
                         }
                         ProtobufConstantMessageFieldName::AnyTypeUrl(..) => {
                             // TODO: implement any type url in constants
+                            self.unsupported(format!(
+                                "`Any` type URL `{}` in value of option `{}`",
+                                n, option_name_for_diag
+                            ))?;
                         }
                     }
                 }
@@ -635,6 +707,8 @@ impl<'a> OptionResoler<'a> {
                     option_name_for_diag,
                 );
             }
+            // Lists are only allowed for fields of message constants.
+            model::ProtobufConstant::Repeated(..) => {}
         };
 
         Err(match field_type {
@@ -712,6 +786,7 @@ impl<'a> OptionResoler<'a> {
     where
         M: MessageFull,
     {
+        self.option_loc.set(option.loc);
         match &option.name {
             ProtobufOptionName::Builtin(simple) => {
                 self.custom_option_builtin(scope, options, simple, &option.value)
@@ -881,6 +956,33 @@ impl<'a> OptionResoler<'a> {
         field_model: &model::Field,
     ) -> anyhow::Result<()> {
         field_proto.options = self.field_options(scope, &field_model.options)?.into();
+
+        // Like `protoc`, reject `packed` on fields which are never packed.
+        if field_proto.options.has_packed()
+            && (field_proto.label() != Label::LABEL_REPEATED || !is_packable(field_proto.type_()))
+        {
+            if let Some(option) = field_model
+                .options
+                .iter()
+                .find(|o| o.name == ProtobufOptionName::simple("packed"))
+            {
+                self.option_loc.set(option.loc);
+            }
+            return Err(
+                OptionResolverError::PackedOnNonPackableField(field_model.name.clone()).into(),
+            );
+        }
+
+        // Edition 2023 defaults to `features.repeated_field_encoding = PACKED`.
+        if let model::Syntax::Editions(..) = self.resolver.current_file.syntax {
+            if field_proto.label() == Label::LABEL_REPEATED
+                && is_packable(field_proto.type_())
+                && !field_proto.options.has_packed()
+            {
+                field_proto.options.mut_or_insert_default().set_packed(true);
+            }
+        }
+
         Ok(())
     }
 
@@ -1004,3 +1106,159 @@ impl<'a> OptionResoler<'a> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use protobuf::UnknownValueRef;
+
+    use crate::Parser;
+
+    #[test]
+    fn custom_options() {
+        let dir = tempfile::tempdir().unwrap();
+        let opts_proto = dir.path().join("opts.proto");
+        let a_proto = dir.path().join("a.proto");
+        fs::write(
+            &opts_proto,
+            r#"syntax = "proto2";
+            package my;
+            import "google/protobuf/descriptor.proto";
+            message Limits { optional int32 min = 1; optional int32 max = 2; }
+            extend google.protobuf.FileOptions { optional string file_tag = 50001; }
+            extend google.protobuf.MessageOptions { optional Limits limits = 50002; }
+            extend google.protobuf.FieldOptions { optional bool secret = 50003; }"#,
+        )
+        .unwrap();
+        fs::write(
+            &a_proto,
+            r#"syntax = "proto3";
+            import "opts.proto";
+            option (my.file_tag) = "fruit";
+            message Apple {
+                option (my.limits) = { min: 1 max: 10 };
+                string name = 1 [(my.secret) = true];
+            }"#,
+        )
+        .unwrap();
+
+        let parsed = Parser::new()
+            .pure()
+            .include(dir.path())
+            .input(&a_proto)
+            .parse_and_typecheck()
+            .unwrap();
+
+        let a = parsed
+            .file_descriptors
+            .iter()
+            .find(|f| f.name() == "a.proto")
+            .unwrap();
+        assert_eq!(
+            UnknownValueRef::LengthDelimited(b"fruit"),
+            a.options
+                .special_fields
+                .unknown_fields()
+                .get(50001)
+                .unwrap()
+        );
+        assert_eq!(
+            UnknownValueRef::LengthDelimited(&[0x08, 1, 0x10, 10]),
+            a.message_type[0]
+                .options
+                .special_fields
+                .unknown_fields()
+                .get(50002)
+                .unwrap()
+        );
+        assert_eq!(
+            UnknownValueRef::Varint(1),
+            a.message_type[0].field[0]
+                .options
+                .special_fields
+                .unknown_fields()
+                .get(50003)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn aggregate_option_value() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        fs::write(
+            &a_proto,
+            r#"syntax = "proto2";
+            import "google/protobuf/descriptor.proto";
+            enum Color { RED = 1; GREEN = 2; }
+            message Inner { optional string c = 1; }
+            message Opt {
+                repeated int32 nums = 1;
+                repeated Inner inner = 2;
+                optional Color color = 3;
+                optional bool flag = 4;
+            }
+            extend google.protobuf.MessageOptions { optional Opt opt = 50000; }
+            message Apple {
+                option (opt) = { nums: [1, 2] inner { c: "x" } inner: { c: "y" } color: GREEN flag: true };
+            }"#,
+        )
+        .unwrap();
+
+        let parsed = Parser::new()
+            .pure()
+            .include(dir.path())
+            .input(&a_proto)
+            .parse_and_typecheck()
+            .unwrap();
+
+        let a = parsed
+            .file_descriptors
+            .iter()
+            .find(|f| f.name() == "a.proto")
+            .unwrap();
+        let apple = a.message_type.iter().find(|m| m.name() == "Apple").unwrap();
+        assert_eq!(
+            UnknownValueRef::LengthDelimited(&[
+                0x08, 1, 0x08, 2, 0x12, 3, 0x0a, 1, b'x', 0x12, 3, 0x0a, 1, b'y', 0x18, 2, 0x20, 1
+            ]),
+            apple
+                .options
+                .special_fields
+                .unknown_fields()
+                .get(50000)
+                .unwrap()
+        );
+    }
+
+    #[test]
+    fn aggregate_option_value_duplicate_field() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        fs::write(
+            &a_proto,
+            r#"syntax = "proto2";
+            import "google/protobuf/descriptor.proto";
+            message Opt { optional int32 a = 1; repeated int32 b = 2; }
+            extend google.protobuf.MessageOptions { optional Opt opt = 50000; }
+            message Apple {
+                option (opt) = { a: 1 b: 2 b: 3 a: 4 };
+            }"#,
+        )
+        .unwrap();
+
+        let err = Parser::new()
+            .pure()
+            .include(dir.path())
+            .input(&a_proto)
+            .parse_and_typecheck()
+            .err()
+            .unwrap();
+        assert!(
+            format!("{:#}", err).contains("non-repeated field `a` is specified more than once"),
+            "{:#}",
+            err
+        );
+    }
+}
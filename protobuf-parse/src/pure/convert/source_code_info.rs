@@ -0,0 +1,279 @@
+//! Build `SourceCodeInfo` from declaration locations and comments.
+
+use protobuf::descriptor::source_code_info::Location;
+use protobuf::descriptor::DescriptorProto;
+use protobuf::descriptor::EnumDescriptorProto;
+use protobuf::descriptor::FieldDescriptorProto;
+use protobuf::descriptor::FileDescriptorProto;
+use protobuf::descriptor::ServiceDescriptorProto;
+use protobuf::descriptor::SourceCodeInfo;
+use protobuf_support::lexer::loc::Loc;
+
+use crate::pure::comments::Comments;
+use crate::pure::model;
+
+// Field numbers from `descriptor.proto`.
+const FILE_MESSAGE_TYPE: i32 = 4;
+const FILE_ENUM_TYPE: i32 = 5;
+const FILE_SERVICE: i32 = 6;
+const FILE_EXTENSION: i32 = 7;
+const MESSAGE_FIELD: i32 = 2;
+const MESSAGE_NESTED_TYPE: i32 = 3;
+const MESSAGE_ENUM_TYPE: i32 = 4;
+const MESSAGE_EXTENSION: i32 = 6;
+const MESSAGE_ONEOF_DECL: i32 = 8;
+const ENUM_VALUE: i32 = 2;
+const SERVICE_METHOD: i32 = 2;
+
+struct Builder<'a> {
+    comments: &'a Comments,
+    locations: Vec<Location>,
+}
+
+fn position<T>(items: &[T], name: impl Fn(&T) -> &str, expected: &str) -> Option<i32> {
+    items
+        .iter()
+        .position(|item| name(item) == expected)
+        .map(|i| i as i32)
+}
+
+fn child_path(path: &[i32], field: i32, index: i32) -> Vec<i32> {
+    let mut path = path.to_vec();
+    path.push(field);
+    path.push(index);
+    path
+}
+
+impl<'a> Builder<'a> {
+    fn location(&mut self, path: Vec<i32>, loc: Loc) {
+        let mut location = Location::new();
+        location.path = path;
+        let line = loc.line as i32 - 1;
+        let col = loc.col as i32 - 1;
+        match self.comments.declaration(loc) {
+            Some(declaration) => {
+                // Span is `[start line, start col, end line, end col]`,
+                // end line is omitted when it is the same as start line.
+                let end_line = declaration.end.line as i32 - 1;
+                let end_col = declaration.end.col as i32 - 1;
+                location.span = if end_line == line {
+                    vec![line, col, end_col]
+                } else {
+                    vec![line, col, end_line, end_col]
+                };
+                if let Some(leading) = declaration.leading {
+                    location.set_leading_comments(leading.to_owned());
+                }
+                if let Some(trailing) = declaration.trailing {
+                    location.set_trailing_comments(trailing.to_owned());
+                }
+                location.leading_detached_comments = declaration.detached.to_vec();
+            }
+            None => location.span = vec![line, col, col],
+        }
+        self.locations.push(location);
+    }
+
+    fn fields(
+        &mut self,
+        path: &[i32],
+        field_number: i32,
+        input: &[&model::WithLoc<model::Field>],
+        output: &[FieldDescriptorProto],
+    ) {
+        for f in input {
+            if let Some(index) = position(output, |o| o.name(), &f.t.name) {
+                self.location(child_path(path, field_number, index), f.loc);
+            }
+        }
+    }
+
+    fn extensions(
+        &mut self,
+        path: &[i32],
+        field_number: i32,
+        input: &[model::WithLoc<model::Extension>],
+        output: &[FieldDescriptorProto],
+    ) {
+        let fields: Vec<_> = input.iter().map(|e| &e.t.field).collect();
+        self.fields(path, field_number, &fields, output);
+    }
+
+    fn message(
+        &mut self,
+        path: Vec<i32>,
+        input: &model::WithLoc<model::Message>,
+        output: &DescriptorProto,
+    ) {
+        self.location(path.clone(), input.loc);
+
+        for fo in &input.t.fields {
+            match &fo.t {
+                model::FieldOrOneOf::Field(f) => {
+                    self.fields(&path, MESSAGE_FIELD, &[f], &output.field);
+                }
+                model::FieldOrOneOf::OneOf(o) => {
+                    if let Some(index) = position(&output.oneof_decl, |o| o.name(), &o.name) {
+                        self.location(child_path(&path, MESSAGE_ONEOF_DECL, index), fo.loc);
+                    }
+                    let fields: Vec<_> = o.fields.iter().collect();
+                    self.fields(&path, MESSAGE_FIELD, &fields, &output.field);
+                }
+            }
+        }
+
+        for m in &input.t.messages {
+            if let Some(index) = position(&output.nested_type, |o| o.name(), &m.t.name) {
+                self.message(
+                    child_path(&path, MESSAGE_NESTED_TYPE, index),
+                    m,
+                    &output.nested_type[index as usize],
+                );
+            }
+        }
+
+        for e in &input.t.enums {
+            if let Some(index) = position(&output.enum_type, |o| o.name(), &e.t.name) {
+                self.enumeration(
+                    child_path(&path, MESSAGE_ENUM_TYPE, index),
+                    e,
+                    &output.enum_type[index as usize],
+                );
+            }
+        }
+
+        self.extensions(
+            &path,
+            MESSAGE_EXTENSION,
+            &input.t.extensions,
+            &output.extension,
+        );
+    }
+
+    fn enumeration(
+        &mut self,
+        path: Vec<i32>,
+        input: &model::WithLoc<model::Enumeration>,
+        output: &EnumDescriptorProto,
+    ) {
+        self.location(path.clone(), input.loc);
+        for v in &input.t.values {
+            if let Some(index) = position(&output.value, |o| o.name(), &v.t.name) {
+                self.location(child_path(&path, ENUM_VALUE, index), v.loc);
+            }
+        }
+    }
+
+    fn service(
+        &mut self,
+        path: Vec<i32>,
+        input: &model::WithLoc<model::Service>,
+        output: &ServiceDescriptorProto,
+    ) {
+        self.location(path.clone(), input.loc);
+        for m in &input.t.methods {
+            if let Some(index) = position(&output.method, |o| o.name(), &m.t.name) {
+                self.location(child_path(&path, SERVICE_METHOD, index), m.loc);
+            }
+        }
+    }
+}
+
+/// Locations with leading and trailing comments of declarations.
+pub(crate) fn source_code_info(
+    input: &model::FileDescriptor,
+    output: &FileDescriptorProto,
+    comments: &Comments,
+) -> SourceCodeInfo {
+    let mut builder = Builder {
+        comments,
+        locations: Vec::new(),
+    };
+
+    for m in &input.messages {
+        if let Some(index) = position(&output.message_type, |o| o.name(), &m.t.name) {
+            builder.message(
+                vec![FILE_MESSAGE_TYPE, index],
+                m,
+                &output.message_type[index as usize],
+            );
+        }
+    }
+
+    for e in &input.enums {
+        if let Some(index) = position(&output.enum_type, |o| o.name(), &e.t.name) {
+            builder.enumeration(
+                vec![FILE_ENUM_TYPE, index],
+                e,
+                &output.enum_type[index as usize],
+            );
+        }
+    }
+
+    for s in &input.services {
+        if let Some(index) = position(&output.service, |o| o.name(), &s.t.name) {
+            builder.service(
+                vec![FILE_SERVICE, index],
+                s,
+                &output.service[index as usize],
+            );
+        }
+    }
+
+    builder.extensions(&[], FILE_EXTENSION, &input.extensions, &output.extension);
+
+    let mut source_code_info = SourceCodeInfo::new();
+    source_code_info.location = builder.locations;
+    source_code_info
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use crate::Parser;
+
+    #[test]
+    fn source_code_info() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        fs::write(
+            &a_proto,
+            r#"syntax = "proto3";
+            // Fruit.
+            message Apple {
+                int32 a = 1; // Field a.
+                /* Kind of apple. */
+                enum Kind {
+                    // Red apple.
+                    RED = 0;
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let parsed = Parser::new()
+            .pure()
+            .include(dir.path())
+            .input(&a_proto)
+            .include_source_info(true)
+            .parse_and_typecheck()
+            .unwrap();
+
+        let comments: Vec<_> = parsed.file_descriptors[0]
+            .source_code_info
+            .location
+            .iter()
+            .map(|l| (l.path.clone(), l.leading_comments(), l.trailing_comments()))
+            .collect();
+        assert_eq!(
+            vec![
+                (vec![4, 0], " Fruit.\n", ""),
+                (vec![4, 0, 2, 0], "", " Field a.\n"),
+                (vec![4, 0, 4, 0], " Kind of apple. ", ""),
+                (vec![4, 0, 4, 0, 2, 0], " Red apple.\n", ""),
+            ],
+            comments
+        );
+    }
+}
@@ -1,3 +1,5 @@
+use std::cell::RefCell;
+use std::collections::HashSet;
 use std::iter;
 
 use crate::model;
@@ -18,6 +20,8 @@ enum TypeResolverError {
     NotFoundByAbsPath(ProtobufAbsPath),
     #[error("object is not found by path `{0}` in scope `{1}`")]
     NotFoundByRelPath(ProtobufRelPath, ProtobufAbsPath),
+    #[error("`{0}` is resolved to `{1}`, which is not defined; the innermost scope is searched first in name resolution, consider using a leading `.` (i.e. `.{0}`) to start from the outermost scope")]
+    ResolvedToUndefined(ProtobufRelPath, ProtobufAbsPath),
 }
 
 pub(crate) enum MessageOrEnum<'a> {
@@ -140,7 +144,12 @@ impl<'a> LookupScope<'a> {
 
 pub(crate) struct TypeResolver<'a> {
     pub(crate) current_file: &'a model::FileDescriptor,
-    pub(crate) deps: &'a [FileDescriptorPair],
+    /// Files which types can be referenced by name.
+    pub(crate) deps: Vec<&'a FileDescriptorPair>,
+    /// All transitive dependencies.
+    pub(crate) all_deps: &'a [FileDescriptorPair],
+    /// Full names of types and extensions resolved so far, to find unused imports.
+    pub(crate) used: RefCell<HashSet<ProtobufAbsPath>>,
 }
 
 impl<'a> TypeResolver<'a> {
@@ -150,19 +159,69 @@ impl<'a> TypeResolver<'a> {
             .collect()
     }
 
-    pub(crate) fn find_message_or_enum_by_abs_name(
-        &self,
+    fn find_message_or_enum_in(
+        files: impl IntoIterator<Item = &'a model::FileDescriptor>,
         absolute_path: &ProtobufAbsPath,
-    ) -> anyhow::Result<WithFullName<MessageOrEnum<'a>>> {
-        for file in self.all_files() {
+    ) -> Option<WithFullName<MessageOrEnum<'a>>> {
+        for file in files {
             if let Some(relative) = absolute_path.remove_prefix(&file.package) {
                 if let Some(w) = LookupScope::File(file).find_message_or_enum(&relative) {
-                    return Ok(w);
+                    return Some(w);
                 }
             }
         }
+        None
+    }
+
+    fn find_message_or_enum(
+        &self,
+        absolute_path: &ProtobufAbsPath,
+    ) -> Option<WithFullName<MessageOrEnum<'a>>> {
+        Self::find_message_or_enum_in(self.all_files(), absolute_path)
+    }
 
-        return Err(TypeResolverError::NotFoundByAbsPath(absolute_path.clone()).into());
+    /// Find a type referenced by full name from an already resolved declaration,
+    /// like type of an extension used in option, which may be defined
+    /// in any transitive dependency.
+    pub(crate) fn find_message_or_enum_by_full_name(
+        &self,
+        full_name: &ProtobufAbsPath,
+    ) -> anyhow::Result<WithFullName<MessageOrEnum<'a>>> {
+        let files = iter::once(self.current_file).chain(self.all_deps.iter().map(|p| &p.parsed));
+        match Self::find_message_or_enum_in(files, full_name) {
+            Some(w) => {
+                self.used.borrow_mut().insert(w.full_name.clone());
+                Ok(w)
+            }
+            None => Err(TypeResolverError::NotFoundByAbsPath(full_name.clone()).into()),
+        }
+    }
+
+    pub(crate) fn find_message_or_enum_by_abs_name(
+        &self,
+        absolute_path: &ProtobufAbsPath,
+    ) -> anyhow::Result<WithFullName<MessageOrEnum<'a>>> {
+        match self.find_message_or_enum(absolute_path) {
+            Some(w) => {
+                self.used.borrow_mut().insert(w.full_name.clone());
+                Ok(w)
+            }
+            None => Err(TypeResolverError::NotFoundByAbsPath(absolute_path.clone()).into()),
+        }
+    }
+
+    /// Symbol which can contain other symbols: a package, a message, an enum or a service.
+    ///
+    /// Fields and other symbols are not aggregates, so `protoc` looks past them.
+    fn is_aggregate(&self, path: &ProtobufAbsPath) -> bool {
+        self.find_message_or_enum(path).is_some()
+            || self.all_files().into_iter().any(|file| {
+                file.package.starts_with(path)
+                    || match path.remove_prefix(&file.package) {
+                        Some(rel) => file.services.iter().any(|s| s.t.name == rel.as_str()),
+                        None => false,
+                    }
+            })
     }
 
     pub(crate) fn resolve_message_or_enum(
@@ -173,12 +232,31 @@ impl<'a> TypeResolver<'a> {
         match name {
             ProtobufPath::Abs(name) => Ok(self.find_message_or_enum_by_abs_name(&name)?),
             ProtobufPath::Rel(name) => {
-                // find message or enum in current package
+                // Like `protoc`, look up the first component from innermost scope outward.
+                // Once it is found, the rest of a compound name is resolved in it,
+                // and outer scopes are not searched even if it is not found there.
+                let (first, rest) = match name.split_first_rem() {
+                    Some(split) => split,
+                    None => {
+                        return Err(TypeResolverError::NotFoundByRelPath(
+                            name.clone(),
+                            scope.to_owned(),
+                        )
+                        .into())
+                    }
+                };
                 for p in scope.self_and_parents() {
                     let mut fq = p.to_owned();
-                    fq.push_relative(&name);
-                    if let Ok(me) = self.find_message_or_enum_by_abs_name(&fq) {
-                        return Ok(me);
+                    fq.push_simple(first);
+                    if rest.is_empty() {
+                        if let Ok(me) = self.find_message_or_enum_by_abs_name(&fq) {
+                            return Ok(me);
+                        }
+                    } else if self.is_aggregate(&fq) {
+                        fq.push_relative(rest);
+                        return self.find_message_or_enum_by_abs_name(&fq).map_err(|_| {
+                            TypeResolverError::ResolvedToUndefined(name.clone(), fq).into()
+                        });
                     }
                 }
 
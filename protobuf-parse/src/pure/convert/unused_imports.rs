@@ -0,0 +1,124 @@
+//! Imports which supply nothing used by a file.
+
+use std::collections::HashSet;
+
+use protobuf::descriptor::DescriptorProto;
+use protobuf::descriptor::FieldDescriptorProto;
+use protobuf::descriptor::FileDescriptorProto;
+
+use crate::parse_and_typecheck::file_type_names;
+use crate::pure::model;
+use crate::FileDescriptorPair;
+use crate::ProtobufAbsPath;
+use crate::ProtobufIdentRef;
+
+fn extension_names(
+    scope: &ProtobufAbsPath,
+    extensions: &[FieldDescriptorProto],
+    messages: &[DescriptorProto],
+    names: &mut Vec<String>,
+) {
+    for extension in extensions {
+        let mut path = scope.clone();
+        path.push_simple(ProtobufIdentRef::new(extension.name()));
+        names.push(path.to_string());
+    }
+    for message in messages {
+        let mut path = scope.clone();
+        path.push_simple(ProtobufIdentRef::new(message.name()));
+        extension_names(&path, &message.extension, &message.nested_type, names);
+    }
+}
+
+/// Types and extensions defined in a file or re-exported by its `import public`.
+fn exported_names(
+    file: &FileDescriptorProto,
+    deps: &[FileDescriptorPair],
+    names: &mut Vec<String>,
+) {
+    names.extend(file_type_names(file, true));
+    let package = ProtobufAbsPath::package_from_file_proto(file);
+    extension_names(&package, &file.extension, &file.message_type, names);
+    for &i in &file.public_dependency {
+        let public = &file.dependency[i as usize];
+        if let Some(dep) = deps.iter().find(|d| d.descriptor_proto.name() == public) {
+            exported_names(&dep.descriptor_proto, deps, names);
+        }
+    }
+}
+
+/// Imports supplying none of `used` types and extensions.
+///
+/// `import public` re-exports the file, so it is never unused,
+/// and `import weak` is optional by definition, so it is not checked either.
+pub(crate) fn unused_imports<'a>(
+    file: &'a model::FileDescriptor,
+    deps: &[FileDescriptorPair],
+    used: &HashSet<ProtobufAbsPath>,
+) -> Vec<&'a model::Import> {
+    let used: HashSet<String> = used.iter().map(|u| u.to_string()).collect();
+    file.imports
+        .iter()
+        .filter(|import| import.vis == model::ImportVis::Default)
+        .filter(|import| {
+            let dep = match deps
+                .iter()
+                .find(|d| d.descriptor_proto.name() == import.path.to_str())
+            {
+                Some(dep) => dep,
+                None => return false,
+            };
+            let mut names = Vec::new();
+            exported_names(&dep.descriptor_proto, deps, &mut names);
+            !names.iter().any(|n| used.contains(n))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use crate::Parser;
+
+    #[test]
+    fn import_unused() {
+        let dir = tempfile::tempdir().unwrap();
+        let write = |name: &str, content: &str| fs::write(dir.path().join(name), content).unwrap();
+        write("a.proto", "syntax = 'proto2'; package a; message Apple {}");
+        write("b.proto", "syntax = 'proto2'; import public 'a.proto';");
+        write(
+            "d.proto",
+            "syntax = 'proto2'; import 'google/protobuf/descriptor.proto'; \
+             extend google.protobuf.MessageOptions { optional int32 d = 50000; }",
+        );
+        write("e.proto", "syntax = 'proto2'; package e; message Elder {}");
+        write(
+            "main.proto",
+            r#"syntax = 'proto2';
+import 'b.proto';
+import 'd.proto';
+import 'e.proto';
+import public 'a.proto';
+message Main {
+    option (d) = 1;
+    optional a.Apple apple = 1;
+}
+"#,
+        );
+
+        let parsed = Parser::new()
+            .pure()
+            .include(dir.path())
+            .input(dir.path().join("main.proto"))
+            .parse_and_typecheck()
+            .unwrap();
+
+        assert_eq!(1, parsed.warnings.len(), "{:?}", parsed.warnings);
+        assert!(
+            parsed.warnings[0].ends_with("main.proto:4:1: import `e.proto` is unused"),
+            "{}",
+            parsed.warnings[0]
+        );
+    }
+}
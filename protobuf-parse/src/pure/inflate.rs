@@ -0,0 +1,311 @@
+//! Decoder of DEFLATE compressed data (RFC 1951), used to read `.tar.gz` and zip archives.
+
+const MAX_BITS: usize = 15;
+
+/// Base lengths and extra bits of length codes 257..=285.
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+/// Base distances and extra bits of distance codes 0..=29.
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+/// Order of code length code lengths in dynamic block header.
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+struct Bits<'a> {
+    data: &'a [u8],
+    pos: usize,
+    buf: u32,
+    count: u32,
+}
+
+impl<'a> Bits<'a> {
+    /// Read `n` bits, least significant bit first, `n <= 16`.
+    fn bits(&mut self, n: u32) -> Option<u32> {
+        while self.count < n {
+            self.buf |= (*self.data.get(self.pos)? as u32) << self.count;
+            self.pos += 1;
+            self.count += 8;
+        }
+        let r = self.buf & ((1 << n) - 1);
+        self.buf >>= n;
+        self.count -= n;
+        Some(r)
+    }
+
+    /// Skip to byte boundary. Less than 8 bits are buffered after any read.
+    fn align(&mut self) {
+        self.buf = 0;
+        self.count = 0;
+    }
+
+    fn bytes(&mut self, n: usize) -> Option<&'a [u8]> {
+        let r = self.data.get(self.pos..self.pos + n)?;
+        self.pos += n;
+        Some(r)
+    }
+}
+
+/// Decompressed data, at most `limit` bytes.
+struct Output {
+    data: Vec<u8>,
+    limit: usize,
+    exceeded: bool,
+}
+
+impl Output {
+    /// Check that `n` more bytes fit.
+    fn reserve(&mut self, n: usize) -> Option<()> {
+        if n > self.limit - self.data.len() {
+            self.exceeded = true;
+            return None;
+        }
+        Some(())
+    }
+}
+
+/// Canonical Huffman code.
+struct Huffman {
+    /// Number of codes of each length.
+    count: [u16; MAX_BITS + 1],
+    /// Symbols ordered by code.
+    symbol: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Option<Huffman> {
+        let mut count = [0; MAX_BITS + 1];
+        for &len in lengths {
+            count[len as usize] += 1;
+        }
+        // Over-subscribed code is invalid, incomplete code is allowed.
+        let mut left: i32 = 1;
+        for &c in &count[1..] {
+            left = (left << 1) - c as i32;
+            if left < 0 {
+                return None;
+            }
+        }
+
+        let mut offset = [0; MAX_BITS + 1];
+        for len in 1..MAX_BITS {
+            offset[len + 1] = offset[len] + count[len];
+        }
+        let mut symbol = vec![0; lengths.len()];
+        for (s, &len) in lengths.iter().enumerate() {
+            if len != 0 {
+                symbol[offset[len as usize] as usize] = s as u16;
+                offset[len as usize] += 1;
+            }
+        }
+        Some(Huffman { count, symbol })
+    }
+
+    fn decode(&self, bits: &mut Bits) -> Option<u16> {
+        // Codes of each length are consecutive and follow codes of shorter lengths.
+        let mut code: i32 = 0;
+        let mut first: i32 = 0;
+        let mut index: i32 = 0;
+        for &count in &self.count[1..] {
+            code |= bits.bits(1)? as i32;
+            let count = count as i32;
+            if code - first < count {
+                return self.symbol.get((index + code - first) as usize).copied();
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        None
+    }
+}
+
+fn codes(bits: &mut Bits, out: &mut Output, lencode: &Huffman, distcode: &Huffman) -> Option<()> {
+    loop {
+        let symbol = lencode.decode(bits)? as usize;
+        match symbol {
+            0..=255 => {
+                out.reserve(1)?;
+                out.data.push(symbol as u8);
+            }
+            256 => return Some(()),
+            _ => {
+                let symbol = symbol - 257;
+                let len = *LENGTH_BASE.get(symbol)? as usize
+                    + bits.bits(LENGTH_EXTRA[symbol] as u32)? as usize;
+                let symbol = distcode.decode(bits)? as usize;
+                let dist = *DIST_BASE.get(symbol)? as usize
+                    + bits.bits(DIST_EXTRA[symbol] as u32)? as usize;
+                if dist > out.data.len() {
+                    return None;
+                }
+                out.reserve(len)?;
+                for _ in 0..len {
+                    out.data.push(out.data[out.data.len() - dist]);
+                }
+            }
+        }
+    }
+}
+
+fn fixed(bits: &mut Bits, out: &mut Output) -> Option<()> {
+    let mut lengths = [0; 288];
+    lengths[..144].fill(8);
+    lengths[144..256].fill(9);
+    lengths[256..280].fill(7);
+    lengths[280..].fill(8);
+    let lencode = Huffman::new(&lengths)?;
+    let distcode = Huffman::new(&[5; 30])?;
+    codes(bits, out, &lencode, &distcode)
+}
+
+fn dynamic(bits: &mut Bits, out: &mut Output) -> Option<()> {
+    let nlen = bits.bits(5)? as usize + 257;
+    let ndist = bits.bits(5)? as usize + 1;
+    let ncode = bits.bits(4)? as usize + 4;
+    if nlen > 286 || ndist > 30 {
+        return None;
+    }
+
+    let mut code_lengths = [0; 19];
+    for &i in &CODE_LENGTH_ORDER[..ncode] {
+        code_lengths[i] = bits.bits(3)? as u8;
+    }
+    let lencode = Huffman::new(&code_lengths)?;
+
+    let mut lengths = Vec::with_capacity(nlen + ndist);
+    while lengths.len() < nlen + ndist {
+        let (len, repeat) = match lencode.decode(bits)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => (*lengths.last()?, 3 + bits.bits(2)?),
+            17 => (0, 3 + bits.bits(3)?),
+            18 => (0, 11 + bits.bits(7)?),
+            _ => return None,
+        };
+        for _ in 0..repeat {
+            lengths.push(len);
+        }
+    }
+    // Code lengths must not run past the end, and end of block code must be present.
+    if lengths.len() != nlen + ndist || lengths[256] == 0 {
+        return None;
+    }
+
+    let lencode = Huffman::new(&lengths[..nlen])?;
+    let distcode = Huffman::new(&lengths[nlen..])?;
+    codes(bits, out, &lencode, &distcode)
+}
+
+fn blocks(bits: &mut Bits, out: &mut Output) -> Option<()> {
+    loop {
+        let last = bits.bits(1)? == 1;
+        match bits.bits(2)? {
+            0 => {
+                bits.align();
+                let header = bits.bytes(4)?;
+                let len = u16::from_le_bytes([header[0], header[1]]);
+                let nlen = u16::from_le_bytes([header[2], header[3]]);
+                if len != !nlen {
+                    return None;
+                }
+                let stored = bits.bytes(len as usize)?;
+                out.reserve(stored.len())?;
+                out.data.extend_from_slice(stored);
+            }
+            1 => fixed(bits, out)?,
+            2 => dynamic(bits, out)?,
+            _ => return None,
+        }
+        if last {
+            return Some(());
+        }
+    }
+}
+
+/// Error of [`inflate`].
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum InflateError {
+    /// Data is not a valid DEFLATE stream.
+    Invalid,
+    /// Decompressed data is larger than the limit.
+    TooLarge,
+}
+
+/// Decompress raw DEFLATE stream into at most `limit` bytes.
+///
+/// Returns decompressed data and length of the compressed stream.
+pub(crate) fn inflate(data: &[u8], limit: usize) -> Result<(Vec<u8>, usize), InflateError> {
+    let mut bits = Bits {
+        data,
+        pos: 0,
+        buf: 0,
+        count: 0,
+    };
+    let mut out = Output {
+        data: Vec::new(),
+        limit,
+        exceeded: false,
+    };
+    match blocks(&mut bits, &mut out) {
+        Some(()) => Ok((out.data, bits.pos)),
+        None if out.exceeded => Err(InflateError::TooLarge),
+        None => Err(InflateError::Invalid),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::inflate;
+    use super::InflateError;
+
+    #[test]
+    fn stored_and_fixed() {
+        // Stored block with "ab", then fixed block with "a" and a copy of it at distance 1.
+        let data = [
+            0x00, 0x02, 0x00, 0xfd, 0xff, b'a', b'b', 0x4b, 0x4c, 0x84, 0x01, 0x00,
+        ];
+        assert_eq!(
+            Ok((b"abaaaaaaaaaa".to_vec(), data.len())),
+            inflate(&data, 100)
+        );
+        assert_eq!(Err(InflateError::TooLarge), inflate(&data, 11));
+        assert_eq!(Err(InflateError::TooLarge), inflate(&data, 1));
+
+        assert_eq!(
+            Err(InflateError::Invalid),
+            inflate(&[0x00, 0x02, 0x00, 0xfc, 0xff, b'a', b'b'], 100)
+        );
+        assert_eq!(Err(InflateError::Invalid), inflate(&[0x07], 100));
+    }
+
+    #[test]
+    fn dynamic() {
+        // Output of zlib with skewed literal frequencies, so it picks a dynamic block.
+        let data = [
+            0x1d, 0x87, 0x87, 0x0d, 0x00, 0x00, 0x0c, 0x82, 0x6e, 0x75, 0xfc, 0x7f, 0x43, 0xb1,
+            0x26, 0x10, 0x4c, 0xb5, 0xe1, 0xa0, 0xc8, 0x6f, 0x19, 0x68, 0x8e, 0x17, 0xab, 0xa4,
+            0xad, 0x0e,
+        ];
+        let expected = b"cdaaaaadaacadacabadacaabcaaabaadabaaabbaadccddda";
+        assert_eq!(Ok((expected.to_vec(), data.len())), inflate(&data, 100));
+        assert_eq!(Err(InflateError::TooLarge), inflate(&data, 47));
+
+        for len in 0..data.len() {
+            assert_eq!(Err(InflateError::Invalid), inflate(&data[..len], 100));
+        }
+        // Too many literal/length codes.
+        assert_eq!(Err(InflateError::Invalid), inflate(&[0xfd, 0xff], 100));
+    }
+}
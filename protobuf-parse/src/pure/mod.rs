@@ -1,10 +1,55 @@
 //! Pure rust `.proto` file parser.
 
+mod archive_file_loader;
+mod cache;
+mod comments;
 pub(crate) mod convert;
+mod inflate;
+/// Syntax tree of `.proto` file, before typechecking.
+///
+/// This API is less stable than the rest of the crate.
+#[cfg(feature = "model-api")]
+pub mod model;
+#[cfg(not(feature = "model-api"))]
 pub(crate) mod model;
 pub(crate) mod parse_and_typecheck;
 pub(crate) mod parse_dependencies;
+mod parse_single;
 mod parser;
+mod suggest;
+#[cfg(feature = "tokenizer-api")]
+mod tokenize;
+mod validate;
 
+pub use archive_file_loader::ArchiveFileLoader;
+#[doc(hidden)]
+pub use parse_and_typecheck::is_io_error;
 pub use parse_and_typecheck::parse_and_typecheck_custom;
+pub use parse_and_typecheck::FileLoader;
+pub use parse_and_typecheck::ParseObserver;
+pub use parse_and_typecheck::WellKnownTypes;
+#[doc(hidden)]
+pub use parse_and_typecheck::WellKnownTypesHolder;
+#[doc(hidden)]
+pub use parse_and_typecheck::WithFileError;
 pub use parse_dependencies::*;
+pub use parse_single::parse_single;
+pub use parser::ParserErrorKind;
+pub use parser::ParserErrorWithLocation;
+pub use parser::ParserErrorsWithLocation;
+pub use parser::SourcePosition;
+#[cfg(feature = "tokenizer-api")]
+pub use tokenize::tokenize;
+#[cfg(feature = "tokenizer-api")]
+pub use tokenize::Token;
+#[cfg(feature = "tokenizer-api")]
+pub use tokenize::TokenKind;
+pub use validate::validate_descriptors;
+pub use validate::ValidateError;
+
+/// Parse `.proto` file content into syntax tree without typechecking,
+/// reporting all the errors found.
+#[cfg(feature = "model-api")]
+pub fn parse_model(content: &str) -> Result<model::FileDescriptor, ParserErrorsWithLocation> {
+    model::FileDescriptor::parse_collect_errors(content)
+}
@@ -5,7 +5,9 @@
 
 use std::fmt;
 use std::fmt::Write;
+use std::mem;
 use std::ops::Deref;
+use std::slice;
 
 use indexmap::IndexMap;
 use protobuf::reflect::ReflectValueBox;
@@ -18,9 +20,10 @@ use crate::model;
 use crate::proto_path::ProtoPathBuf;
 use crate::protobuf_abs_path::ProtobufAbsPath;
 use crate::protobuf_ident::ProtobufIdent;
-use crate::protobuf_path::ProtobufPath;
+pub use crate::protobuf_path::ProtobufPath;
 use crate::pure::parser::Parser;
 pub use crate::pure::parser::ParserErrorWithLocation;
+pub use crate::pure::parser::ParserErrorsWithLocation;
 
 #[derive(thiserror::Error, Debug)]
 enum ModelError {
@@ -29,7 +32,7 @@ enum ModelError {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub(crate) struct WithLoc<T> {
+pub struct WithLoc<T> {
     pub loc: Loc,
     pub t: T,
 }
@@ -53,11 +56,45 @@ impl<T> WithLoc<T> {
 
 /// Protobuf syntax.
 #[derive(Debug, Clone, Copy, Eq, PartialEq)]
-pub(crate) enum Syntax {
+pub enum Syntax {
     /// Protobuf syntax [2](https://developers.google.com/protocol-buffers/docs/proto) (default)
     Proto2,
     /// Protobuf syntax [3](https://developers.google.com/protocol-buffers/docs/proto3)
     Proto3,
+    /// Protobuf [editions](https://protobuf.dev/editions/overview/)
+    Editions(Edition),
+}
+
+/// Protobuf edition.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Edition {
+    /// Edition 2023
+    Edition2023,
+}
+
+impl Edition {
+    /// Parse edition string like `2023`.
+    pub fn parse(edition: &str) -> Option<Edition> {
+        match edition {
+            "2023" => Some(Edition::Edition2023),
+            _ => None,
+        }
+    }
+
+    /// Value of `google.protobuf.Edition` enum.
+    pub fn number(&self) -> i32 {
+        match self {
+            Edition::Edition2023 => 1000,
+        }
+    }
+
+    /// Edition by value of `google.protobuf.Edition` enum.
+    pub fn from_number(number: u64) -> Option<Edition> {
+        match number {
+            1000 => Some(Edition::Edition2023),
+            _ => None,
+        }
+    }
 }
 
 impl Default for Syntax {
@@ -68,7 +105,7 @@ impl Default for Syntax {
 
 /// A field rule
 #[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
-pub(crate) enum Rule {
+pub enum Rule {
     /// A well-formed message can have zero or one of this field (but not more than one).
     Optional,
     /// This field can be repeated any number of times (including zero) in a well-formed message.
@@ -92,7 +129,7 @@ impl Rule {
 
 /// Protobuf group
 #[derive(Debug, Clone, PartialEq)]
-pub(crate) struct Group {
+pub struct Group {
     /// Group name
     pub name: String,
     pub fields: Vec<WithLoc<Field>>,
@@ -100,7 +137,7 @@ pub(crate) struct Group {
 
 /// Protobuf supported field types
 #[derive(Debug, Clone, PartialEq)]
-pub(crate) enum FieldType {
+pub enum FieldType {
     /// Protobuf int32
     ///
     /// # Remarks
@@ -193,7 +230,7 @@ pub(crate) enum FieldType {
 
 /// A Protobuf Field
 #[derive(Debug, Clone, PartialEq)]
-pub(crate) struct Field {
+pub struct Field {
     /// Field name
     pub name: String,
     /// Field `Rule`
@@ -208,23 +245,36 @@ pub(crate) struct Field {
 
 /// A Protobuf field of oneof group
 #[derive(Debug, Clone, PartialEq)]
-pub(crate) enum FieldOrOneOf {
+pub enum FieldOrOneOf {
     Field(WithLoc<Field>),
     OneOf(OneOf),
 }
 
+/// Maximum field number, and the upper bound of `to max` ranges in messages.
+pub(crate) const FIELD_NUMBER_MAX: i32 = 0x20000000 - 1;
+
 /// Extension range
 #[derive(Default, Debug, Eq, PartialEq, Copy, Clone)]
-pub(crate) struct FieldNumberRange {
+pub struct FieldNumberRange {
     /// First number
     pub from: i32,
     /// Inclusive
     pub to: i32,
 }
 
+impl fmt::Display for FieldNumberRange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.from == self.to {
+            write!(f, "{}", self.from)
+        } else {
+            write!(f, "{} to {}", self.from, self.to)
+        }
+    }
+}
+
 /// A protobuf message
 #[derive(Debug, Clone, Default)]
-pub(crate) struct Message {
+pub struct Message {
     /// Message name
     pub name: String,
     /// Message fields and oneofs
@@ -300,7 +350,7 @@ impl Message {
 
 /// A protobuf enumeration field
 #[derive(Debug, Clone)]
-pub(crate) struct EnumValue {
+pub struct EnumValue {
     /// enum value name
     pub name: String,
     /// enum value number
@@ -311,18 +361,22 @@ pub(crate) struct EnumValue {
 
 /// A protobuf enumerator
 #[derive(Debug, Clone)]
-pub(crate) struct Enumeration {
+pub struct Enumeration {
     /// enum name
     pub name: String,
     /// enum values
-    pub values: Vec<EnumValue>,
+    pub values: Vec<WithLoc<EnumValue>>,
     /// enum options
     pub options: Vec<ProtobufOption>,
+    /// enum reserved numbers
+    pub reserved_nums: Vec<FieldNumberRange>,
+    /// enum reserved names
+    pub reserved_names: Vec<String>,
 }
 
 /// A OneOf
 #[derive(Debug, Clone, Default, PartialEq)]
-pub(crate) struct OneOf {
+pub struct OneOf {
     /// OneOf name
     pub name: String,
     /// OneOf fields
@@ -332,7 +386,7 @@ pub(crate) struct OneOf {
 }
 
 #[derive(Debug, Clone)]
-pub(crate) struct Extension {
+pub struct Extension {
     /// Extend this type with field
     pub extendee: ProtobufPath,
     /// Extension field
@@ -341,7 +395,7 @@ pub(crate) struct Extension {
 
 /// Service method
 #[derive(Debug, Clone)]
-pub(crate) struct Method {
+pub struct Method {
     /// Method name
     pub name: String,
     /// Input type
@@ -349,10 +403,8 @@ pub(crate) struct Method {
     /// Output type
     pub output_type: ProtobufPath,
     /// If this method is client streaming
-    #[allow(dead_code)] // TODO
     pub client_streaming: bool,
     /// If this method is server streaming
-    #[allow(dead_code)] // TODO
     pub server_streaming: bool,
     /// Method options
     pub options: Vec<ProtobufOption>,
@@ -360,17 +412,17 @@ pub(crate) struct Method {
 
 /// Service definition
 #[derive(Debug, Clone)]
-pub(crate) struct Service {
+pub struct Service {
     /// Service name
     pub name: String,
-    pub methods: Vec<Method>,
+    pub methods: Vec<WithLoc<Method>>,
     pub options: Vec<ProtobufOption>,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub(crate) struct AnyTypeUrl {
-    pub(crate) prefix: String,
-    pub(crate) full_type_name: ProtobufPath,
+pub struct AnyTypeUrl {
+    pub prefix: String,
+    pub full_type_name: ProtobufPath,
 }
 
 impl fmt::Display for AnyTypeUrl {
@@ -380,7 +432,7 @@ impl fmt::Display for AnyTypeUrl {
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
-pub(crate) enum ProtobufConstantMessageFieldName {
+pub enum ProtobufConstantMessageFieldName {
     Regular(String),
     Extension(ProtobufPath),
     AnyTypeUrl(AnyTypeUrl),
@@ -397,14 +449,14 @@ impl fmt::Display for ProtobufConstantMessageFieldName {
 }
 
 #[derive(Debug, Clone, PartialEq, Default)]
-pub(crate) struct ProtobufConstantMessage {
-    pub(crate) fields: IndexMap<ProtobufConstantMessageFieldName, ProtobufConstant>,
+pub struct ProtobufConstantMessage {
+    pub fields: IndexMap<ProtobufConstantMessageFieldName, ProtobufConstant>,
 }
 
 /// constant = fullIdent | ( [ "-" | "+" ] intLit ) | ( [ "-" | "+" ] floatLit ) |
 //                 strLit | boolLit
 #[derive(Debug, Clone, PartialEq)]
-pub(crate) enum ProtobufConstant {
+pub enum ProtobufConstant {
     U64(u64),
     I64(i64),
     F64(f64), // TODO: eq
@@ -412,6 +464,8 @@ pub(crate) enum ProtobufConstant {
     Ident(ProtobufPath),
     String(StrLit),
     Message(ProtobufConstantMessage),
+    /// `[a, b]` list or repeated field in message constant.
+    Repeated(Vec<ProtobufConstant>),
 }
 
 impl fmt::Display for ProtobufConstant {
@@ -425,15 +479,38 @@ impl fmt::Display for ProtobufConstant {
             ProtobufConstant::String(v) => write!(f, "{}", v),
             // TODO: text format explicitly
             ProtobufConstant::Message(v) => write!(f, "{:?}", v),
+            ProtobufConstant::Repeated(v) => write!(f, "{:?}", v),
         }
     }
 }
 
 impl ProtobufConstantMessage {
+    /// Add field value, collecting values of a field specified more than once.
+    pub(crate) fn add_field(
+        &mut self,
+        name: ProtobufConstantMessageFieldName,
+        value: ProtobufConstant,
+    ) {
+        match self.fields.entry(name) {
+            indexmap::map::Entry::Vacant(e) => {
+                e.insert(value);
+            }
+            indexmap::map::Entry::Occupied(mut e) => {
+                let prev = mem::replace(e.get_mut(), ProtobufConstant::Repeated(Vec::new()));
+                let mut values = prev.into_repeated();
+                values.extend(value.into_repeated());
+                *e.get_mut() = ProtobufConstant::Repeated(values);
+            }
+        }
+    }
+
     pub fn format(&self) -> String {
         let mut s = String::new();
         write!(s, "{{").unwrap();
-        for (n, v) in &self.fields {
+        for (i, (n, v)) in self.fields.iter().enumerate() {
+            if i != 0 {
+                write!(s, " ").unwrap();
+            }
             match v {
                 ProtobufConstant::Message(m) => write!(s, "{} {}", n, m.format()).unwrap(),
                 v => write!(s, "{}: {}", n, v.format()).unwrap(),
@@ -454,6 +531,25 @@ impl ProtobufConstant {
             ProtobufConstant::Ident(ref i) => format!("{}", i),
             ProtobufConstant::String(ref s) => s.quoted(),
             ProtobufConstant::Message(ref s) => s.format(),
+            ProtobufConstant::Repeated(ref v) => format!(
+                "[{}]",
+                v.iter().map(|v| v.format()).collect::<Vec<_>>().join(", ")
+            ),
+        }
+    }
+
+    /// Values of possibly repeated constant.
+    pub(crate) fn as_repeated(&self) -> &[ProtobufConstant] {
+        match self {
+            ProtobufConstant::Repeated(v) => v,
+            v => slice::from_ref(v),
+        }
+    }
+
+    fn into_repeated(self) -> Vec<ProtobufConstant> {
+        match self {
+            ProtobufConstant::Repeated(v) => v,
+            v => vec![v],
         }
     }
 
@@ -477,7 +573,7 @@ impl ProtobufConstant {
 
 /// Equivalent of `UninterpretedOption.NamePart`.
 #[derive(Debug, Clone, PartialEq)]
-pub(crate) enum ProtobufOptionNamePart {
+pub enum ProtobufOptionNamePart {
     Direct(ProtobufIdent),
     Ext(ProtobufPath),
 }
@@ -492,10 +588,10 @@ impl fmt::Display for ProtobufOptionNamePart {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub(crate) struct ProtobufOptionNameExt(pub Vec<ProtobufOptionNamePart>);
+pub struct ProtobufOptionNameExt(pub Vec<ProtobufOptionNamePart>);
 
 #[derive(Debug, Clone, PartialEq)]
-pub(crate) enum ProtobufOptionName {
+pub enum ProtobufOptionName {
     Builtin(ProtobufIdent),
     Ext(ProtobufOptionNameExt),
 }
@@ -528,14 +624,16 @@ impl fmt::Display for ProtobufOptionName {
 }
 
 #[derive(Debug, Clone, PartialEq)]
-pub(crate) struct ProtobufOption {
+pub struct ProtobufOption {
     pub name: ProtobufOptionName,
     pub value: ProtobufConstant,
+    /// Location of option name
+    pub loc: Loc,
 }
 
 /// Visibility of import statement
 #[derive(Debug, Clone, Eq, PartialEq)]
-pub(crate) enum ImportVis {
+pub enum ImportVis {
     Default,
     Public,
     Weak,
@@ -548,21 +646,25 @@ impl Default for ImportVis {
 }
 
 /// Import statement
-#[derive(Debug, Default, Clone)]
-pub(crate) struct Import {
+#[derive(Debug, Clone)]
+pub struct Import {
     pub path: ProtoPathBuf,
     pub vis: ImportVis,
+    pub loc: Loc,
 }
 
 /// A File descriptor representing a whole .proto file
 #[derive(Debug, Default, Clone)]
-pub(crate) struct FileDescriptor {
+pub struct FileDescriptor {
     /// Imports
     pub imports: Vec<Import>,
     /// Package
     pub package: ProtobufAbsPath,
     /// Protobuf Syntax
     pub syntax: Syntax,
+    /// File has `syntax` or `edition` statement, otherwise it is proto2
+    /// or configured default syntax
+    pub syntax_declared: bool,
     /// Top level messages
     pub messages: Vec<WithLoc<Message>>,
     /// Enums
@@ -581,10 +683,32 @@ impl FileDescriptor {
         let mut parser = Parser::new(file.as_ref());
         match parser.next_proto() {
             Ok(r) => Ok(r),
-            Err(error) => {
-                let Loc { line, col } = parser.tokenizer.loc();
-                Err(ParserErrorWithLocation { error, line, col })
-            }
+            Err(error) => Err(ParserErrorWithLocation::new(
+                error,
+                parser.tokenizer.loc(),
+                parser.tokenizer.loc_end(),
+                file.as_ref(),
+            )),
         }
     }
+
+    /// Parses a .proto file content into a `FileDescriptor`,
+    /// but instead of stopping at the first error,
+    /// skip invalid statements and report all errors found.
+    pub fn parse_collect_errors<S: AsRef<str>>(file: S) -> Result<Self, ParserErrorsWithLocation> {
+        Self::parse_collect_errors_with_default_syntax(file, Syntax::Proto2)
+    }
+
+    /// Like [`parse_collect_errors`](Self::parse_collect_errors),
+    /// but assume given syntax when file has no `syntax` or `edition` statement.
+    pub(crate) fn parse_collect_errors_with_default_syntax<S: AsRef<str>>(
+        file: S,
+        default_syntax: Syntax,
+    ) -> Result<Self, ParserErrorsWithLocation> {
+        let mut parser = Parser::new(file.as_ref());
+        parser.default_syntax = default_syntax;
+        parser
+            .next_proto_collect_errors()
+            .map_err(ParserErrorsWithLocation)
+    }
 }
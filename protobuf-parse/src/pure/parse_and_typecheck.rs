@@ -1,20 +1,45 @@
+use std::borrow::Cow;
+use std::collections::BTreeSet;
+use std::collections::HashMap;
+use std::collections::HashSet;
 use std::fmt;
 use std::fs;
 use std::io;
+use std::panic;
+use std::path::Component;
 use std::path::Path;
 use std::path::PathBuf;
 use std::str;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+use std::time::Instant;
 
 use indexmap::IndexMap;
+use log::debug;
+use log::warn;
 use protobuf::descriptor::FileDescriptorProto;
 use protobuf::reflect::FileDescriptor;
+use protobuf_support::lexer::loc::Loc;
 
+use crate::parse_and_typecheck::file_type_names;
 use crate::parse_and_typecheck::ParsedAndTypechecked;
+use crate::parse_and_typecheck::Unsupported;
+use crate::path::normalize_separators;
 use crate::proto;
 use crate::proto_path::ProtoPath;
 use crate::proto_path::ProtoPathBuf;
+use crate::pure::cache::Cache;
+use crate::pure::cache::Entry;
+use crate::pure::cache::Hash;
+use crate::pure::comments::Comments;
 use crate::pure::convert;
+use crate::pure::convert::ConvertErrorWithLoc;
 use crate::pure::model;
+use crate::pure::parse_single::model_from_descriptor;
+use crate::pure::parser::ParserErrorsWithLocation;
+use crate::pure::suggest;
+use crate::DuplicateIncludePolicy;
 use crate::FileDescriptorPair;
 use crate::Parser;
 
@@ -22,22 +47,78 @@ use crate::Parser;
 enum ParseAndTypeckError {
     #[error("file `{0}` content is not UTF-8")]
     FileContentIsNotUtf8(String),
-    #[error("protobuf path `{0}` is not found in import path {1}")]
-    FileNotFoundInImportPath(String, String),
+    #[error("protobuf path `{0}` is not found in import path {1}{2}{3}")]
+    FileNotFoundInImportPath(String, String, String, String),
     #[error("file `{0}` must reside in include path {1}")]
     FileMustResideInImportPath(String, String),
     #[error("could not read file `{0}`: {1}")]
     CouldNotReadFile(String, io::Error),
+    #[error("import cycle: {0}")]
+    ImportCycle(String),
+    #[error("files `{1}` and `{2}` both map to protobuf path `{0}`")]
+    InputProtoPathCollision(String, String, String),
+    #[error("protobuf path `{0}` is found in more than one include directory: {1}")]
+    AmbiguousInclude(String, String),
+    #[error("file `{0}` is referenced both as `{1}` and `{2}`")]
+    FileReferencedAsDifferentProtoPaths(String, String, String),
+    #[error("more than {0} files are parsed, last one is `{1}`")]
+    TooManyFiles(usize, String),
+    #[error("import depth exceeds {0}: {1}")]
+    ImportTooDeep(usize, String),
+    #[error("type `{0}` is defined in both `{1}` and `{2}`")]
+    DuplicateType(String, String, String),
+    #[error("protobuf path `{0}` resolves to `{1}` which differs in case, so it is not found on case-sensitive filesystems")]
+    CaseMismatch(String, String),
 }
 
+/// Error in a particular `.proto` file, wraps parse and typecheck errors.
+///
+/// Not a part of public API, used by `protobuf-codegen` to categorize errors.
+#[doc(hidden)]
 #[derive(Debug, thiserror::Error)]
-#[error("error in `{file}`: {error}")]
-struct WithFileError {
+pub struct WithFileError {
     file: String,
+    /// Location of the offending declaration for typecheck errors.
+    loc: Option<Loc>,
     #[source]
     error: anyhow::Error,
 }
 
+impl WithFileError {
+    fn new(file: &str, error: anyhow::Error) -> WithFileError {
+        let (loc, error) = match error.downcast::<ConvertErrorWithLoc>() {
+            Ok(e) => (Some(e.loc), e.error),
+            Err(error) => (None, error),
+        };
+        WithFileError {
+            file: file.to_owned(),
+            loc,
+            error,
+        }
+    }
+
+    /// Path of the file, as passed to the parser or found in include path.
+    pub fn file(&self) -> &str {
+        &self.file
+    }
+
+    /// 1-based line and column of the offending declaration, for typecheck errors.
+    ///
+    /// Parse errors carry location in [`ParserErrorsWithLocation`](crate::pure::ParserErrorsWithLocation).
+    pub fn line_col(&self) -> Option<(u32, u32)> {
+        self.loc.map(|loc| (loc.line, loc.col))
+    }
+}
+
+impl fmt::Display for WithFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.loc {
+            Some(loc) => write!(f, "error in `{}:{}`: {}", self.file, loc, self.error),
+            None => write!(f, "error in `{}`: {}", self.file, self.error),
+        }
+    }
+}
+
 /// Resolve `.proto` files. `Display` is used for error messages.
 pub trait ProtoPathResolver: fmt::Display {
     /// Resolve a `.proto` file.
@@ -45,6 +126,148 @@ pub trait ProtoPathResolver: fmt::Display {
     /// Return `None` if a path is unknown, and if a path is a built-in protobuf file,
     /// like `google/protobuf/descriptor.proto`, it will be handled by the library.
     fn resolve(&self, path: &ProtoPath) -> anyhow::Result<Option<ResolvedProtoFile>>;
+
+    /// Locations probed when resolving a path, listed when the path is not found.
+    fn probed(&self, _path: &ProtoPath) -> Vec<String> {
+        Vec::new()
+    }
+
+    /// Paths of all the files this resolver can find,
+    /// used to suggest similar paths when a path is not found.
+    fn files(&self) -> Vec<ProtoPathBuf> {
+        Vec::new()
+    }
+}
+
+/// Load `.proto` files from a virtual filesystem, like an in-memory map or an archive.
+pub trait FileLoader: Send + Sync + 'static {
+    /// Load a `.proto` file by path relative to include path.
+    ///
+    /// Return `None` to fall back to looking up the file in include path.
+    fn load(&self, proto_path: &ProtoPath) -> io::Result<Option<String>>;
+}
+
+impl FileLoader for Arc<dyn FileLoader> {
+    fn load(&self, proto_path: &ProtoPath) -> io::Result<Option<String>> {
+        (**self).load(proto_path)
+    }
+}
+
+/// Receive notifications about parsing progress, e.g. to display a progress bar.
+///
+/// All methods do nothing by default.
+pub trait ParseObserver: Send + Sync + 'static {
+    /// A file is parsed and is about to be typechecked.
+    ///
+    /// With [`parallelism`](crate::Parser::parallelism), elapsed time is measured
+    /// when file is parsed, but this is called when file is typechecked.
+    /// This is not called for files taken from [cache](crate::Parser::cache_dir).
+    fn file_parsed(&self, _proto_path: &ProtoPath, _elapsed: Duration) {}
+    /// A file is typechecked and converted to descriptor.
+    ///
+    /// This is not called for files taken from [cache](crate::Parser::cache_dir).
+    fn file_converted(&self, _proto_path: &ProtoPath, _elapsed: Duration) {}
+}
+
+impl ParseObserver for Arc<dyn ParseObserver> {
+    fn file_parsed(&self, proto_path: &ProtoPath, elapsed: Duration) {
+        (**self).file_parsed(proto_path, elapsed)
+    }
+
+    fn file_converted(&self, proto_path: &ProtoPath, elapsed: Duration) {
+        (**self).file_converted(proto_path, elapsed)
+    }
+}
+
+/// Well-known type protos, like `google/protobuf/timestamp.proto`,
+/// used instead of the ones bundled with the parser,
+/// e.g. to match a particular protobuf release.
+pub trait WellKnownTypes: Send + Sync + 'static {
+    /// Protobuf release the protos come from, like `3.21.12`.
+    fn version(&self) -> &str;
+
+    /// Content of a proto under `google/protobuf/`.
+    ///
+    /// Return `None` if the file is not provided, it is then looked up in include path only.
+    fn proto(&self, proto_path: &ProtoPath) -> Option<String>;
+}
+
+impl WellKnownTypes for Arc<dyn WellKnownTypes> {
+    fn version(&self) -> &str {
+        (**self).version()
+    }
+
+    fn proto(&self, proto_path: &ProtoPath) -> Option<String> {
+        (**self).proto(proto_path)
+    }
+}
+
+/// Not a part of public API, used by `protobuf-codegen` to store [`WellKnownTypes`].
+#[doc(hidden)]
+#[derive(Clone)]
+pub struct WellKnownTypesHolder(pub Arc<dyn WellKnownTypes>);
+
+impl fmt::Debug for WellKnownTypesHolder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("WellKnownTypesHolder")
+            .field("version", &self.0.version())
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct ParseObserverHolder(pub(crate) Arc<dyn ParseObserver>);
+
+impl fmt::Debug for ParseObserverHolder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ParseObserverHolder")
+            .finish_non_exhaustive()
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct FileLoaderHolder(pub(crate) Arc<dyn FileLoader>);
+
+impl fmt::Debug for FileLoaderHolder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FileLoaderHolder").finish_non_exhaustive()
+    }
+}
+
+impl FileLoaderHolder {
+    fn load(&self, proto_path: &ProtoPath) -> anyhow::Result<Option<String>> {
+        self.0
+            .load(proto_path)
+            .map_err(|e| ParseAndTypeckError::CouldNotReadFile(proto_path.to_string(), e).into())
+    }
+}
+
+/// Files registered with [`Parser::include_content`], consulted before user file loader.
+struct ContentFileLoader {
+    files: Vec<(ProtoPathBuf, String)>,
+    next: Option<FileLoaderHolder>,
+}
+
+impl FileLoader for ContentFileLoader {
+    fn load(&self, proto_path: &ProtoPath) -> io::Result<Option<String>> {
+        if let Some((_, content)) = self.files.iter().find(|(p, _)| p.as_path() == proto_path) {
+            return Ok(Some(content.clone()));
+        }
+        match &self.next {
+            Some(next) => next.0.load(proto_path),
+            None => Ok(None),
+        }
+    }
+}
+
+fn file_loader(parser: &Parser) -> Option<FileLoaderHolder> {
+    if parser.content_files.is_empty() {
+        return parser.file_loader.clone();
+    }
+    Some(FileLoaderHolder(Arc::new(ContentFileLoader {
+        files: parser.content_files.clone(),
+        next: parser.file_loader.clone(),
+    })))
 }
 
 struct Run<R>
@@ -53,6 +276,61 @@ where
 {
     parsed_files: IndexMap<ProtoPathBuf, FileDescriptorPair>,
     resolver: R,
+    /// Paths (as reported by resolver) of files read.
+    read_files: Vec<String>,
+    /// Files which were not resolved and taken from bundled protos.
+    embedded_files: Vec<ProtoPathBuf>,
+    /// Use protos bundled with this crate.
+    embedded_protos: bool,
+    /// Use protos bundled with this crate before looking them up in include path.
+    prefer_embedded_well_known: bool,
+    /// Warn when bundled protos found in include path differ from bundled versions.
+    warn_well_known_drift: bool,
+    /// User provided protos used when file is not resolved.
+    extra_embedded_protos: Vec<(ProtoPathBuf, String)>,
+    /// Replace bundled well-known type protos.
+    well_known_types: Option<WellKnownTypesHolder>,
+    /// Populate `source_code_info` with declaration comments.
+    include_source_info: bool,
+    /// Files being parsed, outermost first.
+    parse_stack: Vec<ProtoPathBuf>,
+    /// Consulted before the resolver.
+    file_loader: Option<FileLoaderHolder>,
+    /// Notified when files are parsed and typechecked.
+    observer: Option<ParseObserverHolder>,
+    /// Canonical filesystem paths of files read, to detect a file read under different names.
+    fs_files: IndexMap<PathBuf, ProtoPathBuf>,
+    /// Parse files on this many threads before typechecking.
+    parallelism: usize,
+    /// Files resolved and parsed by [`Run::parse_in_parallel`], not yet typechecked.
+    parsed_in_parallel: HashMap<ProtoPathBuf, ParsedInParallel>,
+    /// Reuse typechecked descriptors from previous runs.
+    cache: Option<Cache>,
+    /// Cache keys of typechecked files.
+    cache_keys: HashMap<ProtoPathBuf, Hash>,
+    /// Empty files standing for weak imports which were not found.
+    missing_weak_imports: HashMap<ProtoPathBuf, FileDescriptorPair>,
+    /// Descriptors added with [`Parser::add_descriptor`], not yet typechecked.
+    seeded: HashMap<ProtoPathBuf, FileDescriptorProto>,
+    /// Fail on constructs which cannot be fully represented.
+    strict: bool,
+    /// Syntax of files without `syntax` or `edition` statement.
+    default_syntax: model::Syntax,
+    /// Constructs dropped from the output in non-strict mode.
+    warnings: Vec<String>,
+    unsupported: Vec<Unsupported>,
+    /// Fail when more files are parsed.
+    max_files: Option<usize>,
+    /// Fail when imports are nested deeper.
+    max_import_depth: Option<usize>,
+    /// Include directories in search order, empty when resolver is not filesystem-based.
+    includes: Vec<PathBuf>,
+    duplicate_include_policy: DuplicateIncludePolicy,
+    prefer_importer_include: bool,
+    require_exact_case: bool,
+    /// Include directory each input was found in, searched first for its imports.
+    /// Imports found there inherit it.
+    file_includes: HashMap<ProtoPathBuf, PathBuf>,
 }
 
 impl<R> Run<R>
@@ -71,12 +349,15 @@ where
         let pair = self
             .parsed_files
             .get(protobuf_path)
+            .or_else(|| self.missing_weak_imports.get(protobuf_path))
             .expect("must be already parsed");
         result.insert(protobuf_path.to_proto_path_buf(), pair.clone());
 
         self.all_deps_already_parsed(&pair.parsed, result);
     }
 
+    /// All transitive dependencies. Only some of them are visible by name,
+    /// but option values may use types from any of them.
     fn all_deps_already_parsed(
         &self,
         parsed: &model::FileDescriptor,
@@ -87,37 +368,209 @@ where
         }
     }
 
+    /// Parse and typecheck a file and its imports.
+    ///
+    /// Content is taken by value and released right after parsing,
+    /// so contents of files along the import chain are not held at the same time.
+    ///
+    /// `parsed` is the result of parsing the content in [`Run::parse_in_parallel`].
     fn add_file_content(
         &mut self,
         protobuf_path: &ProtoPath,
-        resolved: &ResolvedProtoFile,
+        resolved: ResolvedProtoFile,
+        parsed: Option<ParseResult>,
+    ) -> anyhow::Result<()> {
+        if let Some(max_files) = self.max_files {
+            // Files being parsed are not yet in `parsed_files`.
+            if self.parsed_files.len() + self.parse_stack.len() >= max_files {
+                return Err(ParseAndTypeckError::TooManyFiles(
+                    max_files,
+                    protobuf_path.to_string(),
+                )
+                .into());
+            }
+        }
+        if let Some(max_import_depth) = self.max_import_depth {
+            if self.parse_stack.len() >= max_import_depth {
+                let chain: Vec<&str> = self
+                    .parse_stack
+                    .iter()
+                    .map(|p| p.to_str())
+                    .chain([protobuf_path.to_str()])
+                    .collect();
+                return Err(ParseAndTypeckError::ImportTooDeep(
+                    max_import_depth,
+                    chain.join(" -> "),
+                )
+                .into());
+            }
+        }
+        self.parse_stack.push(protobuf_path.to_proto_path_buf());
+        let r = self.add_file_content_in_stack(protobuf_path, resolved, parsed);
+        self.parse_stack.pop();
+        r
+    }
+
+    fn add_file_content_in_stack(
+        &mut self,
+        protobuf_path: &ProtoPath,
+        resolved: ResolvedProtoFile,
+        parsed: Option<ParseResult>,
     ) -> anyhow::Result<()> {
-        let content = str::from_utf8(&resolved.content)
-            .map_err(|_| ParseAndTypeckError::FileContentIsNotUtf8(protobuf_path.to_string()))?;
+        let ResolvedProtoFile { path, content } = resolved;
+
+        let content_hash = self.cache.as_ref().map(|_| {
+            Hash::content(
+                protobuf_path,
+                &content,
+                self.include_source_info,
+                self.strict,
+                self.default_syntax,
+            )
+        });
+
+        if let (Some(cache), Some(content_hash)) = (self.cache.clone(), content_hash) {
+            if let Some(entry) = cache.load(content_hash, &content) {
+                // Dependencies might have changed, so process them first.
+                for (i, dep) in entry.descriptor.dependency.iter().enumerate() {
+                    let weak = entry.descriptor.weak_dependency.contains(&(i as i32));
+                    self.add_import(&path, ProtoPath::new(dep)?, weak)?;
+                }
+                if entry.key == self.cache_key(content_hash, &entry.descriptor) {
+                    self.add_warnings(protobuf_path, &path, entry.warnings, entry.unsupported);
+                    // Model restored from descriptor has no options and locations,
+                    // which are not needed to typecheck files importing this one.
+                    let parsed = model_from_descriptor(&entry.descriptor);
+                    return self.add_typechecked(
+                        protobuf_path,
+                        parsed,
+                        entry.descriptor,
+                        Some(entry.key),
+                    );
+                }
+            }
+        }
+
+        let text = content_str(&content)
+            .ok_or_else(|| ParseAndTypeckError::FileContentIsNotUtf8(protobuf_path.to_string()))?;
 
-        let parsed = model::FileDescriptor::parse(&content).map_err(|e| WithFileError {
-            file: resolved.path.clone(),
-            error: e.into(),
-        })?;
+        let parsed = parsed.unwrap_or_else(|| parse_file(text, self.default_syntax));
+        let (parsed, elapsed) = parsed.map_err(|e| WithFileError::new(&path, e.into()))?;
+        if let Some(observer) = &self.observer {
+            observer.0.file_parsed(protobuf_path, elapsed);
+        }
+
+        let comments = if self.include_source_info {
+            Some(Comments::parse(text))
+        } else {
+            None
+        };
+        // Parsed model and comments is all that is needed from now on,
+        // content is only kept to be stored in cache.
+        let content = match self.cache {
+            Some(_) => content,
+            None => Vec::new(),
+        };
 
         for import in &parsed.imports {
-            self.add_imported_file(&import.path)?;
+            self.add_import(&path, &import.path, import.vis == model::ImportVis::Weak)?;
         }
+        let has_missing_weak_imports = parsed
+            .imports
+            .iter()
+            .any(|import| self.missing_weak_imports.contains_key(&import.path));
 
         let mut this_file_deps = IndexMap::new();
         self.all_deps_already_parsed(&parsed, &mut this_file_deps);
 
         let this_file_deps: Vec<_> = this_file_deps.into_iter().map(|(_, v)| v).collect();
 
-        let descriptor_proto = convert::file_descriptor(protobuf_path, &parsed, &this_file_deps)
-            .map_err(|e| WithFileError {
-                file: resolved.path.clone(),
-                error: e.into(),
-            })?;
+        let start = self.observer.as_ref().map(|_| Instant::now());
+        let convert::FileDescriptorOutput {
+            descriptor: mut descriptor_proto,
+            warnings,
+            unsupported,
+        } = convert::file_descriptor(
+            protobuf_path,
+            &parsed,
+            &this_file_deps,
+            comments.as_ref(),
+            self.strict,
+        )
+        .map_err(|e| WithFileError::new(&path, e))?;
+        if let (Some(observer), Some(start)) = (&self.observer, start) {
+            observer.0.file_converted(protobuf_path, start.elapsed());
+        }
+
+        let cache_key = match (&self.cache, content_hash) {
+            (Some(cache), Some(content_hash)) => {
+                let entry = Entry {
+                    key: self.cache_key(content_hash, &descriptor_proto),
+                    descriptor: descriptor_proto,
+                    warnings,
+                    unsupported,
+                };
+                // Missing weak imports are not covered by the key,
+                // so the result may change when they appear.
+                if !has_missing_weak_imports {
+                    cache.store(content_hash, &content, &entry);
+                }
+                self.add_warnings(protobuf_path, &path, entry.warnings, entry.unsupported);
+                descriptor_proto = entry.descriptor;
+                Some(entry.key)
+            }
+            _ => {
+                self.add_warnings(protobuf_path, &path, warnings, unsupported);
+                None
+            }
+        };
+
+        self.add_typechecked(protobuf_path, parsed, descriptor_proto, cache_key)
+    }
+
+    /// Record warnings and unsupported constructs reported when converting a file.
+    fn add_warnings(
+        &mut self,
+        protobuf_path: &ProtoPath,
+        path: &str,
+        warnings: Vec<String>,
+        unsupported: Vec<(Loc, String)>,
+    ) {
+        for w in &warnings {
+            warn!("{}:{}", path, w);
+            self.warnings.push(format!("{}:{}", path, w));
+        }
+        for (loc, construct) in unsupported {
+            self.unsupported.push(Unsupported {
+                file: protobuf_path.to_proto_path_buf(),
+                line: loc.line,
+                col: loc.col,
+                construct,
+            });
+        }
+    }
+
+    /// Key of cache entry for a file with all dependencies already processed.
+    fn cache_key(&self, content_hash: Hash, descriptor_proto: &FileDescriptorProto) -> Hash {
+        let deps: Vec<Hash> = descriptor_proto
+            .dependency
+            .iter()
+            .map(|dep| self.cache_keys[ProtoPath::new(dep).expect("valid path")])
+            .collect();
+        Hash::key(content_hash, &deps)
+    }
 
+    fn add_typechecked(
+        &mut self,
+        protobuf_path: &ProtoPath,
+        parsed: model::FileDescriptor,
+        descriptor_proto: FileDescriptorProto,
+        cache_key: Option<Hash>,
+    ) -> anyhow::Result<()> {
         let deps: Vec<FileDescriptor> = self
             .parsed_files
             .values()
+            .chain(self.missing_weak_imports.values())
             .map(|v| v.descriptor.clone())
             .collect();
         let descriptor = FileDescriptor::new_dynamic(descriptor_proto.clone(), &deps)?;
@@ -130,170 +583,982 @@ where
                 descriptor,
             },
         );
+        if let Some(cache_key) = cache_key {
+            self.cache_keys
+                .insert(protobuf_path.to_proto_path_buf(), cache_key);
+        }
 
         Ok(())
     }
 
+    fn add_read_file(&mut self, path: &str, protobuf_path: &ProtoPath) -> anyhow::Result<()> {
+        let canonical = canonical_path(Path::new(path));
+        if let Some(prev) = self.fs_files.get(&canonical) {
+            if prev.as_path() != protobuf_path {
+                return Err(ParseAndTypeckError::FileReferencedAsDifferentProtoPaths(
+                    path.to_owned(),
+                    prev.to_string(),
+                    protobuf_path.to_string(),
+                )
+                .into());
+            }
+        } else {
+            self.fs_files
+                .insert(canonical, protobuf_path.to_proto_path_buf());
+        }
+        if !self.read_files.iter().any(|p| p == path) {
+            self.read_files.push(path.to_owned());
+        }
+        Ok(())
+    }
+
+    /// Process an import of file at `path`.
+    ///
+    /// Weak import which is not found is replaced with an empty file,
+    /// so the dependency stays in the descriptor, but provides no types.
+    fn add_import(&mut self, path: &str, import: &ProtoPath, weak: bool) -> anyhow::Result<()> {
+        match self.add_imported_file(import) {
+            Err(e) if weak && is_file_not_found(&e) => {
+                let warning = format!("{}: weak import `{}` not found, skipping", path, import);
+                warn!("{}", warning);
+                self.warnings.push(warning);
+                if !self.missing_weak_imports.contains_key(import) {
+                    self.missing_weak_imports
+                        .insert(import.to_proto_path_buf(), missing_file_placeholder(import));
+                    if self.cache.is_some() {
+                        self.cache_keys
+                            .insert(import.to_proto_path_buf(), Hash::missing(import));
+                    }
+                }
+                Ok(())
+            }
+            r => r,
+        }
+    }
+
     fn add_imported_file(&mut self, protobuf_path: &ProtoPath) -> anyhow::Result<()> {
         if let Some(_) = self.parsed_files.get(protobuf_path) {
             return Ok(());
         }
 
-        let resolved = self.resolver.resolve(protobuf_path)?;
-        if let Some(resolved) = resolved {
-            return self.add_file_content(protobuf_path, &resolved);
-        }
-
-        let embedded = match protobuf_path.to_str() {
-            "rustproto.proto" => Some(proto::RUSTPROTO_PROTO),
-            "google/protobuf/any.proto" => Some(proto::ANY_PROTO),
-            "google/protobuf/api.proto" => Some(proto::API_PROTO),
-            "google/protobuf/descriptor.proto" => Some(proto::DESCRIPTOR_PROTO),
-            "google/protobuf/duration.proto" => Some(proto::DURATION_PROTO),
-            "google/protobuf/empty.proto" => Some(proto::EMPTY_PROTO),
-            "google/protobuf/field_mask.proto" => Some(proto::FIELD_MASK_PROTO),
-            "google/protobuf/source_context.proto" => Some(proto::SOURCE_CONTEXT_PROTO),
-            "google/protobuf/struct.proto" => Some(proto::STRUCT_PROTO),
-            "google/protobuf/timestamp.proto" => Some(proto::TIMESTAMP_PROTO),
-            "google/protobuf/type.proto" => Some(proto::TYPE_PROTO),
-            "google/protobuf/wrappers.proto" => Some(proto::WRAPPERS_PROTO),
-            _ => None,
-        };
+        if let Some(pos) = self
+            .parse_stack
+            .iter()
+            .position(|p| p.as_path() == protobuf_path)
+        {
+            let cycle: Vec<&str> = self.parse_stack[pos..]
+                .iter()
+                .map(|p| p.to_str())
+                .chain([protobuf_path.to_str()])
+                .collect();
+            return Err(ParseAndTypeckError::ImportCycle(cycle.join(" -> ")).into());
+        }
 
-        match embedded {
-            Some(content) => self.add_file_content(
-                protobuf_path,
-                &ResolvedProtoFile {
-                    path: protobuf_path.to_string(),
-                    content: content.as_bytes().to_vec(),
-                },
-            ),
-            None => Err(ParseAndTypeckError::FileNotFoundInImportPath(
-                protobuf_path.to_string(),
-                format!("{}", self.resolver),
-            )
-            .into()),
+        if let Some(descriptor) = self.seeded.remove(protobuf_path) {
+            self.parse_stack.push(protobuf_path.to_proto_path_buf());
+            let r = self.add_seeded(protobuf_path, descriptor);
+            self.parse_stack.pop();
+            return r;
+        }
+
+        let importer = self.parse_stack.last().cloned();
+        let (resolved, source, parsed) =
+            match self.take_parsed_import(importer.as_deref(), protobuf_path) {
+                Some(p) => (p.resolved, p.source, p.parsed),
+                None => {
+                    let (resolved, source) =
+                        self.find_imported_file(protobuf_path, importer.as_deref())?;
+                    self.inherit_include(protobuf_path, importer.as_deref(), &resolved);
+                    (resolved, source, None)
+                }
+            };
+        match source {
+            FileSource::Loaded => {}
+            FileSource::Read => {
+                self.add_read_file(&resolved.path, protobuf_path)?;
+                self.check_well_known_drift(protobuf_path, &resolved);
+            }
+            FileSource::Embedded => self.embedded_files.push(protobuf_path.to_proto_path_buf()),
         }
+        self.add_file_content(protobuf_path, resolved, parsed)
     }
-}
 
-pub(crate) fn path_to_proto_path(
-    path: &Path,
-    includes: &[PathBuf],
-) -> anyhow::Result<ProtoPathBuf> {
-    for include in includes {
-        if include == Path::new(".") && path.is_relative() {
-            // Special handling of `.` to allow using `.` as an include path
-            // and `foo.proto` as input.
-            return ProtoPathBuf::from_path(path);
+    /// Add descriptor added with [`Parser::add_descriptor`]
+    /// after its dependencies, which are looked up like imports of parsed files.
+    fn add_seeded(
+        &mut self,
+        protobuf_path: &ProtoPath,
+        descriptor: FileDescriptorProto,
+    ) -> anyhow::Result<()> {
+        for (i, dep) in descriptor.dependency.iter().enumerate() {
+            let weak = descriptor.weak_dependency.contains(&(i as i32));
+            self.add_import(descriptor.name(), ProtoPath::new(dep)?, weak)?;
         }
-        match path.strip_prefix(include) {
-            Ok(stripped) => return ProtoPathBuf::from_path(stripped),
-            Err(_) => continue,
+        let cache_key = self
+            .cache
+            .as_ref()
+            .map(|_| self.cache_key(Hash::descriptor(&descriptor), &descriptor));
+        let parsed = model_from_descriptor(&descriptor);
+        self.add_typechecked(protobuf_path, parsed, descriptor, cache_key)
+    }
+
+    /// File found and parsed by [`Run::parse_in_parallel`]
+    /// the same way it would be found when imported from `importer` now.
+    fn take_parsed_import(
+        &mut self,
+        importer: Option<&ProtoPath>,
+        protobuf_path: &ProtoPath,
+    ) -> Option<ParsedInParallel> {
+        let include = self.importer_include(importer).cloned();
+        match self.parsed_in_parallel.get(protobuf_path) {
+            Some(p) if !p.input && p.include == include => {}
+            _ => return None,
         }
+        self.parsed_in_parallel.remove(protobuf_path)
     }
-    Err(ParseAndTypeckError::FileMustResideInImportPath(
-        path.display().to_string(),
-        format!("{:?}", includes),
-    )
-    .into())
-}
 
-/// `.proto` file result provided from the [`ProtoPathResolver`].
-pub struct ResolvedProtoFile {
-    /// For error reporting.
-    pub path: String,
-    /// File content.
-    pub content: Vec<u8>,
-}
+    /// Warn if file read from include path differs from bundled file with the same path.
+    fn check_well_known_drift(&mut self, protobuf_path: &ProtoPath, resolved: &ResolvedProtoFile) {
+        if !self.warn_well_known_drift {
+            return;
+        }
+        let embedded = match self.embedded_proto(protobuf_path) {
+            Some(embedded) => embedded,
+            None => return,
+        };
+        let normalize = |s: &str| s.split_whitespace().collect::<Vec<_>>().join(" ");
+        let content = String::from_utf8_lossy(&resolved.content);
+        if normalize(&content) != normalize(&embedded) {
+            let warning = format!(
+                "{}: `{}` differs from the version bundled with the parser",
+                resolved.path, protobuf_path
+            );
+            warn!("{}", warning);
+            self.warnings.push(warning);
+        }
+    }
 
-fn fs_resolver(includes: &[PathBuf]) -> impl ProtoPathResolver {
-    struct Impl {
-        includes: Vec<PathBuf>,
+    /// Content of bundled proto, or of well-known type proto
+    /// provided by [`Parser::well_known_types`].
+    fn embedded_proto(&self, protobuf_path: &ProtoPath) -> Option<Cow<'static, str>> {
+        match &self.well_known_types {
+            Some(well_known_types) if protobuf_path.to_str().starts_with("google/protobuf/") => {
+                well_known_types.0.proto(protobuf_path).map(Cow::Owned)
+            }
+            _ => embedded_proto(protobuf_path).map(Cow::Borrowed),
+        }
     }
 
-    impl fmt::Display for Impl {
-        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-            write!(f, "{:?}", self.includes)
+    /// Include directory of the importer, if `protobuf_path` should be looked up there first.
+    fn importer_include(&self, importer: Option<&ProtoPath>) -> Option<&PathBuf> {
+        if !self.prefer_importer_include {
+            return None;
         }
+        self.file_includes.get(importer?)
     }
 
-    impl ProtoPathResolver for Impl {
-        fn resolve(&self, proto_path: &ProtoPath) -> anyhow::Result<Option<ResolvedProtoFile>> {
-            for include_dir in &self.includes {
-                let fs_path = include_dir.join(proto_path.to_path());
-                match fs::read_to_string(&fs_path) {
-                    Ok(content) => {
-                        return Ok(Some(ResolvedProtoFile {
-                            path: fs_path.display().to_string(),
-                            content: content.into_bytes(),
-                        }))
-                    }
-                    Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
-                    Err(e) => {
-                        return Err(ParseAndTypeckError::CouldNotReadFile(
-                            fs_path.display().to_string(),
-                            e,
-                        )
-                        .into())
-                    }
-                }
+    /// Remember that imported file was found in importer include directory,
+    /// so its own imports are looked up there first too.
+    fn inherit_include(
+        &mut self,
+        protobuf_path: &ProtoPath,
+        importer: Option<&ProtoPath>,
+        resolved: &ResolvedProtoFile,
+    ) {
+        if self.file_includes.contains_key(protobuf_path) {
+            return;
+        }
+        if let Some(include) = self.importer_include(importer) {
+            if Path::new(&resolved.path) == include.join(protobuf_path.to_path()) {
+                let include = include.clone();
+                self.file_includes
+                    .insert(protobuf_path.to_proto_path_buf(), include);
             }
-            Ok(None)
         }
     }
 
-    Impl {
-        includes: includes.to_vec(),
-    }
-}
+    /// Find imported file content without parsing it.
+    fn find_imported_file(
+        &self,
+        protobuf_path: &ProtoPath,
+        importer: Option<&ProtoPath>,
+    ) -> anyhow::Result<(ResolvedProtoFile, FileSource)> {
+        if let Some(file_loader) = &self.file_loader {
+            if let Some(content) = file_loader.load(protobuf_path)? {
+                debug!("`{}` is loaded by file loader", protobuf_path);
+                return Ok((
+                    ResolvedProtoFile {
+                        path: protobuf_path.to_string(),
+                        content: content.into_bytes(),
+                    },
+                    FileSource::Loaded,
+                ));
+            }
+        }
 
-/// Parse `.proto` files using pure Rust implementation.
-pub fn parse_and_typecheck(parser: &Parser) -> anyhow::Result<ParsedAndTypechecked> {
-    let mut run = Run {
-        parsed_files: IndexMap::new(),
-        resolver: fs_resolver(&parser.includes),
-    };
+        if self.embedded_protos && self.prefer_embedded_well_known {
+            if let Some(content) = self.embedded_proto(protobuf_path) {
+                debug!("`{}` is bundled, not looking it up", protobuf_path);
+                return Ok((
+                    ResolvedProtoFile {
+                        path: protobuf_path.to_string(),
+                        content: content.into_owned().into_bytes(),
+                    },
+                    FileSource::Embedded,
+                ));
+            }
+        }
 
-    let relative_paths = parser
-        .inputs
-        .iter()
-        .map(|input| Ok((path_to_proto_path(input, &parser.includes)?, input)))
-        .collect::<anyhow::Result<Vec<_>>>()?;
+        if let Some(include) = self.importer_include(importer) {
+            let includes: Vec<PathBuf> = [include.clone()]
+                .into_iter()
+                .chain(self.includes.iter().filter(|i| *i != include).cloned())
+                .collect();
+            let resolver = fs_resolver(
+                &includes,
+                self.duplicate_include_policy,
+                self.require_exact_case,
+            );
+            if let Some(resolved) = resolver.resolve(protobuf_path)? {
+                debug!("`{}` is resolved to `{}`", protobuf_path, resolved.path);
+                return Ok((resolved, FileSource::Read));
+            }
+        }
 
-    for (proto_path, path) in &relative_paths {
+        let resolved = self.resolver.resolve(protobuf_path)?;
+        if let Some(resolved) = resolved {
+            debug!("`{}` is resolved to `{}`", protobuf_path, resolved.path);
+            return Ok((resolved, FileSource::Read));
+        }
+
+        let extra_embedded = self
+            .extra_embedded_protos
+            .iter()
+            .find(|(path, _)| path.as_path() == protobuf_path)
+            .map(|(_, content)| content.clone());
+
+        let embedded = match extra_embedded {
+            Some(content) => Some(content),
+            None if self.embedded_protos => self
+                .embedded_proto(protobuf_path)
+                .map(|content| content.into_owned()),
+            None => None,
+        };
+
+        match embedded {
+            Some(content) => {
+                debug!("`{}` is not resolved, using embedded file", protobuf_path);
+                Ok((
+                    ResolvedProtoFile {
+                        path: protobuf_path.to_string(),
+                        content: content.into_bytes(),
+                    },
+                    FileSource::Embedded,
+                ))
+            }
+            None => {
+                let probed = self.resolver.probed(protobuf_path);
+                let probed = if probed.is_empty() {
+                    String::new()
+                } else {
+                    format!(", tried {}", probed.join(", "))
+                };
+                let files = self.resolver.files();
+                let candidates = files
+                    .iter()
+                    .map(|p| p.to_str())
+                    .chain(self.extra_embedded_protos.iter().map(|(p, _)| p.to_str()));
+                let suggestions = suggest::closest(protobuf_path.to_str(), candidates);
+                Err(ParseAndTypeckError::FileNotFoundInImportPath(
+                    protobuf_path.to_string(),
+                    format!("{}", self.resolver),
+                    probed,
+                    suggest::did_you_mean(&suggestions),
+                )
+                .into())
+            }
+        }
+    }
+
+    /// Find input file content without parsing it.
+    fn find_input_file(
+        &self,
+        protobuf_path: &ProtoPath,
+        path: &Path,
+    ) -> anyhow::Result<(ResolvedProtoFile, FileSource)> {
+        if let Some(file_loader) = &self.file_loader {
+            if let Some(content) = file_loader.load(protobuf_path)? {
+                return Ok((
+                    ResolvedProtoFile {
+                        path: path.display().to_string(),
+                        content: content.into_bytes(),
+                    },
+                    FileSource::Loaded,
+                ));
+            }
+        }
         let content = fs::read_to_string(path)
             .map_err(|e| ParseAndTypeckError::CouldNotReadFile(path.display().to_string(), e))?;
-        run.add_file_content(
-            proto_path,
-            &ResolvedProtoFile {
+        Ok((
+            ResolvedProtoFile {
                 path: path.display().to_string(),
                 content: content.into_bytes(),
             },
-        )?;
+            FileSource::Read,
+        ))
     }
 
-    let file_descriptors: Vec<_> = run
-        .parsed_files
-        .into_iter()
-        .map(|(_, v)| v.descriptor_proto)
-        .collect();
+    /// Find and parse all files reachable from inputs in parallel,
+    /// so that the sequential pass only typechecks them.
+    ///
+    /// Parse errors are kept to be reported by the sequential pass.
+    /// Files which cannot be found are left for the sequential pass to report.
+    fn parse_in_parallel(&mut self, inputs: &[(ProtoPathBuf, &PathBuf)]) {
+        let mut queued: HashSet<ProtoPathBuf> = HashSet::new();
+        let mut wave = Vec::new();
+        for (proto_path, path) in inputs {
+            if queued.insert(proto_path.clone()) {
+                if let Ok((resolved, source)) = self.find_input_file(proto_path, path) {
+                    wave.push((
+                        proto_path.clone(),
+                        ParsedInParallel {
+                            resolved,
+                            source,
+                            input: true,
+                            include: None,
+                            parsed: None,
+                        },
+                    ));
+                }
+            }
+        }
 
-    Ok(ParsedAndTypechecked {
-        relative_paths: relative_paths.into_iter().map(|(p, _)| p).collect(),
-        file_descriptors,
-        parser: "pure".to_owned(),
-    })
+        while !wave.is_empty() {
+            parse_files(&mut wave, self.parallelism, self.default_syntax);
+            let mut next_wave = Vec::new();
+            for (proto_path, file) in wave {
+                if let Some(Ok((parsed, _))) = &file.parsed {
+                    for import in &parsed.imports {
+                        // Sequential pass reports the error.
+                        if self.max_files.is_some_and(|max| queued.len() >= max) {
+                            break;
+                        }
+                        if self.parsed_files.contains_key(&import.path) {
+                            continue;
+                        }
+                        if queued.insert(import.path.clone()) {
+                            if let Ok((resolved, source)) =
+                                self.find_imported_file(&import.path, Some(&proto_path))
+                            {
+                                let include = self.importer_include(Some(&proto_path)).cloned();
+                                self.inherit_include(&import.path, Some(&proto_path), &resolved);
+                                next_wave.push((
+                                    import.path.clone(),
+                                    ParsedInParallel {
+                                        resolved,
+                                        source,
+                                        input: false,
+                                        include,
+                                        parsed: None,
+                                    },
+                                ));
+                            }
+                        }
+                    }
+                }
+                self.parsed_in_parallel.insert(proto_path, file);
+            }
+            wave = next_wave;
+        }
+    }
 }
 
-/// TODO: this API is to be refactored.
-pub fn parse_and_typecheck_custom(
-    input: &[ProtoPathBuf],
-    resolver: impl ProtoPathResolver,
-) -> anyhow::Result<Vec<FileDescriptorProto>> {
+/// Where file content came from.
+enum FileSource {
+    /// From [`FileLoader`].
+    Loaded,
+    /// From filesystem or resolver.
+    Read,
+    /// Protos bundled with this crate or registered by user.
+    Embedded,
+}
+
+/// Parsed file and time it took to parse it.
+type ParseResult = Result<(model::FileDescriptor, Duration), ParserErrorsWithLocation>;
+
+/// File found and parsed by [`Run::parse_in_parallel`].
+struct ParsedInParallel {
+    resolved: ResolvedProtoFile,
+    source: FileSource,
+    /// Found as input file rather than as import.
+    input: bool,
+    /// Importer include directory the import was looked up in first.
+    include: Option<PathBuf>,
+    /// `None` if content is not valid UTF-8.
+    parsed: Option<ParseResult>,
+}
+
+/// File content as string without UTF-8 byte order mark some editors write.
+fn content_str(content: &[u8]) -> Option<&str> {
+    let content = str::from_utf8(content).ok()?;
+    Some(content.strip_prefix('\u{feff}').unwrap_or(content))
+}
+
+fn parse_file(content: &str, default_syntax: model::Syntax) -> ParseResult {
+    let start = Instant::now();
+    let parsed =
+        model::FileDescriptor::parse_collect_errors_with_default_syntax(content, default_syntax)?;
+    Ok((parsed, start.elapsed()))
+}
+
+/// Parse files on up to `parallelism` threads.
+fn parse_files(
+    files: &mut [(ProtoPathBuf, ParsedInParallel)],
+    parallelism: usize,
+    default_syntax: model::Syntax,
+) {
+    let chunk_size = files.len().div_ceil(parallelism.max(1));
+    thread::scope(|scope| {
+        let handles: Vec<_> = files
+            .chunks_mut(chunk_size.max(1))
+            .map(|chunk| {
+                scope.spawn(move || {
+                    for (_, file) in chunk {
+                        file.parsed = content_str(&file.resolved.content)
+                            .map(|content| parse_file(content, default_syntax));
+                    }
+                })
+            })
+            .collect();
+        for h in handles {
+            h.join().unwrap_or_else(|e| panic::resume_unwind(e));
+        }
+    })
+}
+
+/// Empty file standing for a weak import which was not found,
+/// so descriptors of files importing it can be built.
+fn missing_file_placeholder(protobuf_path: &ProtoPath) -> FileDescriptorPair {
+    let mut descriptor_proto = FileDescriptorProto::new();
+    descriptor_proto.set_name(protobuf_path.to_string());
+    let descriptor =
+        FileDescriptor::new_dynamic(descriptor_proto.clone(), &[]).expect("empty file is valid");
+    FileDescriptorPair {
+        parsed: model::FileDescriptor::default(),
+        descriptor_proto,
+        descriptor,
+    }
+}
+
+fn is_file_not_found(error: &anyhow::Error) -> bool {
+    matches!(
+        error.downcast_ref::<ParseAndTypeckError>(),
+        Some(ParseAndTypeckError::FileNotFoundInImportPath(..))
+    )
+}
+
+/// Whether error returned by the parser is caused by
+/// a `.proto` file which cannot be found or read.
+///
+/// Not a part of public API, used by `protobuf-codegen` to categorize errors.
+#[doc(hidden)]
+pub fn is_io_error(error: &anyhow::Error) -> bool {
+    error.chain().any(|e| {
+        matches!(
+            e.downcast_ref::<ParseAndTypeckError>(),
+            Some(
+                ParseAndTypeckError::FileNotFoundInImportPath(..)
+                    | ParseAndTypeckError::CouldNotReadFile(..)
+            )
+        )
+    })
+}
+
+/// Content of `.proto` file bundled with this crate.
+pub(crate) fn embedded_proto(protobuf_path: &ProtoPath) -> Option<&'static str> {
+    match protobuf_path.to_str() {
+        "rustproto.proto" => Some(proto::RUSTPROTO_PROTO),
+        "google/protobuf/any.proto" => Some(proto::ANY_PROTO),
+        "google/protobuf/api.proto" => Some(proto::API_PROTO),
+        "google/protobuf/descriptor.proto" => Some(proto::DESCRIPTOR_PROTO),
+        "google/protobuf/duration.proto" => Some(proto::DURATION_PROTO),
+        "google/protobuf/empty.proto" => Some(proto::EMPTY_PROTO),
+        "google/protobuf/field_mask.proto" => Some(proto::FIELD_MASK_PROTO),
+        "google/protobuf/source_context.proto" => Some(proto::SOURCE_CONTEXT_PROTO),
+        "google/protobuf/struct.proto" => Some(proto::STRUCT_PROTO),
+        "google/protobuf/timestamp.proto" => Some(proto::TIMESTAMP_PROTO),
+        "google/protobuf/type.proto" => Some(proto::TYPE_PROTO),
+        "google/protobuf/wrappers.proto" => Some(proto::WRAPPERS_PROTO),
+        _ => None,
+    }
+}
+
+pub(crate) fn path_to_proto_path(
+    path: &Path,
+    includes: &[PathBuf],
+) -> anyhow::Result<ProtoPathBuf> {
+    path_to_proto_path_and_include(path, includes).map(|(proto_path, _)| proto_path)
+}
+
+/// Proto path of a file and the include directory it is found in.
+fn path_to_proto_path_and_include(
+    path: &Path,
+    includes: &[PathBuf],
+) -> anyhow::Result<(ProtoPathBuf, PathBuf)> {
+    let original = path;
+    let path = &*normalize_separators(path);
+    for include in includes {
+        let include = &*normalize_separators(include);
+        if include == Path::new(".") && path.is_relative() {
+            // Special handling of `.` to allow using `.` as an include path
+            // and `foo.proto` as input.
+            return Ok((ProtoPathBuf::from_path(path)?, include.to_owned()));
+        }
+        match path.strip_prefix(include) {
+            Ok(stripped) if !stripped.components().any(|c| c == Component::ParentDir) => {
+                return Ok((ProtoPathBuf::from_path(stripped)?, include.to_owned()));
+            }
+            _ => continue,
+        }
+    }
+    // Path may be spelled differently than include, like `./foo/../foo/bar.proto`.
+    let canonical = canonical_path(path);
+    for include in includes {
+        if let Ok(stripped) = canonical.strip_prefix(canonical_path(include)) {
+            return Ok((ProtoPathBuf::from_path(stripped)?, include.clone()));
+        }
+    }
+    Err(ParseAndTypeckError::FileMustResideInImportPath(
+        original.display().to_string(),
+        format!("{:?}", includes),
+    )
+    .into())
+}
+
+/// Resolve `.`, `..` and symlinks if the file exists.
+fn canonical_path(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_owned())
+}
+
+/// Include directories in search order.
+///
+/// With `auto_include_input_dirs`, parent directories of inputs
+/// outside of include path are appended as implicit include directories.
+pub(crate) fn effective_includes(parser: &Parser) -> Vec<PathBuf> {
+    let mut includes = parser
+        .duplicate_include_policy
+        .search_order(&parser.includes);
+    if parser.auto_include_input_dirs {
+        for input in &parser.inputs {
+            if path_to_proto_path(input, &includes).is_ok() {
+                continue;
+            }
+            if let Some(parent) = input.parent() {
+                includes.push(parent.to_owned());
+            }
+        }
+    }
+    includes
+}
+
+/// `.proto` file result provided from the [`ProtoPathResolver`].
+pub struct ResolvedProtoFile {
+    /// For error reporting.
+    pub path: String,
+    /// File content.
+    pub content: Vec<u8>,
+}
+
+/// Find a file in include directories given in search order.
+pub(crate) fn find_in_includes(
+    proto_path: &ProtoPath,
+    includes: &[PathBuf],
+    policy: DuplicateIncludePolicy,
+) -> anyhow::Result<Option<PathBuf>> {
+    let mut found = includes
+        .iter()
+        .map(|include| include.join(proto_path.to_path()))
+        .filter(|fs_path| {
+            let is_file = fs_path.is_file();
+            debug!("probing `{}`: found: {}", fs_path.display(), is_file);
+            is_file
+        });
+    let first = match found.next() {
+        Some(first) => first,
+        None => return Ok(None),
+    };
+    if policy == DuplicateIncludePolicy::Error {
+        let rest: Vec<PathBuf> = found.collect();
+        if !rest.is_empty() {
+            let all: Vec<&PathBuf> = [&first].into_iter().chain(&rest).collect();
+            return Err(ParseAndTypeckError::AmbiguousInclude(
+                proto_path.to_string(),
+                format!("{:?}", all),
+            )
+            .into());
+        }
+    }
+    Ok(Some(first))
+}
+
+/// Recursively list `.proto` files in a directory, ignoring unreadable entries.
+fn list_proto_files(dir: &Path, prefix: &str, files: &mut Vec<ProtoPathBuf>) {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return,
+    };
+    for entry in entries.flatten() {
+        let name = match entry.file_name().into_string() {
+            Ok(name) => name,
+            Err(_) => continue,
+        };
+        let path = format!("{}{}", prefix, name);
+        match entry.file_type() {
+            Ok(t) if t.is_dir() => list_proto_files(&entry.path(), &format!("{}/", path), files),
+            Ok(_) if name.ends_with(".proto") => files.extend(ProtoPathBuf::new(path).ok()),
+            _ => {}
+        }
+    }
+}
+
+/// Check that names of file found at `fs_path` and its directories
+/// match `proto_path` exactly, and not only on case-insensitive filesystem.
+fn check_exact_case(fs_path: &Path, proto_path: &ProtoPath) -> anyhow::Result<()> {
+    let mut path = fs_path;
+    for expected in proto_path.to_str().rsplit('/') {
+        let dir = match path.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir,
+            _ => Path::new("."),
+        };
+        let names: Vec<_> = match fs::read_dir(dir) {
+            Ok(entries) => entries.flatten().map(|e| e.file_name()).collect(),
+            Err(_) => Vec::new(),
+        };
+        if !names.iter().any(|n| n.to_str() == Some(expected)) {
+            if let Some(actual) = names
+                .iter()
+                .find(|n| n.to_str().is_some_and(|n| n.eq_ignore_ascii_case(expected)))
+            {
+                return Err(ParseAndTypeckError::CaseMismatch(
+                    proto_path.to_string(),
+                    dir.join(actual).display().to_string(),
+                )
+                .into());
+            }
+        }
+        path = dir;
+    }
+    Ok(())
+}
+
+fn fs_resolver(
+    includes: &[PathBuf],
+    policy: DuplicateIncludePolicy,
+    require_exact_case: bool,
+) -> impl ProtoPathResolver {
+    struct Impl {
+        includes: Vec<PathBuf>,
+        policy: DuplicateIncludePolicy,
+        require_exact_case: bool,
+    }
+
+    impl fmt::Display for Impl {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "{:?}", self.includes)
+        }
+    }
+
+    impl ProtoPathResolver for Impl {
+        fn resolve(&self, proto_path: &ProtoPath) -> anyhow::Result<Option<ResolvedProtoFile>> {
+            let fs_path = match find_in_includes(proto_path, &self.includes, self.policy)? {
+                Some(fs_path) => fs_path,
+                None => return Ok(None),
+            };
+            if self.require_exact_case {
+                check_exact_case(&fs_path, proto_path)?;
+            }
+            let content = fs::read_to_string(&fs_path).map_err(|e| {
+                ParseAndTypeckError::CouldNotReadFile(fs_path.display().to_string(), e)
+            })?;
+            Ok(Some(ResolvedProtoFile {
+                path: fs_path.display().to_string(),
+                content: content.into_bytes(),
+            }))
+        }
+
+        fn probed(&self, proto_path: &ProtoPath) -> Vec<String> {
+            self.includes
+                .iter()
+                .map(|include| include.join(proto_path.to_path()).display().to_string())
+                .collect()
+        }
+
+        fn files(&self) -> Vec<ProtoPathBuf> {
+            let mut files = Vec::new();
+            for include in &self.includes {
+                list_proto_files(include, "", &mut files);
+            }
+            files
+        }
+    }
+
+    Impl {
+        includes: includes.to_vec(),
+        policy,
+        require_exact_case,
+    }
+}
+
+/// Check no type is defined in more than one file.
+///
+/// Files which do not import each other are typechecked independently,
+/// so such duplicates are not found when files are typechecked.
+pub(crate) fn check_duplicate_types(files: &[FileDescriptorProto]) -> anyhow::Result<()> {
+    let mut defined_in: HashMap<String, &str> = HashMap::new();
+    for file in files {
+        for name in file_type_names(file, true) {
+            if let Some(prev) = defined_in.get(&name) {
+                return Err(ParseAndTypeckError::DuplicateType(
+                    name,
+                    prev.to_string(),
+                    file.name().to_owned(),
+                )
+                .into());
+            }
+            defined_in.insert(name, file.name());
+        }
+    }
+    Ok(())
+}
+
+/// Order files so that each file follows its dependencies,
+/// and otherwise by name, so the result does not depend on order of inputs.
+pub(crate) fn sort_file_descriptors(files: Vec<FileDescriptorProto>) -> Vec<FileDescriptorProto> {
+    let mut files: IndexMap<String, FileDescriptorProto> = files
+        .into_iter()
+        .map(|f| (f.name().to_owned(), f))
+        .collect();
+
+    // Number of not yet emitted dependencies of each file.
+    let mut pending: HashMap<&str, usize> = HashMap::new();
+    let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (name, file) in &files {
+        let deps: HashSet<&str> = file
+            .dependency
+            .iter()
+            .map(|d| d.as_str())
+            .filter(|d| files.contains_key(*d))
+            .collect();
+        pending.insert(name, deps.len());
+        for dep in deps {
+            dependents.entry(dep).or_default().push(name);
+        }
+    }
+
+    let mut ready: BTreeSet<&str> = pending
+        .iter()
+        .filter(|(_, n)| **n == 0)
+        .map(|(name, _)| *name)
+        .collect();
+    let mut order: Vec<String> = Vec::with_capacity(files.len());
+    while let Some(name) = ready.pop_first() {
+        order.push(name.to_owned());
+        for dependent in dependents.get(name).into_iter().flatten() {
+            let n = pending.get_mut(dependent).unwrap();
+            *n -= 1;
+            if *n == 0 {
+                ready.insert(dependent);
+            }
+        }
+    }
+    // Files in import cycle, cannot happen with typechecked files.
+    let mut rest: Vec<&String> = files.keys().filter(|f| !order.contains(f)).collect();
+    rest.sort();
+    let rest: Vec<String> = rest.into_iter().cloned().collect();
+    order.extend(rest);
+
+    order
+        .iter()
+        .map(|name| files.swap_remove(name).unwrap())
+        .collect()
+}
+
+/// Parse `.proto` files using pure Rust implementation.
+pub fn parse_and_typecheck(parser: &Parser) -> anyhow::Result<ParsedAndTypechecked> {
+    let policy = parser.duplicate_include_policy;
+    let includes = effective_includes(parser);
+
+    let mut run = Run {
+        parsed_files: IndexMap::new(),
+        resolver: fs_resolver(&includes, policy, parser.require_exact_case.unwrap_or(true)),
+        read_files: Vec::new(),
+        embedded_files: Vec::new(),
+        embedded_protos: !parser.disable_embedded_protos,
+        prefer_embedded_well_known: parser.prefer_embedded_well_known,
+        warn_well_known_drift: parser.warn_well_known_drift,
+        extra_embedded_protos: parser.extra_embedded_protos.clone(),
+        well_known_types: parser.well_known_types.clone(),
+        include_source_info: parser.include_source_info,
+        parse_stack: Vec::new(),
+        file_loader: file_loader(parser),
+        observer: parser.observer.clone(),
+        fs_files: IndexMap::new(),
+        parallelism: parser.parallelism,
+        parsed_in_parallel: HashMap::new(),
+        cache: parser.cache_dir.clone().map(Cache::new),
+        cache_keys: HashMap::new(),
+        missing_weak_imports: HashMap::new(),
+        seeded: HashMap::new(),
+        strict: parser.strict,
+        default_syntax: match parser.default_syntax {
+            Some(protobuf::reflect::Syntax::Proto3) => model::Syntax::Proto3,
+            _ => model::Syntax::Proto2,
+        },
+        warnings: Vec::new(),
+        unsupported: Vec::new(),
+        max_files: parser.max_files,
+        max_import_depth: parser.max_import_depth,
+        includes: includes.clone(),
+        duplicate_include_policy: policy,
+        prefer_importer_include: parser.prefer_importer_include,
+        require_exact_case: parser.require_exact_case.unwrap_or(true),
+        file_includes: HashMap::new(),
+    };
+
+    let relative_paths = parser
+        .inputs
+        .iter()
+        .map(|input| {
+            let (proto_path, include) = path_to_proto_path_and_include(input, &includes)?;
+            find_in_includes(&proto_path, &includes, policy)?;
+            run.file_includes
+                .entry(proto_path.clone())
+                .or_insert(include);
+            Ok((proto_path, input))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+
+    for (i, (proto_path, path)) in relative_paths.iter().enumerate() {
+        if let Some((_, prev)) = relative_paths[..i]
+            .iter()
+            .find(|(p, prev)| p == proto_path && canonical_path(prev) != canonical_path(path))
+        {
+            return Err(ParseAndTypeckError::InputProtoPathCollision(
+                proto_path.to_string(),
+                prev.display().to_string(),
+                path.display().to_string(),
+            )
+            .into());
+        }
+    }
+
+    // The same file may be specified more than once with different spelling.
+    let mut relative_paths = relative_paths;
+    let mut seen = HashSet::new();
+    relative_paths.retain(|(proto_path, _)| seen.insert(proto_path.clone()));
+
+    let mut seeded = Vec::new();
+    for descriptor in sort_file_descriptors(parser.descriptors.clone()) {
+        let proto_path = ProtoPathBuf::new(descriptor.name().to_owned())?;
+        run.seeded.insert(proto_path.clone(), descriptor);
+        seeded.push(proto_path);
+    }
+    for proto_path in &seeded {
+        run.add_imported_file(proto_path)?;
+    }
+
+    if run.parallelism > 1 {
+        run.parse_in_parallel(&relative_paths);
+    }
+
+    for (proto_path, path) in &relative_paths {
+        if run.parsed_files.contains_key(proto_path.as_path()) {
+            // Already parsed as a dependency of previous input.
+            continue;
+        }
+        let (resolved, source, parsed) = match run.parsed_in_parallel.remove(proto_path) {
+            Some(p) if p.input => (p.resolved, p.source, p.parsed),
+            _ => {
+                let (resolved, source) = run.find_input_file(proto_path, path)?;
+                (resolved, source, None)
+            }
+        };
+        if let FileSource::Read = source {
+            run.add_read_file(&resolved.path, proto_path)?;
+            run.check_well_known_drift(proto_path, &resolved);
+        }
+        run.add_file_content(proto_path, resolved, parsed)?;
+    }
+    for proto_path in &parser.content_inputs {
+        run.add_imported_file(proto_path)?;
+    }
+
+    let file_descriptors = sort_file_descriptors(
+        run.parsed_files
+            .into_iter()
+            .map(|(_, v)| v.descriptor_proto)
+            .collect(),
+    );
+    check_duplicate_types(&file_descriptors)?;
+
+    Ok(ParsedAndTypechecked {
+        relative_paths: relative_paths
+            .into_iter()
+            .map(|(p, _)| p)
+            .chain(
+                parser
+                    .content_inputs
+                    .iter()
+                    .filter(|p| seen.insert((*p).clone()))
+                    .cloned(),
+            )
+            .collect(),
+        file_descriptors,
+        parser: "pure".to_owned(),
+        read_files: run.read_files.into_iter().map(PathBuf::from).collect(),
+        embedded_files: run.embedded_files,
+        warnings: run.warnings,
+        unsupported: run.unsupported,
+    })
+}
+
+/// TODO: this API is to be refactored.
+pub fn parse_and_typecheck_custom(
+    input: &[ProtoPathBuf],
+    resolver: impl ProtoPathResolver,
+) -> anyhow::Result<Vec<FileDescriptorProto>> {
     let mut run = Run {
         parsed_files: IndexMap::new(),
         resolver,
+        read_files: Vec::new(),
+        embedded_files: Vec::new(),
+        embedded_protos: true,
+        prefer_embedded_well_known: false,
+        warn_well_known_drift: false,
+        extra_embedded_protos: Vec::new(),
+        well_known_types: None,
+        include_source_info: false,
+        parse_stack: Vec::new(),
+        file_loader: None,
+        observer: None,
+        fs_files: IndexMap::new(),
+        parallelism: 1,
+        parsed_in_parallel: HashMap::new(),
+        cache: None,
+        cache_keys: HashMap::new(),
+        missing_weak_imports: HashMap::new(),
+        seeded: HashMap::new(),
+        strict: false,
+        default_syntax: model::Syntax::Proto2,
+        warnings: Vec::new(),
+        unsupported: Vec::new(),
+        max_files: None,
+        max_import_depth: None,
+        includes: Vec::new(),
+        duplicate_include_policy: DuplicateIncludePolicy::default(),
+        prefer_importer_include: false,
+        require_exact_case: false,
+        file_includes: HashMap::new(),
     };
 
     for proto_path in input {
@@ -310,12 +1575,57 @@ pub fn parse_and_typecheck_custom(
 #[cfg(test)]
 mod test {
     use std::fmt;
+    use std::fs;
+    use std::io;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use protobuf::descriptor::field_descriptor_proto::Label;
+    use protobuf::descriptor::FileDescriptorSet;
+    use protobuf::UnknownValueRef;
 
     use crate::proto_path::ProtoPath;
+    use crate::pure::parse_and_typecheck::check_exact_case;
+    use crate::pure::parse_and_typecheck::FileLoader;
+    use crate::pure::parse_and_typecheck::ParseObserver;
     use crate::pure::parse_and_typecheck::ProtoPathResolver;
     use crate::pure::parse_and_typecheck::ResolvedProtoFile;
+    use crate::pure::parse_and_typecheck::WellKnownTypes;
+    use crate::pure::parse_single;
+    use crate::DuplicateIncludePolicy;
+    use crate::Parser;
     use crate::ProtoPathBuf;
 
+    #[cfg(windows)]
+    #[test]
+    fn backslash_separators() {
+        use std::path::Path;
+        use std::path::PathBuf;
+
+        use crate::pure::parse_and_typecheck::path_to_proto_path;
+
+        assert_eq!(
+            "sub/a.proto",
+            path_to_proto_path(
+                Path::new("protos\\sub\\a.proto"),
+                &[PathBuf::from("protos")]
+            )
+            .unwrap()
+            .to_str()
+        );
+        assert_eq!(
+            "sub/a.proto",
+            path_to_proto_path(
+                Path::new("root/protos/sub/a.proto"),
+                &[PathBuf::from("root\\protos")]
+            )
+            .unwrap()
+            .to_str()
+        );
+    }
+
     #[test]
     fn parse_and_typecheck_custom() {
         struct ResolverImpl;
@@ -347,4 +1657,1383 @@ mod test {
         assert_eq!(1, resolved.len());
         assert_eq!("Foo", resolved[0].message_type[0].name());
     }
+
+    #[test]
+    fn read_and_embedded_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        let b_proto = dir.path().join("b.proto");
+        fs::write(
+            &a_proto,
+            "syntax = 'proto3'; import 'google/protobuf/empty.proto'; message Apple {}",
+        )
+        .unwrap();
+        fs::write(
+            &b_proto,
+            "syntax = 'proto3'; import 'a.proto'; message Banana { Apple a = 1; }",
+        )
+        .unwrap();
+
+        let parsed = Parser::new()
+            .pure()
+            .include(dir.path())
+            .input(&b_proto)
+            .parse_and_typecheck()
+            .unwrap();
+
+        assert_eq!(vec![b_proto, a_proto], parsed.read_files);
+        assert_eq!(
+            vec!["google/protobuf/empty.proto"],
+            parsed
+                .embedded_files
+                .iter()
+                .map(|p| p.to_str())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn embedded_protos() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        fs::write(
+            &a_proto,
+            "syntax = 'proto3'; import 'google/protobuf/empty.proto'; message Apple {}",
+        )
+        .unwrap();
+
+        let err = Parser::new()
+            .pure()
+            .include(dir.path())
+            .input(&a_proto)
+            .embedded_protos(false)
+            .parse_and_typecheck()
+            .err()
+            .expect("error");
+        assert!(
+            format!("{:#}", err).contains("google/protobuf/empty.proto"),
+            "{:#}",
+            err
+        );
+
+        let parsed = Parser::new()
+            .pure()
+            .include(dir.path())
+            .input(&a_proto)
+            .embedded_protos(false)
+            .embedded_proto(
+                ProtoPathBuf::new("google/protobuf/empty.proto".to_owned()).unwrap(),
+                "syntax = 'proto3'; package google.protobuf; message Empty { int32 x = 1; }",
+            )
+            .parse_and_typecheck()
+            .unwrap();
+        let empty = parsed
+            .file_descriptors
+            .iter()
+            .find(|f| f.name() == "google/protobuf/empty.proto")
+            .unwrap();
+        assert_eq!(1, empty.message_type[0].field.len());
+    }
+
+    #[test]
+    fn typecheck_error_location() {
+        let error = |content: &str| {
+            let err = Parser::new()
+                .pure()
+                .input_content(ProtoPathBuf::new("a.proto".to_owned()).unwrap(), content)
+                .parse_and_typecheck()
+                .err()
+                .expect("error");
+            format!("{}", err.downcast_ref::<super::WithFileError>().unwrap())
+        };
+        assert_eq!(
+            "error in `a.proto:3:5`: object is not found by path `Missing` in scope `.M`",
+            error("syntax = 'proto3';\nmessage M {\n    Missing m = 1;\n}\n")
+        );
+        assert!(
+            error("syntax = 'proto3';\nenum E {\n  A = 0;\n  B = 0;\n}\n")
+                .starts_with("error in `a.proto:4:3`: values `A` and `B`"),
+        );
+        assert_eq!(
+            "error in `a.proto:3:10`: extension not found: x",
+            error("syntax = 'proto3';\nmessage M {\n  option (x) = 1;\n}\n")
+        );
+    }
+
+    #[test]
+    fn string_literals() {
+        let parse = |content: &str| {
+            Parser::new()
+                .pure()
+                .input_content(ProtoPathBuf::new("a.proto".to_owned()).unwrap(), content)
+                .parse_and_typecheck()
+        };
+
+        let parsed = parse(
+            r#"syntax = "proto2";
+            message M {
+                optional bytes b = 1 [default = "\xff\0\u00e9"];
+                optional string s = 2 [default = "\x41\101\u00e9\U0001F600"];
+            }"#,
+        )
+        .unwrap();
+        let fields = &parsed.file_descriptors[0].message_type[0].field;
+        assert_eq!("\\377\\000\\303\\251", fields[0].default_value());
+        assert_eq!("AA\u{e9}\u{1f600}", fields[1].default_value());
+
+        let error = |content: &str| format!("{:#}", parse(content).err().expect("error"));
+        let err = error(
+            "syntax = 'proto2';\nmessage M {\n  optional string s = 1 [default = '\\xff'];\n}\n",
+        );
+        assert!(
+            err.contains("a.proto:3:3")
+                && err.contains("string literal is not valid UTF-8, use `bytes` type"),
+            "{}",
+            err
+        );
+        let err = error("syntax = 'proto2';\noption java_package = 'a\\qb';\n");
+        assert!(
+            err.contains("at 2:") && err.contains("Invalid escape sequence in string literal"),
+            "{}",
+            err
+        );
+    }
+
+    #[test]
+    fn prefer_embedded_well_known() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("google/protobuf")).unwrap();
+        fs::write(
+            dir.path().join("google/protobuf/empty.proto"),
+            "syntax = 'proto3'; package google.protobuf; message Empty { int32 x = 1; }",
+        )
+        .unwrap();
+        let a_proto = dir.path().join("a.proto");
+        fs::write(
+            &a_proto,
+            "syntax = 'proto3'; import 'google/protobuf/empty.proto'; message A { google.protobuf.Empty e = 1; }",
+        )
+        .unwrap();
+
+        let parse = |prefer_embedded_well_known| {
+            Parser::new()
+                .pure()
+                .include(dir.path())
+                .input(&a_proto)
+                .prefer_embedded_well_known(prefer_embedded_well_known)
+                .parse_and_typecheck()
+                .unwrap()
+        };
+
+        let parsed = parse(false);
+        assert_eq!(1, parsed.file_descriptors[0].message_type[0].field.len());
+        assert!(parsed.embedded_files.is_empty());
+
+        let parsed = parse(true);
+        assert_eq!(
+            "google/protobuf/empty.proto",
+            parsed.file_descriptors[0].name()
+        );
+        assert_eq!(0, parsed.file_descriptors[0].message_type[0].field.len());
+        assert_eq!(
+            vec![ProtoPathBuf::new("google/protobuf/empty.proto".to_owned()).unwrap()],
+            parsed.embedded_files
+        );
+        assert_eq!(1, parsed.read_files.len());
+    }
+
+    #[test]
+    fn well_known_types() {
+        struct Pinned;
+
+        impl WellKnownTypes for Pinned {
+            fn version(&self) -> &str {
+                "1.2.3"
+            }
+
+            fn proto(&self, proto_path: &ProtoPath) -> Option<String> {
+                match proto_path.to_str() {
+                    "google/protobuf/empty.proto" => Some(
+                        "syntax = 'proto3'; package google.protobuf; message Empty { int32 x = 1; }"
+                            .to_owned(),
+                    ),
+                    _ => None,
+                }
+            }
+        }
+
+        let parse = |content: &str| {
+            Parser::new()
+                .pure()
+                .input_content(ProtoPathBuf::new("a.proto".to_owned()).unwrap(), content)
+                .well_known_types(Pinned)
+                .parse_and_typecheck()
+        };
+
+        let parsed = parse("syntax = 'proto3'; import 'google/protobuf/empty.proto';").unwrap();
+        assert_eq!(
+            "google/protobuf/empty.proto",
+            parsed.file_descriptors[0].name()
+        );
+        assert_eq!(1, parsed.file_descriptors[0].message_type[0].field.len());
+        assert_eq!(
+            vec![ProtoPathBuf::new("google/protobuf/empty.proto".to_owned()).unwrap()],
+            parsed.embedded_files
+        );
+        // Bundled well-known types are not used.
+        let err = parse("syntax = 'proto3'; import 'google/protobuf/any.proto';")
+            .err()
+            .expect("error");
+        assert!(format!("{:?}", err).contains("is not found"), "{:?}", err);
+    }
+
+    #[test]
+    fn warn_well_known_drift() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("google/protobuf")).unwrap();
+        // Same content with different whitespace.
+        fs::write(
+            dir.path().join("google/protobuf/empty.proto"),
+            crate::proto::EMPTY_PROTO.replace('\n', "\r\n  "),
+        )
+        .unwrap();
+        fs::write(
+            dir.path().join("google/protobuf/duration.proto"),
+            "syntax = 'proto3'; package google.protobuf; message Duration { int64 seconds = 1; }",
+        )
+        .unwrap();
+        let a_proto = dir.path().join("a.proto");
+        fs::write(
+            &a_proto,
+            "syntax = 'proto3'; \
+            import 'google/protobuf/empty.proto'; import 'google/protobuf/duration.proto'; \
+            message A { google.protobuf.Empty e = 1; google.protobuf.Duration d = 2; }",
+        )
+        .unwrap();
+
+        let parse = |warn_well_known_drift| {
+            Parser::new()
+                .pure()
+                .include(dir.path())
+                .input(&a_proto)
+                .warn_well_known_drift(warn_well_known_drift)
+                .parse_and_typecheck()
+                .unwrap()
+                .warnings
+        };
+
+        assert!(parse(false).is_empty());
+        let warnings = parse(true);
+        assert_eq!(1, warnings.len(), "{:?}", warnings);
+        assert!(
+            warnings[0].ends_with(
+                "`google/protobuf/duration.proto` differs from the version bundled with the parser"
+            ),
+            "{:?}",
+            warnings
+        );
+    }
+
+    #[test]
+    fn content() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("b.proto"),
+            "syntax = 'proto3'; message Disk {}",
+        )
+        .unwrap();
+
+        let parsed = Parser::new()
+            .pure()
+            .include(dir.path())
+            .input_content(
+                ProtoPathBuf::new("a.proto".to_owned()).unwrap(),
+                "syntax = 'proto3'; import 'b.proto'; message A { Memory m = 1; }",
+            )
+            .include_content(
+                ProtoPathBuf::new("b.proto".to_owned()).unwrap(),
+                "syntax = 'proto3'; message Memory {}",
+            )
+            .parse_and_typecheck()
+            .unwrap();
+
+        assert_eq!(
+            vec![ProtoPathBuf::new("a.proto".to_owned()).unwrap()],
+            parsed.relative_paths
+        );
+        assert_eq!(
+            vec!["b.proto", "a.proto"],
+            parsed
+                .file_descriptors
+                .iter()
+                .map(|f| f.name())
+                .collect::<Vec<_>>()
+        );
+        assert!(parsed.read_files.is_empty());
+    }
+
+    #[test]
+    fn not_found_lists_probed_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let src = dir.path().join("src");
+        let other = dir.path().join("other");
+        fs::create_dir_all(&src).unwrap();
+        fs::create_dir_all(&other).unwrap();
+        let a_proto = src.join("a.proto");
+        fs::write(&a_proto, "syntax = 'proto3'; import 'x/b.proto';").unwrap();
+
+        let err = Parser::new()
+            .pure()
+            .include(&src)
+            .include(&other)
+            .input(&a_proto)
+            .parse_and_typecheck()
+            .err()
+            .expect("error");
+        let err = format!("{:#}", err);
+        for include in [&src, &other] {
+            let probed = include.join("x").join("b.proto");
+            assert!(err.contains(&probed.display().to_string()), "{}", err);
+        }
+    }
+
+    #[test]
+    fn duplicate_types() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        let b_proto = dir.path().join("b.proto");
+        fs::write(&a_proto, "syntax = 'proto3'; package p; message Foo {}").unwrap();
+        fs::write(
+            &b_proto,
+            "syntax = 'proto3'; package p; message Bar { enum E { A = 0; } } message Foo {}",
+        )
+        .unwrap();
+
+        let err = Parser::new()
+            .pure()
+            .include(dir.path())
+            .inputs([&a_proto, &b_proto])
+            .parse_and_typecheck()
+            .err()
+            .expect("error");
+        assert_eq!(
+            "type `.p.Foo` is defined in both `a.proto` and `b.proto`",
+            format!("{}", err.root_cause())
+        );
+    }
+
+    #[test]
+    fn limits() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        fs::write(&a_proto, "syntax = 'proto3'; import 'b.proto';").unwrap();
+        fs::write(
+            dir.path().join("b.proto"),
+            "syntax = 'proto3'; import 'c.proto';",
+        )
+        .unwrap();
+        fs::write(dir.path().join("c.proto"), "syntax = 'proto3';").unwrap();
+
+        let parse = |max_files: usize, max_import_depth: usize, parallelism: usize| {
+            Parser::new()
+                .pure()
+                .include(dir.path())
+                .input(&a_proto)
+                .max_files(max_files)
+                .max_import_depth(max_import_depth)
+                .parallelism(parallelism)
+                .parse_and_typecheck()
+                .map(|p| p.file_descriptors.len())
+                .map_err(|e| format!("{:#}", e))
+        };
+
+        for parallelism in [1, 4] {
+            assert_eq!(Ok(3), parse(3, 3, parallelism));
+            let err = parse(2, 3, parallelism).unwrap_err();
+            assert!(
+                err.contains("more than 2 files are parsed, last one is `c.proto`"),
+                "{}",
+                err
+            );
+            let err = parse(3, 2, parallelism).unwrap_err();
+            assert!(
+                err.contains("import depth exceeds 2: a.proto -> b.proto -> c.proto"),
+                "{}",
+                err
+            );
+        }
+    }
+
+    #[test]
+    fn proto3_optional() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        fs::write(
+            &a_proto,
+            r#"syntax = "proto3";
+            message Apple {
+                optional int32 a = 1;
+                oneof o { int32 b = 2; }
+                optional string _c = 3;
+                int32 d = 4;
+            }"#,
+        )
+        .unwrap();
+
+        let parsed = Parser::new()
+            .pure()
+            .include(dir.path())
+            .input(&a_proto)
+            .parse_and_typecheck()
+            .unwrap();
+
+        // Same as `protoc` output: synthetic oneofs follow real oneofs.
+        let apple = &parsed.file_descriptors[0].message_type[0];
+        assert_eq!(
+            vec!["o", "_a", "X_c"],
+            apple
+                .oneof_decl
+                .iter()
+                .map(|o| o.name())
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            vec![
+                (Some(1), Some(true)),
+                (Some(0), None),
+                (Some(2), Some(true)),
+                (None, None)
+            ],
+            apple
+                .field
+                .iter()
+                .map(|f| (f.oneof_index, f.proto3_optional))
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn file_loader() {
+        struct Loader;
+
+        impl FileLoader for Loader {
+            fn load(&self, proto_path: &ProtoPath) -> io::Result<Option<String>> {
+                Ok(match proto_path.to_str() {
+                    "a.proto" => Some("syntax = 'proto3'; message Apple {}".to_owned()),
+                    "b.proto" => Some(
+                        "syntax = 'proto3'; import 'a.proto'; message Banana { Apple a = 1; }"
+                            .to_owned(),
+                    ),
+                    _ => None,
+                })
+            }
+        }
+
+        let parsed = Parser::new()
+            .pure()
+            .include("virtual")
+            .input("virtual/b.proto")
+            .file_loader(Loader)
+            .parse_and_typecheck()
+            .unwrap();
+
+        assert_eq!(
+            vec!["a.proto", "b.proto"],
+            parsed
+                .file_descriptors
+                .iter()
+                .map(|f| f.name())
+                .collect::<Vec<_>>()
+        );
+        assert!(parsed.read_files.is_empty());
+    }
+
+    #[test]
+    fn input_proto_path_collision() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a");
+        let b = dir.path().join("b");
+        fs::create_dir(&a).unwrap();
+        fs::create_dir(&b).unwrap();
+        fs::write(a.join("x.proto"), "syntax = 'proto3';").unwrap();
+        fs::write(b.join("x.proto"), "syntax = 'proto3';").unwrap();
+
+        let err = Parser::new()
+            .pure()
+            .include(&a)
+            .include(&b)
+            .input(a.join("x.proto"))
+            .input(b.join("x.proto"))
+            .parse_and_typecheck()
+            .err()
+            .expect("error");
+        assert_eq!(
+            format!(
+                "files `{}` and `{}` both map to protobuf path `x.proto`",
+                a.join("x.proto").display(),
+                b.join("x.proto").display()
+            ),
+            err.root_cause().to_string()
+        );
+    }
+
+    #[test]
+    fn duplicate_include() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = dir.path().join("a");
+        let b = dir.path().join("b");
+        fs::create_dir(&a).unwrap();
+        fs::create_dir(&b).unwrap();
+        fs::write(a.join("dep.proto"), "syntax = 'proto3'; message A {}").unwrap();
+        fs::write(b.join("dep.proto"), "syntax = 'proto3'; message B {}").unwrap();
+        let main_proto = a.join("main.proto");
+        fs::write(&main_proto, "syntax = 'proto3'; import 'dep.proto';").unwrap();
+
+        let parse = |policy| {
+            Parser::new()
+                .pure()
+                .include(&a)
+                .include(&b)
+                .input(&main_proto)
+                .on_duplicate_include(policy)
+                .parse_and_typecheck()
+        };
+        let dep_message = |policy| {
+            let parsed = parse(policy).unwrap();
+            let dep = parsed
+                .file_descriptors
+                .iter()
+                .find(|f| f.name() == "dep.proto")
+                .unwrap();
+            dep.message_type[0].name().to_owned()
+        };
+
+        assert_eq!("A", dep_message(DuplicateIncludePolicy::FirstWins));
+        assert_eq!("B", dep_message(DuplicateIncludePolicy::LastWins));
+        let err = parse(DuplicateIncludePolicy::Error).err().expect("error");
+        assert!(
+            format!("{:#}", err)
+                .contains("protobuf path `dep.proto` is found in more than one include directory"),
+            "{:#}",
+            err
+        );
+    }
+
+    #[test]
+    fn imports_prefer_input_include() {
+        let dir = tempfile::tempdir().unwrap();
+        let vendor = dir.path().join("vendor");
+        let protos = dir.path().join("protos");
+        fs::create_dir(&vendor).unwrap();
+        fs::create_dir(&protos).unwrap();
+        fs::write(
+            vendor.join("common.proto"),
+            "syntax = 'proto3'; message Vendor {}",
+        )
+        .unwrap();
+        fs::write(
+            vendor.join("dep.proto"),
+            "syntax = 'proto3'; message Dep {}",
+        )
+        .unwrap();
+        fs::write(
+            protos.join("common.proto"),
+            "syntax = 'proto3'; message Common {}",
+        )
+        .unwrap();
+        fs::write(
+            protos.join("b.proto"),
+            "syntax = 'proto3'; import 'common.proto'; message B {}",
+        )
+        .unwrap();
+        let a_proto = protos.join("a.proto");
+        fs::write(
+            &a_proto,
+            "syntax = 'proto3'; import 'b.proto'; import 'dep.proto'; \
+            message A { B b = 1; Dep d = 2; }",
+        )
+        .unwrap();
+
+        let parse = |policy, prefer_importer_include| {
+            Parser::new()
+                .pure()
+                .include(&vendor)
+                .include(&protos)
+                .input(&a_proto)
+                .on_duplicate_include(policy)
+                .prefer_importer_include(prefer_importer_include)
+                .parse_and_typecheck()
+        };
+
+        let common_message = |policy, prefer_importer_include| {
+            let parsed = parse(policy, prefer_importer_include).unwrap();
+            assert!(parsed.read_files.contains(&vendor.join("dep.proto")));
+            let common = parsed
+                .file_descriptors
+                .iter()
+                .find(|f| f.name() == "common.proto")
+                .unwrap();
+            common.message_type[0].name().to_owned()
+        };
+        // Same as `protoc`.
+        assert_eq!(
+            "Vendor",
+            common_message(DuplicateIncludePolicy::FirstWins, false)
+        );
+        assert_eq!(
+            "Common",
+            common_message(DuplicateIncludePolicy::FirstWins, true)
+        );
+        assert_eq!(
+            "Common",
+            common_message(DuplicateIncludePolicy::LastWins, true)
+        );
+
+        let err = parse(DuplicateIncludePolicy::Error, true)
+            .err()
+            .expect("error");
+        assert!(
+            format!("{:#}", err).contains(
+                "protobuf path `common.proto` is found in more than one include directory"
+            ),
+            "{:#}",
+            err
+        );
+    }
+
+    #[test]
+    fn auto_include_input_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let include = dir.path().join("include");
+        let protos = dir.path().join("protos");
+        fs::create_dir(&include).unwrap();
+        fs::create_dir(&protos).unwrap();
+        fs::write(
+            protos.join("dep.proto"),
+            "syntax = 'proto3'; message Dep {}",
+        )
+        .unwrap();
+        let main_proto = protos.join("main.proto");
+        fs::write(
+            &main_proto,
+            "syntax = 'proto3'; import 'dep.proto'; message Main { Dep dep = 1; }",
+        )
+        .unwrap();
+
+        let mut parser = Parser::new();
+        parser.pure().include(&include).input(&main_proto);
+        let err = parser.parse_and_typecheck().err().expect("error");
+        assert!(
+            format!("{:#}", err).contains("must reside in include path"),
+            "{:#}",
+            err
+        );
+
+        let parsed = parser
+            .auto_include_input_dirs(true)
+            .parse_and_typecheck()
+            .unwrap();
+        assert_eq!(
+            vec![ProtoPathBuf::new("main.proto".to_owned()).unwrap()],
+            parsed.relative_paths
+        );
+        assert_eq!(2, parsed.file_descriptors.len());
+    }
+
+    #[test]
+    fn redundant_path_spelling() {
+        let dir = tempfile::tempdir().unwrap();
+        let protos = dir.path().join("protos");
+        fs::create_dir(&protos).unwrap();
+        fs::write(protos.join("a.proto"), "syntax = 'proto3'; message A {}").unwrap();
+
+        let parsed = Parser::new()
+            .pure()
+            .include(&protos)
+            .input(protos.join("a.proto"))
+            .input(dir.path().join("./protos/../protos/a.proto"))
+            .parse_and_typecheck()
+            .unwrap();
+        assert_eq!(
+            vec![ProtoPathBuf::new("a.proto".to_owned()).unwrap()],
+            parsed.relative_paths
+        );
+        assert_eq!(1, parsed.file_descriptors.len());
+    }
+
+    #[test]
+    fn file_imported_as_different_proto_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        let sub = dir.path().join("sub");
+        fs::create_dir(&sub).unwrap();
+        fs::write(sub.join("x.proto"), "syntax = 'proto3'; message X {}").unwrap();
+        let main_proto = dir.path().join("main.proto");
+        fs::write(
+            &main_proto,
+            "syntax = 'proto3'; import 'sub/x.proto'; import 'x.proto';",
+        )
+        .unwrap();
+
+        let err = Parser::new()
+            .pure()
+            .include(dir.path())
+            .include(&sub)
+            .input(&main_proto)
+            .parse_and_typecheck()
+            .err()
+            .expect("error");
+        assert!(
+            format!("{:#}", err).contains("is referenced both as `sub/x.proto` and `x.proto`"),
+            "{:#}",
+            err
+        );
+    }
+
+    #[test]
+    fn observer() {
+        #[derive(Default)]
+        struct Counts {
+            parsed: AtomicUsize,
+            converted: AtomicUsize,
+        }
+
+        impl ParseObserver for Arc<Counts> {
+            fn file_parsed(&self, _proto_path: &ProtoPath, _elapsed: Duration) {
+                self.parsed.fetch_add(1, Ordering::Relaxed);
+            }
+
+            fn file_converted(&self, _proto_path: &ProtoPath, _elapsed: Duration) {
+                self.converted.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        fs::write(&a_proto, "syntax = 'proto3'; import 'b.proto';").unwrap();
+        fs::write(dir.path().join("b.proto"), "syntax = 'proto3';").unwrap();
+
+        for parallelism in [1, 2] {
+            let cache_dir = dir.path().join(format!("cache{}", parallelism));
+            let parse = || {
+                let counts = Arc::new(Counts::default());
+                Parser::new()
+                    .pure()
+                    .include(dir.path())
+                    .input(&a_proto)
+                    .parallelism(parallelism)
+                    .cache_dir(&cache_dir)
+                    .observer(counts.clone())
+                    .parse_and_typecheck()
+                    .unwrap();
+                (
+                    counts.parsed.load(Ordering::Relaxed),
+                    counts.converted.load(Ordering::Relaxed),
+                )
+            };
+            assert_eq!((2, 2), parse());
+            // Files parsed in parallel are discarded when taken from cache.
+            assert_eq!((0, 0), parse());
+        }
+    }
+
+    #[test]
+    fn exact_case() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        let a_proto = dir.path().join("a.proto");
+        let b_proto = dir.path().join("sub").join("b.proto");
+        fs::write(&a_proto, "syntax = 'proto3';").unwrap();
+        fs::write(&b_proto, "syntax = 'proto3';").unwrap();
+
+        check_exact_case(&b_proto, ProtoPath::new("sub/b.proto").unwrap()).unwrap();
+        // Paths as found on case-insensitive filesystem.
+        let err = check_exact_case(
+            &dir.path().join("A.proto"),
+            ProtoPath::new("A.proto").unwrap(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            format!(
+                "protobuf path `A.proto` resolves to `{}` which differs in case, \
+                so it is not found on case-sensitive filesystems",
+                a_proto.display()
+            ),
+            err.to_string()
+        );
+        let err = check_exact_case(
+            &dir.path().join("Sub").join("b.proto"),
+            ProtoPath::new("Sub/b.proto").unwrap(),
+        )
+        .unwrap_err();
+        let sub = dir.path().join("sub").display().to_string();
+        assert!(err.to_string().contains(&sub), "{}", err);
+    }
+
+    #[test]
+    fn parallelism() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut inputs = Vec::new();
+        for i in 0..10 {
+            let path = dir.path().join(format!("f{}.proto", i));
+            let import = match i {
+                0 => String::new(),
+                i => format!("import 'f{}.proto';", i / 2),
+            };
+            fs::write(
+                &path,
+                format!(
+                    "syntax = 'proto3'; package p; {} message M{} {{ int32 x = 1; }}",
+                    import, i
+                ),
+            )
+            .unwrap();
+            inputs.push(path);
+        }
+        inputs.reverse();
+
+        let parse = |parallelism| {
+            Parser::new()
+                .pure()
+                .include(dir.path())
+                .inputs(&inputs)
+                .parallelism(parallelism)
+                .parse_and_typecheck()
+                .unwrap()
+        };
+        let sequential = parse(1);
+        let parallel = parse(4);
+        assert_eq!(10, parallel.file_descriptors.len());
+        assert_eq!(sequential.file_descriptors, parallel.file_descriptors);
+        assert_eq!(sequential.read_files, parallel.read_files);
+    }
+
+    #[test]
+    fn parallelism_parse_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        fs::write(&a_proto, "syntax = 'proto3'; import 'b.proto';").unwrap();
+        fs::write(dir.path().join("b.proto"), "syntax = 'proto3'; message {}").unwrap();
+
+        let parse = |parallelism| {
+            let err = Parser::new()
+                .pure()
+                .include(dir.path())
+                .input(&a_proto)
+                .parallelism(parallelism)
+                .parse_and_typecheck()
+                .err()
+                .unwrap();
+            format!("{:#}", err)
+        };
+        let sequential = parse(1);
+        assert!(
+            sequential.contains("b.proto`: at 1:28: Expecting identifier"),
+            "{}",
+            sequential
+        );
+        assert_eq!(sequential, parse(4));
+    }
+
+    #[test]
+    fn deterministic_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let files = [
+            ("z.proto", "syntax = 'proto3';"),
+            ("y.proto", "syntax = 'proto3'; import 'z.proto';"),
+            ("b.proto", "syntax = 'proto3'; import 'y.proto';"),
+            ("a.proto", "syntax = 'proto3';"),
+            (
+                "c.proto",
+                "syntax = 'proto3'; import 'a.proto'; import 'z.proto';",
+            ),
+        ];
+        for (name, content) in files {
+            fs::write(dir.path().join(name), content).unwrap();
+        }
+
+        let parse = |inputs: &[&str]| {
+            Parser::new()
+                .pure()
+                .include(dir.path())
+                .inputs(inputs.iter().map(|i| dir.path().join(i)))
+                .parse_and_typecheck()
+                .unwrap()
+                .file_descriptors
+                .iter()
+                .map(|f| f.name().to_owned())
+                .collect::<Vec<_>>()
+        };
+
+        let expected = ["a.proto", "z.proto", "c.proto", "y.proto", "b.proto"];
+        assert_eq!(expected, *parse(&["b.proto", "c.proto"]));
+        assert_eq!(expected, *parse(&["c.proto", "b.proto"]));
+        assert_eq!(
+            expected,
+            *parse(&["y.proto", "z.proto", "a.proto", "c.proto", "b.proto"])
+        );
+    }
+
+    #[test]
+    fn strict() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        fs::write(
+            &a_proto,
+            r#"syntax = "proto2";
+import "google/protobuf/descriptor.proto";
+message Opt { optional int32 a = 1; extensions 100 to 200; }
+extend Opt { optional int32 b = 100; }
+extend google.protobuf.MessageOptions { optional Opt opt = 50000; }
+message M {
+    option (opt) = { a: 1 [b]: 2 };
+}
+"#,
+        )
+        .unwrap();
+
+        let parse = |strict| {
+            Parser::new()
+                .pure()
+                .include(dir.path())
+                .input(&a_proto)
+                .strict(strict)
+                .parse_and_typecheck()
+        };
+
+        let parsed = parse(false).unwrap();
+        assert_eq!(1, parsed.warnings.len());
+        assert!(
+            parsed.warnings[0].ends_with(
+                "a.proto:7:12: extension field `[b]` in value of option `(opt)` is not supported"
+            ),
+            "{}",
+            parsed.warnings[0]
+        );
+        assert_eq!(1, parsed.unsupported.len());
+        assert_eq!(
+            "a.proto:7:12: extension field `[b]` in value of option `(opt)`",
+            parsed.unsupported[0].to_string()
+        );
+
+        let err = parse(true).err().expect("error");
+        assert!(
+            format!("{:#}", err).contains("7:12: extension field `[b]`"),
+            "{:#}",
+            err
+        );
+    }
+
+    #[test]
+    fn default_syntax() {
+        let parser = |content: &str, default_syntax: Option<protobuf::reflect::Syntax>| {
+            let mut parser = Parser::new();
+            parser
+                .pure()
+                .input_content(ProtoPathBuf::new("a.proto".to_owned()).unwrap(), content);
+            if let Some(default_syntax) = default_syntax {
+                parser.default_syntax(default_syntax);
+            }
+            parser
+        };
+        let parse = |content: &str, default_syntax| {
+            parser(content, default_syntax)
+                .parse_and_typecheck()
+                .unwrap()
+        };
+        let proto3 = Some(protobuf::reflect::Syntax::Proto3);
+
+        let parsed = parse("message M { int32 a = 1; }", proto3);
+        assert!(parsed.warnings.is_empty());
+        assert_eq!("proto3", parsed.file_descriptors[0].syntax());
+
+        // Field without label is not allowed in proto2.
+        let err = parser("message M { int32 a = 1; }", None)
+            .parse_and_typecheck()
+            .err()
+            .expect("error");
+        let err = format!("{:#}", err);
+        assert!(
+            err.contains(
+                "error in `a.proto`: at 1:13: field label `optional`, `required` or `repeated` \
+                is required in proto2"
+            ),
+            "{}",
+            err
+        );
+
+        // Explicit declaration wins.
+        let parsed = parse(
+            "syntax = 'proto2'; message M { optional int32 a = 1; }",
+            proto3,
+        );
+        assert_eq!("proto2", parsed.file_descriptors[0].syntax());
+
+        let parsed = parse("message M { optional int32 a = 1; }", None);
+        assert!(!parsed.file_descriptors[0].has_syntax());
+
+        // Missing declaration is an error in strict mode.
+        let err = parser("message M { int32 a = 1; }", proto3)
+            .strict(true)
+            .parse_and_typecheck()
+            .err()
+            .expect("error");
+        let err = format!("{:#}", err);
+        assert!(
+            err.contains("error in `a.proto:1:1`: no syntax specified"),
+            "{}",
+            err
+        );
+        parser("syntax = 'proto3'; message M { int32 a = 1; }", proto3)
+            .strict(true)
+            .parse_and_typecheck()
+            .unwrap();
+    }
+
+    #[test]
+    fn import_public() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        let b_proto = dir.path().join("b.proto");
+        let c_proto = dir.path().join("c.proto");
+        fs::write(&a_proto, "syntax = 'proto3'; package a; message Apple {}").unwrap();
+        fs::write(
+            &b_proto,
+            "syntax = 'proto3'; import 'google/protobuf/empty.proto'; import public 'a.proto';",
+        )
+        .unwrap();
+        fs::write(
+            &c_proto,
+            "syntax = 'proto3'; import 'b.proto'; message Cherry { a.Apple a = 1; }",
+        )
+        .unwrap();
+
+        let parsed = Parser::new()
+            .pure()
+            .include(dir.path())
+            .input(&c_proto)
+            .parse_and_typecheck()
+            .unwrap();
+
+        let b = parsed
+            .file_descriptors
+            .iter()
+            .find(|f| f.name() == "b.proto")
+            .unwrap();
+        assert_eq!(vec![1], b.public_dependency);
+        let c = parsed
+            .file_descriptors
+            .iter()
+            .find(|f| f.name() == "c.proto")
+            .unwrap();
+        assert_eq!(".a.Apple", c.message_type[0].field[0].type_name());
+    }
+
+    #[test]
+    fn import_not_public() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        let b_proto = dir.path().join("b.proto");
+        let c_proto = dir.path().join("c.proto");
+        fs::write(&a_proto, "syntax = 'proto3'; package a; message Apple {}").unwrap();
+        fs::write(&b_proto, "syntax = 'proto3'; import 'a.proto';").unwrap();
+        fs::write(
+            &c_proto,
+            "syntax = 'proto3'; import 'b.proto'; message Cherry { a.Apple a = 1; }",
+        )
+        .unwrap();
+
+        let err = Parser::new()
+            .pure()
+            .include(dir.path())
+            .input(&c_proto)
+            .parse_and_typecheck()
+            .err()
+            .unwrap();
+        // `a.proto` is imported by `b.proto`, but not re-exported.
+        assert!(
+            format!("{:#}", err).contains("object is not found by path `a.Apple`"),
+            "{:#}",
+            err
+        );
+    }
+
+    #[test]
+    fn option_type_in_transitive_import() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        let b_proto = dir.path().join("b.proto");
+        let c_proto = dir.path().join("c.proto");
+        fs::write(
+            &a_proto,
+            "syntax = 'proto2'; package a; enum Color { RED = 0; GREEN = 1; }",
+        )
+        .unwrap();
+        fs::write(
+            &b_proto,
+            "syntax = 'proto2'; package b; import 'a.proto';\n\
+            import 'google/protobuf/descriptor.proto';\n\
+            extend google.protobuf.MessageOptions { optional a.Color color = 50000; }",
+        )
+        .unwrap();
+        // `a.Color` is not visible from `c.proto`, but the option type is resolved
+        // in `b.proto`, so `protoc` accepts it.
+        fs::write(
+            &c_proto,
+            "syntax = 'proto2'; import 'b.proto'; message M { option (b.color) = GREEN; }",
+        )
+        .unwrap();
+
+        let parsed = Parser::new()
+            .pure()
+            .include(dir.path())
+            .input(&c_proto)
+            .parse_and_typecheck()
+            .unwrap();
+        let c = parsed.file_descriptors.last().unwrap();
+        assert_eq!("c.proto", c.name());
+        let options = c.message_type[0].options.special_fields.unknown_fields();
+        assert_eq!(Some(UnknownValueRef::Varint(1)), options.get(50000),);
+    }
+
+    #[test]
+    fn import_weak() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        let b_proto = dir.path().join("b.proto");
+        fs::write(&a_proto, "syntax = 'proto2'; message Apple {}").unwrap();
+        fs::write(
+            &b_proto,
+            "syntax = 'proto2'; import weak 'missing.proto'; import weak 'a.proto';",
+        )
+        .unwrap();
+
+        let parsed = Parser::new()
+            .pure()
+            .include(dir.path())
+            .input(&b_proto)
+            .parse_and_typecheck()
+            .unwrap();
+
+        // Missing file is kept in dependencies, so indices are preserved.
+        assert_eq!(2, parsed.file_descriptors.len());
+        let b = &parsed.file_descriptors[1];
+        assert_eq!("b.proto", b.name());
+        assert_eq!(vec!["missing.proto", "a.proto"], b.dependency);
+        assert_eq!(vec![0, 1], b.weak_dependency);
+        assert_eq!(1, parsed.warnings.len());
+        assert!(
+            parsed.warnings[0].ends_with("weak import `missing.proto` not found, skipping"),
+            "{}",
+            parsed.warnings[0]
+        );
+    }
+
+    #[test]
+    fn import_cycle() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        let b_proto = dir.path().join("b.proto");
+        fs::write(&a_proto, "syntax = 'proto3'; import 'b.proto';").unwrap();
+        fs::write(&b_proto, "syntax = 'proto3'; import 'a.proto';").unwrap();
+
+        let err = Parser::new()
+            .pure()
+            .include(dir.path())
+            .input(&a_proto)
+            .parse_and_typecheck()
+            .err()
+            .expect("error");
+        assert!(
+            format!("{:#}", err).contains("import cycle: a.proto -> b.proto -> a.proto"),
+            "{:#}",
+            err
+        );
+    }
+
+    #[test]
+    fn edition_2023() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        fs::write(
+            &a_proto,
+            r#"edition = "2023";
+            enum Color {
+                NONE = 0;
+                RED = 1;
+            }
+            message Apple {
+                int32 a = 1;
+                repeated int32 b = 2;
+                repeated int32 c = 3 [packed = false];
+                repeated string d = 4;
+                Color e = 5 [default = RED];
+                Apple f = 6;
+            }"#,
+        )
+        .unwrap();
+
+        let parsed = Parser::new()
+            .pure()
+            .include(dir.path())
+            .input(&a_proto)
+            .parse_and_typecheck()
+            .unwrap();
+
+        let file = &parsed.file_descriptors[0];
+        assert_eq!("editions", file.syntax());
+        assert_eq!(
+            Some(1000),
+            file.special_fields
+                .unknown_fields()
+                .get(14)
+                .map(|v| match v {
+                    UnknownValueRef::Varint(v) => v,
+                    _ => panic!(),
+                })
+        );
+        let apple = &file.message_type[0];
+        assert_eq!(Label::LABEL_OPTIONAL, apple.field[0].label());
+        assert_eq!(None, apple.field[0].proto3_optional);
+        assert_eq!(Some(true), apple.field[1].options.packed);
+        assert_eq!(Some(false), apple.field[2].options.packed);
+        assert_eq!(None, apple.field[3].options.packed);
+        assert_eq!(Label::LABEL_OPTIONAL, apple.field[4].label());
+        assert_eq!("RED", apple.field[4].default_value());
+        assert_eq!(".Color", apple.field[4].type_name());
+        assert_eq!(".Apple", apple.field[5].type_name());
+
+        let b_proto = dir.path().join("b.proto");
+        fs::write(&b_proto, r#"edition = "2024";"#).unwrap();
+        let err = Parser::new()
+            .pure()
+            .include(dir.path())
+            .input(&b_proto)
+            .parse_and_typecheck()
+            .err()
+            .expect("error");
+        assert!(
+            format!("{:#}", err).contains("unsupported edition: `2024`"),
+            "{:#}",
+            err
+        );
+    }
+
+    #[test]
+    fn bom_and_crlf() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        fs::write(
+            &a_proto,
+            "\u{feff}syntax = 'proto3';\r\n\r\n// Fruit.\r\nmessage Apple {\r\n}\r\n",
+        )
+        .unwrap();
+
+        for parallelism in [1, 2] {
+            let parsed = Parser::new()
+                .pure()
+                .include(dir.path())
+                .input(&a_proto)
+                .include_source_info(true)
+                .parallelism(parallelism)
+                .parse_and_typecheck()
+                .unwrap();
+            let location = &parsed.file_descriptors[0].source_code_info.location[0];
+            assert_eq!(vec![3, 0, 4, 1], location.span);
+            assert_eq!(" Fruit.\n", location.leading_comments());
+        }
+    }
+
+    #[test]
+    fn add_descriptor_set() {
+        let a = parse_single(
+            ProtoPath::new("dep/a.proto").unwrap(),
+            "syntax = 'proto3'; package a; message Apple {}",
+            &[],
+        )
+        .unwrap();
+        let b = parse_single(
+            ProtoPath::new("dep/b.proto").unwrap(),
+            "syntax = 'proto3'; package b; import 'dep/a.proto'; message Box { a.Apple a = 1; }",
+            std::slice::from_ref(&a),
+        )
+        .unwrap();
+        let mut descriptor_set = FileDescriptorSet::new();
+        // Dependencies may come in any order.
+        descriptor_set.file = vec![b, a];
+
+        let dir = tempfile::tempdir().unwrap();
+        let c_proto = dir.path().join("c.proto");
+        fs::write(
+            &c_proto,
+            "syntax = 'proto3'; import 'dep/b.proto'; message C { b.Box box = 1; }",
+        )
+        .unwrap();
+
+        for parallelism in [1, 2] {
+            let parsed = Parser::new()
+                .pure()
+                .include(dir.path())
+                .input(&c_proto)
+                .add_descriptor_set(descriptor_set.clone())
+                .parallelism(parallelism)
+                .parse_and_typecheck()
+                .unwrap();
+            let c = parsed.file_descriptors.last().unwrap();
+            assert_eq!("c.proto", c.name());
+            assert_eq!(".b.Box", c.message_type[0].field[0].type_name());
+            assert!(parsed.read_files.iter().all(|p| p.ends_with("c.proto")));
+            assert!(parsed.embedded_files.is_empty());
+        }
+    }
+
+    #[test]
+    fn add_descriptor_set_cache_dir() {
+        let dir = tempfile::tempdir().unwrap();
+        let cache_dir = dir.path().join("cache");
+        // Dependency of seeded file is found in include path.
+        fs::write(
+            dir.path().join("a.proto"),
+            "syntax = 'proto3'; package a; message Apple {}",
+        )
+        .unwrap();
+        let a = parse_single(
+            ProtoPath::new("a.proto").unwrap(),
+            "syntax = 'proto3'; package a; message Apple {}",
+            &[],
+        )
+        .unwrap();
+        let b = parse_single(
+            ProtoPath::new("b.proto").unwrap(),
+            "syntax = 'proto3'; package b; import 'a.proto'; message Box { a.Apple a = 1; }",
+            std::slice::from_ref(&a),
+        )
+        .unwrap();
+        let mut descriptor_set = FileDescriptorSet::new();
+        descriptor_set.file = vec![b];
+        let c_proto = dir.path().join("c.proto");
+        fs::write(
+            &c_proto,
+            "syntax = 'proto3'; import 'b.proto'; message C { b.Box box = 1; }",
+        )
+        .unwrap();
+
+        let parse = || {
+            Parser::new()
+                .pure()
+                .include(dir.path())
+                .input(&c_proto)
+                .add_descriptor_set(descriptor_set.clone())
+                .cache_dir(&cache_dir)
+                .parse_and_typecheck()
+                .unwrap()
+                .file_descriptors
+        };
+        let first = parse();
+        assert_eq!(
+            vec!["a.proto", "b.proto", "c.proto"],
+            first.iter().map(|f| f.name()).collect::<Vec<_>>()
+        );
+        assert_eq!(first[0], a);
+        assert_eq!(first, parse());
+    }
 }
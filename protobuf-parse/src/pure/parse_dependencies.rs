@@ -1,8 +1,24 @@
+use std::collections::HashSet;
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::Context;
 use protobuf::descriptor::FileDescriptorProto;
 
+use crate::proto_path::ProtoPath;
+use crate::proto_path::ProtoPathBuf;
 use crate::pure::convert::populate_dependencies;
 use crate::pure::model;
+use crate::pure::parse_and_typecheck::embedded_proto;
+use crate::pure::parse_and_typecheck::find_in_includes;
 use crate::pure::parser::ParserErrorWithLocation;
+use crate::DuplicateIncludePolicy;
+
+#[derive(Debug, thiserror::Error)]
+enum ParseDependenciesError {
+    #[error("protobuf path `{0}` is not found in import path {1:?}")]
+    FileNotFoundInImportPath(String, Vec<PathBuf>),
+}
 
 /// Parse imports from a `.proto` file.
 ///
@@ -14,11 +30,113 @@ pub fn parse_dependencies(content: &str) -> Result<FileDescriptorProto, ParserEr
     Ok(output)
 }
 
+/// Modifier of import statement.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportVisibility {
+    /// `import`
+    Default,
+    /// `import public`
+    Public,
+    /// `import weak`
+    Weak,
+}
+
+/// Import statement of a `.proto` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportInfo {
+    /// Imported file, like `google/protobuf/empty.proto`.
+    pub path: ProtoPathBuf,
+    /// Modifier of the import.
+    pub visibility: ImportVisibility,
+}
+
+/// Parse imports from a `.proto` file, in declaration order.
+///
+/// Unlike [`parse_dependencies`], modifiers are returned along with each import.
+pub fn imports_of(content: &str) -> Result<Vec<ImportInfo>, ParserErrorWithLocation> {
+    let input = model::FileDescriptor::parse(content)?;
+    Ok(input
+        .imports
+        .into_iter()
+        .map(|import| ImportInfo {
+            path: import.path,
+            visibility: match import.vis {
+                model::ImportVis::Default => ImportVisibility::Default,
+                model::ImportVis::Public => ImportVisibility::Public,
+                model::ImportVis::Weak => ImportVisibility::Weak,
+            },
+        })
+        .collect())
+}
+
+/// Filesystem paths of `inputs` and all the files they import transitively,
+/// found in `includes` without typechecking.
+///
+/// This is what build scripts need for `cargo:rerun-if-changed`.
+/// Imports of protos bundled with this crate and missing weak imports
+/// are skipped if not found in include path.
+pub fn resolve_dependency_graph(
+    includes: &[PathBuf],
+    inputs: &[PathBuf],
+) -> anyhow::Result<Vec<PathBuf>> {
+    let mut result: Vec<PathBuf> = Vec::new();
+    for input in inputs {
+        if !result.contains(input) {
+            result.push(input.clone());
+        }
+    }
+
+    let mut seen_imports = HashSet::new();
+    let mut i = 0;
+    while i < result.len() {
+        let path = result[i].clone();
+        i += 1;
+
+        let content =
+            fs::read_to_string(&path).with_context(|| format!("reading `{}`", path.display()))?;
+        let deps = parse_dependencies(&content)
+            .with_context(|| format!("parsing `{}`", path.display()))?;
+
+        for (index, dep) in deps.dependency.iter().enumerate() {
+            if !seen_imports.insert(dep.clone()) {
+                continue;
+            }
+            let proto_path = ProtoPath::new(dep)?;
+            match find_in_includes(proto_path, includes, DuplicateIncludePolicy::FirstWins)? {
+                Some(fs_path) => {
+                    if !result.contains(&fs_path) {
+                        result.push(fs_path);
+                    }
+                }
+                None if embedded_proto(proto_path).is_some() => {}
+                None if deps.weak_dependency.contains(&(index as i32)) => {}
+                None => {
+                    return Err(ParseDependenciesError::FileNotFoundInImportPath(
+                        dep.clone(),
+                        includes.to_vec(),
+                    )
+                    .into())
+                }
+            }
+        }
+    }
+    Ok(result)
+}
+
 #[cfg(test)]
 mod test {
+    use std::fs;
+
+    use super::imports_of;
+    use super::parse_dependencies;
+    use super::resolve_dependency_graph;
+    use super::ImportInfo;
+    use super::ImportVisibility;
+    use crate::ProtoPathBuf;
+
     #[test]
-    fn parse_dependencies() {
-        let deps = crate::pure::parse_dependencies::parse_dependencies(
+    fn test_parse_dependencies() {
+        let deps = parse_dependencies(
             r"
 syntax = 'proto3';
 
@@ -38,4 +156,45 @@ message IgnoreMe {}
         );
         assert_eq!(&[1], &deps.public_dependency[..]);
     }
+
+    #[test]
+    fn test_imports_of() {
+        let imports = imports_of(
+            "syntax = 'proto3'; import 'a.proto'; import public 'b.proto'; import weak 'c/d.proto';",
+        )
+        .unwrap();
+        let import = |path: &str, visibility| ImportInfo {
+            path: ProtoPathBuf::new(path.to_owned()).unwrap(),
+            visibility,
+        };
+        assert_eq!(
+            vec![
+                import("a.proto", ImportVisibility::Default),
+                import("b.proto", ImportVisibility::Public),
+                import("c/d.proto", ImportVisibility::Weak),
+            ],
+            imports
+        );
+    }
+
+    #[test]
+    fn test_resolve_dependency_graph() {
+        let dir = tempfile::tempdir().unwrap();
+        let a_proto = dir.path().join("a.proto");
+        let b_proto = dir.path().join("sub/b.proto");
+        let c_proto = dir.path().join("c.proto");
+        fs::create_dir(dir.path().join("sub")).unwrap();
+        fs::write(
+            &a_proto,
+            "syntax = 'proto3'; import 'sub/b.proto'; import 'google/protobuf/empty.proto';",
+        )
+        .unwrap();
+        fs::write(&b_proto, "syntax = 'proto3'; import 'c.proto';").unwrap();
+        fs::write(&c_proto, "syntax = 'proto3'; import public 'sub/b.proto';").unwrap();
+
+        let paths =
+            resolve_dependency_graph(&[dir.path().to_owned()], std::slice::from_ref(&a_proto))
+                .unwrap();
+        assert_eq!(vec![a_proto, b_proto, c_proto], paths);
+    }
 }
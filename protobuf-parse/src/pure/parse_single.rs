@@ -0,0 +1,262 @@
+use protobuf::descriptor::field_descriptor_proto::Label;
+use protobuf::descriptor::field_descriptor_proto::Type;
+use protobuf::descriptor::DescriptorProto;
+use protobuf::descriptor::EnumDescriptorProto;
+use protobuf::descriptor::FieldDescriptorProto;
+use protobuf::descriptor::FileDescriptorProto;
+use protobuf::descriptor::ServiceDescriptorProto;
+use protobuf::reflect::FileDescriptor;
+use protobuf::UnknownValueRef;
+use protobuf_support::lexer::loc::Loc;
+
+use crate::proto_path::ProtoPath;
+use crate::proto_path::ProtoPathBuf;
+use crate::protobuf_path::ProtobufPath;
+use crate::pure::convert;
+use crate::pure::model;
+use crate::FileDescriptorPair;
+use crate::ProtobufAbsPath;
+
+/// Parse and typecheck a single `.proto` file content without filesystem access.
+///
+/// `deps` must contain all the files `content` imports, including transitive imports.
+pub fn parse_single(
+    name: &ProtoPath,
+    content: &str,
+    deps: &[FileDescriptorProto],
+) -> anyhow::Result<FileDescriptorProto> {
+    let parsed = model::FileDescriptor::parse_collect_errors(content)?;
+
+    let descriptors = FileDescriptor::new_dynamic_fds(deps.to_vec(), &[])?;
+    let deps: Vec<FileDescriptorPair> = deps
+        .iter()
+        .zip(descriptors)
+        .map(|(proto, descriptor)| FileDescriptorPair {
+            parsed: model_from_descriptor(proto),
+            descriptor_proto: proto.clone(),
+            descriptor,
+        })
+        .collect();
+
+    Ok(convert::file_descriptor(name, &parsed, &deps, None, false)?.descriptor)
+}
+
+fn with_loc<T>(t: T) -> model::WithLoc<T> {
+    model::WithLoc {
+        loc: Loc::start(),
+        t,
+    }
+}
+
+/// Restore model from descriptor of already typechecked file.
+///
+/// Only the parts of the model needed to resolve types and to validate the file
+/// are restored, options are not.
+pub(crate) fn model_from_descriptor(proto: &FileDescriptorProto) -> model::FileDescriptor {
+    model::FileDescriptor {
+        imports: proto
+            .dependency
+            .iter()
+            .enumerate()
+            .map(|(i, path)| model::Import {
+                path: ProtoPathBuf::new(path.clone()).expect("valid path in descriptor"),
+                vis: if proto.public_dependency.contains(&(i as i32)) {
+                    model::ImportVis::Public
+                } else {
+                    model::ImportVis::Default
+                },
+                loc: Loc::start(),
+            })
+            .collect(),
+        package: ProtobufAbsPath::package_from_file_proto(proto),
+        syntax: match proto.syntax() {
+            "proto3" => model::Syntax::Proto3,
+            "editions" => match proto
+                .special_fields
+                .unknown_fields()
+                .get(convert::FILE_DESCRIPTOR_PROTO_EDITION)
+            {
+                Some(UnknownValueRef::Varint(n)) => model::Edition::from_number(n)
+                    .map(model::Syntax::Editions)
+                    .unwrap_or(model::Syntax::Proto2),
+                _ => model::Syntax::Proto2,
+            },
+            _ => model::Syntax::Proto2,
+        },
+        syntax_declared: proto.has_syntax(),
+        messages: proto.message_type.iter().map(message).collect(),
+        enums: proto.enum_type.iter().map(enumeration).collect(),
+        extensions: proto.extension.iter().map(extension).collect(),
+        services: proto.service.iter().map(service).collect(),
+        ..model::FileDescriptor::default()
+    }
+}
+
+fn message(proto: &DescriptorProto) -> model::WithLoc<model::Message> {
+    let mut fields: Vec<model::WithLoc<model::FieldOrOneOf>> = Vec::new();
+    for f in &proto.field {
+        // Synthetic oneofs of proto3 optional fields are not declared in `.proto` file.
+        let oneof = if f.has_oneof_index() && !f.proto3_optional() {
+            proto.oneof_decl.get(f.oneof_index() as usize)
+        } else {
+            None
+        };
+        let oneof = match oneof {
+            Some(oneof) => oneof,
+            None => {
+                fields.push(with_loc(model::FieldOrOneOf::Field(with_loc(field(f)))));
+                continue;
+            }
+        };
+        let existing = fields.iter_mut().find_map(|fo| match &mut fo.t {
+            model::FieldOrOneOf::OneOf(o) if o.name == oneof.name() => Some(o),
+            _ => None,
+        });
+        match existing {
+            Some(o) => o.fields.push(with_loc(field(f))),
+            None => fields.push(with_loc(model::FieldOrOneOf::OneOf(model::OneOf {
+                name: oneof.name().to_owned(),
+                fields: vec![with_loc(field(f))],
+                options: Vec::new(),
+            }))),
+        }
+    }
+    with_loc(model::Message {
+        name: proto.name().to_owned(),
+        fields,
+        messages: proto.nested_type.iter().map(message).collect(),
+        enums: proto.enum_type.iter().map(enumeration).collect(),
+        extension_ranges: proto
+            .extension_range
+            .iter()
+            .map(|r| model::FieldNumberRange {
+                from: r.start(),
+                to: r.end() - 1,
+            })
+            .collect(),
+        reserved_nums: proto
+            .reserved_range
+            .iter()
+            .map(|r| model::FieldNumberRange {
+                from: r.start(),
+                to: r.end() - 1,
+            })
+            .collect(),
+        reserved_names: proto.reserved_name.clone(),
+        extensions: proto.extension.iter().map(extension).collect(),
+        ..model::Message::default()
+    })
+}
+
+fn extension(proto: &FieldDescriptorProto) -> model::WithLoc<model::Extension> {
+    with_loc(model::Extension {
+        extendee: ProtobufPath::new(proto.extendee()),
+        field: with_loc(field(proto)),
+    })
+}
+
+fn service(proto: &ServiceDescriptorProto) -> model::WithLoc<model::Service> {
+    with_loc(model::Service {
+        name: proto.name().to_owned(),
+        methods: proto
+            .method
+            .iter()
+            .map(|m| {
+                with_loc(model::Method {
+                    name: m.name().to_owned(),
+                    input_type: ProtobufPath::new(m.input_type()),
+                    output_type: ProtobufPath::new(m.output_type()),
+                    client_streaming: m.client_streaming(),
+                    server_streaming: m.server_streaming(),
+                    options: Vec::new(),
+                })
+            })
+            .collect(),
+        options: Vec::new(),
+    })
+}
+
+fn enumeration(proto: &EnumDescriptorProto) -> model::WithLoc<model::Enumeration> {
+    with_loc(model::Enumeration {
+        name: proto.name().to_owned(),
+        values: proto
+            .value
+            .iter()
+            .map(|v| {
+                with_loc(model::EnumValue {
+                    name: v.name().to_owned(),
+                    number: v.number(),
+                    options: Vec::new(),
+                })
+            })
+            .collect(),
+        options: Vec::new(),
+        // Unlike message reserved ranges, enum reserved ranges are inclusive.
+        reserved_nums: proto
+            .reserved_range
+            .iter()
+            .map(|r| model::FieldNumberRange {
+                from: r.start(),
+                to: r.end(),
+            })
+            .collect(),
+        reserved_names: proto.reserved_name.clone(),
+    })
+}
+
+fn field(proto: &FieldDescriptorProto) -> model::Field {
+    let typ = match proto.type_() {
+        Type::TYPE_DOUBLE => model::FieldType::Double,
+        Type::TYPE_FLOAT => model::FieldType::Float,
+        Type::TYPE_INT64 => model::FieldType::Int64,
+        Type::TYPE_UINT64 => model::FieldType::Uint64,
+        Type::TYPE_INT32 => model::FieldType::Int32,
+        Type::TYPE_FIXED64 => model::FieldType::Fixed64,
+        Type::TYPE_FIXED32 => model::FieldType::Fixed32,
+        Type::TYPE_BOOL => model::FieldType::Bool,
+        Type::TYPE_STRING => model::FieldType::String,
+        Type::TYPE_BYTES => model::FieldType::Bytes,
+        Type::TYPE_UINT32 => model::FieldType::Uint32,
+        Type::TYPE_SFIXED32 => model::FieldType::Sfixed32,
+        Type::TYPE_SFIXED64 => model::FieldType::Sfixed64,
+        Type::TYPE_SINT32 => model::FieldType::Sint32,
+        Type::TYPE_SINT64 => model::FieldType::Sint64,
+        Type::TYPE_MESSAGE | Type::TYPE_ENUM | Type::TYPE_GROUP => {
+            model::FieldType::MessageOrEnum(ProtobufPath::new(proto.type_name()))
+        }
+    };
+    model::Field {
+        name: proto.name().to_owned(),
+        rule: Some(match proto.label() {
+            Label::LABEL_OPTIONAL => model::Rule::Optional,
+            Label::LABEL_REQUIRED => model::Rule::Required,
+            Label::LABEL_REPEATED => model::Rule::Repeated,
+        }),
+        typ,
+        number: proto.number(),
+        options: Vec::new(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::proto_path::ProtoPath;
+    use crate::pure::parse_single;
+
+    #[test]
+    fn resolve_types_from_deps() {
+        let a = parse_single(
+            ProtoPath::new("a.proto").unwrap(),
+            "syntax = 'proto3'; package a; message Apple { enum Kind { RED = 0; } }",
+            &[],
+        )
+        .unwrap();
+        let b = parse_single(
+            ProtoPath::new("b.proto").unwrap(),
+            "syntax = 'proto3'; import 'a.proto'; message Banana { a.Apple.Kind k = 1; }",
+            &[a],
+        )
+        .unwrap();
+        assert_eq!(".a.Apple.Kind", b.message_type[0].field[0].type_name());
+    }
+}
@@ -1,7 +1,11 @@
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
 use std::str;
 
 use protobuf_support::lexer::int;
 use protobuf_support::lexer::lexer_impl::LexerError;
+use protobuf_support::lexer::loc::Loc;
 use protobuf_support::lexer::num_lit::NumLit;
 use protobuf_support::lexer::parser_language::ParserLanguage;
 use protobuf_support::lexer::str_lit::StrLitDecodeError;
@@ -17,6 +21,7 @@ use crate::protobuf_ident::ProtobufIdent;
 use crate::protobuf_path::ProtobufPath;
 use crate::protobuf_rel_path::ProtobufRelPath;
 use crate::pure::model;
+use crate::pure::model::Edition;
 use crate::pure::model::EnumValue;
 use crate::pure::model::Enumeration;
 use crate::pure::model::Extension;
@@ -40,6 +45,7 @@ use crate::pure::model::Rule;
 use crate::pure::model::Service;
 use crate::pure::model::Syntax;
 use crate::pure::model::WithLoc;
+use crate::pure::model::FIELD_NUMBER_MAX;
 
 /// Basic information about parsing error.
 #[derive(Debug, thiserror::Error)]
@@ -55,16 +61,22 @@ pub(crate) enum ParserError {
     ExpectConstant,
     #[error("unknown syntax")]
     UnknownSyntax,
+    #[error("unsupported edition: `{0}`")]
+    UnsupportedEdition(String),
+    #[error("group is not allowed in editions, use `features.message_encoding`")]
+    GroupNotAllowedInEditions,
     #[error("integer overflow")]
     IntegerOverflow,
-    #[error("label not allowed")]
-    LabelNotAllowed,
-    #[error("label required")]
+    #[error("label `{0}` is not allowed in {1}")]
+    LabelNotAllowed(&'static str, &'static str),
+    #[error("field label `optional`, `required` or `repeated` is required in proto2")]
     LabelRequired,
     #[error("group name should start with upper case")]
     GroupNameShouldStartWithUpperCase,
-    #[error("map field not allowed")]
+    #[error("map field is not allowed in oneof")]
     MapFieldNotAllowed,
+    #[error("field labels (required/optional/repeated) are not allowed on map fields")]
+    MapFieldWithLabel,
     #[error("string literal decode error: {0}")]
     StrLitDecodeError(#[source] StrLitDecodeError),
     #[error("lexer error: {0}")]
@@ -101,8 +113,51 @@ impl From<int::Overflow> for ParserError {
     }
 }
 
+/// Position in `.proto` file content.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct SourcePosition {
+    /// 1-based
+    pub line: u32,
+    /// 1-based, in chars
+    pub col: u32,
+    /// 0-based, in bytes
+    pub offset: usize,
+}
+
+impl SourcePosition {
+    fn new(loc: Loc, input: &str) -> SourcePosition {
+        let line_start: usize = input
+            .split_inclusive('\n')
+            .take((loc.line as usize).saturating_sub(1))
+            .map(|l| l.len())
+            .sum();
+        let col_offset: usize = input[line_start..]
+            .chars()
+            .take_while(|&c| c != '\n')
+            .take((loc.col as usize).saturating_sub(1))
+            .map(|c| c.len_utf8())
+            .sum();
+        SourcePosition {
+            line: loc.line,
+            col: loc.col,
+            offset: line_start + col_offset,
+        }
+    }
+}
+
+/// Category of parser error, for programmatic use.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ParserErrorKind {
+    /// Input is not a valid token, like unterminated string literal.
+    Lexer,
+    /// Input ended unexpectedly.
+    UnexpectedEof,
+    /// Tokens do not form valid `.proto` file.
+    Syntax,
+}
+
 #[derive(Debug, thiserror::Error)]
-#[error("at {line}:{col}: {error}")]
 pub struct ParserErrorWithLocation {
     #[source]
     pub error: anyhow::Error,
@@ -110,8 +165,179 @@ pub struct ParserErrorWithLocation {
     pub line: u32,
     /// 1-based
     pub col: u32,
+    /// Source lines ending with the error line, used to print a snippet.
+    pub context: Vec<String>,
+    /// Error position with byte offset.
+    position: SourcePosition,
+    /// End of the offending token if error points to a token.
+    end: Option<SourcePosition>,
+    /// Error points past the last token.
+    at_eof: bool,
+}
+
+/// How many lines before the error line to include in the snippet.
+const ERROR_CONTEXT_LINES_BEFORE: usize = 2;
+
+impl ParserErrorWithLocation {
+    pub(crate) fn new(
+        error: anyhow::Error,
+        loc: Loc,
+        end: Option<Loc>,
+        input: &str,
+    ) -> ParserErrorWithLocation {
+        let Loc { line, col } = loc;
+        let line_index = (line as usize).saturating_sub(1);
+        let first = line_index.saturating_sub(ERROR_CONTEXT_LINES_BEFORE);
+        let mut context: Vec<String> = input
+            .lines()
+            .skip(first)
+            .take(line_index + 1 - first)
+            .map(|l| l.to_owned())
+            .collect();
+        // Error at the end of input may point past the last line.
+        context.resize(line_index + 1 - first, String::new());
+        let position = SourcePosition::new(loc, input);
+        ParserErrorWithLocation {
+            error,
+            line,
+            col,
+            context,
+            position,
+            end: end.map(|end| SourcePosition::new(end, input)),
+            at_eof: end.is_none() && input[position.offset..].trim().is_empty(),
+        }
+    }
+
+    /// 1-based line of error.
+    pub fn line(&self) -> u32 {
+        self.position.line
+    }
+
+    /// 1-based column of error, in chars.
+    pub fn column(&self) -> u32 {
+        self.position.col
+    }
+
+    /// 0-based byte offset of error.
+    pub fn offset(&self) -> usize {
+        self.position.offset
+    }
+
+    /// End of the token where error occurred, to highlight a span rather than a point.
+    pub fn end(&self) -> Option<SourcePosition> {
+        self.end
+    }
+
+    /// Category of error.
+    pub fn kind(&self) -> ParserErrorKind {
+        for e in self.error.chain() {
+            // Like unterminated string literal.
+            if e.is::<LexerError>() || e.is::<StrLitDecodeError>() {
+                return ParserErrorKind::Lexer;
+            }
+            match e.downcast_ref() {
+                Some(TokenizerError::LexerError(..) | TokenizerError::StrLitDecodeError(..)) => {
+                    return ParserErrorKind::Lexer
+                }
+                Some(TokenizerError::UnexpectedEof) => return ParserErrorKind::UnexpectedEof,
+                _ => {}
+            }
+        }
+        if self.at_eof {
+            ParserErrorKind::UnexpectedEof
+        } else {
+            ParserErrorKind::Syntax
+        }
+    }
+}
+
+impl fmt::Display for ParserErrorWithLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "at {}:{}: {}", self.line, self.col, self.error)?;
+
+        let error_line = match self.context.last() {
+            Some(error_line) => error_line,
+            None => return Ok(()),
+        };
+
+        let first_line = self.line as usize + 1 - self.context.len();
+        let width = self.line.to_string().len();
+        writeln!(f)?;
+        writeln!(f, "{:width$} |", "", width = width)?;
+        for (i, l) in self.context.iter().enumerate() {
+            writeln!(f, "{:>width$} | {}", first_line + i, l, width = width)?;
+        }
+        // Keep tabs so the caret is aligned with the error column.
+        let padding: String = error_line
+            .chars()
+            .take((self.col as usize).saturating_sub(1))
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+        write!(f, "{:width$} | {}^", "", padding, width = width)
+    }
 }
 
+/// Multiple errors found while parsing a single file.
+#[derive(Debug, thiserror::Error)]
+pub struct ParserErrorsWithLocation(pub Vec<ParserErrorWithLocation>);
+
+impl fmt::Display for ParserErrorsWithLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0.as_slice() {
+            [error] => write!(f, "{}", error),
+            errors => {
+                write!(f, "{} errors:", errors.len())?;
+                for error in errors {
+                    write!(f, "\n{}", error)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Keywords which start a top level statement, where parser resumes after an error.
+const TOP_LEVEL_STATEMENT_KEYWORDS: &[&str] = &[
+    "syntax", "edition", "import", "package", "option", "message", "enum", "service", "extend",
+];
+
+/// Keywords and scalar types which start a message body statement,
+/// where parser resumes after an error.
+const MESSAGE_BODY_STATEMENT_KEYWORDS: &[&str] = &[
+    "optional",
+    "required",
+    "repeated",
+    "map",
+    "oneof",
+    "option",
+    "message",
+    "enum",
+    "extend",
+    "extensions",
+    "reserved",
+    "group",
+    "double",
+    "float",
+    "int32",
+    "int64",
+    "uint32",
+    "uint64",
+    "sint32",
+    "sint64",
+    "fixed32",
+    "fixed64",
+    "sfixed32",
+    "sfixed64",
+    "bool",
+    "string",
+    "bytes",
+];
+
+/// Parser stopped after an error it could not skip, errors are already collected.
+#[derive(Debug, thiserror::Error)]
+#[error("parsing aborted")]
+struct ParsingAborted;
+
 trait ToU8 {
     fn to_u8(&self) -> anyhow::Result<u8>;
 }
@@ -180,8 +406,14 @@ impl ToU8 for u32 {
 
 #[derive(Clone)]
 pub(crate) struct Parser<'a> {
+    input: &'a str,
     pub tokenizer: Tokenizer<'a>,
     syntax: Syntax,
+    /// Syntax of files without `syntax` or `edition` statement.
+    pub(crate) default_syntax: Syntax,
+    /// Recoverable errors, when parser is configured to collect them.
+    /// Shared between parser clones.
+    collected_errors: Option<Rc<RefCell<Vec<ParserErrorWithLocation>>>>,
 }
 
 #[derive(Copy, Clone)]
@@ -191,36 +423,77 @@ enum MessageBodyParseMode {
     Oneof,
     ExtendProto2,
     ExtendProto3,
+    MessageEditions,
+    ExtendEditions,
 }
 
 impl MessageBodyParseMode {
+    fn for_message(syntax: Syntax) -> MessageBodyParseMode {
+        match syntax {
+            Syntax::Proto2 => MessageBodyParseMode::MessageProto2,
+            Syntax::Proto3 => MessageBodyParseMode::MessageProto3,
+            Syntax::Editions(..) => MessageBodyParseMode::MessageEditions,
+        }
+    }
+
+    fn for_extend(syntax: Syntax) -> MessageBodyParseMode {
+        match syntax {
+            Syntax::Proto2 => MessageBodyParseMode::ExtendProto2,
+            Syntax::Proto3 => MessageBodyParseMode::ExtendProto3,
+            Syntax::Editions(..) => MessageBodyParseMode::ExtendEditions,
+        }
+    }
+
     fn label_allowed(&self, label: Rule) -> bool {
         match label {
             Rule::Repeated => match *self {
                 MessageBodyParseMode::MessageProto2
                 | MessageBodyParseMode::MessageProto3
+                | MessageBodyParseMode::MessageEditions
                 | MessageBodyParseMode::ExtendProto2
-                | MessageBodyParseMode::ExtendProto3 => true,
+                | MessageBodyParseMode::ExtendProto3
+                | MessageBodyParseMode::ExtendEditions => true,
                 MessageBodyParseMode::Oneof => false,
             },
             Rule::Optional => match *self {
                 MessageBodyParseMode::MessageProto2 | MessageBodyParseMode::ExtendProto2 => true,
                 MessageBodyParseMode::MessageProto3 | MessageBodyParseMode::ExtendProto3 => true,
+                // Presence is controlled by `features.field_presence` in editions.
+                MessageBodyParseMode::MessageEditions | MessageBodyParseMode::ExtendEditions => {
+                    false
+                }
                 MessageBodyParseMode::Oneof => false,
             },
             Rule::Required => match *self {
                 MessageBodyParseMode::MessageProto2 | MessageBodyParseMode::ExtendProto2 => true,
-                MessageBodyParseMode::MessageProto3 | MessageBodyParseMode::ExtendProto3 => false,
+                MessageBodyParseMode::MessageProto3
+                | MessageBodyParseMode::ExtendProto3
+                | MessageBodyParseMode::MessageEditions
+                | MessageBodyParseMode::ExtendEditions => false,
                 MessageBodyParseMode::Oneof => false,
             },
         }
     }
 
+    /// Where the field is declared, for error messages.
+    fn context(&self) -> &'static str {
+        match *self {
+            MessageBodyParseMode::MessageProto2 | MessageBodyParseMode::ExtendProto2 => "proto2",
+            MessageBodyParseMode::MessageProto3 | MessageBodyParseMode::ExtendProto3 => "proto3",
+            MessageBodyParseMode::MessageEditions | MessageBodyParseMode::ExtendEditions => {
+                "editions"
+            }
+            MessageBodyParseMode::Oneof => "oneof",
+        }
+    }
+
     fn some_label_required(&self) -> bool {
         match *self {
             MessageBodyParseMode::MessageProto2 | MessageBodyParseMode::ExtendProto2 => true,
             MessageBodyParseMode::MessageProto3
             | MessageBodyParseMode::ExtendProto3
+            | MessageBodyParseMode::MessageEditions
+            | MessageBodyParseMode::ExtendEditions
             | MessageBodyParseMode::Oneof => false,
         }
     }
@@ -229,17 +502,22 @@ impl MessageBodyParseMode {
         match *self {
             MessageBodyParseMode::MessageProto2
             | MessageBodyParseMode::MessageProto3
+            | MessageBodyParseMode::MessageEditions
             | MessageBodyParseMode::ExtendProto2
-            | MessageBodyParseMode::ExtendProto3 => true,
+            | MessageBodyParseMode::ExtendProto3
+            | MessageBodyParseMode::ExtendEditions => true,
             MessageBodyParseMode::Oneof => false,
         }
     }
 
     fn is_most_non_fields_allowed(&self) -> bool {
         match *self {
-            MessageBodyParseMode::MessageProto2 | MessageBodyParseMode::MessageProto3 => true,
+            MessageBodyParseMode::MessageProto2
+            | MessageBodyParseMode::MessageProto3
+            | MessageBodyParseMode::MessageEditions => true,
             MessageBodyParseMode::ExtendProto2
             | MessageBodyParseMode::ExtendProto3
+            | MessageBodyParseMode::ExtendEditions
             | MessageBodyParseMode::Oneof => false,
         }
     }
@@ -248,14 +526,17 @@ impl MessageBodyParseMode {
         match *self {
             MessageBodyParseMode::MessageProto2
             | MessageBodyParseMode::MessageProto3
+            | MessageBodyParseMode::MessageEditions
             | MessageBodyParseMode::Oneof => true,
-            MessageBodyParseMode::ExtendProto2 | MessageBodyParseMode::ExtendProto3 => false,
+            MessageBodyParseMode::ExtendProto2
+            | MessageBodyParseMode::ExtendProto3
+            | MessageBodyParseMode::ExtendEditions => false,
         }
     }
 
     fn is_extensions_allowed(&self) -> bool {
         match self {
-            MessageBodyParseMode::MessageProto2 => true,
+            MessageBodyParseMode::MessageProto2 | MessageBodyParseMode::MessageEditions => true,
             _ => false,
         }
     }
@@ -293,8 +574,11 @@ impl NumLitEx for NumLit {
 impl<'a> Parser<'a> {
     pub(crate) fn new(input: &'a str) -> Parser<'a> {
         Parser {
+            input,
             tokenizer: Tokenizer::new(input, ParserLanguage::Proto),
             syntax: Syntax::Proto2,
+            default_syntax: Syntax::Proto2,
+            collected_errors: None,
         }
     }
 
@@ -407,7 +691,11 @@ impl<'a> Parser<'a> {
         while !self.tokenizer.lookahead_is_symbol('}')? {
             let n = self.next_message_constant_field_name()?;
             let v = self.next_field_value()?;
-            r.fields.insert(n, v);
+            r.add_field(n, v);
+            // Fields may be separated by `,` or `;` like in text format.
+            if !self.tokenizer.next_symbol_if_eq(',')? {
+                self.tokenizer.next_symbol_if_eq(';')?;
+            }
         }
         self.tokenizer
             .next_symbol_expect_eq('}', "message constant")?;
@@ -454,11 +742,36 @@ impl<'a> Parser<'a> {
         Err(ParserError::ExpectConstant.into())
     }
 
+    // list = "[" [ value { "," value } ] "]"
+    fn next_list_constant(
+        &mut self,
+        mut next_value: impl FnMut(&mut Self) -> anyhow::Result<ProtobufConstant>,
+    ) -> anyhow::Result<ProtobufConstant> {
+        let mut r = Vec::new();
+        self.tokenizer.next_symbol_expect_eq('[', "list constant")?;
+        if !self.tokenizer.lookahead_is_symbol(']')? {
+            loop {
+                r.push(next_value(self)?);
+                if !self.tokenizer.next_symbol_if_eq(',')? {
+                    break;
+                }
+            }
+        }
+        self.tokenizer.next_symbol_expect_eq(']', "list constant")?;
+        Ok(ProtobufConstant::Repeated(r))
+    }
+
     fn next_field_value(&mut self) -> anyhow::Result<ProtobufConstant> {
         if self.tokenizer.next_symbol_if_eq(':')? {
-            // Colon is optional when reading message constant.
+            if self.tokenizer.lookahead_is_symbol('[')? {
+                return self.next_list_constant(|p| p.next_constant());
+            }
             self.next_constant()
+        } else if self.tokenizer.lookahead_is_symbol('[')? {
+            // Colon is optional when reading message constant.
+            self.next_list_constant(|p| Ok(ProtobufConstant::Message(p.next_message_constant()?)))
         } else {
+            // Colon is optional when reading message constant.
             Ok(ProtobufConstant::Message(self.next_message_constant()?))
         }
     }
@@ -474,6 +787,7 @@ impl<'a> Parser<'a> {
 
     // syntax = "syntax" "=" quote "proto2" quote ";"
     // syntax = "syntax" "=" quote "proto3" quote ";"
+    // edition = "edition" "=" quote "2023" quote ";"
     fn next_syntax(&mut self) -> anyhow::Result<Option<Syntax>> {
         if self.tokenizer.next_ident_if_eq("syntax")? {
             self.tokenizer.next_symbol_expect_eq('=', "syntax")?;
@@ -487,6 +801,13 @@ impl<'a> Parser<'a> {
             };
             self.tokenizer.next_symbol_expect_eq(';', "syntax")?;
             Ok(Some(syntax))
+        } else if self.tokenizer.next_ident_if_eq("edition")? {
+            self.tokenizer.next_symbol_expect_eq('=', "edition")?;
+            let edition_str = self.tokenizer.next_str_lit()?.decode_utf8()?;
+            let edition = Edition::parse(&edition_str)
+                .ok_or_else(|| ParserError::UnsupportedEdition(edition_str.clone()))?;
+            self.tokenizer.next_symbol_expect_eq(';', "edition")?;
+            Ok(Some(Syntax::Editions(edition)))
         } else {
             Ok(None)
         }
@@ -496,6 +817,7 @@ impl<'a> Parser<'a> {
 
     // import = "import" [ "weak" | "public" ] strLit ";"
     fn next_import_opt(&mut self) -> anyhow::Result<Option<model::Import>> {
+        let loc = self.tokenizer.lookahead_loc();
         if self.tokenizer.next_ident_if_eq("import")? {
             let vis = if self.tokenizer.next_ident_if_eq("weak")? {
                 ImportVis::Weak
@@ -507,7 +829,7 @@ impl<'a> Parser<'a> {
             let path = self.tokenizer.next_str_lit()?.decode_utf8()?;
             self.tokenizer.next_symbol_expect_eq(';', "import")?;
             let path = ProtoPathBuf::new(path)?;
-            Ok(Some(model::Import { path, vis }))
+            Ok(Some(model::Import { path, vis, loc }))
         } else {
             Ok(None)
         }
@@ -562,11 +884,12 @@ impl<'a> Parser<'a> {
     // option = "option" optionName  "=" constant ";"
     fn next_option_opt(&mut self) -> anyhow::Result<Option<ProtobufOption>> {
         if self.tokenizer.next_ident_if_eq("option")? {
+            let loc = self.tokenizer.lookahead_loc();
             let name = self.next_option_name()?;
             self.tokenizer.next_symbol_expect_eq('=', "option")?;
             let value = self.next_constant()?;
             self.tokenizer.next_symbol_expect_eq(';', "option")?;
-            Ok(Some(ProtobufOption { name, value }))
+            Ok(Some(ProtobufOption { name, value, loc }))
         } else {
             Ok(None)
         }
@@ -580,7 +903,7 @@ impl<'a> Parser<'a> {
             let mut clone = self.clone();
             if clone.tokenizer.next_ident_if_eq(rule.as_str())? {
                 if !mode.label_allowed(rule) {
-                    return Err(ParserError::LabelNotAllowed.into());
+                    return Err(ParserError::LabelNotAllowed(rule.as_str(), mode.context()).into());
                 }
 
                 *self = clone;
@@ -637,10 +960,11 @@ impl<'a> Parser<'a> {
 
     // fieldOption = optionName "=" constant
     fn next_field_option(&mut self) -> anyhow::Result<ProtobufOption> {
+        let loc = self.tokenizer.lookahead_loc();
         let name = self.next_option_name()?;
         self.tokenizer.next_symbol_expect_eq('=', "field option")?;
         let value = self.next_constant()?;
-        Ok(ProtobufOption { name, value })
+        Ok(ProtobufOption { name, value, loc })
     }
 
     // fieldOptions = fieldOption { ","  fieldOption }
@@ -669,14 +993,14 @@ impl<'a> Parser<'a> {
             self.next_label(mode)?
         };
         if self.tokenizer.next_ident_if_eq("group")? {
+            if let Syntax::Editions(..) = self.syntax {
+                return Err(ParserError::GroupNotAllowedInEditions.into());
+            }
             let name = self.next_group_name()?.to_owned();
             self.tokenizer.next_symbol_expect_eq('=', "group")?;
             let number = self.next_field_number()?;
 
-            let mode = match self.syntax {
-                Syntax::Proto2 => MessageBodyParseMode::MessageProto2,
-                Syntax::Proto3 => MessageBodyParseMode::MessageProto3,
-            };
+            let mode = MessageBodyParseMode::for_message(self.syntax);
 
             let MessageBody { fields, .. } = self.next_message_body(mode)?;
 
@@ -701,6 +1025,9 @@ impl<'a> Parser<'a> {
             Ok(WithLoc { t: field, loc })
         } else {
             let typ = self.next_field_type()?;
+            if let (Some(_), FieldType::Map(..)) = (rule, &typ) {
+                return Err(ParserError::MapFieldWithLabel.into());
+            }
             let name = self.tokenizer.next_ident()?.to_owned();
             self.tokenizer.next_symbol_expect_eq('=', "field")?;
             let number = self.next_field_number()?;
@@ -757,7 +1084,7 @@ impl<'a> Parser<'a> {
         if self.tokenizer.next_ident_if_eq("map")? {
             self.tokenizer
                 .next_symbol_expect_eq('<', "map field type")?;
-            // TODO: restrict key types
+            // Key type is checked when types are resolved.
             let key = self.next_field_type()?;
             self.tokenizer
                 .next_symbol_expect_eq(',', "map field type")?;
@@ -779,7 +1106,7 @@ impl<'a> Parser<'a> {
         let from = self.next_field_number()?;
         let to = if self.tokenizer.next_ident_if_eq("to")? {
             if self.tokenizer.next_ident_if_eq("max")? {
-                0x20000000 - 1
+                FIELD_NUMBER_MAX
             } else {
                 self.next_field_number()?
             }
@@ -791,10 +1118,17 @@ impl<'a> Parser<'a> {
 
     // ranges = range { "," range }
     fn next_ranges(&mut self) -> anyhow::Result<Vec<FieldNumberRange>> {
+        self.next_ranges_with(Self::next_range)
+    }
+
+    fn next_ranges_with(
+        &mut self,
+        next_range: fn(&mut Self) -> anyhow::Result<FieldNumberRange>,
+    ) -> anyhow::Result<Vec<FieldNumberRange>> {
         let mut ranges = Vec::new();
-        ranges.push(self.next_range()?);
+        ranges.push(next_range(self)?);
         while self.tokenizer.next_symbol_if_eq(',')? {
-            ranges.push(self.next_range()?);
+            ranges.push(next_range(self)?);
         }
         Ok(ranges)
     }
@@ -815,6 +1149,13 @@ impl<'a> Parser<'a> {
     // fieldNames = fieldName { "," fieldName }
     fn next_reserved_opt(
         &mut self,
+    ) -> anyhow::Result<Option<(Vec<FieldNumberRange>, Vec<String>)>> {
+        self.next_reserved_opt_with(Self::next_range)
+    }
+
+    fn next_reserved_opt_with(
+        &mut self,
+        next_range: fn(&mut Self) -> anyhow::Result<FieldNumberRange>,
     ) -> anyhow::Result<Option<(Vec<FieldNumberRange>, Vec<String>)>> {
         if self.tokenizer.next_ident_if_eq("reserved")? {
             let (ranges, names) = if let &Token::StrLit(..) = self.tokenizer.lookahead_some()? {
@@ -825,7 +1166,7 @@ impl<'a> Parser<'a> {
                 }
                 (Vec::new(), names)
             } else {
-                (self.next_ranges()?, Vec::new())
+                (self.next_ranges_with(next_range)?, Vec::new())
             };
 
             self.tokenizer.next_symbol_expect_eq(';', "reserved")?;
@@ -842,11 +1183,12 @@ impl<'a> Parser<'a> {
 
     // enumValueOption = optionName "=" constant
     fn next_enum_value_option(&mut self) -> anyhow::Result<ProtobufOption> {
+        let loc = self.tokenizer.lookahead_loc();
         let name = self.next_option_name()?;
         self.tokenizer
             .next_symbol_expect_eq('=', "enum value option")?;
         let value = self.next_constant()?;
-        Ok(ProtobufOption { name, value })
+        Ok(ProtobufOption { name, value, loc })
     }
 
     // https://github.com/google/protobuf/issues/4561
@@ -864,8 +1206,24 @@ impl<'a> Parser<'a> {
         })
     }
 
+    // enumRange = enumValue [ "to" ( enumValue | "max" ) ]
+    fn next_enum_range(&mut self) -> anyhow::Result<FieldNumberRange> {
+        let from = self.next_enum_value()?;
+        let to = if self.tokenizer.next_ident_if_eq("to")? {
+            if self.tokenizer.next_ident_if_eq("max")? {
+                i32::MAX
+            } else {
+                self.next_enum_value()?
+            }
+        } else {
+            from
+        };
+        Ok(FieldNumberRange { from, to })
+    }
+
     // enumField = ident "=" intLit [ "[" enumValueOption { ","  enumValueOption } "]" ]";"
-    fn next_enum_field(&mut self) -> anyhow::Result<EnumValue> {
+    fn next_enum_field(&mut self) -> anyhow::Result<WithLoc<EnumValue>> {
+        let loc = self.tokenizer.lookahead_loc();
         let name = self.tokenizer.next_ident()?.to_owned();
         self.tokenizer.next_symbol_expect_eq('=', "enum field")?;
         let number = self.next_enum_value()?;
@@ -878,15 +1236,18 @@ impl<'a> Parser<'a> {
             self.tokenizer.next_symbol_expect_eq(']', "enum field")?;
         }
 
-        Ok(EnumValue {
-            name,
-            number,
-            options,
+        Ok(WithLoc {
+            loc,
+            t: EnumValue {
+                name,
+                number,
+                options,
+            },
         })
     }
 
     // enum = "enum" enumName enumBody
-    // enumBody = "{" { option | enumField | emptyStatement } "}"
+    // enumBody = "{" { option | enumField | reserved | emptyStatement } "}"
     fn next_enum_opt(&mut self) -> anyhow::Result<Option<WithLoc<Enumeration>>> {
         let loc = self.tokenizer.lookahead_loc();
 
@@ -895,6 +1256,8 @@ impl<'a> Parser<'a> {
 
             let mut values = Vec::new();
             let mut options = Vec::new();
+            let mut reserved_nums = Vec::new();
+            let mut reserved_names = Vec::new();
 
             self.tokenizer.next_symbol_expect_eq('{', "enum")?;
             while self.tokenizer.lookahead_if_symbol()? != Some('}') {
@@ -908,6 +1271,12 @@ impl<'a> Parser<'a> {
                     continue;
                 }
 
+                if let Some((nums, names)) = self.next_reserved_opt_with(Self::next_enum_range)? {
+                    reserved_nums.extend(nums);
+                    reserved_names.extend(names);
+                    continue;
+                }
+
                 values.push(self.next_enum_field()?);
             }
             self.tokenizer.next_symbol_expect_eq('}', "enum")?;
@@ -915,6 +1284,8 @@ impl<'a> Parser<'a> {
                 name,
                 values,
                 options,
+                reserved_nums,
+                reserved_names,
             };
             Ok(Some(WithLoc {
                 loc,
@@ -935,73 +1306,86 @@ impl<'a> Parser<'a> {
         let mut r = MessageBody::default();
 
         while self.tokenizer.lookahead_if_symbol()? != Some('}') {
-            let loc = self.tokenizer.lookahead_loc();
-
-            // emptyStatement
-            if self.tokenizer.next_symbol_if_eq(';')? {
-                continue;
+            let start = self.tokenizer.lookahead_loc();
+            if let Err(e) = self.next_message_body_statement(mode, &mut r) {
+                self.recover_or_fail(e, start, false)?;
             }
+        }
 
-            if mode.is_most_non_fields_allowed() {
-                if let Some((field_nums, field_names)) = self.next_reserved_opt()? {
-                    r.reserved_nums.extend(field_nums);
-                    r.reserved_names.extend(field_names);
-                    continue;
-                }
+        self.tokenizer.next_symbol_expect_eq('}', "message body")?;
 
-                if let Some(oneof) = self.next_oneof_opt()? {
-                    let one_of = FieldOrOneOf::OneOf(oneof);
-                    r.fields.push(WithLoc { t: one_of, loc });
-                    continue;
-                }
+        Ok(r)
+    }
 
-                if let Some(extensions) = self.next_extend_opt()? {
-                    r.extensions.extend(extensions);
-                    continue;
-                }
+    // Single statement of message body
+    fn next_message_body_statement(
+        &mut self,
+        mode: MessageBodyParseMode,
+        r: &mut MessageBody,
+    ) -> anyhow::Result<()> {
+        let loc = self.tokenizer.lookahead_loc();
 
-                if let Some(nested_message) = self.next_message_opt()? {
-                    r.messages.push(nested_message);
-                    continue;
-                }
+        // emptyStatement
+        if self.tokenizer.next_symbol_if_eq(';')? {
+            return Ok(());
+        }
 
-                if let Some(nested_enum) = self.next_enum_opt()? {
-                    r.enums.push(nested_enum);
-                    continue;
-                }
-            } else {
-                self.tokenizer.next_ident_if_eq_error("reserved")?;
-                self.tokenizer.next_ident_if_eq_error("oneof")?;
-                self.tokenizer.next_ident_if_eq_error("extend")?;
-                self.tokenizer.next_ident_if_eq_error("message")?;
-                self.tokenizer.next_ident_if_eq_error("enum")?;
+        if mode.is_most_non_fields_allowed() {
+            if let Some((field_nums, field_names)) = self.next_reserved_opt()? {
+                r.reserved_nums.extend(field_nums);
+                r.reserved_names.extend(field_names);
+                return Ok(());
             }
 
-            if mode.is_extensions_allowed() {
-                if let Some(extension_ranges) = self.next_extensions_opt()? {
-                    r.extension_ranges.extend(extension_ranges);
-                    continue;
-                }
-            } else {
-                self.tokenizer.next_ident_if_eq_error("extensions")?;
+            if let Some(oneof) = self.next_oneof_opt()? {
+                let one_of = FieldOrOneOf::OneOf(oneof);
+                r.fields.push(WithLoc { t: one_of, loc });
+                return Ok(());
             }
 
-            if mode.is_option_allowed() {
-                if let Some(option) = self.next_option_opt()? {
-                    r.options.push(option);
-                    continue;
-                }
-            } else {
-                self.tokenizer.next_ident_if_eq_error("option")?;
+            if let Some(extensions) = self.next_extend_opt()? {
+                r.extensions.extend(extensions);
+                return Ok(());
             }
 
-            let field = FieldOrOneOf::Field(self.next_field(mode)?);
-            r.fields.push(WithLoc { t: field, loc });
+            if let Some(nested_message) = self.next_message_opt()? {
+                r.messages.push(nested_message);
+                return Ok(());
+            }
+
+            if let Some(nested_enum) = self.next_enum_opt()? {
+                r.enums.push(nested_enum);
+                return Ok(());
+            }
+        } else {
+            self.tokenizer.next_ident_if_eq_error("reserved")?;
+            self.tokenizer.next_ident_if_eq_error("oneof")?;
+            self.tokenizer.next_ident_if_eq_error("extend")?;
+            self.tokenizer.next_ident_if_eq_error("message")?;
+            self.tokenizer.next_ident_if_eq_error("enum")?;
         }
 
-        self.tokenizer.next_symbol_expect_eq('}', "message body")?;
+        if mode.is_extensions_allowed() {
+            if let Some(extension_ranges) = self.next_extensions_opt()? {
+                r.extension_ranges.extend(extension_ranges);
+                return Ok(());
+            }
+        } else {
+            self.tokenizer.next_ident_if_eq_error("extensions")?;
+        }
 
-        Ok(r)
+        if mode.is_option_allowed() {
+            if let Some(option) = self.next_option_opt()? {
+                r.options.push(option);
+                return Ok(());
+            }
+        } else {
+            self.tokenizer.next_ident_if_eq_error("option")?;
+        }
+
+        let field = FieldOrOneOf::Field(self.next_field(mode)?);
+        r.fields.push(WithLoc { t: field, loc });
+        Ok(())
     }
 
     // message = "message" messageName messageBody
@@ -1011,10 +1395,7 @@ impl<'a> Parser<'a> {
         if self.tokenizer.next_ident_if_eq("message")? {
             let name = self.tokenizer.next_ident()?.to_owned();
 
-            let mode = match self.syntax {
-                Syntax::Proto2 => MessageBodyParseMode::MessageProto2,
-                Syntax::Proto3 => MessageBodyParseMode::MessageProto3,
-            };
+            let mode = MessageBodyParseMode::for_message(self.syntax);
 
             let MessageBody {
                 fields,
@@ -1057,10 +1438,7 @@ impl<'a> Parser<'a> {
 
             let extendee = self.next_message_or_enum_type()?;
 
-            let mode = match self.syntax {
-                Syntax::Proto2 => MessageBodyParseMode::ExtendProto2,
-                Syntax::Proto3 => MessageBodyParseMode::ExtendProto3,
-            };
+            let mode = MessageBodyParseMode::for_extend(self.syntax);
 
             let MessageBody { fields, .. } = self.next_message_body(mode)?;
 
@@ -1116,8 +1494,9 @@ impl<'a> Parser<'a> {
 
     // stream = "stream" streamName "(" messageType "," messageType ")"
     //        (( "{" { option | emptyStatement } "}") | ";" )
-    fn next_stream_opt(&mut self) -> anyhow::Result<Option<Method>> {
+    fn next_stream_opt(&mut self) -> anyhow::Result<Option<WithLoc<Method>>> {
         assert_eq!(Syntax::Proto2, self.syntax);
+        let loc = self.tokenizer.lookahead_loc();
         if self.tokenizer.next_ident_if_eq("stream")? {
             let name = self.tokenizer.next_ident()?;
             self.tokenizer.next_symbol_expect_eq('(', "stream")?;
@@ -1126,13 +1505,16 @@ impl<'a> Parser<'a> {
             let output_type = self.next_message_or_enum_type()?;
             self.tokenizer.next_symbol_expect_eq(')', "stream")?;
             let options = self.next_options_or_colon()?;
-            Ok(Some(Method {
-                name,
-                input_type,
-                output_type,
-                client_streaming: true,
-                server_streaming: true,
-                options,
+            Ok(Some(WithLoc {
+                loc,
+                t: Method {
+                    name,
+                    input_type,
+                    output_type,
+                    client_streaming: true,
+                    server_streaming: true,
+                    options,
+                },
             }))
         } else {
             Ok(None)
@@ -1142,7 +1524,8 @@ impl<'a> Parser<'a> {
     // rpc = "rpc" rpcName "(" [ "stream" ] messageType ")"
     //     "returns" "(" [ "stream" ] messageType ")"
     //     (( "{" { option | emptyStatement } "}" ) | ";" )
-    fn next_rpc_opt(&mut self) -> anyhow::Result<Option<Method>> {
+    fn next_rpc_opt(&mut self) -> anyhow::Result<Option<WithLoc<Method>>> {
+        let loc = self.tokenizer.lookahead_loc();
         if self.tokenizer.next_ident_if_eq("rpc")? {
             let name = self.tokenizer.next_ident()?;
             self.tokenizer.next_symbol_expect_eq('(', "rpc")?;
@@ -1155,13 +1538,16 @@ impl<'a> Parser<'a> {
             let output_type = self.next_message_or_enum_type()?;
             self.tokenizer.next_symbol_expect_eq(')', "rpc")?;
             let options = self.next_options_or_colon()?;
-            Ok(Some(Method {
-                name,
-                input_type,
-                output_type,
-                client_streaming,
-                server_streaming,
-                options,
+            Ok(Some(WithLoc {
+                loc,
+                t: Method {
+                    name,
+                    input_type,
+                    output_type,
+                    client_streaming,
+                    server_streaming,
+                    options,
+                },
             }))
         } else {
             Ok(None)
@@ -1224,70 +1610,184 @@ impl<'a> Parser<'a> {
     // proto = syntax { import | package | option | topLevelDef | emptyStatement }
     // topLevelDef = message | enum | extend | service
     pub fn next_proto(&mut self) -> anyhow::Result<FileDescriptor> {
-        let syntax = self.next_syntax()?.unwrap_or(Syntax::Proto2);
+        let declared = self.next_syntax()?;
+        let syntax = declared.unwrap_or(self.default_syntax);
         self.syntax = syntax;
 
-        let mut imports = Vec::new();
-        let mut package = ProtobufAbsPath::root();
-        let mut messages = Vec::new();
-        let mut enums = Vec::new();
-        let mut extensions = Vec::new();
-        let mut options = Vec::new();
-        let mut services = Vec::new();
+        let mut file = FileDescriptor {
+            package: ProtobufAbsPath::root(),
+            syntax,
+            syntax_declared: declared.is_some(),
+            ..FileDescriptor::default()
+        };
 
         while !self.tokenizer.syntax_eof()? {
-            if let Some(import) = self.next_import_opt()? {
-                imports.push(import);
-                continue;
+            let start = self.tokenizer.lookahead_loc();
+            if let Err(e) = self.next_top_level_statement(&mut file) {
+                self.recover_or_fail(e, start, true)?;
             }
+        }
 
-            if let Some(next_package) = self.next_package_opt()? {
-                package = next_package;
-                continue;
-            }
+        Ok(file)
+    }
 
-            if let Some(option) = self.next_option_opt()? {
-                options.push(option);
-                continue;
-            }
+    // import | package | option | topLevelDef | emptyStatement
+    fn next_top_level_statement(&mut self, file: &mut FileDescriptor) -> anyhow::Result<()> {
+        if let Some(import) = self.next_import_opt()? {
+            file.imports.push(import);
+            return Ok(());
+        }
 
-            if let Some(message) = self.next_message_opt()? {
-                messages.push(message);
-                continue;
-            }
+        if let Some(next_package) = self.next_package_opt()? {
+            file.package = next_package;
+            return Ok(());
+        }
 
-            if let Some(enumeration) = self.next_enum_opt()? {
-                enums.push(enumeration);
-                continue;
-            }
+        if let Some(option) = self.next_option_opt()? {
+            file.options.push(option);
+            return Ok(());
+        }
 
-            if let Some(more_extensions) = self.next_extend_opt()? {
-                extensions.extend(more_extensions);
-                continue;
-            }
+        if let Some(message) = self.next_message_opt()? {
+            file.messages.push(message);
+            return Ok(());
+        }
 
-            if let Some(service) = self.next_service_opt()? {
-                services.push(service);
-                continue;
-            }
+        if let Some(enumeration) = self.next_enum_opt()? {
+            file.enums.push(enumeration);
+            return Ok(());
+        }
+
+        if let Some(more_extensions) = self.next_extend_opt()? {
+            file.extensions.extend(more_extensions);
+            return Ok(());
+        }
+
+        if let Some(service) = self.next_service_opt()? {
+            file.services.push(service);
+            return Ok(());
+        }
+
+        if self.tokenizer.next_symbol_if_eq(';')? {
+            return Ok(());
+        }
+
+        Err(ParserError::IncorrectInput.into())
+    }
+
+    // Error recovery
 
-            if self.tokenizer.next_symbol_if_eq(';')? {
-                continue;
+    /// Like [`next_proto`](Self::next_proto), but skip statements which failed to parse
+    /// and return all errors found.
+    pub fn next_proto_collect_errors(
+        &mut self,
+    ) -> Result<FileDescriptor, Vec<ParserErrorWithLocation>> {
+        let collected_errors = Rc::new(RefCell::new(Vec::new()));
+        self.collected_errors = Some(collected_errors.clone());
+        let r = self.next_proto();
+        self.collected_errors = None;
+        let mut errors = collected_errors.take();
+        match r {
+            Ok(r) if errors.is_empty() => Ok(r),
+            Ok(_) => Err(errors),
+            Err(error) => {
+                if !error.is::<ParsingAborted>() {
+                    errors.push(ParserErrorWithLocation::new(
+                        error,
+                        self.tokenizer.loc(),
+                        self.tokenizer.loc_end(),
+                        self.input,
+                    ));
+                }
+                Err(errors)
             }
+        }
+    }
 
-            return Err(ParserError::IncorrectInput.into());
+    /// Record an error and skip the rest of the failed statement
+    /// which started at `start`, or return the error if errors are not collected.
+    fn recover_or_fail(
+        &mut self,
+        error: anyhow::Error,
+        start: Loc,
+        top_level: bool,
+    ) -> anyhow::Result<()> {
+        if self.collected_errors.is_none() || error.is::<ParsingAborted>() {
+            return Err(error);
         }
 
-        Ok(FileDescriptor {
-            imports,
-            package,
-            syntax,
-            messages,
-            enums,
-            extensions,
-            services,
-            options,
-        })
+        self.push_collected_error(ParserErrorWithLocation::new(
+            error,
+            self.tokenizer.loc(),
+            self.tokenizer.loc_end(),
+            self.input,
+        ));
+
+        if let Err(error) = self.skip_statement(start, top_level) {
+            self.push_collected_error(ParserErrorWithLocation::new(
+                error,
+                self.tokenizer.loc(),
+                self.tokenizer.loc_end(),
+                self.input,
+            ));
+            return Err(ParsingAborted.into());
+        }
+        Ok(())
+    }
+
+    fn push_collected_error(&mut self, error: ParserErrorWithLocation) {
+        self.collected_errors
+            .as_ref()
+            .expect("collecting errors")
+            .borrow_mut()
+            .push(error);
+    }
+
+    /// Skip tokens until the end of current statement: `;` or a balanced `{ ... }` block,
+    /// or until a keyword which starts the next statement, like `message`.
+    ///
+    /// Unbalanced `}` is not consumed unless it is found at the top level.
+    fn skip_statement(&mut self, start: Loc, top_level: bool) -> anyhow::Result<()> {
+        let keywords = if top_level {
+            TOP_LEVEL_STATEMENT_KEYWORDS
+        } else {
+            MESSAGE_BODY_STATEMENT_KEYWORDS
+        };
+        let mut depth = 0;
+        while !self.tokenizer.syntax_eof()? {
+            if depth == 0 && self.tokenizer.lookahead_loc() != start {
+                if let Token::Ident(ident) = self.tokenizer.lookahead_some()? {
+                    if keywords.contains(&ident.as_str()) {
+                        return Ok(());
+                    }
+                }
+            }
+            match self.tokenizer.lookahead_if_symbol()? {
+                Some(';') if depth == 0 => {
+                    self.tokenizer.advance()?;
+                    return Ok(());
+                }
+                Some('{') => {
+                    depth += 1;
+                }
+                Some('}') if depth == 0 => {
+                    if top_level {
+                        self.tokenizer.advance()?;
+                    }
+                    return Ok(());
+                }
+                Some('}') => {
+                    depth -= 1;
+                    if depth == 0 {
+                        self.tokenizer.advance()?;
+                        return Ok(());
+                    }
+                }
+                _ => {}
+            }
+            self.tokenizer.advance()?;
+        }
+        Ok(())
     }
 }
 
@@ -1332,6 +1832,22 @@ mod test {
         assert_eq!(Syntax::Proto3, mess);
     }
 
+    #[test]
+    fn test_edition() {
+        let msg = r#"  edition = "2023";  "#;
+        let mess = parse_opt(msg, |p| p.next_syntax());
+        assert_eq!(Syntax::Editions(Edition::Edition2023), mess);
+
+        let err = Parser::new(r#"edition = "2077";"#)
+            .next_syntax()
+            .unwrap_err();
+        assert_eq!("unsupported edition: `2077`", err.to_string());
+
+        Parser::new(r#"edition = "2023"; message M { optional int32 a = 1; }"#)
+            .next_proto()
+            .unwrap_err();
+    }
+
     #[test]
     fn test_field_default_value_int() {
         let msg = r#"  optional int64 f = 4 [default = 12];  "#;
@@ -1356,6 +1872,16 @@ mod test {
         assert_eq!("10", mess.t.options[0].value.format());
     }
 
+    #[test]
+    fn test_message_constant() {
+        let msg = r#"{ a: 1 b { c: "x" }, b: { c: "y" }; l: [1, -2, 3] e: RED t: true m [{}, {d: false}] }"#;
+        let value = parse(msg, |p| p.next_constant());
+        assert_eq!(
+            r#"{a: 1 b: [{c: "x"}, {c: "y"}] l: [1, -2, 3] e: RED t: true m: [{}, {d: false}]}"#,
+            value.format()
+        );
+    }
+
     #[test]
     fn test_message() {
         let msg = r#"message ReferenceData
@@ -1437,7 +1963,7 @@ mod test {
     fn test_map() {
         let msg = r#"message A
     {
-        optional map<string, int32> b = 1;
+        map<string, int32> b = 1;
     }"#;
 
         let mess = parse_opt(msg, |p| p.next_message_opt());
@@ -1451,6 +1977,18 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_map_with_label() {
+        for label in ["repeated", "optional", "required"] {
+            let msg = format!("message A {{ {} map<string, int32> b = 1; }}", label);
+            let err = Parser::new(&msg).next_message_opt().unwrap_err();
+            assert_eq!(
+                "field labels (required/optional/repeated) are not allowed on map fields",
+                err.to_string()
+            );
+        }
+    }
+
     #[test]
     fn test_oneof() {
         let msg = r#"message A
@@ -1495,6 +2033,34 @@ mod test {
         assert_eq!(2, mess.t.fields.len());
     }
 
+    #[test]
+    fn test_enum_reserved() {
+        let msg = r#"enum Sample {
+       reserved -3, 2, 9 to 11, 40 to max;
+       reserved "FOO", "BAR";
+       A = 1;
+    }"#;
+
+        let enumeration = parse_opt(msg, |p| p.next_enum_opt());
+        assert_eq!(
+            vec![
+                FieldNumberRange { from: -3, to: -3 },
+                FieldNumberRange { from: 2, to: 2 },
+                FieldNumberRange { from: 9, to: 11 },
+                FieldNumberRange {
+                    from: 40,
+                    to: i32::MAX
+                },
+            ],
+            enumeration.t.reserved_nums
+        );
+        assert_eq!(
+            vec!["FOO".to_string(), "BAR".to_string()],
+            enumeration.t.reserved_names
+        );
+        assert_eq!(1, enumeration.t.values.len());
+    }
+
     #[test]
     fn test_default_value_int() {
         let msg = r#"message Sample {
@@ -1579,4 +2145,131 @@ mod test {
         let err = FileDescriptor::parse(msg).err().expect("err");
         assert_eq!(4, err.line);
     }
+
+    #[test]
+    fn test_collect_errors() {
+        let msg = r#"
+            message Foo {
+                optional int32 a = ;
+                optional int32 b = 2;
+                optional int32 c = x;
+            }
+
+            dfgdg
+
+            message Bar {}
+        "#;
+
+        let mut parser = Parser::new(msg);
+        let errors = parser.next_proto_collect_errors().unwrap_err();
+        assert_eq!(
+            vec![3, 5, 8],
+            errors.iter().map(|e| e.line).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_collect_errors_consecutive_statements() {
+        let msg = r#"
+            message Foo {
+                optional int32 a = 1
+                optional int32 b = ;
+            }
+            package p
+            message Bar { optional int32 = 3; }
+        "#;
+
+        let mut parser = Parser::new(msg);
+        let errors = parser.next_proto_collect_errors().unwrap_err();
+        assert_eq!(
+            vec![(4, 17), (4, 36), (7, 13), (7, 42)],
+            errors.iter().map(|e| (e.line, e.col)).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_error_snippet() {
+        let msg = "message Foo {\n    optional int32 a = ;\n}\n";
+
+        let err = FileDescriptor::parse(msg).unwrap_err();
+        assert_eq!(
+            concat!(
+                "at 2:24: incorrect input\n",
+                "  |\n",
+                "1 | message Foo {\n",
+                "2 |     optional int32 a = ;\n",
+                "  |                        ^",
+            ),
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn test_error_position() {
+        let msg = "// ö\nmessage Foo {\n    optional int32 a = foo;\n}\n";
+
+        let err = FileDescriptor::parse(msg).unwrap_err();
+        assert_eq!(ParserErrorKind::Syntax, err.kind());
+        assert_eq!(3, err.line());
+        assert_eq!(24, err.column());
+        assert_eq!(&msg[err.offset()..err.offset() + 3], "foo");
+        assert_eq!(
+            Some(SourcePosition {
+                line: 3,
+                col: 27,
+                offset: err.offset() + 3,
+            }),
+            err.end()
+        );
+
+        let err = FileDescriptor::parse("message Foo {").unwrap_err();
+        assert_eq!(ParserErrorKind::UnexpectedEof, err.kind());
+        assert_eq!(None, err.end());
+
+        let err = FileDescriptor::parse("option a = \"foo").unwrap_err();
+        assert_eq!(ParserErrorKind::Lexer, err.kind());
+    }
+
+    #[test]
+    fn test_error_position_crlf() {
+        let msg = "message Foo {\r\n    optional int32 a = foo;\r\n}\r\n";
+
+        let err = FileDescriptor::parse(msg).unwrap_err();
+        assert_eq!(2, err.line());
+        assert_eq!(24, err.column());
+        assert_eq!(&msg[err.offset()..err.offset() + 3], "foo");
+        assert!(!err.to_string().contains('\r'), "{:?}", err.to_string());
+    }
+
+    #[test]
+    fn test_label_errors() {
+        let error = |msg: &str| {
+            let err = FileDescriptor::parse(msg).unwrap_err();
+            (err.line(), err.column(), format!("{}", err.error))
+        };
+        assert_eq!(
+            (3, 5, "label `required` is not allowed in proto3".to_owned()),
+            error("syntax = 'proto3';\nmessage M {\n    required int32 a = 1;\n}")
+        );
+        assert_eq!(
+            (
+                1,
+                38,
+                "field label `optional`, `required` or `repeated` is required in proto2".to_owned()
+            ),
+            error("syntax = 'proto2'; extend google.M { int32 a = 1; }")
+        );
+        assert_eq!(
+            (1, 23, "label `optional` is not allowed in oneof".to_owned()),
+            error("message M { oneof o { optional int32 a = 1; } }")
+        );
+        assert_eq!(
+            (
+                1,
+                31,
+                "label `optional` is not allowed in editions".to_owned()
+            ),
+            error("edition = '2023'; message M { optional int32 a = 1; }")
+        );
+    }
 }
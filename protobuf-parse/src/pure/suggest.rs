@@ -0,0 +1,107 @@
+//! Suggest similar names for names which are not found.
+
+/// Number of single character insertions, deletions and substitutions
+/// needed to turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    let mut cur = vec![0; b.len() + 1];
+    for (i, ca) in a.chars().enumerate() {
+        cur[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let substitute = prev[j] + (ca != cb) as usize;
+            cur[j + 1] = substitute.min(prev[j + 1] + 1).min(cur[j] + 1);
+        }
+        std::mem::swap(&mut prev, &mut cur);
+    }
+    prev[b.len()]
+}
+
+/// Up to three candidates closest to `name`, closest first.
+///
+/// Candidates too different from `name` are not returned.
+pub(crate) fn closest<'a>(
+    name: &str,
+    candidates: impl IntoIterator<Item = &'a str>,
+) -> Vec<&'a str> {
+    let max_distance = (name.chars().count() / 4).max(1);
+    let mut r: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .map(|c| (edit_distance(name, c), c))
+        .filter(|&(d, _)| d <= max_distance)
+        .collect();
+    r.sort();
+    r.dedup();
+    r.into_iter().take(3).map(|(_, c)| c).collect()
+}
+
+/// Format `did you mean` suffix for an error message, empty if there are no suggestions.
+pub(crate) fn did_you_mean(suggestions: &[&str]) -> String {
+    match suggestions {
+        [] => String::new(),
+        _ => format!(
+            "; did you mean {}?",
+            suggestions
+                .iter()
+                .map(|s| format!("`{}`", s))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::fs;
+
+    use super::*;
+    use crate::Parser;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(0, edit_distance("abc", "abc"));
+        assert_eq!(1, edit_distance("foo/bar.proto", "foo/baz.proto"));
+        assert_eq!(2, edit_distance("improt", "import"));
+        assert_eq!(3, edit_distance("", "abc"));
+    }
+
+    #[test]
+    fn test_closest() {
+        let candidates = [
+            "foo/baz.proto",
+            "foo/bar.proto",
+            "foo/bar2.proto",
+            "foo/qux.proto",
+            "other.proto",
+        ];
+        assert_eq!(
+            vec!["foo/bar.proto", "foo/baz.proto", "foo/bar2.proto"],
+            closest("foo/bax.proto", candidates)
+        );
+        assert_eq!(Vec::<&str>::new(), closest("zzz.proto", candidates));
+    }
+
+    #[test]
+    fn not_found_suggests_similar_paths() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join("foo")).unwrap();
+        for name in ["foo/baz.proto", "foo/bar2.proto", "foo/other.proto"] {
+            fs::write(dir.path().join(name), "syntax = 'proto3';").unwrap();
+        }
+        let a_proto = dir.path().join("a.proto");
+        fs::write(&a_proto, "syntax = 'proto3'; import 'foo/bar.proto';").unwrap();
+
+        let err = Parser::new()
+            .pure()
+            .include(dir.path())
+            .input(&a_proto)
+            .parse_and_typecheck()
+            .err()
+            .expect("error");
+        assert!(
+            format!("{:#}", err).ends_with("; did you mean `foo/bar2.proto`, `foo/baz.proto`?"),
+            "{:#}",
+            err
+        );
+    }
+}
@@ -0,0 +1,79 @@
+//! Token stream of `.proto` file, for tools like formatters which must preserve
+//! comments and layout.
+
+use std::ops::Range;
+
+use protobuf_support::lexer::lexer_impl::Lexer;
+use protobuf_support::lexer::parser_language::ParserLanguage;
+use protobuf_support::lexer::token;
+
+use crate::pure::parser::ParserErrorWithLocation;
+
+/// Kind of [`Token`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum TokenKind {
+    /// Identifier or keyword, like `message`.
+    Ident,
+    /// Punctuation, like `{` or `=`.
+    Symbol,
+    /// Integer literal, without sign.
+    IntLit,
+    /// Float literal, without sign, including `inf` and `nan`.
+    FloatLit,
+    /// String literal, including quotes.
+    StrLit,
+    /// `//` or `/* */` comment.
+    Comment,
+}
+
+/// Token of `.proto` file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    /// Kind of token.
+    pub kind: TokenKind,
+    /// Source text of token. Line comment does not include trailing line break.
+    pub text: String,
+    /// Byte range of token in input.
+    pub span: Range<usize>,
+}
+
+/// Split `.proto` file content into tokens, including comments.
+///
+/// Whitespace between tokens is not reported, but can be recovered from spans.
+pub fn tokenize(content: &str) -> Result<Vec<Token>, ParserErrorWithLocation> {
+    let mut lexer = Lexer::new(content, ParserLanguage::Proto);
+    let mut r = Vec::new();
+    loop {
+        lexer.skip_whitespaces();
+        let start = lexer.pos();
+        let loc = lexer.loc;
+        let error = |e| ParserErrorWithLocation::new(anyhow::Error::from(e), loc, None, content);
+
+        if let Some(comment) = lexer.next_comment_opt().map_err(error)? {
+            r.push(Token {
+                kind: TokenKind::Comment,
+                text: comment.to_owned(),
+                span: start..start + comment.len(),
+            });
+            continue;
+        }
+        if lexer.eof() {
+            return Ok(r);
+        }
+
+        let kind = match lexer.next_token_inner().map_err(error)? {
+            token::Token::Ident(..) => TokenKind::Ident,
+            token::Token::Symbol(..) => TokenKind::Symbol,
+            token::Token::IntLit(..) => TokenKind::IntLit,
+            token::Token::FloatLit(..) | token::Token::JsonNumber(..) => TokenKind::FloatLit,
+            token::Token::StrLit(..) => TokenKind::StrLit,
+        };
+        let span = start..lexer.pos();
+        r.push(Token {
+            kind,
+            text: content[span.clone()].to_owned(),
+            span,
+        });
+    }
+}
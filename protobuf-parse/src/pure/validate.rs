@@ -0,0 +1,204 @@
+//! Typecheck file descriptors which were not produced by the parser.
+
+use std::collections::HashSet;
+
+use indexmap::IndexMap;
+use protobuf::descriptor::FileDescriptorProto;
+use protobuf::reflect::FileDescriptor;
+
+use crate::proto_path::ProtoPath;
+use crate::pure::convert;
+use crate::pure::convert::ConvertErrorWithLoc;
+use crate::pure::parse_and_typecheck::check_duplicate_types;
+use crate::pure::parse_and_typecheck::sort_file_descriptors;
+use crate::pure::parse_single::model_from_descriptor;
+use crate::FileDescriptorPair;
+
+/// Error returned by [`validate_descriptors`].
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum ValidateError {
+    /// Two descriptors have the same name.
+    #[error("file `{0}` is specified more than once")]
+    DuplicateFile(String),
+    /// A file imports a file which is not specified.
+    #[error("file `{0}` imports `{1}`, which is not specified")]
+    MissingDependency(String, String),
+    /// Files import each other.
+    #[error("file `{0}` is part of an import cycle")]
+    ImportCycle(String),
+    /// A file is not valid, e.g. references a type which is not defined.
+    #[error("error in `{file}`: {error}")]
+    InvalidFile {
+        /// Name of the file.
+        file: String,
+        /// What is wrong with it.
+        #[source]
+        error: anyhow::Error,
+    },
+}
+
+/// Check file descriptors, e.g. assembled programmatically,
+/// the same way files parsed by pure parser are checked.
+///
+/// All the imported files must be specified too.
+/// Options are not checked.
+pub fn validate_descriptors(files: &[FileDescriptorProto]) -> anyhow::Result<()> {
+    let mut names = HashSet::new();
+    for file in files {
+        if !names.insert(file.name()) {
+            return Err(ValidateError::DuplicateFile(file.name().to_owned()).into());
+        }
+        for dep in &file.dependency {
+            if !files.iter().any(|f| f.name() == dep) {
+                return Err(
+                    ValidateError::MissingDependency(file.name().to_owned(), dep.clone()).into(),
+                );
+            }
+        }
+    }
+
+    let mut validated: IndexMap<String, FileDescriptorPair> = IndexMap::new();
+    for file in sort_file_descriptors(files.to_vec()) {
+        let invalid = |error: anyhow::Error| {
+            // Restored model has no locations.
+            let error = match error.downcast::<ConvertErrorWithLoc>() {
+                Ok(e) => e.error,
+                Err(error) => error,
+            };
+            ValidateError::InvalidFile {
+                file: file.name().to_owned(),
+                error,
+            }
+        };
+
+        let mut deps = Vec::new();
+        collect_deps(&file, &validated, &mut deps)?;
+        let parsed = model_from_descriptor(&file);
+        let name = ProtoPath::new(file.name()).map_err(invalid)?;
+        convert::file_descriptor(name, &parsed, &deps, None, false).map_err(invalid)?;
+
+        let dep_descriptors: Vec<FileDescriptor> =
+            validated.values().map(|d| d.descriptor.clone()).collect();
+        let descriptor = FileDescriptor::new_dynamic(file.clone(), &dep_descriptors)
+            .map_err(|e| invalid(e.into()))?;
+        validated.insert(
+            file.name().to_owned(),
+            FileDescriptorPair {
+                parsed,
+                descriptor_proto: file,
+                descriptor,
+            },
+        );
+    }
+
+    let files: Vec<FileDescriptorProto> = validated
+        .into_values()
+        .map(|d| d.descriptor_proto)
+        .collect();
+    check_duplicate_types(&files)
+}
+
+/// Already validated transitive dependencies of a file.
+fn collect_deps(
+    file: &FileDescriptorProto,
+    validated: &IndexMap<String, FileDescriptorPair>,
+    deps: &mut Vec<FileDescriptorPair>,
+) -> anyhow::Result<()> {
+    for dep in &file.dependency {
+        if deps.iter().any(|d| d.descriptor_proto.name() == dep) {
+            continue;
+        }
+        // Files are sorted by dependencies, so a dependency not yet validated is in a cycle.
+        let pair = validated
+            .get(dep)
+            .ok_or_else(|| ValidateError::ImportCycle(file.name().to_owned()))?;
+        deps.push(pair.clone());
+        collect_deps(&pair.descriptor_proto, validated, deps)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use protobuf::descriptor::FileDescriptorProto;
+
+    use crate::proto_path::ProtoPath;
+    use crate::pure::parse_single;
+    use crate::pure::validate_descriptors;
+
+    fn parse(name: &str, content: &str, deps: &[FileDescriptorProto]) -> FileDescriptorProto {
+        parse_single(ProtoPath::new(name).unwrap(), content, deps).unwrap()
+    }
+
+    #[test]
+    fn valid() {
+        let a = parse(
+            "a.proto",
+            "syntax = 'proto2'; package a; message A { extensions 100 to 200; }",
+            &[],
+        );
+        let b = parse(
+            "b.proto",
+            r#"syntax = "proto3";
+            package b;
+            import "a.proto";
+            message B {
+                a.A a = 1;
+                optional int32 x = 2;
+                oneof o { string s = 3; int32 i = 4; }
+                map<string, a.A> m = 5;
+                reserved 10 to 12;
+            }
+            enum E { ZERO = 0; reserved 5; }
+            service S { rpc Call(B) returns (a.A); }"#,
+            std::slice::from_ref(&a),
+        );
+        validate_descriptors(&[b.clone(), a.clone()]).unwrap();
+        validate_descriptors(&[protobuf::descriptor::file_descriptor().proto().clone()]).unwrap();
+    }
+
+    #[test]
+    fn errors() {
+        let a = parse(
+            "a.proto",
+            "syntax = 'proto3'; message A { int32 x = 1; }",
+            &[],
+        );
+        let b = parse(
+            "b.proto",
+            "syntax = 'proto3'; import 'a.proto'; message B { A a = 1; }",
+            std::slice::from_ref(&a),
+        );
+
+        let error =
+            |files: &[FileDescriptorProto]| format!("{}", validate_descriptors(files).unwrap_err());
+
+        assert_eq!(
+            "file `b.proto` imports `a.proto`, which is not specified",
+            error(std::slice::from_ref(&b))
+        );
+        assert_eq!(
+            "file `a.proto` is specified more than once",
+            error(&[a.clone(), a.clone()])
+        );
+
+        let mut invalid = b.clone();
+        invalid.message_type[0].field[0].set_type_name(".Missing".to_owned());
+        assert_eq!(
+            "error in `b.proto`: object is not found by path: .Missing",
+            error(&[a.clone(), invalid])
+        );
+
+        let mut invalid = a.clone();
+        invalid.message_type[0].reserved_name.push("x".to_owned());
+        assert_eq!(
+            "error in `a.proto`: field `x` in message `.A` uses reserved name",
+            error(&[invalid])
+        );
+
+        let mut cycle = a.clone();
+        cycle.dependency.push("b.proto".to_owned());
+        assert!(error(&[cycle, b]).contains("import cycle"));
+    }
+}
@@ -0,0 +1,497 @@
+//! Compare two versions of a schema to find changes which break wire compatibility.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use protobuf::descriptor::field_descriptor_proto::Label;
+use protobuf::descriptor::field_descriptor_proto::Type;
+use protobuf::descriptor::DescriptorProto;
+use protobuf::descriptor::EnumDescriptorProto;
+use protobuf::descriptor::FieldDescriptorProto;
+use protobuf::descriptor::FileDescriptorSet;
+
+use crate::ProtobufAbsPath;
+use crate::ProtobufIdentRef;
+
+/// Change between two versions of a schema, found by [`schema_diff`].
+///
+/// Messages and enums are identified by fully qualified names like `.my.pkg.Message`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum SchemaChange {
+    /// Message is removed.
+    MessageRemoved { message: String },
+    /// Enum is removed.
+    EnumRemoved { enum_name: String },
+    /// Field is removed. Not breaking if its number is reserved.
+    FieldRemoved {
+        message: String,
+        field: String,
+        number: i32,
+        reserved: bool,
+    },
+    /// Field with the same number has different name.
+    FieldRenamed {
+        message: String,
+        old_name: String,
+        new_name: String,
+    },
+    /// Field has different number.
+    FieldNumberChanged {
+        message: String,
+        field: String,
+        old_number: i32,
+        new_number: i32,
+    },
+    /// Field has different type, like `int32` or `.my.pkg.Message`.
+    FieldTypeChanged {
+        message: String,
+        field: String,
+        old_type: String,
+        new_type: String,
+        compatible: bool,
+    },
+    /// Field changed between `optional`, `required` and `repeated`.
+    FieldLabelChanged {
+        message: String,
+        field: String,
+        old_label: Label,
+        new_label: Label,
+        compatible: bool,
+    },
+    /// Enum value is removed. Not breaking if its number is reserved.
+    EnumValueRemoved {
+        enum_name: String,
+        value: String,
+        number: i32,
+        reserved: bool,
+    },
+}
+
+impl SchemaChange {
+    /// Old and new versions cannot exchange messages in binary format.
+    pub fn is_breaking(&self) -> bool {
+        match self {
+            SchemaChange::MessageRemoved { .. } | SchemaChange::EnumRemoved { .. } => true,
+            SchemaChange::FieldRemoved { reserved, .. } => !reserved,
+            SchemaChange::FieldRenamed { .. } => false,
+            SchemaChange::FieldNumberChanged { .. } => true,
+            SchemaChange::FieldTypeChanged { compatible, .. } => !compatible,
+            SchemaChange::FieldLabelChanged { compatible, .. } => !compatible,
+            SchemaChange::EnumValueRemoved { reserved, .. } => !reserved,
+        }
+    }
+}
+
+fn label_name(label: Label) -> &'static str {
+    match label {
+        Label::LABEL_OPTIONAL => "optional",
+        Label::LABEL_REQUIRED => "required",
+        Label::LABEL_REPEATED => "repeated",
+    }
+}
+
+impl fmt::Display for SchemaChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaChange::MessageRemoved { message } => {
+                write!(f, "message `{}` is removed", message)
+            }
+            SchemaChange::EnumRemoved { enum_name } => {
+                write!(f, "enum `{}` is removed", enum_name)
+            }
+            SchemaChange::FieldRemoved {
+                message,
+                field,
+                number,
+                reserved,
+            } => {
+                write!(f, "field `{}.{}` = {} is removed", message, field, number)?;
+                if *reserved {
+                    write!(f, ", number is reserved")?;
+                }
+                Ok(())
+            }
+            SchemaChange::FieldRenamed {
+                message,
+                old_name,
+                new_name,
+            } => write!(
+                f,
+                "field `{}.{}` is renamed to `{}`",
+                message, old_name, new_name
+            ),
+            SchemaChange::FieldNumberChanged {
+                message,
+                field,
+                old_number,
+                new_number,
+            } => write!(
+                f,
+                "field `{}.{}` number is changed from {} to {}",
+                message, field, old_number, new_number
+            ),
+            SchemaChange::FieldTypeChanged {
+                message,
+                field,
+                old_type,
+                new_type,
+                ..
+            } => write!(
+                f,
+                "field `{}.{}` type is changed from `{}` to `{}`",
+                message, field, old_type, new_type
+            ),
+            SchemaChange::FieldLabelChanged {
+                message,
+                field,
+                old_label,
+                new_label,
+                ..
+            } => write!(
+                f,
+                "field `{}.{}` is changed from `{}` to `{}`",
+                message,
+                field,
+                label_name(*old_label),
+                label_name(*new_label)
+            ),
+            SchemaChange::EnumValueRemoved {
+                enum_name,
+                value,
+                number,
+                reserved,
+            } => {
+                write!(
+                    f,
+                    "enum value `{}.{}` = {} is removed",
+                    enum_name, value, number
+                )?;
+                if *reserved {
+                    write!(f, ", number is reserved")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Messages and enums of all files by fully qualified name, in declaration order.
+#[derive(Default)]
+struct Types<'a> {
+    messages: Vec<(String, &'a DescriptorProto)>,
+    enums: Vec<(String, &'a EnumDescriptorProto)>,
+}
+
+impl<'a> Types<'a> {
+    fn new(set: &'a FileDescriptorSet) -> Types<'a> {
+        let mut types = Types::default();
+        for file in &set.file {
+            let package = ProtobufAbsPath::package_from_file_proto(file);
+            types.add(&package, &file.message_type, &file.enum_type);
+        }
+        types
+    }
+
+    fn add(
+        &mut self,
+        scope: &ProtobufAbsPath,
+        messages: &'a [DescriptorProto],
+        enums: &'a [EnumDescriptorProto],
+    ) {
+        for message in messages {
+            let mut path = scope.clone();
+            path.push_simple(ProtobufIdentRef::new(message.name()));
+            self.messages.push((path.to_string(), message));
+            self.add(&path, &message.nested_type, &message.enum_type);
+        }
+        for enumeration in enums {
+            let mut path = scope.clone();
+            path.push_simple(ProtobufIdentRef::new(enumeration.name()));
+            self.enums.push((path.to_string(), enumeration));
+        }
+    }
+}
+
+fn type_name(field: &FieldDescriptorProto) -> String {
+    match field.type_() {
+        Type::TYPE_MESSAGE | Type::TYPE_ENUM | Type::TYPE_GROUP => field.type_name().to_owned(),
+        t => format!("{:?}", t)["TYPE_".len()..].to_lowercase(),
+    }
+}
+
+/// Types which can be changed into each other keeping old data readable.
+fn types_compatible(old: &FieldDescriptorProto, new: &FieldDescriptorProto) -> bool {
+    fn group(field: &FieldDescriptorProto) -> Option<u32> {
+        match field.type_() {
+            Type::TYPE_INT32
+            | Type::TYPE_UINT32
+            | Type::TYPE_INT64
+            | Type::TYPE_UINT64
+            | Type::TYPE_BOOL
+            | Type::TYPE_ENUM => Some(0),
+            Type::TYPE_SINT32 | Type::TYPE_SINT64 => Some(1),
+            Type::TYPE_FIXED32 | Type::TYPE_SFIXED32 => Some(2),
+            Type::TYPE_FIXED64 | Type::TYPE_SFIXED64 => Some(3),
+            // Compatible as long as bytes are valid UTF-8.
+            Type::TYPE_STRING | Type::TYPE_BYTES => Some(4),
+            Type::TYPE_FLOAT | Type::TYPE_DOUBLE | Type::TYPE_MESSAGE | Type::TYPE_GROUP => None,
+        }
+    }
+    match (group(old), group(new)) {
+        (Some(old), Some(new)) => old == new,
+        _ => false,
+    }
+}
+
+/// `optional` and `repeated` are compatible for length-delimited types,
+/// because such values are never packed.
+fn labels_compatible(old: &FieldDescriptorProto, new: &FieldDescriptorProto) -> bool {
+    let length_delimited = matches!(
+        new.type_(),
+        Type::TYPE_STRING | Type::TYPE_BYTES | Type::TYPE_MESSAGE
+    );
+    old.label() != Label::LABEL_REQUIRED && new.label() != Label::LABEL_REQUIRED && length_delimited
+}
+
+fn diff_field(
+    message: &str,
+    old: &FieldDescriptorProto,
+    new: &FieldDescriptorProto,
+    changes: &mut Vec<SchemaChange>,
+) {
+    if old.name() != new.name() {
+        changes.push(SchemaChange::FieldRenamed {
+            message: message.to_owned(),
+            old_name: old.name().to_owned(),
+            new_name: new.name().to_owned(),
+        });
+    }
+    if old.number() != new.number() {
+        changes.push(SchemaChange::FieldNumberChanged {
+            message: message.to_owned(),
+            field: old.name().to_owned(),
+            old_number: old.number(),
+            new_number: new.number(),
+        });
+    }
+    let (old_type, new_type) = (type_name(old), type_name(new));
+    if old_type != new_type {
+        changes.push(SchemaChange::FieldTypeChanged {
+            message: message.to_owned(),
+            field: old.name().to_owned(),
+            old_type,
+            new_type,
+            compatible: types_compatible(old, new),
+        });
+    }
+    if old.label() != new.label() {
+        changes.push(SchemaChange::FieldLabelChanged {
+            message: message.to_owned(),
+            field: old.name().to_owned(),
+            old_label: old.label(),
+            new_label: new.label(),
+            compatible: labels_compatible(old, new),
+        });
+    }
+}
+
+fn diff_message(
+    name: &str,
+    old: &DescriptorProto,
+    new: &DescriptorProto,
+    changes: &mut Vec<SchemaChange>,
+) {
+    // Match fields by name, then remaining fields by number to detect renames.
+    let mut unmatched_new: Vec<&FieldDescriptorProto> = new
+        .field
+        .iter()
+        .filter(|n| !old.field.iter().any(|o| o.name() == n.name()))
+        .collect();
+    for old_field in &old.field {
+        if let Some(new_field) = new.field.iter().find(|n| n.name() == old_field.name()) {
+            diff_field(name, old_field, new_field, changes);
+            continue;
+        }
+        match unmatched_new
+            .iter()
+            .position(|n| n.number() == old_field.number())
+        {
+            Some(i) => diff_field(name, old_field, unmatched_new.remove(i), changes),
+            None => changes.push(SchemaChange::FieldRemoved {
+                message: name.to_owned(),
+                field: old_field.name().to_owned(),
+                number: old_field.number(),
+                reserved: new
+                    .reserved_range
+                    .iter()
+                    .any(|r| r.start() <= old_field.number() && old_field.number() < r.end()),
+            }),
+        }
+    }
+}
+
+fn diff_enum(
+    name: &str,
+    old: &EnumDescriptorProto,
+    new: &EnumDescriptorProto,
+    changes: &mut Vec<SchemaChange>,
+) {
+    for old_value in &old.value {
+        // Values are encoded by number, so a value renamed keeping the number is not removed.
+        if new.value.iter().any(|n| n.number() == old_value.number()) {
+            continue;
+        }
+        changes.push(SchemaChange::EnumValueRemoved {
+            enum_name: name.to_owned(),
+            value: old_value.name().to_owned(),
+            number: old_value.number(),
+            // Enum reserved range end is inclusive.
+            reserved: new
+                .reserved_range
+                .iter()
+                .any(|r| r.start() <= old_value.number() && old_value.number() <= r.end()),
+        });
+    }
+}
+
+/// Find changes between two versions of a schema, like two results of
+/// [`Parser::file_descriptor_set`](crate::Parser::file_descriptor_set).
+///
+/// Added declarations are not reported, because adding is always compatible.
+/// Use [`SchemaChange::is_breaking`] to find changes which break wire compatibility.
+pub fn schema_diff(old: &FileDescriptorSet, new: &FileDescriptorSet) -> Vec<SchemaChange> {
+    let old_types = Types::new(old);
+    let new_types = Types::new(new);
+    let new_messages: HashMap<&str, &DescriptorProto> = new_types
+        .messages
+        .iter()
+        .map(|(name, m)| (name.as_str(), *m))
+        .collect();
+    let new_enums: HashMap<&str, &EnumDescriptorProto> = new_types
+        .enums
+        .iter()
+        .map(|(name, e)| (name.as_str(), *e))
+        .collect();
+
+    let mut changes = Vec::new();
+    for (name, old_message) in &old_types.messages {
+        match new_messages.get(name.as_str()) {
+            Some(new_message) => diff_message(name, old_message, new_message, &mut changes),
+            None => changes.push(SchemaChange::MessageRemoved {
+                message: name.clone(),
+            }),
+        }
+    }
+    for (name, old_enum) in &old_types.enums {
+        match new_enums.get(name.as_str()) {
+            Some(new_enum) => diff_enum(name, old_enum, new_enum, &mut changes),
+            None => changes.push(SchemaChange::EnumRemoved {
+                enum_name: name.clone(),
+            }),
+        }
+    }
+    changes
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::proto_path::ProtoPath;
+    use crate::pure::parse_single;
+
+    fn set(content: &str) -> FileDescriptorSet {
+        let mut set = FileDescriptorSet::new();
+        set.file
+            .push(parse_single(ProtoPath::new("a.proto").unwrap(), content, &[]).unwrap());
+        set
+    }
+
+    #[test]
+    fn diff() {
+        let old = set(r#"syntax = "proto2";
+            package p;
+            message M {
+                optional int32 a = 1;
+                optional int32 b = 2;
+                optional string c = 3;
+                optional int32 d = 4;
+                optional int32 e = 5;
+                optional int32 f = 6;
+                required string g = 7;
+                optional float h = 8;
+                message Gone {}
+            }
+            enum E { X = 0; Y = 1; Z = 2; }"#);
+        let new = set(r#"syntax = "proto2";
+            package p;
+            message M {
+                reserved 2;
+                optional int64 a = 1;
+                repeated string c = 3;
+                optional int32 renamed = 4;
+                optional int32 e = 50;
+                optional sint32 g = 7;
+                optional double h = 8;
+            }
+            enum E { reserved 1; X = 0; }"#);
+
+        let changes = schema_diff(&old, &new);
+        let summary: Vec<(String, bool)> = changes
+            .iter()
+            .map(|c| (c.to_string(), c.is_breaking()))
+            .collect();
+        assert_eq!(
+            vec![
+                (
+                    "field `.p.M.a` type is changed from `int32` to `int64`".to_owned(),
+                    false
+                ),
+                (
+                    "field `.p.M.b` = 2 is removed, number is reserved".to_owned(),
+                    false
+                ),
+                (
+                    "field `.p.M.c` is changed from `optional` to `repeated`".to_owned(),
+                    false
+                ),
+                ("field `.p.M.d` is renamed to `renamed`".to_owned(), false),
+                (
+                    "field `.p.M.e` number is changed from 5 to 50".to_owned(),
+                    true
+                ),
+                ("field `.p.M.f` = 6 is removed".to_owned(), true),
+                (
+                    "field `.p.M.g` type is changed from `string` to `sint32`".to_owned(),
+                    true
+                ),
+                (
+                    "field `.p.M.g` is changed from `required` to `optional`".to_owned(),
+                    true
+                ),
+                (
+                    "field `.p.M.h` type is changed from `float` to `double`".to_owned(),
+                    true
+                ),
+                ("message `.p.M.Gone` is removed".to_owned(), true),
+                (
+                    "enum value `.p.E.Y` = 1 is removed, number is reserved".to_owned(),
+                    false
+                ),
+                ("enum value `.p.E.Z` = 2 is removed".to_owned(), true),
+            ],
+            summary
+        );
+        assert_eq!(
+            SchemaChange::FieldLabelChanged {
+                message: ".p.M".to_owned(),
+                field: "g".to_owned(),
+                old_label: Label::LABEL_REQUIRED,
+                new_label: Label::LABEL_OPTIONAL,
+                compatible: false,
+            },
+            changes[7]
+        );
+        assert!(schema_diff(&old, &old).is_empty());
+    }
+}
@@ -0,0 +1,26 @@
+#![cfg(feature = "model-api")]
+
+use protobuf_parse::pure::model::FieldOrOneOf;
+use protobuf_parse::pure::model::Syntax;
+use protobuf_parse::pure::parse_model;
+
+#[test]
+fn parse_model_without_typecheck() {
+    let model = parse_model(
+        "syntax = 'proto3'; package p; import 'unknown.proto'; message M { Unknown u = 1; }",
+    )
+    .unwrap();
+    assert_eq!(Syntax::Proto3, model.syntax);
+    assert_eq!("unknown.proto", model.imports[0].path.to_str());
+    assert_eq!("M", model.messages[0].t.name);
+    match &model.messages[0].t.fields[0].t {
+        FieldOrOneOf::Field(f) => assert_eq!("u", f.t.name),
+        FieldOrOneOf::OneOf(_) => panic!("field expected"),
+    }
+}
+
+#[test]
+fn parse_model_reports_errors() {
+    let errors = parse_model("message M { int32 = 1; }\nmessage N { int32 = 2; }").unwrap_err();
+    assert_eq!(2, errors.0.len());
+}
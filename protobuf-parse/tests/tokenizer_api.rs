@@ -0,0 +1,60 @@
+#![cfg(feature = "tokenizer-api")]
+
+use protobuf_parse::pure::tokenize;
+use protobuf_parse::pure::ParserErrorKind;
+use protobuf_parse::pure::TokenKind;
+
+#[test]
+fn tokenize_with_comments() {
+    let content = "// Header\nmessage M { /* x */ int32 x = 1; float f = 2 [default = -1.5]; string s = 3 [default = \"a b\"]; }\n";
+    let tokens = tokenize(content).unwrap();
+    for token in &tokens {
+        assert_eq!(&content[token.span.clone()], token.text);
+    }
+    let tokens: Vec<_> = tokens.iter().map(|t| (t.kind, t.text.as_str())).collect();
+    assert_eq!(
+        vec![
+            (TokenKind::Comment, "// Header"),
+            (TokenKind::Ident, "message"),
+            (TokenKind::Ident, "M"),
+            (TokenKind::Symbol, "{"),
+            (TokenKind::Comment, "/* x */"),
+            (TokenKind::Ident, "int32"),
+            (TokenKind::Ident, "x"),
+            (TokenKind::Symbol, "="),
+            (TokenKind::IntLit, "1"),
+            (TokenKind::Symbol, ";"),
+            (TokenKind::Ident, "float"),
+            (TokenKind::Ident, "f"),
+            (TokenKind::Symbol, "="),
+            (TokenKind::IntLit, "2"),
+            (TokenKind::Symbol, "["),
+            (TokenKind::Ident, "default"),
+            (TokenKind::Symbol, "="),
+            (TokenKind::Symbol, "-"),
+            (TokenKind::FloatLit, "1.5"),
+            (TokenKind::Symbol, "]"),
+            (TokenKind::Symbol, ";"),
+            (TokenKind::Ident, "string"),
+            (TokenKind::Ident, "s"),
+            (TokenKind::Symbol, "="),
+            (TokenKind::IntLit, "3"),
+            (TokenKind::Symbol, "["),
+            (TokenKind::Ident, "default"),
+            (TokenKind::Symbol, "="),
+            (TokenKind::StrLit, "\"a b\""),
+            (TokenKind::Symbol, "]"),
+            (TokenKind::Symbol, ";"),
+            (TokenKind::Symbol, "}"),
+        ],
+        tokens
+    );
+}
+
+#[test]
+fn tokenize_error() {
+    let error = tokenize("message M {\n  /* unterminated").unwrap_err();
+    assert_eq!(2, error.line());
+    assert_eq!(3, error.column());
+    assert_eq!(ParserErrorKind::Lexer, error.kind());
+}
@@ -8,7 +8,7 @@ pub fn json_name(input: &str) -> String {
         if c == '_' {
             capitalize_next = true;
         } else if capitalize_next {
-            result.extend(c.to_uppercase());
+            result.push(c.to_ascii_uppercase());
             capitalize_next = false;
         } else {
             result.push(c);
@@ -17,3 +17,19 @@ pub fn json_name(input: &str) -> String {
 
     result
 }
+
+#[cfg(test)]
+mod test {
+    use super::json_name;
+
+    #[test]
+    fn test_json_name() {
+        assert_eq!("fooBar", json_name("foo_bar"));
+        assert_eq!("fooBar2", json_name("foo_bar_2"));
+        assert_eq!("foo3bar", json_name("foo_3bar"));
+        assert_eq!("X", json_name("__x__"));
+        assert_eq!("FooBar", json_name("_foo__bar"));
+        assert_eq!("FOO", json_name("FOO"));
+        assert_eq!("fooBAR", json_name("foo_BAR"));
+    }
+}
@@ -42,6 +42,10 @@ pub enum LexerError {
     ExpectOctDigit,
     #[error("Expecting dec digit")]
     ExpectDecDigit,
+    #[error("Invalid escape sequence in string literal: `\\{0}`")]
+    InvalidEscape(char),
+    #[error("Octal escape in string literal is out of range: `\\{0:o}`")]
+    OctalEscapeOutOfRange(u32),
     #[error(transparent)]
     StrLitDecodeError(#[from] StrLitDecodeError),
     #[error("Expecting identifier")]
@@ -95,6 +99,11 @@ impl<'a> Lexer<'a> {
         self.pos == self.input.len()
     }
 
+    /// Byte offset of the next char
+    pub fn pos(&self) -> usize {
+        self.pos
+    }
+
     /// Remaining chars
     fn rem_chars(&self) -> &'a str {
         &self.input[self.pos..]
@@ -120,6 +129,8 @@ impl<'a> Lexer<'a> {
             if c == '\n' {
                 self.loc.line += 1;
                 self.loc.col = FIRST_COL;
+            } else if c == '\r' && self.rem_chars().starts_with('\n') {
+                // `\r\n` is a single line break.
             } else {
                 self.loc.col += 1;
             }
@@ -132,7 +143,7 @@ impl<'a> Lexer<'a> {
     }
 
     /// Skip whitespaces
-    fn skip_whitespaces(&mut self) {
+    pub fn skip_whitespaces(&mut self) {
         self.take_while(|c| c.is_whitespace());
     }
 
@@ -188,6 +199,27 @@ impl<'a> Lexer<'a> {
         Ok(())
     }
 
+    /// Next comment, without trailing line break
+    pub fn next_comment_opt(&mut self) -> LexerResult<Option<&'a str>> {
+        let pos = self.pos;
+        match self.language {
+            ParserLanguage::Proto => {
+                self.skip_c_comment()?;
+                if pos == self.pos {
+                    self.skip_cpp_comment();
+                }
+            }
+            ParserLanguage::TextFormat => self.skip_sh_comment(),
+            ParserLanguage::Json => {}
+        }
+        let comment = &self.input[pos..self.pos];
+        Ok(if comment.is_empty() {
+            None
+        } else {
+            Some(comment.trim_end_matches(['\n', '\r']))
+        })
+    }
+
     pub fn skip_ws(&mut self) -> LexerResult<()> {
         loop {
             let pos = self.pos;
@@ -230,7 +262,7 @@ impl<'a> Lexer<'a> {
         }
     }
 
-    fn next_char_if<P>(&mut self, p: P) -> Option<char>
+    pub(crate) fn next_char_if<P>(&mut self, p: P) -> Option<char>
     where
         P: FnOnce(char) -> bool,
     {
@@ -367,7 +399,7 @@ impl<'a> Lexer<'a> {
 
     // octalDigit   = "0" … "7"
     fn next_octal_digit(&mut self) -> LexerResult<u32> {
-        self.next_char_expect(|c| c >= '0' && c <= '9', LexerError::ExpectOctDigit)
+        self.next_char_expect(|c| ('0'..='7').contains(&c), LexerError::ExpectOctDigit)
             .map(|c| c as u32 - '0' as u32)
     }
 
@@ -435,50 +467,94 @@ impl<'a> Lexer<'a> {
 
     // String literals
 
-    // charValue = hexEscape | octEscape | charEscape | /[^\0\n\\]/
-    // hexEscape = '\' ( "x" | "X" ) hexDigit hexDigit
+    // charValue = hexEscape | octEscape | charEscape | unicodeEscape | /[^\0\n\\]/
+    // hexEscape = '\' ( "x" | "X" ) hexDigit [ hexDigit ]
     // https://github.com/google/protobuf/issues/4560
-    // octEscape = '\' octalDigit octalDigit octalDigit
-    // charEscape = '\' ( "a" | "b" | "f" | "n" | "r" | "t" | "v" | '\' | "'" | '"' )
+    // octEscape = '\' octalDigit [ octalDigit [ octalDigit ] ]
+    // charEscape = '\' ( "a" | "b" | "f" | "n" | "r" | "t" | "v" | '\' | "'" | '"' | "?" )
+    // unicodeEscape = '\' "u" hexDigit{4} | '\' "U" hexDigit{8}
     // quote = "'" | '"'
-    pub fn next_byte_value(&mut self) -> LexerResult<u8> {
+    /// Decode next character of string literal, appending its bytes to `r`.
+    ///
+    /// Unicode escapes and unescaped non-ASCII characters are stored as UTF-8.
+    pub fn next_str_lit_value(&mut self, r: &mut Vec<u8>) -> LexerResult<()> {
         match self.next_char()? {
-            '\\' => {
-                match self.next_char()? {
-                    '\'' => Ok(b'\''),
-                    '"' => Ok(b'"'),
-                    '\\' => Ok(b'\\'),
-                    'a' => Ok(b'\x07'),
-                    'b' => Ok(b'\x08'),
-                    'f' => Ok(b'\x0c'),
-                    'n' => Ok(b'\n'),
-                    'r' => Ok(b'\r'),
-                    't' => Ok(b'\t'),
-                    'v' => Ok(b'\x0b'),
-                    'x' => {
-                        let d1 = self.next_hex_digit()? as u8;
-                        let d2 = self.next_hex_digit()? as u8;
-                        Ok(((d1 << 4) | d2) as u8)
-                    }
-                    d if d >= '0' && d <= '7' => {
-                        let mut r = d as u8 - b'0';
-                        for _ in 0..2 {
-                            match self.next_octal_digit() {
-                                Err(_) => break,
-                                Ok(d) => r = (r << 3) + d as u8,
-                            }
-                        }
-                        Ok(r)
+            '\\' => {}
+            '\n' | '\0' => return Err(LexerError::IncorrectInput),
+            c => {
+                r.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                return Ok(());
+            }
+        }
+        let b = match self.next_char()? {
+            '\'' => b'\'',
+            '"' => b'"',
+            '\\' => b'\\',
+            '?' => b'?',
+            'a' => b'\x07',
+            'b' => b'\x08',
+            'f' => b'\x0c',
+            'n' => b'\n',
+            'r' => b'\r',
+            't' => b'\t',
+            'v' => b'\x0b',
+            'x' | 'X' => {
+                let mut v = self.next_hex_digit()?;
+                if let Ok(d) = self.next_hex_digit() {
+                    v = (v << 4) | d;
+                }
+                v as u8
+            }
+            d if d >= '0' && d <= '7' => {
+                let mut v = d as u32 - '0' as u32;
+                for _ in 0..2 {
+                    let mut clone = *self;
+                    match clone.next_octal_digit() {
+                        Ok(d) => v = (v << 3) | d,
+                        Err(_) => break,
                     }
-                    // https://github.com/google/protobuf/issues/4562
-                    // TODO: overflow
-                    c => Ok(c as u8),
+                    *self = clone;
+                }
+                if v > 0xff {
+                    return Err(LexerError::OctalEscapeOutOfRange(v));
+                }
+                v as u8
+            }
+            u @ ('u' | 'U') => {
+                let c = self.next_unicode_escape(u)?;
+                r.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                return Ok(());
+            }
+            // https://github.com/google/protobuf/issues/4562
+            c => return Err(LexerError::InvalidEscape(c)),
+        };
+        r.push(b);
+        Ok(())
+    }
+
+    /// Code point of `\u` or `\U` escape, with the backslash and `u` already consumed.
+    ///
+    /// Like `protoc`, a `\u` high surrogate followed by `\u` low surrogate is a single character.
+    fn next_unicode_escape(&mut self, u: char) -> LexerResult<char> {
+        let digits = if u == 'u' { 4 } else { 8 };
+        let mut v: u32 = 0;
+        for _ in 0..digits {
+            v = v * 16 + self.next_hex_digit()?;
+        }
+        if (0xd800..0xdc00).contains(&v) {
+            let mut clone = *self;
+            if clone.skip_if_lookahead_is_str("\\u") {
+                let mut low = 0;
+                for _ in 0..4 {
+                    low = low * 16 + clone.next_hex_digit()?;
+                }
+                if (0xdc00..0xe000).contains(&low) {
+                    *self = clone;
+                    return Self::char_try_from(0x10000 + ((v - 0xd800) << 10) + (low - 0xdc00));
                 }
             }
-            '\n' | '\0' => Err(LexerError::IncorrectInput),
-            // TODO: check overflow
-            c => Ok(c as u8),
         }
+        Self::char_try_from(v)
     }
 
     fn char_try_from(i: u32) -> LexerResult<char> {
@@ -530,8 +606,9 @@ impl<'a> Lexer<'a> {
                 None => return Err(LexerError::IncorrectInput),
             };
             first = false;
+            let mut bytes = Vec::new();
             while self.lookahead_char() != Some(q) {
-                self.next_byte_value()?;
+                self.next_str_lit_value(&mut bytes)?;
             }
             self.next_char_expect_eq(q)?;
 
@@ -600,7 +677,8 @@ impl<'a> Lexer<'a> {
         Ok(Some(JsonNumberLit(s)))
     }
 
-    fn next_token_inner(&mut self) -> LexerResult<Token> {
+    /// Next token, input must not start with whitespace or comment
+    pub fn next_token_inner(&mut self) -> LexerResult<Token> {
         if self.language == ParserLanguage::Json {
             if let Some(v) = self.next_json_number_opt()? {
                 return Ok(Token::JsonNumber(v));
@@ -656,10 +734,11 @@ impl<'a> Lexer<'a> {
             None
         } else {
             let token = self.next_token_inner()?;
+            let end = self.loc;
             // Skip whitespace here to update location
             // to the beginning of the next token
             self.skip_ws()?;
-            Some(TokenWithLocation { token, loc })
+            Some(TokenWithLocation { token, loc, end })
         })
     }
 }
@@ -689,6 +768,17 @@ mod test {
         r
     }
 
+    #[test]
+    fn test_crlf_loc() {
+        let mut lexer = Lexer::new("a\r\nb", ParserLanguage::Proto);
+        assert_eq!(Some('a'), lexer.next_char_opt());
+        assert_eq!(Some('\r'), lexer.next_char_opt());
+        assert_eq!(Loc { line: 1, col: 2 }, lexer.loc);
+        assert_eq!(Some('\n'), lexer.next_char_opt());
+        assert_eq!(Some('b'), lexer.next_char_opt());
+        assert_eq!(Loc { line: 2, col: 2 }, lexer.loc);
+    }
+
     #[test]
     fn test_lexer_int_lit() {
         let msg = r#"10"#;
@@ -709,4 +799,43 @@ mod test {
         let mess = lex(msg, |p| p.next_token_inner());
         assert_eq!(Token::FloatLit(1_000_000_000.0), mess);
     }
+
+    #[test]
+    fn test_lexer_str_lit_escapes() {
+        let decode = |input: &str| {
+            let mut lexer = Lexer::new(input, ParserLanguage::Proto);
+            let mut r = Vec::new();
+            while !lexer.eof() {
+                lexer.next_str_lit_value(&mut r)?;
+            }
+            Ok::<_, LexerError>(r)
+        };
+        assert_eq!(
+            b"\x07\x08\x0c\n\r\t\x0b\\'\"?",
+            &decode(r#"\a\b\f\n\r\t\v\\\'\"\?"#).unwrap()[..]
+        );
+        assert_eq!(b"\x0f\xff\x01g", &decode(r"\xf\xFF\X1g").unwrap()[..]);
+        assert_eq!(b"\0\x0a\xff8", &decode(r"\0\12\3778").unwrap()[..]);
+        assert_eq!(
+            "\u{e9}\u{1234}\u{1f600}\u{1f600}".as_bytes(),
+            &decode(r"\u00e9\u1234\U0001f600\ud83d\ude00").unwrap()[..]
+        );
+        assert_eq!("\u{e9}".as_bytes(), &decode("\u{e9}").unwrap()[..]);
+
+        assert!(matches!(decode(r"\xg"), Err(LexerError::ExpectHexDigit)));
+        assert!(matches!(
+            decode(r"\400"),
+            Err(LexerError::OctalEscapeOutOfRange(0o400))
+        ));
+        assert!(matches!(decode(r"\u12zz"), Err(LexerError::ExpectHexDigit)));
+        assert!(matches!(
+            decode(r"\U00110000"),
+            Err(LexerError::IncorrectUnicodeChar)
+        ));
+        assert!(matches!(
+            decode(r"\ud83d"),
+            Err(LexerError::IncorrectUnicodeChar)
+        ));
+        assert!(matches!(decode(r"\q"), Err(LexerError::InvalidEscape('q'))));
+    }
 }
@@ -6,7 +6,7 @@ use crate::lexer::parser_language::ParserLanguage;
 
 #[derive(Debug, thiserror::Error)]
 pub enum StrLitDecodeError {
-    #[error(transparent)]
+    #[error("string literal is not valid UTF-8, use `bytes` type for binary data: {0}")]
     FromUtf8Error(#[from] FromUtf8Error),
     #[error("String literal decode error")]
     OtherError,
@@ -29,27 +29,16 @@ impl fmt::Display for StrLit {
 impl StrLit {
     /// May fail if not valid UTF8
     pub fn decode_utf8(&self) -> StrLitDecodeResult<String> {
-        let mut lexer = Lexer::new(&self.escaped, ParserLanguage::Json);
-        let mut r = Vec::new();
-        while !lexer.eof() {
-            r.push(
-                lexer
-                    .next_byte_value()
-                    .map_err(|_| StrLitDecodeError::OtherError)?,
-            );
-        }
-        Ok(String::from_utf8(r)?)
+        Ok(String::from_utf8(self.decode_bytes()?)?)
     }
 
     pub fn decode_bytes(&self) -> StrLitDecodeResult<Vec<u8>> {
         let mut lexer = Lexer::new(&self.escaped, ParserLanguage::Json);
         let mut r = Vec::new();
         while !lexer.eof() {
-            r.push(
-                lexer
-                    .next_byte_value()
-                    .map_err(|_| StrLitDecodeError::OtherError)?,
-            );
+            lexer
+                .next_str_lit_value(&mut r)
+                .map_err(|_| StrLitDecodeError::OtherError)?;
         }
         Ok(r)
     }
@@ -74,4 +63,16 @@ mod test {
             .unwrap()
         )
     }
+
+    #[test]
+    fn decode_non_ascii() {
+        let lit = StrLit {
+            escaped: "\u{e9}\\n\u{1234}".to_owned(),
+        };
+        assert_eq!("\u{e9}\n\u{1234}", lit.decode_utf8().unwrap());
+        assert_eq!(
+            b"\xc3\xa9\n\xe1\x88\xb4",
+            lit.decode_bytes().unwrap().as_slice()
+        );
+    }
 }
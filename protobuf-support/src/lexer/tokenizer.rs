@@ -45,6 +45,7 @@ pub struct Tokenizer<'a> {
     lexer: Lexer<'a>,
     next_token: Option<TokenWithLocation>,
     last_token_loc: Option<Loc>,
+    last_token_end: Option<Loc>,
 }
 
 impl<'a> Tokenizer<'a> {
@@ -53,6 +54,7 @@ impl<'a> Tokenizer<'a> {
             lexer: Lexer::new(input, comment_style),
             next_token: None,
             last_token_loc: None,
+            last_token_end: None,
         }
     }
 
@@ -67,6 +69,14 @@ impl<'a> Tokenizer<'a> {
             .unwrap_or(self.lexer.loc)
     }
 
+    /// End of the token at [`loc`](Self::loc), if there is such token.
+    pub fn loc_end(&self) -> Option<Loc> {
+        self.next_token
+            .as_ref()
+            .map(|t| t.end)
+            .or(self.last_token_end)
+    }
+
     pub fn lookahead_loc(&mut self) -> Loc {
         drop(self.lookahead());
         // TODO: does not handle EOF properly
@@ -79,6 +89,7 @@ impl<'a> Tokenizer<'a> {
             None => {
                 self.next_token = self.lexer.next_token()?;
                 self.last_token_loc = self.next_token.as_ref().map(|t| t.loc.clone());
+                self.last_token_end = self.next_token.as_ref().map(|t| t.end);
                 match self.next_token {
                     Some(ref token) => Some(&token.token),
                     None => None,
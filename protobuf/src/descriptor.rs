@@ -21,8 +21,8 @@
 
 //! Generated file from `google/protobuf/descriptor.proto`
 
-///  The protocol compiler can output a FileDescriptorSet containing the .proto
-///  files it parses.
+/// The protocol compiler can output a FileDescriptorSet containing the .proto
+/// files it parses.
 // @@protoc_insertion_point(message:google.protobuf.FileDescriptorSet)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct FileDescriptorSet {
@@ -151,7 +151,7 @@ impl crate::reflect::ProtobufValue for FileDescriptorSet {
     type RuntimeType = crate::reflect::rt::RuntimeTypeMessage<Self>;
 }
 
-///  Describes a complete .proto file.
+/// Describes a complete .proto file.
 // @@protoc_insertion_point(message:google.protobuf.FileDescriptorProto)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct FileDescriptorProto {
@@ -160,17 +160,17 @@ pub struct FileDescriptorProto {
     pub name: ::std::option::Option<::std::string::String>,
     // @@protoc_insertion_point(field:google.protobuf.FileDescriptorProto.package)
     pub package: ::std::option::Option<::std::string::String>,
-    ///  Names of files imported by this file.
+    /// Names of files imported by this file.
     // @@protoc_insertion_point(field:google.protobuf.FileDescriptorProto.dependency)
     pub dependency: ::std::vec::Vec<::std::string::String>,
-    ///  Indexes of the public imported files in the dependency list above.
+    /// Indexes of the public imported files in the dependency list above.
     // @@protoc_insertion_point(field:google.protobuf.FileDescriptorProto.public_dependency)
     pub public_dependency: ::std::vec::Vec<i32>,
-    ///  Indexes of the weak imported files in the dependency list.
-    ///  For Google-internal migration only. Do not use.
+    /// Indexes of the weak imported files in the dependency list.
+    /// For Google-internal migration only. Do not use.
     // @@protoc_insertion_point(field:google.protobuf.FileDescriptorProto.weak_dependency)
     pub weak_dependency: ::std::vec::Vec<i32>,
-    ///  All top-level definitions in this file.
+    /// All top-level definitions in this file.
     // @@protoc_insertion_point(field:google.protobuf.FileDescriptorProto.message_type)
     pub message_type: ::std::vec::Vec<DescriptorProto>,
     // @@protoc_insertion_point(field:google.protobuf.FileDescriptorProto.enum_type)
@@ -181,14 +181,14 @@ pub struct FileDescriptorProto {
     pub extension: ::std::vec::Vec<FieldDescriptorProto>,
     // @@protoc_insertion_point(field:google.protobuf.FileDescriptorProto.options)
     pub options: crate::MessageField<FileOptions>,
-    ///  This field contains optional information about the original source code.
-    ///  You may safely remove this entire field without harming runtime
-    ///  functionality of the descriptors -- the information is needed only by
-    ///  development tools.
+    /// This field contains optional information about the original source code.
+    /// You may safely remove this entire field without harming runtime
+    /// functionality of the descriptors -- the information is needed only by
+    /// development tools.
     // @@protoc_insertion_point(field:google.protobuf.FileDescriptorProto.source_code_info)
     pub source_code_info: crate::MessageField<SourceCodeInfo>,
-    ///  The syntax of the proto file.
-    ///  The supported values are "proto2" and "proto3".
+    /// The syntax of the proto file.
+    /// The supported values are "proto2" and "proto3".
     // @@protoc_insertion_point(field:google.protobuf.FileDescriptorProto.syntax)
     pub syntax: ::std::option::Option<::std::string::String>,
     // special fields
@@ -633,7 +633,7 @@ impl crate::reflect::ProtobufValue for FileDescriptorProto {
     type RuntimeType = crate::reflect::rt::RuntimeTypeMessage<Self>;
 }
 
-///  Describes a message type.
+/// Describes a message type.
 // @@protoc_insertion_point(message:google.protobuf.DescriptorProto)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct DescriptorProto {
@@ -656,8 +656,8 @@ pub struct DescriptorProto {
     pub options: crate::MessageField<MessageOptions>,
     // @@protoc_insertion_point(field:google.protobuf.DescriptorProto.reserved_range)
     pub reserved_range: ::std::vec::Vec<descriptor_proto::ReservedRange>,
-    ///  Reserved field names, which may not be used by fields in the same message.
-    ///  A given name may only be reserved once.
+    /// Reserved field names, which may not be used by fields in the same message.
+    /// A given name may only be reserved once.
     // @@protoc_insertion_point(field:google.protobuf.DescriptorProto.reserved_name)
     pub reserved_name: ::std::vec::Vec<::std::string::String>,
     // special fields
@@ -1208,9 +1208,9 @@ pub mod descriptor_proto {
         type RuntimeType = crate::reflect::rt::RuntimeTypeMessage<Self>;
     }
 
-    ///  Range of reserved tag numbers. Reserved tag numbers may not be used by
-    ///  fields or extension ranges in the same message. Reserved ranges may
-    ///  not overlap.
+    /// Range of reserved tag numbers. Reserved tag numbers may not be used by
+    /// fields or extension ranges in the same message. Reserved ranges may
+    /// not overlap.
     // @@protoc_insertion_point(message:google.protobuf.DescriptorProto.ReservedRange)
     #[derive(PartialEq,Clone,Default,Debug)]
     pub struct ReservedRange {
@@ -1394,7 +1394,7 @@ pub mod descriptor_proto {
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct ExtensionRangeOptions {
     // message fields
-    ///  The parser stores options it doesn't recognize here. See above.
+    /// The parser stores options it doesn't recognize here. See above.
     // @@protoc_insertion_point(field:google.protobuf.ExtensionRangeOptions.uninterpreted_option)
     pub uninterpreted_option: ::std::vec::Vec<UninterpretedOption>,
     // special fields
@@ -1519,7 +1519,7 @@ impl crate::reflect::ProtobufValue for ExtensionRangeOptions {
     type RuntimeType = crate::reflect::rt::RuntimeTypeMessage<Self>;
 }
 
-///  Describes a field within a message.
+/// Describes a field within a message.
 // @@protoc_insertion_point(message:google.protobuf.FieldDescriptorProto)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct FieldDescriptorProto {
@@ -1530,61 +1530,61 @@ pub struct FieldDescriptorProto {
     pub number: ::std::option::Option<i32>,
     // @@protoc_insertion_point(field:google.protobuf.FieldDescriptorProto.label)
     pub label: ::std::option::Option<crate::EnumOrUnknown<field_descriptor_proto::Label>>,
-    ///  If type_name is set, this need not be set.  If both this and type_name
-    ///  are set, this must be one of TYPE_ENUM, TYPE_MESSAGE or TYPE_GROUP.
+    /// If type_name is set, this need not be set.  If both this and type_name
+    /// are set, this must be one of TYPE_ENUM, TYPE_MESSAGE or TYPE_GROUP.
     // @@protoc_insertion_point(field:google.protobuf.FieldDescriptorProto.type)
     pub type_: ::std::option::Option<crate::EnumOrUnknown<field_descriptor_proto::Type>>,
-    ///  For message and enum types, this is the name of the type.  If the name
-    ///  starts with a '.', it is fully-qualified.  Otherwise, C++-like scoping
-    ///  rules are used to find the type (i.e. first the nested types within this
-    ///  message are searched, then within the parent, on up to the root
-    ///  namespace).
+    /// For message and enum types, this is the name of the type.  If the name
+    /// starts with a '.', it is fully-qualified.  Otherwise, C++-like scoping
+    /// rules are used to find the type (i.e. first the nested types within this
+    /// message are searched, then within the parent, on up to the root
+    /// namespace).
     // @@protoc_insertion_point(field:google.protobuf.FieldDescriptorProto.type_name)
     pub type_name: ::std::option::Option<::std::string::String>,
-    ///  For extensions, this is the name of the type being extended.  It is
-    ///  resolved in the same manner as type_name.
+    /// For extensions, this is the name of the type being extended.  It is
+    /// resolved in the same manner as type_name.
     // @@protoc_insertion_point(field:google.protobuf.FieldDescriptorProto.extendee)
     pub extendee: ::std::option::Option<::std::string::String>,
-    ///  For numeric types, contains the original text representation of the value.
-    ///  For booleans, "true" or "false".
-    ///  For strings, contains the default text contents (not escaped in any way).
-    ///  For bytes, contains the C escaped value.  All bytes >= 128 are escaped.
-    ///  TODO(kenton):  Base-64 encode?
+    /// For numeric types, contains the original text representation of the value.
+    /// For booleans, "true" or "false".
+    /// For strings, contains the default text contents (not escaped in any way).
+    /// For bytes, contains the C escaped value.  All bytes >= 128 are escaped.
+    /// TODO(kenton):  Base-64 encode?
     // @@protoc_insertion_point(field:google.protobuf.FieldDescriptorProto.default_value)
     pub default_value: ::std::option::Option<::std::string::String>,
-    ///  If set, gives the index of a oneof in the containing type's oneof_decl
-    ///  list.  This field is a member of that oneof.
+    /// If set, gives the index of a oneof in the containing type's oneof_decl
+    /// list.  This field is a member of that oneof.
     // @@protoc_insertion_point(field:google.protobuf.FieldDescriptorProto.oneof_index)
     pub oneof_index: ::std::option::Option<i32>,
-    ///  JSON name of this field. The value is set by protocol compiler. If the
-    ///  user has set a "json_name" option on this field, that option's value
-    ///  will be used. Otherwise, it's deduced from the field's name by converting
-    ///  it to camelCase.
+    /// JSON name of this field. The value is set by protocol compiler. If the
+    /// user has set a "json_name" option on this field, that option's value
+    /// will be used. Otherwise, it's deduced from the field's name by converting
+    /// it to camelCase.
     // @@protoc_insertion_point(field:google.protobuf.FieldDescriptorProto.json_name)
     pub json_name: ::std::option::Option<::std::string::String>,
     // @@protoc_insertion_point(field:google.protobuf.FieldDescriptorProto.options)
     pub options: crate::MessageField<FieldOptions>,
-    ///  If true, this is a proto3 "optional". When a proto3 field is optional, it
-    ///  tracks presence regardless of field type.
+    /// If true, this is a proto3 "optional". When a proto3 field is optional, it
+    /// tracks presence regardless of field type.
     ///
-    ///  When proto3_optional is true, this field must be belong to a oneof to
-    ///  signal to old proto3 clients that presence is tracked for this field. This
-    ///  oneof is known as a "synthetic" oneof, and this field must be its sole
-    ///  member (each proto3 optional field gets its own synthetic oneof). Synthetic
-    ///  oneofs exist in the descriptor only, and do not generate any API. Synthetic
-    ///  oneofs must be ordered after all "real" oneofs.
+    /// When proto3_optional is true, this field must be belong to a oneof to
+    /// signal to old proto3 clients that presence is tracked for this field. This
+    /// oneof is known as a "synthetic" oneof, and this field must be its sole
+    /// member (each proto3 optional field gets its own synthetic oneof). Synthetic
+    /// oneofs exist in the descriptor only, and do not generate any API. Synthetic
+    /// oneofs must be ordered after all "real" oneofs.
     ///
-    ///  For message fields, proto3_optional doesn't create any semantic change,
-    ///  since non-repeated message fields always track presence. However it still
-    ///  indicates the semantic detail of whether the user wrote "optional" or not.
-    ///  This can be useful for round-tripping the .proto file. For consistency we
-    ///  give message fields a synthetic oneof also, even though it is not required
-    ///  to track presence. This is especially important because the parser can't
-    ///  tell if a field is a message or an enum, so it must always create a
-    ///  synthetic oneof.
+    /// For message fields, proto3_optional doesn't create any semantic change,
+    /// since non-repeated message fields always track presence. However it still
+    /// indicates the semantic detail of whether the user wrote "optional" or not.
+    /// This can be useful for round-tripping the .proto file. For consistency we
+    /// give message fields a synthetic oneof also, even though it is not required
+    /// to track presence. This is especially important because the parser can't
+    /// tell if a field is a message or an enum, so it must always create a
+    /// synthetic oneof.
     ///
-    ///  Proto2 optional fields do not set this flag, because they already indicate
-    ///  optional with `LABEL_OPTIONAL`.
+    /// Proto2 optional fields do not set this flag, because they already indicate
+    /// optional with `LABEL_OPTIONAL`.
     // @@protoc_insertion_point(field:google.protobuf.FieldDescriptorProto.proto3_optional)
     pub proto3_optional: ::std::option::Option<bool>,
     // special fields
@@ -2155,14 +2155,20 @@ pub mod field_descriptor_proto {
     #[derive(Clone,Copy,PartialEq,Eq,Debug,Hash)]
     // @@protoc_insertion_point(enum:google.protobuf.FieldDescriptorProto.Type)
     pub enum Type {
+        /// 0 is reserved for errors.
+        /// Order is weird for historical reasons.
         // @@protoc_insertion_point(enum_value:google.protobuf.FieldDescriptorProto.Type.TYPE_DOUBLE)
         TYPE_DOUBLE = 1,
         // @@protoc_insertion_point(enum_value:google.protobuf.FieldDescriptorProto.Type.TYPE_FLOAT)
         TYPE_FLOAT = 2,
+        /// Not ZigZag encoded.  Negative numbers take 10 bytes.  Use TYPE_SINT64 if
+        /// negative values are likely.
         // @@protoc_insertion_point(enum_value:google.protobuf.FieldDescriptorProto.Type.TYPE_INT64)
         TYPE_INT64 = 3,
         // @@protoc_insertion_point(enum_value:google.protobuf.FieldDescriptorProto.Type.TYPE_UINT64)
         TYPE_UINT64 = 4,
+        /// Not ZigZag encoded.  Negative numbers take 10 bytes.  Use TYPE_SINT32 if
+        /// negative values are likely.
         // @@protoc_insertion_point(enum_value:google.protobuf.FieldDescriptorProto.Type.TYPE_INT32)
         TYPE_INT32 = 5,
         // @@protoc_insertion_point(enum_value:google.protobuf.FieldDescriptorProto.Type.TYPE_FIXED64)
@@ -2173,10 +2179,15 @@ pub mod field_descriptor_proto {
         TYPE_BOOL = 8,
         // @@protoc_insertion_point(enum_value:google.protobuf.FieldDescriptorProto.Type.TYPE_STRING)
         TYPE_STRING = 9,
+        /// Tag-delimited aggregate.
+        /// Group type is deprecated and not supported in proto3. However, Proto3
+        /// implementations should still be able to parse the group wire format and
+        /// treat group fields as unknown fields.
         // @@protoc_insertion_point(enum_value:google.protobuf.FieldDescriptorProto.Type.TYPE_GROUP)
         TYPE_GROUP = 10,
         // @@protoc_insertion_point(enum_value:google.protobuf.FieldDescriptorProto.Type.TYPE_MESSAGE)
         TYPE_MESSAGE = 11,
+        /// New in version 2.
         // @@protoc_insertion_point(enum_value:google.protobuf.FieldDescriptorProto.Type.TYPE_BYTES)
         TYPE_BYTES = 12,
         // @@protoc_insertion_point(enum_value:google.protobuf.FieldDescriptorProto.Type.TYPE_UINT32)
@@ -2317,6 +2328,7 @@ pub mod field_descriptor_proto {
     #[derive(Clone,Copy,PartialEq,Eq,Debug,Hash)]
     // @@protoc_insertion_point(enum:google.protobuf.FieldDescriptorProto.Label)
     pub enum Label {
+        /// 0 is reserved for errors
         // @@protoc_insertion_point(enum_value:google.protobuf.FieldDescriptorProto.Label.LABEL_OPTIONAL)
         LABEL_OPTIONAL = 1,
         // @@protoc_insertion_point(enum_value:google.protobuf.FieldDescriptorProto.Label.LABEL_REQUIRED)
@@ -2387,7 +2399,7 @@ pub mod field_descriptor_proto {
     }
 }
 
-///  Describes a oneof.
+/// Describes a oneof.
 // @@protoc_insertion_point(message:google.protobuf.OneofDescriptorProto)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct OneofDescriptorProto {
@@ -2570,7 +2582,7 @@ impl crate::reflect::ProtobufValue for OneofDescriptorProto {
     type RuntimeType = crate::reflect::rt::RuntimeTypeMessage<Self>;
 }
 
-///  Describes an enum type.
+/// Describes an enum type.
 // @@protoc_insertion_point(message:google.protobuf.EnumDescriptorProto)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct EnumDescriptorProto {
@@ -2581,13 +2593,13 @@ pub struct EnumDescriptorProto {
     pub value: ::std::vec::Vec<EnumValueDescriptorProto>,
     // @@protoc_insertion_point(field:google.protobuf.EnumDescriptorProto.options)
     pub options: crate::MessageField<EnumOptions>,
-    ///  Range of reserved numeric values. Reserved numeric values may not be used
-    ///  by enum values in the same enum declaration. Reserved ranges may not
-    ///  overlap.
+    /// Range of reserved numeric values. Reserved numeric values may not be used
+    /// by enum values in the same enum declaration. Reserved ranges may not
+    /// overlap.
     // @@protoc_insertion_point(field:google.protobuf.EnumDescriptorProto.reserved_range)
     pub reserved_range: ::std::vec::Vec<enum_descriptor_proto::EnumReservedRange>,
-    ///  Reserved enum value names, which may not be reused. A given name may only
-    ///  be reserved once.
+    /// Reserved enum value names, which may not be reused. A given name may only
+    /// be reserved once.
     // @@protoc_insertion_point(field:google.protobuf.EnumDescriptorProto.reserved_name)
     pub reserved_name: ::std::vec::Vec<::std::string::String>,
     // special fields
@@ -2826,12 +2838,12 @@ impl crate::reflect::ProtobufValue for EnumDescriptorProto {
 
 /// Nested message and enums of message `EnumDescriptorProto`
 pub mod enum_descriptor_proto {
-    ///  Range of reserved numeric values. Reserved values may not be used by
-    ///  entries in the same enum. Reserved ranges may not overlap.
+    /// Range of reserved numeric values. Reserved values may not be used by
+    /// entries in the same enum. Reserved ranges may not overlap.
     ///
-    ///  Note that this is distinct from DescriptorProto.ReservedRange in that it
-    ///  is inclusive such that it can appropriately represent the entire int32
-    ///  domain.
+    /// Note that this is distinct from DescriptorProto.ReservedRange in that it
+    /// is inclusive such that it can appropriately represent the entire int32
+    /// domain.
     // @@protoc_insertion_point(message:google.protobuf.EnumDescriptorProto.EnumReservedRange)
     #[derive(PartialEq,Clone,Default,Debug)]
     pub struct EnumReservedRange {
@@ -3011,7 +3023,7 @@ pub mod enum_descriptor_proto {
     }
 }
 
-///  Describes a value within an enum.
+/// Describes a value within an enum.
 // @@protoc_insertion_point(message:google.protobuf.EnumValueDescriptorProto)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct EnumValueDescriptorProto {
@@ -3231,7 +3243,7 @@ impl crate::reflect::ProtobufValue for EnumValueDescriptorProto {
     type RuntimeType = crate::reflect::rt::RuntimeTypeMessage<Self>;
 }
 
-///  Describes a service.
+/// Describes a service.
 // @@protoc_insertion_point(message:google.protobuf.ServiceDescriptorProto)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct ServiceDescriptorProto {
@@ -3438,25 +3450,25 @@ impl crate::reflect::ProtobufValue for ServiceDescriptorProto {
     type RuntimeType = crate::reflect::rt::RuntimeTypeMessage<Self>;
 }
 
-///  Describes a method of a service.
+/// Describes a method of a service.
 // @@protoc_insertion_point(message:google.protobuf.MethodDescriptorProto)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct MethodDescriptorProto {
     // message fields
     // @@protoc_insertion_point(field:google.protobuf.MethodDescriptorProto.name)
     pub name: ::std::option::Option<::std::string::String>,
-    ///  Input and output type names.  These are resolved in the same way as
-    ///  FieldDescriptorProto.type_name, but must refer to a message type.
+    /// Input and output type names.  These are resolved in the same way as
+    /// FieldDescriptorProto.type_name, but must refer to a message type.
     // @@protoc_insertion_point(field:google.protobuf.MethodDescriptorProto.input_type)
     pub input_type: ::std::option::Option<::std::string::String>,
     // @@protoc_insertion_point(field:google.protobuf.MethodDescriptorProto.output_type)
     pub output_type: ::std::option::Option<::std::string::String>,
     // @@protoc_insertion_point(field:google.protobuf.MethodDescriptorProto.options)
     pub options: crate::MessageField<MethodOptions>,
-    ///  Identifies if client streams multiple client messages
+    /// Identifies if client streams multiple client messages
     // @@protoc_insertion_point(field:google.protobuf.MethodDescriptorProto.client_streaming)
     pub client_streaming: ::std::option::Option<bool>,
-    ///  Identifies if server streams multiple server messages
+    /// Identifies if server streams multiple server messages
     // @@protoc_insertion_point(field:google.protobuf.MethodDescriptorProto.server_streaming)
     pub server_streaming: ::std::option::Option<bool>,
     // special fields
@@ -3811,57 +3823,57 @@ impl crate::reflect::ProtobufValue for MethodDescriptorProto {
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct FileOptions {
     // message fields
-    ///  Sets the Java package where classes generated from this .proto will be
-    ///  placed.  By default, the proto package is used, but this is often
-    ///  inappropriate because proto packages do not normally start with backwards
-    ///  domain names.
+    /// Sets the Java package where classes generated from this .proto will be
+    /// placed.  By default, the proto package is used, but this is often
+    /// inappropriate because proto packages do not normally start with backwards
+    /// domain names.
     // @@protoc_insertion_point(field:google.protobuf.FileOptions.java_package)
     pub java_package: ::std::option::Option<::std::string::String>,
-    ///  Controls the name of the wrapper Java class generated for the .proto file.
-    ///  That class will always contain the .proto file's getDescriptor() method as
-    ///  well as any top-level extensions defined in the .proto file.
-    ///  If java_multiple_files is disabled, then all the other classes from the
-    ///  .proto file will be nested inside the single wrapper outer class.
+    /// Controls the name of the wrapper Java class generated for the .proto file.
+    /// That class will always contain the .proto file's getDescriptor() method as
+    /// well as any top-level extensions defined in the .proto file.
+    /// If java_multiple_files is disabled, then all the other classes from the
+    /// .proto file will be nested inside the single wrapper outer class.
     // @@protoc_insertion_point(field:google.protobuf.FileOptions.java_outer_classname)
     pub java_outer_classname: ::std::option::Option<::std::string::String>,
-    ///  If enabled, then the Java code generator will generate a separate .java
-    ///  file for each top-level message, enum, and service defined in the .proto
-    ///  file.  Thus, these types will *not* be nested inside the wrapper class
-    ///  named by java_outer_classname.  However, the wrapper class will still be
-    ///  generated to contain the file's getDescriptor() method as well as any
-    ///  top-level extensions defined in the file.
+    /// If enabled, then the Java code generator will generate a separate .java
+    /// file for each top-level message, enum, and service defined in the .proto
+    /// file.  Thus, these types will *not* be nested inside the wrapper class
+    /// named by java_outer_classname.  However, the wrapper class will still be
+    /// generated to contain the file's getDescriptor() method as well as any
+    /// top-level extensions defined in the file.
     // @@protoc_insertion_point(field:google.protobuf.FileOptions.java_multiple_files)
     pub java_multiple_files: ::std::option::Option<bool>,
-    ///  This option does nothing.
+    /// This option does nothing.
     // @@protoc_insertion_point(field:google.protobuf.FileOptions.java_generate_equals_and_hash)
     pub java_generate_equals_and_hash: ::std::option::Option<bool>,
-    ///  If set true, then the Java2 code generator will generate code that
-    ///  throws an exception whenever an attempt is made to assign a non-UTF-8
-    ///  byte sequence to a string field.
-    ///  Message reflection will do the same.
-    ///  However, an extension field still accepts non-UTF-8 byte sequences.
-    ///  This option has no effect on when used with the lite runtime.
+    /// If set true, then the Java2 code generator will generate code that
+    /// throws an exception whenever an attempt is made to assign a non-UTF-8
+    /// byte sequence to a string field.
+    /// Message reflection will do the same.
+    /// However, an extension field still accepts non-UTF-8 byte sequences.
+    /// This option has no effect on when used with the lite runtime.
     // @@protoc_insertion_point(field:google.protobuf.FileOptions.java_string_check_utf8)
     pub java_string_check_utf8: ::std::option::Option<bool>,
     // @@protoc_insertion_point(field:google.protobuf.FileOptions.optimize_for)
     pub optimize_for: ::std::option::Option<crate::EnumOrUnknown<file_options::OptimizeMode>>,
-    ///  Sets the Go package where structs generated from this .proto will be
-    ///  placed. If omitted, the Go package will be derived from the following:
-    ///    - The basename of the package import path, if provided.
-    ///    - Otherwise, the package statement in the .proto file, if present.
-    ///    - Otherwise, the basename of the .proto file, without extension.
+    /// Sets the Go package where structs generated from this .proto will be
+    /// placed. If omitted, the Go package will be derived from the following:
+    ///   - The basename of the package import path, if provided.
+    ///   - Otherwise, the package statement in the .proto file, if present.
+    ///   - Otherwise, the basename of the .proto file, without extension.
     // @@protoc_insertion_point(field:google.protobuf.FileOptions.go_package)
     pub go_package: ::std::option::Option<::std::string::String>,
-    ///  Should generic services be generated in each language?  "Generic" services
-    ///  are not specific to any particular RPC system.  They are generated by the
-    ///  main code generators in each language (without additional plugins).
-    ///  Generic services were the only kind of service generation supported by
-    ///  early versions of google.protobuf.
+    /// Should generic services be generated in each language?  "Generic" services
+    /// are not specific to any particular RPC system.  They are generated by the
+    /// main code generators in each language (without additional plugins).
+    /// Generic services were the only kind of service generation supported by
+    /// early versions of google.protobuf.
     ///
-    ///  Generic services are now considered deprecated in favor of using plugins
-    ///  that generate code specific to your particular RPC system.  Therefore,
-    ///  these default to false.  Old code which depends on generic services should
-    ///  explicitly set them to true.
+    /// Generic services are now considered deprecated in favor of using plugins
+    /// that generate code specific to your particular RPC system.  Therefore,
+    /// these default to false.  Old code which depends on generic services should
+    /// explicitly set them to true.
     // @@protoc_insertion_point(field:google.protobuf.FileOptions.cc_generic_services)
     pub cc_generic_services: ::std::option::Option<bool>,
     // @@protoc_insertion_point(field:google.protobuf.FileOptions.java_generic_services)
@@ -3870,50 +3882,50 @@ pub struct FileOptions {
     pub py_generic_services: ::std::option::Option<bool>,
     // @@protoc_insertion_point(field:google.protobuf.FileOptions.php_generic_services)
     pub php_generic_services: ::std::option::Option<bool>,
-    ///  Is this file deprecated?
-    ///  Depending on the target platform, this can emit Deprecated annotations
-    ///  for everything in the file, or it will be completely ignored; in the very
-    ///  least, this is a formalization for deprecating files.
+    /// Is this file deprecated?
+    /// Depending on the target platform, this can emit Deprecated annotations
+    /// for everything in the file, or it will be completely ignored; in the very
+    /// least, this is a formalization for deprecating files.
     // @@protoc_insertion_point(field:google.protobuf.FileOptions.deprecated)
     pub deprecated: ::std::option::Option<bool>,
-    ///  Enables the use of arenas for the proto messages in this file. This applies
-    ///  only to generated classes for C++.
+    /// Enables the use of arenas for the proto messages in this file. This applies
+    /// only to generated classes for C++.
     // @@protoc_insertion_point(field:google.protobuf.FileOptions.cc_enable_arenas)
     pub cc_enable_arenas: ::std::option::Option<bool>,
-    ///  Sets the objective c class prefix which is prepended to all objective c
-    ///  generated classes from this .proto. There is no default.
+    /// Sets the objective c class prefix which is prepended to all objective c
+    /// generated classes from this .proto. There is no default.
     // @@protoc_insertion_point(field:google.protobuf.FileOptions.objc_class_prefix)
     pub objc_class_prefix: ::std::option::Option<::std::string::String>,
-    ///  Namespace for generated classes; defaults to the package.
+    /// Namespace for generated classes; defaults to the package.
     // @@protoc_insertion_point(field:google.protobuf.FileOptions.csharp_namespace)
     pub csharp_namespace: ::std::option::Option<::std::string::String>,
-    ///  By default Swift generators will take the proto package and CamelCase it
-    ///  replacing '.' with underscore and use that to prefix the types/symbols
-    ///  defined. When this options is provided, they will use this value instead
-    ///  to prefix the types/symbols defined.
+    /// By default Swift generators will take the proto package and CamelCase it
+    /// replacing '.' with underscore and use that to prefix the types/symbols
+    /// defined. When this options is provided, they will use this value instead
+    /// to prefix the types/symbols defined.
     // @@protoc_insertion_point(field:google.protobuf.FileOptions.swift_prefix)
     pub swift_prefix: ::std::option::Option<::std::string::String>,
-    ///  Sets the php class prefix which is prepended to all php generated classes
-    ///  from this .proto. Default is empty.
+    /// Sets the php class prefix which is prepended to all php generated classes
+    /// from this .proto. Default is empty.
     // @@protoc_insertion_point(field:google.protobuf.FileOptions.php_class_prefix)
     pub php_class_prefix: ::std::option::Option<::std::string::String>,
-    ///  Use this option to change the namespace of php generated classes. Default
-    ///  is empty. When this option is empty, the package name will be used for
-    ///  determining the namespace.
+    /// Use this option to change the namespace of php generated classes. Default
+    /// is empty. When this option is empty, the package name will be used for
+    /// determining the namespace.
     // @@protoc_insertion_point(field:google.protobuf.FileOptions.php_namespace)
     pub php_namespace: ::std::option::Option<::std::string::String>,
-    ///  Use this option to change the namespace of php generated metadata classes.
-    ///  Default is empty. When this option is empty, the proto file name will be
-    ///  used for determining the namespace.
+    /// Use this option to change the namespace of php generated metadata classes.
+    /// Default is empty. When this option is empty, the proto file name will be
+    /// used for determining the namespace.
     // @@protoc_insertion_point(field:google.protobuf.FileOptions.php_metadata_namespace)
     pub php_metadata_namespace: ::std::option::Option<::std::string::String>,
-    ///  Use this option to change the package of ruby generated classes. Default
-    ///  is empty. When this option is not set, the package name will be used for
-    ///  determining the ruby package.
+    /// Use this option to change the package of ruby generated classes. Default
+    /// is empty. When this option is not set, the package name will be used for
+    /// determining the ruby package.
     // @@protoc_insertion_point(field:google.protobuf.FileOptions.ruby_package)
     pub ruby_package: ::std::option::Option<::std::string::String>,
-    ///  The parser stores options it doesn't recognize here.
-    ///  See the documentation for the "Options" section above.
+    /// The parser stores options it doesn't recognize here.
+    /// See the documentation for the "Options" section above.
     // @@protoc_insertion_point(field:google.protobuf.FileOptions.uninterpreted_option)
     pub uninterpreted_option: ::std::vec::Vec<UninterpretedOption>,
     // special fields
@@ -4913,12 +4925,13 @@ impl crate::reflect::ProtobufValue for FileOptions {
 
 /// Nested message and enums of message `FileOptions`
 pub mod file_options {
-    ///  Generated classes can be optimized for speed or code size.
+    /// Generated classes can be optimized for speed or code size.
     #[derive(Clone,Copy,PartialEq,Eq,Debug,Hash)]
     // @@protoc_insertion_point(enum:google.protobuf.FileOptions.OptimizeMode)
     pub enum OptimizeMode {
         // @@protoc_insertion_point(enum_value:google.protobuf.FileOptions.OptimizeMode.SPEED)
         SPEED = 1,
+        /// etc.
         // @@protoc_insertion_point(enum_value:google.protobuf.FileOptions.OptimizeMode.CODE_SIZE)
         CODE_SIZE = 2,
         // @@protoc_insertion_point(enum_value:google.protobuf.FileOptions.OptimizeMode.LITE_RUNTIME)
@@ -4993,20 +5006,20 @@ pub struct MessageOptions {
     // message fields
     // @@protoc_insertion_point(field:google.protobuf.MessageOptions.message_set_wire_format)
     pub message_set_wire_format: ::std::option::Option<bool>,
-    ///  Disables the generation of the standard "descriptor()" accessor, which can
-    ///  conflict with a field of the same name.  This is meant to make migration
-    ///  from proto1 easier; new code should avoid fields named "descriptor".
+    /// Disables the generation of the standard "descriptor()" accessor, which can
+    /// conflict with a field of the same name.  This is meant to make migration
+    /// from proto1 easier; new code should avoid fields named "descriptor".
     // @@protoc_insertion_point(field:google.protobuf.MessageOptions.no_standard_descriptor_accessor)
     pub no_standard_descriptor_accessor: ::std::option::Option<bool>,
-    ///  Is this message deprecated?
-    ///  Depending on the target platform, this can emit Deprecated annotations
-    ///  for the message, or it will be completely ignored; in the very least,
-    ///  this is a formalization for deprecating messages.
+    /// Is this message deprecated?
+    /// Depending on the target platform, this can emit Deprecated annotations
+    /// for the message, or it will be completely ignored; in the very least,
+    /// this is a formalization for deprecating messages.
     // @@protoc_insertion_point(field:google.protobuf.MessageOptions.deprecated)
     pub deprecated: ::std::option::Option<bool>,
     // @@protoc_insertion_point(field:google.protobuf.MessageOptions.map_entry)
     pub map_entry: ::std::option::Option<bool>,
-    ///  The parser stores options it doesn't recognize here. See above.
+    /// The parser stores options it doesn't recognize here. See above.
     // @@protoc_insertion_point(field:google.protobuf.MessageOptions.uninterpreted_option)
     pub uninterpreted_option: ::std::vec::Vec<UninterpretedOption>,
     // special fields
@@ -5275,72 +5288,72 @@ impl crate::reflect::ProtobufValue for MessageOptions {
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct FieldOptions {
     // message fields
-    ///  The ctype option instructs the C++ code generator to use a different
-    ///  representation of the field than it normally would.  See the specific
-    ///  options below.  This option is not yet implemented in the open source
-    ///  release -- sorry, we'll try to include it in a future version!
+    /// The ctype option instructs the C++ code generator to use a different
+    /// representation of the field than it normally would.  See the specific
+    /// options below.  This option is not yet implemented in the open source
+    /// release -- sorry, we'll try to include it in a future version!
     // @@protoc_insertion_point(field:google.protobuf.FieldOptions.ctype)
     pub ctype: ::std::option::Option<crate::EnumOrUnknown<field_options::CType>>,
-    ///  The packed option can be enabled for repeated primitive fields to enable
-    ///  a more efficient representation on the wire. Rather than repeatedly
-    ///  writing the tag and type for each element, the entire array is encoded as
-    ///  a single length-delimited blob. In proto3, only explicit setting it to
-    ///  false will avoid using packed encoding.
+    /// The packed option can be enabled for repeated primitive fields to enable
+    /// a more efficient representation on the wire. Rather than repeatedly
+    /// writing the tag and type for each element, the entire array is encoded as
+    /// a single length-delimited blob. In proto3, only explicit setting it to
+    /// false will avoid using packed encoding.
     // @@protoc_insertion_point(field:google.protobuf.FieldOptions.packed)
     pub packed: ::std::option::Option<bool>,
-    ///  The jstype option determines the JavaScript type used for values of the
-    ///  field.  The option is permitted only for 64 bit integral and fixed types
-    ///  (int64, uint64, sint64, fixed64, sfixed64).  A field with jstype JS_STRING
-    ///  is represented as JavaScript string, which avoids loss of precision that
-    ///  can happen when a large value is converted to a floating point JavaScript.
-    ///  Specifying JS_NUMBER for the jstype causes the generated JavaScript code to
-    ///  use the JavaScript "number" type.  The behavior of the default option
-    ///  JS_NORMAL is implementation dependent.
+    /// The jstype option determines the JavaScript type used for values of the
+    /// field.  The option is permitted only for 64 bit integral and fixed types
+    /// (int64, uint64, sint64, fixed64, sfixed64).  A field with jstype JS_STRING
+    /// is represented as JavaScript string, which avoids loss of precision that
+    /// can happen when a large value is converted to a floating point JavaScript.
+    /// Specifying JS_NUMBER for the jstype causes the generated JavaScript code to
+    /// use the JavaScript "number" type.  The behavior of the default option
+    /// JS_NORMAL is implementation dependent.
     ///
-    ///  This option is an enum to permit additional types to be added, e.g.
-    ///  goog.math.Integer.
+    /// This option is an enum to permit additional types to be added, e.g.
+    /// goog.math.Integer.
     // @@protoc_insertion_point(field:google.protobuf.FieldOptions.jstype)
     pub jstype: ::std::option::Option<crate::EnumOrUnknown<field_options::JSType>>,
-    ///  Should this field be parsed lazily?  Lazy applies only to message-type
-    ///  fields.  It means that when the outer message is initially parsed, the
-    ///  inner message's contents will not be parsed but instead stored in encoded
-    ///  form.  The inner message will actually be parsed when it is first accessed.
+    /// Should this field be parsed lazily?  Lazy applies only to message-type
+    /// fields.  It means that when the outer message is initially parsed, the
+    /// inner message's contents will not be parsed but instead stored in encoded
+    /// form.  The inner message will actually be parsed when it is first accessed.
     ///
-    ///  This is only a hint.  Implementations are free to choose whether to use
-    ///  eager or lazy parsing regardless of the value of this option.  However,
-    ///  setting this option true suggests that the protocol author believes that
-    ///  using lazy parsing on this field is worth the additional bookkeeping
-    ///  overhead typically needed to implement it.
+    /// This is only a hint.  Implementations are free to choose whether to use
+    /// eager or lazy parsing regardless of the value of this option.  However,
+    /// setting this option true suggests that the protocol author believes that
+    /// using lazy parsing on this field is worth the additional bookkeeping
+    /// overhead typically needed to implement it.
     ///
-    ///  This option does not affect the public interface of any generated code;
-    ///  all method signatures remain the same.  Furthermore, thread-safety of the
-    ///  interface is not affected by this option; const methods remain safe to
-    ///  call from multiple threads concurrently, while non-const methods continue
-    ///  to require exclusive access.
+    /// This option does not affect the public interface of any generated code;
+    /// all method signatures remain the same.  Furthermore, thread-safety of the
+    /// interface is not affected by this option; const methods remain safe to
+    /// call from multiple threads concurrently, while non-const methods continue
+    /// to require exclusive access.
     ///
     ///
-    ///  Note that implementations may choose not to check required fields within
-    ///  a lazy sub-message.  That is, calling IsInitialized() on the outer message
-    ///  may return true even if the inner message has missing required fields.
-    ///  This is necessary because otherwise the inner message would have to be
-    ///  parsed in order to perform the check, defeating the purpose of lazy
-    ///  parsing.  An implementation which chooses not to check required fields
-    ///  must be consistent about it.  That is, for any particular sub-message, the
-    ///  implementation must either *always* check its required fields, or *never*
-    ///  check its required fields, regardless of whether or not the message has
-    ///  been parsed.
+    /// Note that implementations may choose not to check required fields within
+    /// a lazy sub-message.  That is, calling IsInitialized() on the outer message
+    /// may return true even if the inner message has missing required fields.
+    /// This is necessary because otherwise the inner message would have to be
+    /// parsed in order to perform the check, defeating the purpose of lazy
+    /// parsing.  An implementation which chooses not to check required fields
+    /// must be consistent about it.  That is, for any particular sub-message, the
+    /// implementation must either *always* check its required fields, or *never*
+    /// check its required fields, regardless of whether or not the message has
+    /// been parsed.
     // @@protoc_insertion_point(field:google.protobuf.FieldOptions.lazy)
     pub lazy: ::std::option::Option<bool>,
-    ///  Is this field deprecated?
-    ///  Depending on the target platform, this can emit Deprecated annotations
-    ///  for accessors, or it will be completely ignored; in the very least, this
-    ///  is a formalization for deprecating fields.
+    /// Is this field deprecated?
+    /// Depending on the target platform, this can emit Deprecated annotations
+    /// for accessors, or it will be completely ignored; in the very least, this
+    /// is a formalization for deprecating fields.
     // @@protoc_insertion_point(field:google.protobuf.FieldOptions.deprecated)
     pub deprecated: ::std::option::Option<bool>,
-    ///  For Google-internal migration only. Do not use.
+    /// For Google-internal migration only. Do not use.
     // @@protoc_insertion_point(field:google.protobuf.FieldOptions.weak)
     pub weak: ::std::option::Option<bool>,
-    ///  The parser stores options it doesn't recognize here. See above.
+    /// The parser stores options it doesn't recognize here. See above.
     // @@protoc_insertion_point(field:google.protobuf.FieldOptions.uninterpreted_option)
     pub uninterpreted_option: ::std::vec::Vec<UninterpretedOption>,
     // special fields
@@ -5686,6 +5699,7 @@ pub mod field_options {
     #[derive(Clone,Copy,PartialEq,Eq,Debug,Hash)]
     // @@protoc_insertion_point(enum:google.protobuf.FieldOptions.CType)
     pub enum CType {
+        /// Default mode.
         // @@protoc_insertion_point(enum_value:google.protobuf.FieldOptions.CType.STRING)
         STRING = 0,
         // @@protoc_insertion_point(enum_value:google.protobuf.FieldOptions.CType.CORD)
@@ -5753,10 +5767,13 @@ pub mod field_options {
     #[derive(Clone,Copy,PartialEq,Eq,Debug,Hash)]
     // @@protoc_insertion_point(enum:google.protobuf.FieldOptions.JSType)
     pub enum JSType {
+        /// Use the default type.
         // @@protoc_insertion_point(enum_value:google.protobuf.FieldOptions.JSType.JS_NORMAL)
         JS_NORMAL = 0,
+        /// Use JavaScript strings.
         // @@protoc_insertion_point(enum_value:google.protobuf.FieldOptions.JSType.JS_STRING)
         JS_STRING = 1,
+        /// Use JavaScript numbers.
         // @@protoc_insertion_point(enum_value:google.protobuf.FieldOptions.JSType.JS_NUMBER)
         JS_NUMBER = 2,
     }
@@ -5822,7 +5839,7 @@ pub mod field_options {
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct OneofOptions {
     // message fields
-    ///  The parser stores options it doesn't recognize here. See above.
+    /// The parser stores options it doesn't recognize here. See above.
     // @@protoc_insertion_point(field:google.protobuf.OneofOptions.uninterpreted_option)
     pub uninterpreted_option: ::std::vec::Vec<UninterpretedOption>,
     // special fields
@@ -5951,17 +5968,17 @@ impl crate::reflect::ProtobufValue for OneofOptions {
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct EnumOptions {
     // message fields
-    ///  Set this option to true to allow mapping different tag names to the same
-    ///  value.
+    /// Set this option to true to allow mapping different tag names to the same
+    /// value.
     // @@protoc_insertion_point(field:google.protobuf.EnumOptions.allow_alias)
     pub allow_alias: ::std::option::Option<bool>,
-    ///  Is this enum deprecated?
-    ///  Depending on the target platform, this can emit Deprecated annotations
-    ///  for the enum, or it will be completely ignored; in the very least, this
-    ///  is a formalization for deprecating enums.
+    /// Is this enum deprecated?
+    /// Depending on the target platform, this can emit Deprecated annotations
+    /// for the enum, or it will be completely ignored; in the very least, this
+    /// is a formalization for deprecating enums.
     // @@protoc_insertion_point(field:google.protobuf.EnumOptions.deprecated)
     pub deprecated: ::std::option::Option<bool>,
-    ///  The parser stores options it doesn't recognize here. See above.
+    /// The parser stores options it doesn't recognize here. See above.
     // @@protoc_insertion_point(field:google.protobuf.EnumOptions.uninterpreted_option)
     pub uninterpreted_option: ::std::vec::Vec<UninterpretedOption>,
     // special fields
@@ -6160,13 +6177,13 @@ impl crate::reflect::ProtobufValue for EnumOptions {
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct EnumValueOptions {
     // message fields
-    ///  Is this enum value deprecated?
-    ///  Depending on the target platform, this can emit Deprecated annotations
-    ///  for the enum value, or it will be completely ignored; in the very least,
-    ///  this is a formalization for deprecating enum values.
+    /// Is this enum value deprecated?
+    /// Depending on the target platform, this can emit Deprecated annotations
+    /// for the enum value, or it will be completely ignored; in the very least,
+    /// this is a formalization for deprecating enum values.
     // @@protoc_insertion_point(field:google.protobuf.EnumValueOptions.deprecated)
     pub deprecated: ::std::option::Option<bool>,
-    ///  The parser stores options it doesn't recognize here. See above.
+    /// The parser stores options it doesn't recognize here. See above.
     // @@protoc_insertion_point(field:google.protobuf.EnumValueOptions.uninterpreted_option)
     pub uninterpreted_option: ::std::vec::Vec<UninterpretedOption>,
     // special fields
@@ -6330,13 +6347,13 @@ impl crate::reflect::ProtobufValue for EnumValueOptions {
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct ServiceOptions {
     // message fields
-    ///  Is this service deprecated?
-    ///  Depending on the target platform, this can emit Deprecated annotations
-    ///  for the service, or it will be completely ignored; in the very least,
-    ///  this is a formalization for deprecating services.
+    /// Is this service deprecated?
+    /// Depending on the target platform, this can emit Deprecated annotations
+    /// for the service, or it will be completely ignored; in the very least,
+    /// this is a formalization for deprecating services.
     // @@protoc_insertion_point(field:google.protobuf.ServiceOptions.deprecated)
     pub deprecated: ::std::option::Option<bool>,
-    ///  The parser stores options it doesn't recognize here. See above.
+    /// The parser stores options it doesn't recognize here. See above.
     // @@protoc_insertion_point(field:google.protobuf.ServiceOptions.uninterpreted_option)
     pub uninterpreted_option: ::std::vec::Vec<UninterpretedOption>,
     // special fields
@@ -6500,15 +6517,15 @@ impl crate::reflect::ProtobufValue for ServiceOptions {
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct MethodOptions {
     // message fields
-    ///  Is this method deprecated?
-    ///  Depending on the target platform, this can emit Deprecated annotations
-    ///  for the method, or it will be completely ignored; in the very least,
-    ///  this is a formalization for deprecating methods.
+    /// Is this method deprecated?
+    /// Depending on the target platform, this can emit Deprecated annotations
+    /// for the method, or it will be completely ignored; in the very least,
+    /// this is a formalization for deprecating methods.
     // @@protoc_insertion_point(field:google.protobuf.MethodOptions.deprecated)
     pub deprecated: ::std::option::Option<bool>,
     // @@protoc_insertion_point(field:google.protobuf.MethodOptions.idempotency_level)
     pub idempotency_level: ::std::option::Option<crate::EnumOrUnknown<method_options::IdempotencyLevel>>,
-    ///  The parser stores options it doesn't recognize here. See above.
+    /// The parser stores options it doesn't recognize here. See above.
     // @@protoc_insertion_point(field:google.protobuf.MethodOptions.uninterpreted_option)
     pub uninterpreted_option: ::std::vec::Vec<UninterpretedOption>,
     // special fields
@@ -6708,9 +6725,9 @@ impl crate::reflect::ProtobufValue for MethodOptions {
 
 /// Nested message and enums of message `MethodOptions`
 pub mod method_options {
-    ///  Is this method side-effect-free (or safe in HTTP parlance), or idempotent,
-    ///  or neither? HTTP based RPC implementation may choose GET verb for safe
-    ///  methods, and PUT verb for idempotent methods instead of the default POST.
+    /// Is this method side-effect-free (or safe in HTTP parlance), or idempotent,
+    /// or neither? HTTP based RPC implementation may choose GET verb for safe
+    /// methods, and PUT verb for idempotent methods instead of the default POST.
     #[derive(Clone,Copy,PartialEq,Eq,Debug,Hash)]
     // @@protoc_insertion_point(enum:google.protobuf.MethodOptions.IdempotencyLevel)
     pub enum IdempotencyLevel {
@@ -6779,20 +6796,20 @@ pub mod method_options {
     }
 }
 
-///  A message representing a option the parser does not recognize. This only
-///  appears in options protos created by the compiler::Parser class.
-///  DescriptorPool resolves these when building Descriptor objects. Therefore,
-///  options protos in descriptor objects (e.g. returned by Descriptor::options(),
-///  or produced by Descriptor::CopyTo()) will never have UninterpretedOptions
-///  in them.
+/// A message representing a option the parser does not recognize. This only
+/// appears in options protos created by the compiler::Parser class.
+/// DescriptorPool resolves these when building Descriptor objects. Therefore,
+/// options protos in descriptor objects (e.g. returned by Descriptor::options(),
+/// or produced by Descriptor::CopyTo()) will never have UninterpretedOptions
+/// in them.
 // @@protoc_insertion_point(message:google.protobuf.UninterpretedOption)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct UninterpretedOption {
     // message fields
     // @@protoc_insertion_point(field:google.protobuf.UninterpretedOption.name)
     pub name: ::std::vec::Vec<uninterpreted_option::NamePart>,
-    ///  The value of the uninterpreted option, in whatever type the tokenizer
-    ///  identified it as during parsing. Exactly one of these should be set.
+    /// The value of the uninterpreted option, in whatever type the tokenizer
+    /// identified it as during parsing. Exactly one of these should be set.
     // @@protoc_insertion_point(field:google.protobuf.UninterpretedOption.identifier_value)
     pub identifier_value: ::std::option::Option<::std::string::String>,
     // @@protoc_insertion_point(field:google.protobuf.UninterpretedOption.positive_int_value)
@@ -7190,11 +7207,11 @@ impl crate::reflect::ProtobufValue for UninterpretedOption {
 
 /// Nested message and enums of message `UninterpretedOption`
 pub mod uninterpreted_option {
-    ///  The name of the uninterpreted option.  Each string represents a segment in
-    ///  a dot-separated name.  is_extension is true iff a segment represents an
-    ///  extension (denoted with parentheses in options specs in .proto files).
-    ///  E.g.,{ ["foo", false], ["bar.baz", true], ["qux", false] } represents
-    ///  "foo.(bar.baz).qux".
+    /// The name of the uninterpreted option.  Each string represents a segment in
+    /// a dot-separated name.  is_extension is true iff a segment represents an
+    /// extension (denoted with parentheses in options specs in .proto files).
+    /// E.g.,{ ["foo", false], ["bar.baz", true], ["qux", false] } represents
+    /// "foo.(bar.baz).qux".
     // @@protoc_insertion_point(message:google.protobuf.UninterpretedOption.NamePart)
     #[derive(PartialEq,Clone,Default,Debug)]
     pub struct NamePart {
@@ -7397,8 +7414,8 @@ pub mod uninterpreted_option {
     }
 }
 
-///  Encapsulates information about the original source file from which a
-///  FileDescriptorProto was generated.
+/// Encapsulates information about the original source file from which a
+/// FileDescriptorProto was generated.
 // @@protoc_insertion_point(message:google.protobuf.SourceCodeInfo)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct SourceCodeInfo {
@@ -7530,13 +7547,60 @@ pub mod source_code_info {
         // message fields
         // @@protoc_insertion_point(field:google.protobuf.SourceCodeInfo.Location.path)
         pub path: ::std::vec::Vec<i32>,
-        ///  Always has exactly three or four elements: start line, start column,
-        ///  end line (optional, otherwise assumed same as start line), end column.
-        ///  These are packed into a single field for efficiency.  Note that line
-        ///  and column numbers are zero-based -- typically you will want to add
-        ///  1 to each before displaying to a user.
+        /// Always has exactly three or four elements: start line, start column,
+        /// end line (optional, otherwise assumed same as start line), end column.
+        /// These are packed into a single field for efficiency.  Note that line
+        /// and column numbers are zero-based -- typically you will want to add
+        /// 1 to each before displaying to a user.
         // @@protoc_insertion_point(field:google.protobuf.SourceCodeInfo.Location.span)
         pub span: ::std::vec::Vec<i32>,
+        /// If this SourceCodeInfo represents a complete declaration, these are any
+        /// comments appearing before and after the declaration which appear to be
+        /// attached to the declaration.
+        ///
+        /// A series of line comments appearing on consecutive lines, with no other
+        /// tokens appearing on those lines, will be treated as a single comment.
+        ///
+        /// leading_detached_comments will keep paragraphs of comments that appear
+        /// before (but not connected to) the current element. Each paragraph,
+        /// separated by empty lines, will be one comment element in the repeated
+        /// field.
+        ///
+        /// Only the comment content is provided; comment markers (e.g. //) are
+        /// stripped out.  For block comments, leading whitespace and an asterisk
+        /// will be stripped from the beginning of each line other than the first.
+        /// Newlines are included in the output.
+        ///
+        /// Examples:
+        ///
+        ///   optional int32 foo = 1;  // Comment attached to foo.
+        ///   // Comment attached to bar.
+        ///   optional int32 bar = 2;
+        ///
+        ///   optional string baz = 3;
+        ///   // Comment attached to baz.
+        ///   // Another line attached to baz.
+        ///
+        ///   // Comment attached to qux.
+        ///   //
+        ///   // Another line attached to qux.
+        ///   optional double qux = 4;
+        ///
+        ///   // Detached comment for corge. This is not leading or trailing comments
+        ///   // to qux or corge because there are blank lines separating it from
+        ///   // both.
+        ///
+        ///   // Detached comment for corge paragraph 2.
+        ///
+        ///   optional string corge = 5;
+        ///   /* Block comment attached
+        ///    * to corge.  Leading asterisks
+        ///    * will be removed. */
+        ///   /* Block comment attached to
+        ///    * grault. */
+        ///   optional int32 grault = 6;
+        ///
+        ///   // ignored detached comments.
         // @@protoc_insertion_point(field:google.protobuf.SourceCodeInfo.Location.leading_comments)
         pub leading_comments: ::std::option::Option<::std::string::String>,
         // @@protoc_insertion_point(field:google.protobuf.SourceCodeInfo.Location.trailing_comments)
@@ -7794,15 +7858,15 @@ pub mod source_code_info {
     }
 }
 
-///  Describes the relationship between generated code and its original source
-///  file. A GeneratedCodeInfo message is associated with only one generated
-///  source file, but may contain references to different source .proto files.
+/// Describes the relationship between generated code and its original source
+/// file. A GeneratedCodeInfo message is associated with only one generated
+/// source file, but may contain references to different source .proto files.
 // @@protoc_insertion_point(message:google.protobuf.GeneratedCodeInfo)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct GeneratedCodeInfo {
     // message fields
-    ///  An Annotation connects some span of text in generated code to an element
-    ///  of its generating .proto file.
+    /// An Annotation connects some span of text in generated code to an element
+    /// of its generating .proto file.
     // @@protoc_insertion_point(field:google.protobuf.GeneratedCodeInfo.annotation)
     pub annotation: ::std::vec::Vec<generated_code_info::Annotation>,
     // special fields
@@ -7928,20 +7992,20 @@ pub mod generated_code_info {
     #[derive(PartialEq,Clone,Default,Debug)]
     pub struct Annotation {
         // message fields
-        ///  Identifies the element in the original source .proto file. This field
-        ///  is formatted the same as SourceCodeInfo.Location.path.
+        /// Identifies the element in the original source .proto file. This field
+        /// is formatted the same as SourceCodeInfo.Location.path.
         // @@protoc_insertion_point(field:google.protobuf.GeneratedCodeInfo.Annotation.path)
         pub path: ::std::vec::Vec<i32>,
-        ///  Identifies the filesystem path to the original source .proto.
+        /// Identifies the filesystem path to the original source .proto.
         // @@protoc_insertion_point(field:google.protobuf.GeneratedCodeInfo.Annotation.source_file)
         pub source_file: ::std::option::Option<::std::string::String>,
-        ///  Identifies the starting offset in bytes in the generated code
-        ///  that relates to the identified object.
+        /// Identifies the starting offset in bytes in the generated code
+        /// that relates to the identified object.
         // @@protoc_insertion_point(field:google.protobuf.GeneratedCodeInfo.Annotation.begin)
         pub begin: ::std::option::Option<i32>,
-        ///  Identifies the ending offset in bytes in the generated code that
-        ///  relates to the identified offset. The end offset should be one past
-        ///  the last relevant byte (so the length of the text = end - begin).
+        /// Identifies the ending offset in bytes in the generated code that
+        /// relates to the identified offset. The end offset should be one past
+        /// the last relevant byte (so the length of the text = end - begin).
         // @@protoc_insertion_point(field:google.protobuf.GeneratedCodeInfo.Annotation.end)
         pub end: ::std::option::Option<i32>,
         // special fields
@@ -21,7 +21,7 @@
 
 //! Generated file from `google/protobuf/compiler/plugin.proto`
 
-///  The version number of protocol compiler.
+/// The version number of protocol compiler.
 // @@protoc_insertion_point(message:google.protobuf.compiler.Version)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct Version {
@@ -32,8 +32,8 @@ pub struct Version {
     pub minor: ::std::option::Option<i32>,
     // @@protoc_insertion_point(field:google.protobuf.compiler.Version.patch)
     pub patch: ::std::option::Option<i32>,
-    ///  A suffix for alpha, beta or rc release, e.g., "alpha-1", "rc2". It should
-    ///  be empty for mainline stable releases.
+    /// A suffix for alpha, beta or rc release, e.g., "alpha-1", "rc2". It should
+    /// be empty for mainline stable releases.
     // @@protoc_insertion_point(field:google.protobuf.compiler.Version.suffix)
     pub suffix: ::std::option::Option<::std::string::String>,
     // special fields
@@ -293,36 +293,36 @@ impl crate::reflect::ProtobufValue for Version {
     type RuntimeType = crate::reflect::rt::RuntimeTypeMessage<Self>;
 }
 
-///  An encoded CodeGeneratorRequest is written to the plugin's stdin.
+/// An encoded CodeGeneratorRequest is written to the plugin's stdin.
 // @@protoc_insertion_point(message:google.protobuf.compiler.CodeGeneratorRequest)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct CodeGeneratorRequest {
     // message fields
-    ///  The .proto files that were explicitly listed on the command-line.  The
-    ///  code generator should generate code only for these files.  Each file's
-    ///  descriptor will be included in proto_file, below.
+    /// The .proto files that were explicitly listed on the command-line.  The
+    /// code generator should generate code only for these files.  Each file's
+    /// descriptor will be included in proto_file, below.
     // @@protoc_insertion_point(field:google.protobuf.compiler.CodeGeneratorRequest.file_to_generate)
     pub file_to_generate: ::std::vec::Vec<::std::string::String>,
-    ///  The generator parameter passed on the command-line.
+    /// The generator parameter passed on the command-line.
     // @@protoc_insertion_point(field:google.protobuf.compiler.CodeGeneratorRequest.parameter)
     pub parameter: ::std::option::Option<::std::string::String>,
-    ///  FileDescriptorProtos for all files in files_to_generate and everything
-    ///  they import.  The files will appear in topological order, so each file
-    ///  appears before any file that imports it.
+    /// FileDescriptorProtos for all files in files_to_generate and everything
+    /// they import.  The files will appear in topological order, so each file
+    /// appears before any file that imports it.
     ///
-    ///  protoc guarantees that all proto_files will be written after
-    ///  the fields above, even though this is not technically guaranteed by the
-    ///  protobuf wire format.  This theoretically could allow a plugin to stream
-    ///  in the FileDescriptorProtos and handle them one by one rather than read
-    ///  the entire set into memory at once.  However, as of this writing, this
-    ///  is not similarly optimized on protoc's end -- it will store all fields in
-    ///  memory at once before sending them to the plugin.
+    /// protoc guarantees that all proto_files will be written after
+    /// the fields above, even though this is not technically guaranteed by the
+    /// protobuf wire format.  This theoretically could allow a plugin to stream
+    /// in the FileDescriptorProtos and handle them one by one rather than read
+    /// the entire set into memory at once.  However, as of this writing, this
+    /// is not similarly optimized on protoc's end -- it will store all fields in
+    /// memory at once before sending them to the plugin.
     ///
-    ///  Type names of fields and extensions in the FileDescriptorProto are always
-    ///  fully qualified.
+    /// Type names of fields and extensions in the FileDescriptorProto are always
+    /// fully qualified.
     // @@protoc_insertion_point(field:google.protobuf.compiler.CodeGeneratorRequest.proto_file)
     pub proto_file: ::std::vec::Vec<crate::descriptor::FileDescriptorProto>,
-    ///  The version number of protocol compiler.
+    /// The version number of protocol compiler.
     // @@protoc_insertion_point(field:google.protobuf.compiler.CodeGeneratorRequest.compiler_version)
     pub compiler_version: crate::MessageField<Version>,
     // special fields
@@ -537,23 +537,23 @@ impl crate::reflect::ProtobufValue for CodeGeneratorRequest {
     type RuntimeType = crate::reflect::rt::RuntimeTypeMessage<Self>;
 }
 
-///  The plugin writes an encoded CodeGeneratorResponse to stdout.
+/// The plugin writes an encoded CodeGeneratorResponse to stdout.
 // @@protoc_insertion_point(message:google.protobuf.compiler.CodeGeneratorResponse)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct CodeGeneratorResponse {
     // message fields
-    ///  Error message.  If non-empty, code generation failed.  The plugin process
-    ///  should exit with status code zero even if it reports an error in this way.
+    /// Error message.  If non-empty, code generation failed.  The plugin process
+    /// should exit with status code zero even if it reports an error in this way.
     ///
-    ///  This should be used to indicate errors in .proto files which prevent the
-    ///  code generator from generating correct code.  Errors which indicate a
-    ///  problem in protoc itself -- such as the input CodeGeneratorRequest being
-    ///  unparseable -- should be reported by writing a message to stderr and
-    ///  exiting with a non-zero status code.
+    /// This should be used to indicate errors in .proto files which prevent the
+    /// code generator from generating correct code.  Errors which indicate a
+    /// problem in protoc itself -- such as the input CodeGeneratorRequest being
+    /// unparseable -- should be reported by writing a message to stderr and
+    /// exiting with a non-zero status code.
     // @@protoc_insertion_point(field:google.protobuf.compiler.CodeGeneratorResponse.error)
     pub error: ::std::option::Option<::std::string::String>,
-    ///  A bitmask of supported features that the code generator supports.
-    ///  This is a bitwise "or" of values from the Feature enum.
+    /// A bitmask of supported features that the code generator supports.
+    /// This is a bitwise "or" of values from the Feature enum.
     // @@protoc_insertion_point(field:google.protobuf.compiler.CodeGeneratorResponse.supported_features)
     pub supported_features: ::std::option::Option<u64>,
     // @@protoc_insertion_point(field:google.protobuf.compiler.CodeGeneratorResponse.file)
@@ -764,69 +764,69 @@ impl crate::reflect::ProtobufValue for CodeGeneratorResponse {
 
 /// Nested message and enums of message `CodeGeneratorResponse`
 pub mod code_generator_response {
-    ///  Represents a single generated file.
+    /// Represents a single generated file.
     // @@protoc_insertion_point(message:google.protobuf.compiler.CodeGeneratorResponse.File)
     #[derive(PartialEq,Clone,Default,Debug)]
     pub struct File {
         // message fields
-        ///  The file name, relative to the output directory.  The name must not
-        ///  contain "." or ".." components and must be relative, not be absolute (so,
-        ///  the file cannot lie outside the output directory).  "/" must be used as
-        ///  the path separator, not "\".
+        /// The file name, relative to the output directory.  The name must not
+        /// contain "." or ".." components and must be relative, not be absolute (so,
+        /// the file cannot lie outside the output directory).  "/" must be used as
+        /// the path separator, not "\".
         ///
-        ///  If the name is omitted, the content will be appended to the previous
-        ///  file.  This allows the generator to break large files into small chunks,
-        ///  and allows the generated text to be streamed back to protoc so that large
-        ///  files need not reside completely in memory at one time.  Note that as of
-        ///  this writing protoc does not optimize for this -- it will read the entire
-        ///  CodeGeneratorResponse before writing files to disk.
+        /// If the name is omitted, the content will be appended to the previous
+        /// file.  This allows the generator to break large files into small chunks,
+        /// and allows the generated text to be streamed back to protoc so that large
+        /// files need not reside completely in memory at one time.  Note that as of
+        /// this writing protoc does not optimize for this -- it will read the entire
+        /// CodeGeneratorResponse before writing files to disk.
         // @@protoc_insertion_point(field:google.protobuf.compiler.CodeGeneratorResponse.File.name)
         pub name: ::std::option::Option<::std::string::String>,
-        ///  If non-empty, indicates that the named file should already exist, and the
-        ///  content here is to be inserted into that file at a defined insertion
-        ///  point.  This feature allows a code generator to extend the output
-        ///  produced by another code generator.  The original generator may provide
-        ///  insertion points by placing special annotations in the file that look
-        ///  like:
-        ///    @@protoc_insertion_point(NAME)
-        ///  The annotation can have arbitrary text before and after it on the line,
-        ///  which allows it to be placed in a comment.  NAME should be replaced with
-        ///  an identifier naming the point -- this is what other generators will use
-        ///  as the insertion_point.  Code inserted at this point will be placed
-        ///  immediately above the line containing the insertion point (thus multiple
-        ///  insertions to the same point will come out in the order they were added).
-        ///  The double-@ is intended to make it unlikely that the generated code
-        ///  could contain things that look like insertion points by accident.
+        /// If non-empty, indicates that the named file should already exist, and the
+        /// content here is to be inserted into that file at a defined insertion
+        /// point.  This feature allows a code generator to extend the output
+        /// produced by another code generator.  The original generator may provide
+        /// insertion points by placing special annotations in the file that look
+        /// like:
+        ///   @@protoc_insertion_point(NAME)
+        /// The annotation can have arbitrary text before and after it on the line,
+        /// which allows it to be placed in a comment.  NAME should be replaced with
+        /// an identifier naming the point -- this is what other generators will use
+        /// as the insertion_point.  Code inserted at this point will be placed
+        /// immediately above the line containing the insertion point (thus multiple
+        /// insertions to the same point will come out in the order they were added).
+        /// The double-@ is intended to make it unlikely that the generated code
+        /// could contain things that look like insertion points by accident.
         ///
-        ///  For example, the C++ code generator places the following line in the
-        ///  .pb.h files that it generates:
-        ///    // @@protoc_insertion_point(namespace_scope)
-        ///  This line appears within the scope of the file's package namespace, but
-        ///  outside of any particular class.  Another plugin can then specify the
-        ///  insertion_point "namespace_scope" to generate additional classes or
-        ///  other declarations that should be placed in this scope.
+        /// For example, the C++ code generator places the following line in the
+        /// .pb.h files that it generates:
+        ///   // @@protoc_insertion_point(namespace_scope)
+        /// This line appears within the scope of the file's package namespace, but
+        /// outside of any particular class.  Another plugin can then specify the
+        /// insertion_point "namespace_scope" to generate additional classes or
+        /// other declarations that should be placed in this scope.
         ///
-        ///  Note that if the line containing the insertion point begins with
-        ///  whitespace, the same whitespace will be added to every line of the
-        ///  inserted text.  This is useful for languages like Python, where
-        ///  indentation matters.  In these languages, the insertion point comment
-        ///  should be indented the same amount as any inserted code will need to be
-        ///  in order to work correctly in that context.
+        /// Note that if the line containing the insertion point begins with
+        /// whitespace, the same whitespace will be added to every line of the
+        /// inserted text.  This is useful for languages like Python, where
+        /// indentation matters.  In these languages, the insertion point comment
+        /// should be indented the same amount as any inserted code will need to be
+        /// in order to work correctly in that context.
         ///
-        ///  The code generator that generates the initial file and the one which
-        ///  inserts into it must both run as part of a single invocation of protoc.
-        ///  Code generators are executed in the order in which they appear on the
-        ///  command line.
+        /// The code generator that generates the initial file and the one which
+        /// inserts into it must both run as part of a single invocation of protoc.
+        /// Code generators are executed in the order in which they appear on the
+        /// command line.
         ///
-        ///  If |insertion_point| is present, |name| must also be present.
+        /// If |insertion_point| is present, |name| must also be present.
         // @@protoc_insertion_point(field:google.protobuf.compiler.CodeGeneratorResponse.File.insertion_point)
         pub insertion_point: ::std::option::Option<::std::string::String>,
-        ///  The file contents.
+        /// The file contents.
         // @@protoc_insertion_point(field:google.protobuf.compiler.CodeGeneratorResponse.File.content)
         pub content: ::std::option::Option<::std::string::String>,
-        ///  Information describing the file content being inserted. If an insertion
-        ///  point is used, this information will be appropriately offset and inserted
-        ///  into the code generation metadata for the generated files.
+        /// Information describing the file content being inserted. If an insertion
+        /// point is used, this information will be appropriately offset and inserted
+        /// into the code generation metadata for the generated files.
         // @@protoc_insertion_point(field:google.protobuf.compiler.CodeGeneratorResponse.File.generated_code_info)
         pub generated_code_info: crate::MessageField<crate::descriptor::GeneratedCodeInfo>,
         // special fields
@@ -1102,7 +1102,7 @@ pub mod code_generator_response {
         type RuntimeType = crate::reflect::rt::RuntimeTypeMessage<Self>;
     }
 
-    ///  Sync with code_generator.h.
+    /// Sync with code_generator.h.
     #[derive(Clone,Copy,PartialEq,Eq,Debug,Hash)]
     // @@protoc_insertion_point(enum:google.protobuf.compiler.CodeGeneratorResponse.Feature)
     pub enum Feature {
@@ -21,60 +21,58 @@
 
 //! Generated file from `google/protobuf/api.proto`
 
-///  Api is a light-weight descriptor for an API Interface.
+/// Api is a light-weight descriptor for an API Interface.
 ///
-///  Interfaces are also described as "protocol buffer services" in some contexts,
-///  such as by the "service" keyword in a .proto file, but they are different
-///  from API Services, which represent a concrete implementation of an interface
-///  as opposed to simply a description of methods and bindings. They are also
-///  sometimes simply referred to as "APIs" in other contexts, such as the name of
-///  this message itself. See https://cloud.google.com/apis/design/glossary for
-///  detailed terminology.
+/// Interfaces are also described as "protocol buffer services" in some contexts,
+/// such as by the "service" keyword in a .proto file, but they are different
+/// from API Services, which represent a concrete implementation of an interface
+/// as opposed to simply a description of methods and bindings. They are also
+/// sometimes simply referred to as "APIs" in other contexts, such as the name of
+/// this message itself. See https://cloud.google.com/apis/design/glossary for
+/// detailed terminology.
 // @@protoc_insertion_point(message:google.protobuf.Api)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct Api {
     // message fields
-    ///  The fully qualified name of this interface, including package name
-    ///  followed by the interface's simple name.
+    /// The fully qualified name of this interface, including package name
+    /// followed by the interface's simple name.
     // @@protoc_insertion_point(field:google.protobuf.Api.name)
     pub name: ::std::string::String,
-    ///  The methods of this interface, in unspecified order.
+    /// The methods of this interface, in unspecified order.
     // @@protoc_insertion_point(field:google.protobuf.Api.methods)
     pub methods: ::std::vec::Vec<Method>,
-    ///  Any metadata attached to the interface.
+    /// Any metadata attached to the interface.
     // @@protoc_insertion_point(field:google.protobuf.Api.options)
     pub options: ::std::vec::Vec<crate::well_known_types::type_::Option>,
-    ///  A version string for this interface. If specified, must have the form
-    ///  `major-version.minor-version`, as in `1.10`. If the minor version is
-    ///  omitted, it defaults to zero. If the entire version field is empty, the
-    ///  major version is derived from the package name, as outlined below. If the
-    ///  field is not empty, the version in the package name will be verified to be
-    ///  consistent with what is provided here.
-    ///
-    ///  The versioning schema uses [semantic
-    ///  versioning](http://semver.org) where the major version number
-    ///  indicates a breaking change and the minor version an additive,
-    ///  non-breaking change. Both version numbers are signals to users
-    ///  what to expect from different versions, and should be carefully
-    ///  chosen based on the product plan.
-    ///
-    ///  The major version is also reflected in the package name of the
-    ///  interface, which must end in `v<major-version>`, as in
-    ///  `google.feature.v1`. For major versions 0 and 1, the suffix can
-    ///  be omitted. Zero major versions must only be used for
-    ///  experimental, non-GA interfaces.
+    /// A version string for this interface. If specified, must have the form
+    /// `major-version.minor-version`, as in `1.10`. If the minor version is
+    /// omitted, it defaults to zero. If the entire version field is empty, the
+    /// major version is derived from the package name, as outlined below. If the
+    /// field is not empty, the version in the package name will be verified to be
+    /// consistent with what is provided here.
     ///
+    /// The versioning schema uses [semantic
+    /// versioning](http://semver.org) where the major version number
+    /// indicates a breaking change and the minor version an additive,
+    /// non-breaking change. Both version numbers are signals to users
+    /// what to expect from different versions, and should be carefully
+    /// chosen based on the product plan.
     ///
+    /// The major version is also reflected in the package name of the
+    /// interface, which must end in `v<major-version>`, as in
+    /// `google.feature.v1`. For major versions 0 and 1, the suffix can
+    /// be omitted. Zero major versions must only be used for
+    /// experimental, non-GA interfaces.
     // @@protoc_insertion_point(field:google.protobuf.Api.version)
     pub version: ::std::string::String,
-    ///  Source context for the protocol buffer service represented by this
-    ///  message.
+    /// Source context for the protocol buffer service represented by this
+    /// message.
     // @@protoc_insertion_point(field:google.protobuf.Api.source_context)
     pub source_context: crate::MessageField<crate::well_known_types::source_context::SourceContext>,
-    ///  Included interfaces. See [Mixin][].
+    /// Included interfaces. See [Mixin][].
     // @@protoc_insertion_point(field:google.protobuf.Api.mixins)
     pub mixins: ::std::vec::Vec<Mixin>,
-    ///  The source syntax of the service.
+    /// The source syntax of the service.
     // @@protoc_insertion_point(field:google.protobuf.Api.syntax)
     pub syntax: crate::EnumOrUnknown<crate::well_known_types::type_::Syntax>,
     // special fields
@@ -293,30 +291,30 @@ impl crate::reflect::ProtobufValue for Api {
     type RuntimeType = crate::reflect::rt::RuntimeTypeMessage<Self>;
 }
 
-///  Method represents a method of an API interface.
+/// Method represents a method of an API interface.
 // @@protoc_insertion_point(message:google.protobuf.Method)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct Method {
     // message fields
-    ///  The simple name of this method.
+    /// The simple name of this method.
     // @@protoc_insertion_point(field:google.protobuf.Method.name)
     pub name: ::std::string::String,
-    ///  A URL of the input message type.
+    /// A URL of the input message type.
     // @@protoc_insertion_point(field:google.protobuf.Method.request_type_url)
     pub request_type_url: ::std::string::String,
-    ///  If true, the request is streamed.
+    /// If true, the request is streamed.
     // @@protoc_insertion_point(field:google.protobuf.Method.request_streaming)
     pub request_streaming: bool,
-    ///  The URL of the output message type.
+    /// The URL of the output message type.
     // @@protoc_insertion_point(field:google.protobuf.Method.response_type_url)
     pub response_type_url: ::std::string::String,
-    ///  If true, the response is streamed.
+    /// If true, the response is streamed.
     // @@protoc_insertion_point(field:google.protobuf.Method.response_streaming)
     pub response_streaming: bool,
-    ///  Any metadata attached to the method.
+    /// Any metadata attached to the method.
     // @@protoc_insertion_point(field:google.protobuf.Method.options)
     pub options: ::std::vec::Vec<crate::well_known_types::type_::Option>,
-    ///  The source syntax of this method.
+    /// The source syntax of this method.
     // @@protoc_insertion_point(field:google.protobuf.Method.syntax)
     pub syntax: crate::EnumOrUnknown<crate::well_known_types::type_::Syntax>,
     // special fields
@@ -536,11 +534,11 @@ impl crate::reflect::ProtobufValue for Method {
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct Mixin {
     // message fields
-    ///  The fully qualified name of the interface which is included.
+    /// The fully qualified name of the interface which is included.
     // @@protoc_insertion_point(field:google.protobuf.Mixin.name)
     pub name: ::std::string::String,
-    ///  If non-empty specifies a path under which inherited HTTP paths
-    ///  are rooted.
+    /// If non-empty specifies a path under which inherited HTTP paths
+    /// are rooted.
     // @@protoc_insertion_point(field:google.protobuf.Mixin.root)
     pub root: ::std::string::String,
     // special fields
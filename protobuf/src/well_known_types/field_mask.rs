@@ -25,7 +25,7 @@
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct FieldMask {
     // message fields
-    ///  The set of field mask paths.
+    /// The set of field mask paths.
     // @@protoc_insertion_point(field:google.protobuf.FieldMask.paths)
     pub paths: ::std::vec::Vec<::std::string::String>,
     // special fields
@@ -21,14 +21,14 @@
 
 //! Generated file from `google/protobuf/source_context.proto`
 
-///  `SourceContext` represents information about the source of a
-///  protobuf element, like the file in which it is defined.
+/// `SourceContext` represents information about the source of a
+/// protobuf element, like the file in which it is defined.
 // @@protoc_insertion_point(message:google.protobuf.SourceContext)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct SourceContext {
     // message fields
-    ///  The path-qualified name of the .proto file that contained the associated
-    ///  protobuf element.  For example: `"google/protobuf/source_context.proto"`.
+    /// The path-qualified name of the .proto file that contained the associated
+    /// protobuf element.  For example: `"google/protobuf/source_context.proto"`.
     // @@protoc_insertion_point(field:google.protobuf.SourceContext.file_name)
     pub file_name: ::std::string::String,
     // special fields
@@ -21,19 +21,19 @@
 
 //! Generated file from `google/protobuf/struct.proto`
 
-///  `Struct` represents a structured data value, consisting of fields
-///  which map to dynamically typed values. In some languages, `Struct`
-///  might be supported by a native representation. For example, in
-///  scripting languages like JS a struct is represented as an
-///  object. The details of that representation are described together
-///  with the proto support for the language.
+/// `Struct` represents a structured data value, consisting of fields
+/// which map to dynamically typed values. In some languages, `Struct`
+/// might be supported by a native representation. For example, in
+/// scripting languages like JS a struct is represented as an
+/// object. The details of that representation are described together
+/// with the proto support for the language.
 ///
-///  The JSON representation for `Struct` is JSON object.
+/// The JSON representation for `Struct` is JSON object.
 // @@protoc_insertion_point(message:google.protobuf.Struct)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct Struct {
     // message fields
-    ///  Unordered map of dynamically typed values.
+    /// Unordered map of dynamically typed values.
     // @@protoc_insertion_point(field:google.protobuf.Struct.fields)
     pub fields: ::std::collections::HashMap<::std::string::String, Value>,
     // special fields
@@ -172,12 +172,12 @@ impl crate::reflect::ProtobufValue for Struct {
     type RuntimeType = crate::reflect::rt::RuntimeTypeMessage<Self>;
 }
 
-///  `Value` represents a dynamically typed value which can be either
-///  null, a number, a string, a boolean, a recursive struct value, or a
-///  list of values. A producer of value is expected to set one of these
-///  variants. Absence of any variant indicates an error.
+/// `Value` represents a dynamically typed value which can be either
+/// null, a number, a string, a boolean, a recursive struct value, or a
+/// list of values. A producer of value is expected to set one of these
+/// variants. Absence of any variant indicates an error.
 ///
-///  The JSON representation for `Value` is JSON value.
+/// The JSON representation for `Value` is JSON value.
 // @@protoc_insertion_point(message:google.protobuf.Value)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct Value {
@@ -658,14 +658,14 @@ pub mod value {
     }
 }
 
-///  `ListValue` is a wrapper around a repeated field of values.
+/// `ListValue` is a wrapper around a repeated field of values.
 ///
-///  The JSON representation for `ListValue` is JSON array.
+/// The JSON representation for `ListValue` is JSON array.
 // @@protoc_insertion_point(message:google.protobuf.ListValue)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct ListValue {
     // message fields
-    ///  Repeated field of dynamically typed values.
+    /// Repeated field of dynamically typed values.
     // @@protoc_insertion_point(field:google.protobuf.ListValue.values)
     pub values: ::std::vec::Vec<Value>,
     // special fields
@@ -785,13 +785,14 @@ impl crate::reflect::ProtobufValue for ListValue {
     type RuntimeType = crate::reflect::rt::RuntimeTypeMessage<Self>;
 }
 
-///  `NullValue` is a singleton enumeration to represent the null value for the
-///  `Value` type union.
+/// `NullValue` is a singleton enumeration to represent the null value for the
+/// `Value` type union.
 ///
-///   The JSON representation for `NullValue` is JSON `null`.
+///  The JSON representation for `NullValue` is JSON `null`.
 #[derive(Clone,Copy,PartialEq,Eq,Debug,Hash)]
 // @@protoc_insertion_point(enum:google.protobuf.NullValue)
 pub enum NullValue {
+    /// Null value.
     // @@protoc_insertion_point(enum_value:google.protobuf.NullValue.NULL_VALUE)
     NULL_VALUE = 0,
 }
@@ -21,27 +21,27 @@
 
 //! Generated file from `google/protobuf/type.proto`
 
-///  A protocol buffer message type.
+/// A protocol buffer message type.
 // @@protoc_insertion_point(message:google.protobuf.Type)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct Type {
     // message fields
-    ///  The fully qualified message name.
+    /// The fully qualified message name.
     // @@protoc_insertion_point(field:google.protobuf.Type.name)
     pub name: ::std::string::String,
-    ///  The list of fields.
+    /// The list of fields.
     // @@protoc_insertion_point(field:google.protobuf.Type.fields)
     pub fields: ::std::vec::Vec<Field>,
-    ///  The list of types appearing in `oneof` definitions in this type.
+    /// The list of types appearing in `oneof` definitions in this type.
     // @@protoc_insertion_point(field:google.protobuf.Type.oneofs)
     pub oneofs: ::std::vec::Vec<::std::string::String>,
-    ///  The protocol buffer options.
+    /// The protocol buffer options.
     // @@protoc_insertion_point(field:google.protobuf.Type.options)
     pub options: ::std::vec::Vec<Option>,
-    ///  The source context.
+    /// The source context.
     // @@protoc_insertion_point(field:google.protobuf.Type.source_context)
     pub source_context: crate::MessageField<crate::well_known_types::source_context::SourceContext>,
-    ///  The source syntax.
+    /// The source syntax.
     // @@protoc_insertion_point(field:google.protobuf.Type.syntax)
     pub syntax: crate::EnumOrUnknown<Syntax>,
     // special fields
@@ -243,41 +243,41 @@ impl crate::reflect::ProtobufValue for Type {
     type RuntimeType = crate::reflect::rt::RuntimeTypeMessage<Self>;
 }
 
-///  A single field of a message type.
+/// A single field of a message type.
 // @@protoc_insertion_point(message:google.protobuf.Field)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct Field {
     // message fields
-    ///  The field type.
+    /// The field type.
     // @@protoc_insertion_point(field:google.protobuf.Field.kind)
     pub kind: crate::EnumOrUnknown<field::Kind>,
-    ///  The field cardinality.
+    /// The field cardinality.
     // @@protoc_insertion_point(field:google.protobuf.Field.cardinality)
     pub cardinality: crate::EnumOrUnknown<field::Cardinality>,
-    ///  The field number.
+    /// The field number.
     // @@protoc_insertion_point(field:google.protobuf.Field.number)
     pub number: i32,
-    ///  The field name.
+    /// The field name.
     // @@protoc_insertion_point(field:google.protobuf.Field.name)
     pub name: ::std::string::String,
-    ///  The field type URL, without the scheme, for message or enumeration
-    ///  types. Example: `"type.googleapis.com/google.protobuf.Timestamp"`.
+    /// The field type URL, without the scheme, for message or enumeration
+    /// types. Example: `"type.googleapis.com/google.protobuf.Timestamp"`.
     // @@protoc_insertion_point(field:google.protobuf.Field.type_url)
     pub type_url: ::std::string::String,
-    ///  The index of the field type in `Type.oneofs`, for message or enumeration
-    ///  types. The first type has index 1; zero means the type is not in the list.
+    /// The index of the field type in `Type.oneofs`, for message or enumeration
+    /// types. The first type has index 1; zero means the type is not in the list.
     // @@protoc_insertion_point(field:google.protobuf.Field.oneof_index)
     pub oneof_index: i32,
-    ///  Whether to use alternative packed wire representation.
+    /// Whether to use alternative packed wire representation.
     // @@protoc_insertion_point(field:google.protobuf.Field.packed)
     pub packed: bool,
-    ///  The protocol buffer options.
+    /// The protocol buffer options.
     // @@protoc_insertion_point(field:google.protobuf.Field.options)
     pub options: ::std::vec::Vec<Option>,
-    ///  The field JSON name.
+    /// The field JSON name.
     // @@protoc_insertion_point(field:google.protobuf.Field.json_name)
     pub json_name: ::std::string::String,
-    ///  The string value of the default value of this field. Proto2 syntax only.
+    /// The string value of the default value of this field. Proto2 syntax only.
     // @@protoc_insertion_point(field:google.protobuf.Field.default_value)
     pub default_value: ::std::string::String,
     // special fields
@@ -543,46 +543,65 @@ impl crate::reflect::ProtobufValue for Field {
 
 /// Nested message and enums of message `Field`
 pub mod field {
-    ///  Basic field types.
+    /// Basic field types.
     #[derive(Clone,Copy,PartialEq,Eq,Debug,Hash)]
     // @@protoc_insertion_point(enum:google.protobuf.Field.Kind)
     pub enum Kind {
+        /// Field type unknown.
         // @@protoc_insertion_point(enum_value:google.protobuf.Field.Kind.TYPE_UNKNOWN)
         TYPE_UNKNOWN = 0,
+        /// Field type double.
         // @@protoc_insertion_point(enum_value:google.protobuf.Field.Kind.TYPE_DOUBLE)
         TYPE_DOUBLE = 1,
+        /// Field type float.
         // @@protoc_insertion_point(enum_value:google.protobuf.Field.Kind.TYPE_FLOAT)
         TYPE_FLOAT = 2,
+        /// Field type int64.
         // @@protoc_insertion_point(enum_value:google.protobuf.Field.Kind.TYPE_INT64)
         TYPE_INT64 = 3,
+        /// Field type uint64.
         // @@protoc_insertion_point(enum_value:google.protobuf.Field.Kind.TYPE_UINT64)
         TYPE_UINT64 = 4,
+        /// Field type int32.
         // @@protoc_insertion_point(enum_value:google.protobuf.Field.Kind.TYPE_INT32)
         TYPE_INT32 = 5,
+        /// Field type fixed64.
         // @@protoc_insertion_point(enum_value:google.protobuf.Field.Kind.TYPE_FIXED64)
         TYPE_FIXED64 = 6,
+        /// Field type fixed32.
         // @@protoc_insertion_point(enum_value:google.protobuf.Field.Kind.TYPE_FIXED32)
         TYPE_FIXED32 = 7,
+        /// Field type bool.
         // @@protoc_insertion_point(enum_value:google.protobuf.Field.Kind.TYPE_BOOL)
         TYPE_BOOL = 8,
+        /// Field type string.
         // @@protoc_insertion_point(enum_value:google.protobuf.Field.Kind.TYPE_STRING)
         TYPE_STRING = 9,
+        /// Field type group. Proto2 syntax only, and deprecated.
         // @@protoc_insertion_point(enum_value:google.protobuf.Field.Kind.TYPE_GROUP)
         TYPE_GROUP = 10,
+        /// Field type message.
         // @@protoc_insertion_point(enum_value:google.protobuf.Field.Kind.TYPE_MESSAGE)
         TYPE_MESSAGE = 11,
+        /// Field type bytes.
         // @@protoc_insertion_point(enum_value:google.protobuf.Field.Kind.TYPE_BYTES)
         TYPE_BYTES = 12,
+        /// Field type uint32.
         // @@protoc_insertion_point(enum_value:google.protobuf.Field.Kind.TYPE_UINT32)
         TYPE_UINT32 = 13,
+        /// Field type enum.
         // @@protoc_insertion_point(enum_value:google.protobuf.Field.Kind.TYPE_ENUM)
         TYPE_ENUM = 14,
+        /// Field type sfixed32.
         // @@protoc_insertion_point(enum_value:google.protobuf.Field.Kind.TYPE_SFIXED32)
         TYPE_SFIXED32 = 15,
+        /// Field type sfixed64.
         // @@protoc_insertion_point(enum_value:google.protobuf.Field.Kind.TYPE_SFIXED64)
         TYPE_SFIXED64 = 16,
+        /// Field type sint32.
         // @@protoc_insertion_point(enum_value:google.protobuf.Field.Kind.TYPE_SINT32)
         TYPE_SINT32 = 17,
+        /// Field type sint64.
         // @@protoc_insertion_point(enum_value:google.protobuf.Field.Kind.TYPE_SINT64)
         TYPE_SINT64 = 18,
     }
@@ -691,16 +710,20 @@ pub mod field {
         }
     }
 
-    ///  Whether a field is optional, required, or repeated.
+    /// Whether a field is optional, required, or repeated.
     #[derive(Clone,Copy,PartialEq,Eq,Debug,Hash)]
     // @@protoc_insertion_point(enum:google.protobuf.Field.Cardinality)
     pub enum Cardinality {
+        /// For fields with unknown cardinality.
         // @@protoc_insertion_point(enum_value:google.protobuf.Field.Cardinality.CARDINALITY_UNKNOWN)
         CARDINALITY_UNKNOWN = 0,
+        /// For optional fields.
         // @@protoc_insertion_point(enum_value:google.protobuf.Field.Cardinality.CARDINALITY_OPTIONAL)
         CARDINALITY_OPTIONAL = 1,
+        /// For required fields. Proto2 syntax only.
         // @@protoc_insertion_point(enum_value:google.protobuf.Field.Cardinality.CARDINALITY_REQUIRED)
         CARDINALITY_REQUIRED = 2,
+        /// For repeated fields.
         // @@protoc_insertion_point(enum_value:google.protobuf.Field.Cardinality.CARDINALITY_REPEATED)
         CARDINALITY_REPEATED = 3,
     }
@@ -765,24 +788,24 @@ pub mod field {
     }
 }
 
-///  Enum type definition.
+/// Enum type definition.
 // @@protoc_insertion_point(message:google.protobuf.Enum)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct Enum {
     // message fields
-    ///  Enum type name.
+    /// Enum type name.
     // @@protoc_insertion_point(field:google.protobuf.Enum.name)
     pub name: ::std::string::String,
-    ///  Enum value definitions.
+    /// Enum value definitions.
     // @@protoc_insertion_point(field:google.protobuf.Enum.enumvalue)
     pub enumvalue: ::std::vec::Vec<EnumValue>,
-    ///  Protocol buffer options.
+    /// Protocol buffer options.
     // @@protoc_insertion_point(field:google.protobuf.Enum.options)
     pub options: ::std::vec::Vec<Option>,
-    ///  The source context.
+    /// The source context.
     // @@protoc_insertion_point(field:google.protobuf.Enum.source_context)
     pub source_context: crate::MessageField<crate::well_known_types::source_context::SourceContext>,
-    ///  The source syntax.
+    /// The source syntax.
     // @@protoc_insertion_point(field:google.protobuf.Enum.syntax)
     pub syntax: crate::EnumOrUnknown<Syntax>,
     // special fields
@@ -968,18 +991,18 @@ impl crate::reflect::ProtobufValue for Enum {
     type RuntimeType = crate::reflect::rt::RuntimeTypeMessage<Self>;
 }
 
-///  Enum value definition.
+/// Enum value definition.
 // @@protoc_insertion_point(message:google.protobuf.EnumValue)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct EnumValue {
     // message fields
-    ///  Enum value name.
+    /// Enum value name.
     // @@protoc_insertion_point(field:google.protobuf.EnumValue.name)
     pub name: ::std::string::String,
-    ///  Enum value number.
+    /// Enum value number.
     // @@protoc_insertion_point(field:google.protobuf.EnumValue.number)
     pub number: i32,
-    ///  Protocol buffer options.
+    /// Protocol buffer options.
     // @@protoc_insertion_point(field:google.protobuf.EnumValue.options)
     pub options: ::std::vec::Vec<Option>,
     // special fields
@@ -1131,22 +1154,22 @@ impl crate::reflect::ProtobufValue for EnumValue {
     type RuntimeType = crate::reflect::rt::RuntimeTypeMessage<Self>;
 }
 
-///  A protocol buffer option, which can be attached to a message, field,
-///  enumeration, etc.
+/// A protocol buffer option, which can be attached to a message, field,
+/// enumeration, etc.
 // @@protoc_insertion_point(message:google.protobuf.Option)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct Option {
     // message fields
-    ///  The option's name. For protobuf built-in options (options defined in
-    ///  descriptor.proto), this is the short name. For example, `"map_entry"`.
-    ///  For custom options, it should be the fully-qualified name. For example,
-    ///  `"google.api.http"`.
+    /// The option's name. For protobuf built-in options (options defined in
+    /// descriptor.proto), this is the short name. For example, `"map_entry"`.
+    /// For custom options, it should be the fully-qualified name. For example,
+    /// `"google.api.http"`.
     // @@protoc_insertion_point(field:google.protobuf.Option.name)
     pub name: ::std::string::String,
-    ///  The option's value packed in an Any message. If the value is a primitive,
-    ///  the corresponding wrapper type defined in google/protobuf/wrappers.proto
-    ///  should be used. If the value is an enum, it should be stored as an int32
-    ///  value using the google.protobuf.Int32Value type.
+    /// The option's value packed in an Any message. If the value is a primitive,
+    /// the corresponding wrapper type defined in google/protobuf/wrappers.proto
+    /// should be used. If the value is an enum, it should be stored as an int32
+    /// value using the google.protobuf.Int32Value type.
     // @@protoc_insertion_point(field:google.protobuf.Option.value)
     pub value: crate::MessageField<crate::well_known_types::any::Any>,
     // special fields
@@ -1282,12 +1305,14 @@ impl crate::reflect::ProtobufValue for Option {
     type RuntimeType = crate::reflect::rt::RuntimeTypeMessage<Self>;
 }
 
-///  The syntax in which a protocol buffer element is defined.
+/// The syntax in which a protocol buffer element is defined.
 #[derive(Clone,Copy,PartialEq,Eq,Debug,Hash)]
 // @@protoc_insertion_point(enum:google.protobuf.Syntax)
 pub enum Syntax {
+    /// Syntax `proto2`.
     // @@protoc_insertion_point(enum_value:google.protobuf.Syntax.SYNTAX_PROTO2)
     SYNTAX_PROTO2 = 0,
+    /// Syntax `proto3`.
     // @@protoc_insertion_point(enum_value:google.protobuf.Syntax.SYNTAX_PROTO3)
     SYNTAX_PROTO3 = 1,
 }
@@ -21,14 +21,14 @@
 
 //! Generated file from `google/protobuf/wrappers.proto`
 
-///  Wrapper message for `double`.
+/// Wrapper message for `double`.
 ///
-///  The JSON representation for `DoubleValue` is JSON number.
+/// The JSON representation for `DoubleValue` is JSON number.
 // @@protoc_insertion_point(message:google.protobuf.DoubleValue)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct DoubleValue {
     // message fields
-    ///  The double value.
+    /// The double value.
     // @@protoc_insertion_point(field:google.protobuf.DoubleValue.value)
     pub value: f64,
     // special fields
@@ -147,14 +147,14 @@ impl crate::reflect::ProtobufValue for DoubleValue {
     type RuntimeType = crate::reflect::rt::RuntimeTypeMessage<Self>;
 }
 
-///  Wrapper message for `float`.
+/// Wrapper message for `float`.
 ///
-///  The JSON representation for `FloatValue` is JSON number.
+/// The JSON representation for `FloatValue` is JSON number.
 // @@protoc_insertion_point(message:google.protobuf.FloatValue)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct FloatValue {
     // message fields
-    ///  The float value.
+    /// The float value.
     // @@protoc_insertion_point(field:google.protobuf.FloatValue.value)
     pub value: f32,
     // special fields
@@ -273,14 +273,14 @@ impl crate::reflect::ProtobufValue for FloatValue {
     type RuntimeType = crate::reflect::rt::RuntimeTypeMessage<Self>;
 }
 
-///  Wrapper message for `int64`.
+/// Wrapper message for `int64`.
 ///
-///  The JSON representation for `Int64Value` is JSON string.
+/// The JSON representation for `Int64Value` is JSON string.
 // @@protoc_insertion_point(message:google.protobuf.Int64Value)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct Int64Value {
     // message fields
-    ///  The int64 value.
+    /// The int64 value.
     // @@protoc_insertion_point(field:google.protobuf.Int64Value.value)
     pub value: i64,
     // special fields
@@ -399,14 +399,14 @@ impl crate::reflect::ProtobufValue for Int64Value {
     type RuntimeType = crate::reflect::rt::RuntimeTypeMessage<Self>;
 }
 
-///  Wrapper message for `uint64`.
+/// Wrapper message for `uint64`.
 ///
-///  The JSON representation for `UInt64Value` is JSON string.
+/// The JSON representation for `UInt64Value` is JSON string.
 // @@protoc_insertion_point(message:google.protobuf.UInt64Value)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct UInt64Value {
     // message fields
-    ///  The uint64 value.
+    /// The uint64 value.
     // @@protoc_insertion_point(field:google.protobuf.UInt64Value.value)
     pub value: u64,
     // special fields
@@ -525,14 +525,14 @@ impl crate::reflect::ProtobufValue for UInt64Value {
     type RuntimeType = crate::reflect::rt::RuntimeTypeMessage<Self>;
 }
 
-///  Wrapper message for `int32`.
+/// Wrapper message for `int32`.
 ///
-///  The JSON representation for `Int32Value` is JSON number.
+/// The JSON representation for `Int32Value` is JSON number.
 // @@protoc_insertion_point(message:google.protobuf.Int32Value)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct Int32Value {
     // message fields
-    ///  The int32 value.
+    /// The int32 value.
     // @@protoc_insertion_point(field:google.protobuf.Int32Value.value)
     pub value: i32,
     // special fields
@@ -651,14 +651,14 @@ impl crate::reflect::ProtobufValue for Int32Value {
     type RuntimeType = crate::reflect::rt::RuntimeTypeMessage<Self>;
 }
 
-///  Wrapper message for `uint32`.
+/// Wrapper message for `uint32`.
 ///
-///  The JSON representation for `UInt32Value` is JSON number.
+/// The JSON representation for `UInt32Value` is JSON number.
 // @@protoc_insertion_point(message:google.protobuf.UInt32Value)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct UInt32Value {
     // message fields
-    ///  The uint32 value.
+    /// The uint32 value.
     // @@protoc_insertion_point(field:google.protobuf.UInt32Value.value)
     pub value: u32,
     // special fields
@@ -777,14 +777,14 @@ impl crate::reflect::ProtobufValue for UInt32Value {
     type RuntimeType = crate::reflect::rt::RuntimeTypeMessage<Self>;
 }
 
-///  Wrapper message for `bool`.
+/// Wrapper message for `bool`.
 ///
-///  The JSON representation for `BoolValue` is JSON `true` and `false`.
+/// The JSON representation for `BoolValue` is JSON `true` and `false`.
 // @@protoc_insertion_point(message:google.protobuf.BoolValue)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct BoolValue {
     // message fields
-    ///  The bool value.
+    /// The bool value.
     // @@protoc_insertion_point(field:google.protobuf.BoolValue.value)
     pub value: bool,
     // special fields
@@ -903,14 +903,14 @@ impl crate::reflect::ProtobufValue for BoolValue {
     type RuntimeType = crate::reflect::rt::RuntimeTypeMessage<Self>;
 }
 
-///  Wrapper message for `string`.
+/// Wrapper message for `string`.
 ///
-///  The JSON representation for `StringValue` is JSON string.
+/// The JSON representation for `StringValue` is JSON string.
 // @@protoc_insertion_point(message:google.protobuf.StringValue)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct StringValue {
     // message fields
-    ///  The string value.
+    /// The string value.
     // @@protoc_insertion_point(field:google.protobuf.StringValue.value)
     pub value: ::std::string::String,
     // special fields
@@ -1029,14 +1029,14 @@ impl crate::reflect::ProtobufValue for StringValue {
     type RuntimeType = crate::reflect::rt::RuntimeTypeMessage<Self>;
 }
 
-///  Wrapper message for `bytes`.
+/// Wrapper message for `bytes`.
 ///
-///  The JSON representation for `BytesValue` is JSON string.
+/// The JSON representation for `BytesValue` is JSON string.
 // @@protoc_insertion_point(message:google.protobuf.BytesValue)
 #[derive(PartialEq,Clone,Default,Debug)]
 pub struct BytesValue {
     // message fields
-    ///  The bytes value.
+    /// The bytes value.
     // @@protoc_insertion_point(field:google.protobuf.BytesValue.value)
     pub value: ::std::vec::Vec<u8>,
     // special fields